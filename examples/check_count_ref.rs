@@ -2,7 +2,7 @@ use clap;
 use clap::Parser;
 use jaeger_stats::{
     datetime_micros_str, datetime_millis_str, micros_to_datetime, read_jaeger_trace_file,
-    JaegerTrace,
+    JaegerTrace, Report,
 };
 use std::error::Error;
 
@@ -48,7 +48,7 @@ fn main() -> Result<(), Box<dyn Error>> {
     let input_file = &args.input;
 
     println!("Reading a Jaeger-trace from '{input_file}'");
-    let jt = read_jaeger_trace_file(input_file).unwrap();
+    let jt = read_jaeger_trace_file(&mut Report::default(), input_file).unwrap();
 
     if SHOW_STDOUT {
         println!("{:#?}", jt);
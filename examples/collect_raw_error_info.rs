@@ -1,6 +1,8 @@
 use clap;
 use clap::Parser;
-use jaeger_stats::{read_jaeger_trace_file, JaegerItem, JaegerLog, JaegerSpan, JaegerTrace};
+use jaeger_stats::{
+    read_jaeger_trace_file, JaegerItem, JaegerLog, JaegerSpan, JaegerTrace, Report,
+};
 use serde_json::{value::Number, Value};
 use std::error::Error;
 
@@ -155,7 +157,8 @@ fn main() -> Result<(), Box<dyn Error>> {
     let input_file = &args.input;
 
     println!("Reading a Jaeger-trace from '{}'", input_file);
-    let jt = read_jaeger_trace_file(input_file).expect("Failed to analyze file");
+    let jt =
+        read_jaeger_trace_file(&mut Report::default(), input_file).expect("Failed to analyze file");
 
     if SHOW_STDOUT {
         println!("{:#?}", jt);
@@ -1,6 +1,6 @@
 use clap;
 use clap::Parser;
-use jaeger_stats::{read_jaeger_trace_file, JaegerTrace};
+use jaeger_stats::{read_jaeger_trace_file, JaegerTrace, Report};
 use std::{collections::HashMap, error::Error, path::Path};
 
 /// Collecting all span tags from a file and show frequency of occurance.
@@ -61,7 +61,8 @@ fn main() -> Result<(), Box<dyn Error>> {
     let input_file = &args.input;
 
     println!("Reading a Jaeger-trace from '{}'", input_file);
-    let jt = read_jaeger_trace_file(input_file).expect("Failed to analyze file");
+    let jt =
+        read_jaeger_trace_file(&mut Report::default(), input_file).expect("Failed to analyze file");
 
     if SHOW_STDOUT {
         println!("{:#?}", jt);
@@ -1,7 +1,8 @@
 use clap::Parser;
 use jaeger_stats::{
     types::{ChartDataParameters, ChartLine, ProcessListItem},
-    BestFit, Metric, Stitched, StitchedDataSet, StitchedLine, StitchedSet, TraceScope, Viewer,
+    AnalysisContext, BestFit, Metric, Stitched, StitchedDataSet, StitchedLine, StitchedSet,
+    TraceScope, Viewer,
 };
 use log::{error, info};
 use serde::Serialize;
@@ -52,7 +53,7 @@ fn main() -> Result<(), Box<dyn Error>> {
     println!("Reading stitched from '{input_file}'");
 
     let now = Instant::now();
-    let sd = StitchedDataSet::from_file(&input_file).unwrap();
+    let sd = StitchedDataSet::from_file(&AnalysisContext::default(), &input_file).unwrap();
     println!("Elapsed time after load: {}", now.elapsed().as_secs());
 
     {
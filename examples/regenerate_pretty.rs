@@ -1,7 +1,7 @@
 use clap;
 use clap::Parser;
 /// Read a file and generate JSON again to see if all information was captured completely and correctly in the internal rust-format.
-use jaeger_stats::read_jaeger_trace_file;
+use jaeger_stats::{read_jaeger_trace_file, Report};
 use std::{error::Error, fs::File, io::Write};
 
 /// Read a jaeger json and write it out as pretty-printed json again. A diff of the files should show no differnces.
@@ -26,7 +26,7 @@ fn main() -> Result<(), Box<dyn Error>> {
 
     println!("Read a file and generate JSON again to see if all information was captured completely and correctly in the internal rust-format.");
     println!("Reading a Jaeger-trace from '{input_file}'");
-    let jt = read_jaeger_trace_file(input_file).unwrap();
+    let jt = read_jaeger_trace_file(&mut Report::default(), input_file).unwrap();
 
     if SHOW_STDOUT {
         println!("{:#?}", jt);
@@ -1,18 +1,18 @@
 use jaeger_stats::{
     datetime_micros_str, datetime_millis_str, micros_to_datetime, read_jaeger_trace_file,
-    JaegerTrace,
+    AnalysisContext, JaegerTrace, Report,
 };
 use std::error::Error;
 
 const SHOW_STDOUT: bool = false;
 const INPUT_FILE: &str = "/home/ceesvk/Downloads/372e70a4e259978e.json";
 
-fn show_start_times(jt: &JaegerTrace) {
+fn show_start_times(ctx: &AnalysisContext, jt: &JaegerTrace) {
     jt.data.iter().for_each(|ji| {
         ji.spans.iter().for_each(|span| {
             let dt_u64 = span.startTime;
 
-            let dt = micros_to_datetime(dt_u64);
+            let dt = micros_to_datetime(ctx, dt_u64);
 
             let dt_millis = datetime_millis_str(dt);
             let dt_micros = datetime_micros_str(dt);
@@ -25,12 +25,12 @@ fn show_start_times(jt: &JaegerTrace) {
 
 fn main() -> Result<(), Box<dyn Error>> {
     println!("Reading a Jaeger-trace from '{INPUT_FILE}'");
-    let jt = read_jaeger_trace_file(INPUT_FILE).unwrap();
+    let jt = read_jaeger_trace_file(&mut Report::default(), INPUT_FILE).unwrap();
 
     if SHOW_STDOUT {
         println!("{:#?}", jt);
     }
 
-    show_start_times(&jt);
+    show_start_times(&AnalysisContext::default(), &jt);
     Ok(())
 }
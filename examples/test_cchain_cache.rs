@@ -1,4 +1,4 @@
-use jaeger_stats::CChainEndPointCache;
+use jaeger_stats::{CChainEndPointCache, Report};
 use std::{
     env,
     path::{Path, PathBuf},
@@ -26,7 +26,7 @@ fn main() {
     let path = Path::new(&input_folder).to_path_buf();
     let mut cache = CChainEndPointCache::new(path);
 
-    let cchain = cache.get_cchain_key(&file_key);
+    let cchain = cache.get_cchain_key(&mut Report::default(), &file_key);
 
     println!("Found:\n{cchain:#?}");
 }
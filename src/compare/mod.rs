@@ -0,0 +1,6 @@
+//! Comparison of two analyses, for example the statistics from before and after a release.
+mod stats_comparison;
+mod topology_diff;
+
+pub use stats_comparison::{ComparisonParameters, StatsComparison};
+pub use topology_diff::TopologyDrift;
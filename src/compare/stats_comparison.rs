@@ -0,0 +1,547 @@
+use crate::{
+    context::AnalysisContext,
+    stats::{call_chain::CChainStatsValue, ProcOperStatsValue, StatsRec},
+    utils::{self, MannWhitney, TimeStats},
+};
+use serde::Serialize;
+use std::{collections::BTreeMap, error::Error, ffi::OsString, path::Path};
+
+/// The kind of item that is compared
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ComparisonKind {
+    ServiceOper,
+    CallChain,
+}
+
+impl ComparisonKind {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::ServiceOper => "service_oper",
+            Self::CallChain => "call_chain",
+        }
+    }
+}
+
+/// Summary of the observations of a service/operation or a call-chain in one of both analyses
+#[derive(Debug, Clone, Serialize)]
+pub struct SampleSummary {
+    pub count: usize,
+    pub rate: Option<f64>,
+    pub avg_millis: f64,
+    pub p50_millis: Option<f64>,
+    pub p95_millis: Option<f64>,
+    pub p99_millis: Option<f64>,
+    pub frac_not_http_ok: f64,
+    pub frac_error_logs: f64,
+    #[serde(skip)]
+    duration_micros: Vec<i64>,
+}
+
+impl SampleSummary {
+    fn new(
+        count: usize,
+        duration_micros: &Vec<i64>,
+        start_dt_micros: &Vec<i64>,
+        num_not_http_ok: i32,
+        num_with_error_logs: i32,
+        num_files: i32,
+    ) -> Self {
+        let ts = TimeStats(duration_micros);
+        let count_f = count.max(1) as f64;
+        Self {
+            count,
+            rate: TimeStats(start_dt_micros).get_avg_rate(num_files),
+            avg_millis: ts.get_avg_millis(),
+            p50_millis: ts.get_median_millis(),
+            p95_millis: ts.get_p_millis(0.95),
+            p99_millis: ts.get_p_millis(0.99),
+            frac_not_http_ok: num_not_http_ok as f64 / count_f,
+            frac_error_logs: num_with_error_logs as f64 / count_f,
+            duration_micros: duration_micros.clone(),
+        }
+    }
+
+    fn from_proc_oper(posv: &ProcOperStatsValue, num_files: i32) -> Self {
+        Self::new(
+            posv.count,
+            &posv.duration_micros,
+            &posv.start_dt_micros,
+            posv.num_not_http_ok,
+            posv.num_with_error_logs,
+            num_files,
+        )
+    }
+
+    fn from_call_chain(ccv: &CChainStatsValue, num_files: i32) -> Self {
+        Self::new(
+            ccv.count,
+            &ccv.duration_micros,
+            &ccv.start_dt_micros,
+            ccv.cc_not_http_ok,
+            ccv.cc_with_error_logs,
+            num_files,
+        )
+    }
+
+    /// the header of the csv-columns for this summary, where each label gets the suffix
+    fn csv_header(suffix: &str) -> String {
+        [
+            "Count",
+            "Rate",
+            "Avg_millis",
+            "P50_millis",
+            "P95_millis",
+            "P99_millis",
+            "Frac_not_http_ok",
+            "Frac_error_logs",
+        ]
+        .iter()
+        .map(|label| format!("{label}_{suffix}"))
+        .collect::<Vec<_>>()
+        .join("; ")
+    }
+
+    fn csv_values(ctx: &AnalysisContext, summary: Option<&Self>) -> String {
+        match summary {
+            Some(s) => format!(
+                "{}; {}; {}; {}; {}; {}; {}; {}",
+                s.count,
+                utils::format_float_opt(ctx, s.rate),
+                utils::format_float(ctx, s.avg_millis),
+                utils::format_float_opt(ctx, s.p50_millis),
+                utils::format_float_opt(ctx, s.p95_millis),
+                utils::format_float_opt(ctx, s.p99_millis),
+                utils::format_float(ctx, s.frac_not_http_ok),
+                utils::format_float(ctx, s.frac_error_logs)
+            ),
+            None => "; ; ; ; ; ; ; ".to_owned(),
+        }
+    }
+}
+
+/// Relative change of 'after' compared to 'before'
+fn rel_change(before: Option<f64>, after: Option<f64>) -> Option<f64> {
+    match (before, after) {
+        (Some(b), Some(a)) if b != 0.0 => Some((a - b) / b),
+        _ => None,
+    }
+}
+
+/// The comparison of a single service/operation or call-chain between two analyses
+#[derive(Debug, Clone, Serialize)]
+pub struct ComparisonLine {
+    pub kind: ComparisonKind,
+    pub key: String,
+    pub before: Option<SampleSummary>,
+    pub after: Option<SampleSummary>,
+    pub count_change: Option<f64>,
+    pub avg_millis_change: Option<f64>,
+    pub p95_millis_change: Option<f64>,
+    /// Mann-Whitney U-statistic of the duration samples (None if one of the samples is too small)
+    pub u_statistic: Option<f64>,
+    pub p_value: Option<f64>,
+    /// Probability that a duration after exceeds a duration before (0.5 is no shift)
+    pub effect_size: Option<f64>,
+    /// The durations differ significantly and are larger after the change
+    pub significant_regression: bool,
+    /// Rank of the significant regressions where rank 1 has the largest relative increase of the median duration.
+    pub regression_rank: Option<usize>,
+}
+
+impl ComparisonLine {
+    fn new(
+        kind: ComparisonKind,
+        key: String,
+        before: Option<SampleSummary>,
+        after: Option<SampleSummary>,
+        pars: &ComparisonParameters,
+    ) -> Self {
+        let test = match (&before, &after) {
+            (Some(b), Some(a))
+                if b.duration_micros.len() >= pars.min_samples
+                    && a.duration_micros.len() >= pars.min_samples =>
+            {
+                MannWhitney::test(&b.duration_micros, &a.duration_micros)
+            }
+            _ => None,
+        };
+        let significant_regression = test.is_some_and(|mw| mw.p_value < pars.alpha && mw.z > 0.0);
+        Self {
+            kind,
+            key,
+            count_change: rel_change(
+                before.as_ref().map(|b| b.count as f64),
+                after.as_ref().map(|a| a.count as f64),
+            ),
+            avg_millis_change: rel_change(
+                before.as_ref().map(|b| b.avg_millis),
+                after.as_ref().map(|a| a.avg_millis),
+            ),
+            p95_millis_change: rel_change(
+                before.as_ref().and_then(|b| b.p95_millis),
+                after.as_ref().and_then(|a| a.p95_millis),
+            ),
+            u_statistic: test.map(|mw| mw.u),
+            p_value: test.map(|mw| mw.p_value),
+            effect_size: test.map(|mw| mw.effect_size),
+            significant_regression,
+            regression_rank: None,
+            before,
+            after,
+        }
+    }
+
+    /// The relative increase of the median duration (used for ranking the regressions)
+    fn median_change(&self) -> f64 {
+        rel_change(
+            self.before.as_ref().and_then(|b| b.p50_millis),
+            self.after.as_ref().and_then(|a| a.p50_millis),
+        )
+        .unwrap_or(0.0)
+    }
+
+    /// header for report_stats_line output in ';'-separated csv-format
+    pub fn report_stats_line_header_str() -> String {
+        format!("Kind; Key; {}; {}; Count_change; Avg_millis_change; P95_millis_change; U_statistic; P_value; Effect_size; Significant_regression; Regression_rank",
+            SampleSummary::csv_header("before"),
+            SampleSummary::csv_header("after"))
+    }
+
+    /// reports the comparison as a single line in ';'-separated csv-format
+    pub fn report_stats_line(&self, ctx: &AnalysisContext) -> String {
+        format!(
+            "{}; {}; {}; {}; {}; {}; {}; {}; {}; {}; {}; {}",
+            self.kind.as_str(),
+            self.key,
+            SampleSummary::csv_values(ctx, self.before.as_ref()),
+            SampleSummary::csv_values(ctx, self.after.as_ref()),
+            utils::format_float_opt(ctx, self.count_change),
+            utils::format_float_opt(ctx, self.avg_millis_change),
+            utils::format_float_opt(ctx, self.p95_millis_change),
+            utils::format_float_opt(ctx, self.u_statistic),
+            utils::format_float_opt(ctx, self.p_value),
+            utils::format_float_opt(ctx, self.effect_size),
+            self.significant_regression,
+            self.regression_rank
+                .map_or(String::new(), |rank| rank.to_string())
+        )
+    }
+}
+
+/// Parameters of the comparison
+#[derive(Debug, Clone, Copy)]
+pub struct ComparisonParameters {
+    /// significance level of the Mann-Whitney U test
+    pub alpha: f64,
+    /// minimal number of duration samples in both analyses to perform the test
+    pub min_samples: usize,
+}
+
+impl Default for ComparisonParameters {
+    fn default() -> Self {
+        Self {
+            alpha: 0.01,
+            min_samples: 8,
+        }
+    }
+}
+
+/// Comparison of two analyses (StatsRec files), for example from before and after a release.
+#[derive(Debug, Serialize)]
+pub struct StatsComparison {
+    pub before_file: String,
+    pub after_file: String,
+    pub alpha: f64,
+    pub num_significant_regressions: usize,
+    pub lines: Vec<ComparisonLine>,
+}
+
+/// collect the summaries of the service/operations and call-chains of an analysis, keyed on kind and key
+fn collect_summaries(sr: &StatsRec) -> BTreeMap<(ComparisonKind, String), SampleSummary> {
+    let mut summaries = BTreeMap::new();
+    sr.stats.iter().for_each(|(service, oper_stats)| {
+        oper_stats.operation.0.iter().for_each(|(oper, posv)| {
+            summaries.insert(
+                (ComparisonKind::ServiceOper, format!("{service}/{oper}")),
+                SampleSummary::from_proc_oper(posv, sr.num_files),
+            );
+        });
+        oper_stats.call_chain.0.iter().for_each(|(cck, ccv)| {
+            summaries.insert(
+                (ComparisonKind::CallChain, cck.call_chain_key()),
+                SampleSummary::from_call_chain(ccv, sr.num_files),
+            );
+        });
+    });
+    summaries
+}
+
+impl StatsComparison {
+    /// align both analyses on service/operation and call-chain key and compare each pair.
+    pub fn new(
+        before: &StatsRec,
+        after: &StatsRec,
+        before_file: &str,
+        after_file: &str,
+        pars: &ComparisonParameters,
+    ) -> Self {
+        let mut before = collect_summaries(before);
+        let after = collect_summaries(after);
+
+        let mut lines: Vec<_> = after
+            .into_iter()
+            .map(|(k, a)| {
+                let b = before.remove(&k);
+                ComparisonLine::new(k.0, k.1, b, Some(a), pars)
+            })
+            .collect();
+        // the items that disappeared
+        lines.extend(
+            before
+                .into_iter()
+                .map(|(k, b)| ComparisonLine::new(k.0, k.1, Some(b), None, pars)),
+        );
+        lines.sort_by(|a, b| (a.kind, &a.key).cmp(&(b.kind, &b.key)));
+
+        // rank the significant regressions on the relative increase of the median
+        let mut regressions: Vec<_> = lines
+            .iter_mut()
+            .filter(|l| l.significant_regression)
+            .collect();
+        regressions.sort_by(|a, b| b.median_change().total_cmp(&a.median_change()));
+        let num_significant_regressions = regressions.len();
+        regressions
+            .into_iter()
+            .enumerate()
+            .for_each(|(idx, l)| l.regression_rank = Some(idx + 1));
+
+        Self {
+            before_file: before_file.to_owned(),
+            after_file: after_file.to_owned(),
+            alpha: pars.alpha,
+            num_significant_regressions,
+            lines,
+        }
+    }
+
+    /// read both StatsRec files and compare them
+    pub fn from_files(
+        ctx: &AnalysisContext,
+        before_file: &str,
+        after_file: &str,
+        pars: &ComparisonParameters,
+    ) -> Result<Self, Box<dyn Error>> {
+        let before = StatsRec::read_file(ctx, &OsString::from(before_file))?;
+        let after = StatsRec::read_file(ctx, &OsString::from(after_file))?;
+        Ok(Self::new(&before, &after, before_file, after_file, pars))
+    }
+
+    /// Show the comparison as a ';'-separated csv-string, where the significant regressions are listed first (in order of rank).
+    pub fn to_csv_string(&self, ctx: &AnalysisContext) -> String {
+        let mut lines: Vec<_> = self.lines.iter().collect();
+        lines.sort_by_key(|l| l.regression_rank.unwrap_or(usize::MAX));
+
+        let mut s = vec![
+            format!("before:; {}", self.before_file),
+            format!("after:; {}", self.after_file),
+            format!("alpha:; {}", utils::format_float(ctx, self.alpha)),
+            format!(
+                "num_significant_regressions:; {}",
+                self.num_significant_regressions
+            ),
+            "\n".to_owned(),
+            ComparisonLine::report_stats_line_header_str(),
+        ];
+        lines
+            .into_iter()
+            .for_each(|l| s.push(l.report_stats_line(ctx)));
+        s.push("\n".to_owned());
+        s.join("\n")
+    }
+
+    pub fn write_csv(&self, ctx: &AnalysisContext, path: &Path) {
+        let file_name = path.to_str().unwrap();
+        if let Err(err) = utils::write_string_to_file(file_name, self.to_csv_string(ctx)) {
+            panic!("Writing to file '{file_name}' failed with error: {err:?}");
+        };
+    }
+
+    pub fn write_json(&self, path: &Path) {
+        let file_name = path.to_str().unwrap();
+        let json = serde_json::to_string_pretty(self).expect("Failed to serialize the comparison");
+        if let Err(err) = utils::write_string_to_file(file_name, json) {
+            panic!("Writing to file '{file_name}' failed with error: {err:?}");
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ComparisonKind, ComparisonLine, ComparisonParameters, StatsComparison};
+    use crate::{
+        context::AnalysisContext,
+        stats::{
+            call_chain::{CChainStatsKey, CChainStatsValue},
+            ProcOperStatsValue, StatsRec,
+        },
+    };
+
+    const CHAIN: &str = "gw/GET:/x [Inbound] | a/get [Inbound] & &  *LEAF*";
+
+    /// 'count' durations that cycle through base, base+100, ..., base+400 micro-seconds
+    fn durations(count: usize, base: i64) -> Vec<i64> {
+        (0..count).map(|i| base + (i % 5) as i64 * 100).collect()
+    }
+
+    fn add_oper(sr: &mut StatsRec, service: &str, oper: &str, count: usize, base: i64) {
+        let posv = ProcOperStatsValue {
+            count,
+            duration_micros: durations(count, base),
+            num_not_http_ok: 1,
+            ..Default::default()
+        };
+        sr.stats
+            .entry(service.to_owned())
+            .or_default()
+            .operation
+            .0
+            .insert(oper.to_owned(), posv);
+    }
+
+    fn add_chain(sr: &mut StatsRec, count: usize, base: i64) {
+        let mut ccv = CChainStatsValue::new(2, Vec::new(), true);
+        ccv.count = count;
+        ccv.duration_micros = durations(count, base);
+        sr.stats
+            .get_mut("a")
+            .unwrap()
+            .call_chain
+            .0
+            .insert(CChainStatsKey::parse(CHAIN).unwrap(), ccv);
+    }
+
+    /// 'b/put' disappears, 'c/post' appears, 'a/get' doubles its duration and the call-chain slows down by 50%
+    fn comparison() -> StatsComparison {
+        let mut before = StatsRec {
+            num_files: 1,
+            ..Default::default()
+        };
+        add_oper(&mut before, "a", "get", 20, 10_000);
+        add_oper(&mut before, "b", "put", 20, 5_000);
+        add_chain(&mut before, 20, 10_000);
+        let mut after = StatsRec {
+            num_files: 1,
+            ..Default::default()
+        };
+        add_oper(&mut after, "a", "get", 25, 20_000);
+        add_oper(&mut after, "c", "post", 10, 1_000);
+        add_chain(&mut after, 20, 15_000);
+        StatsComparison::new(
+            &before,
+            &after,
+            "before.json",
+            "after.json",
+            &ComparisonParameters::default(),
+        )
+    }
+
+    #[test]
+    fn align_on_kind_and_key() {
+        let cmp = comparison();
+        let keys: Vec<_> = cmp
+            .lines
+            .iter()
+            .map(|l| {
+                (
+                    l.kind,
+                    l.key.as_str(),
+                    l.before.is_some(),
+                    l.after.is_some(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            keys,
+            [
+                (ComparisonKind::ServiceOper, "a/get", true, true),
+                (ComparisonKind::ServiceOper, "b/put", true, false),
+                (ComparisonKind::ServiceOper, "c/post", false, true),
+                (ComparisonKind::CallChain, CHAIN, true, true),
+            ]
+        );
+    }
+
+    #[test]
+    fn count_avg_and_p95_changes() {
+        let cmp = comparison();
+        let get = &cmp.lines[0];
+        assert_eq!(get.count_change, Some(0.25));
+        // the average goes from 10.2 to 20.2 milli-seconds and the p95 from 10.4 to 20.4
+        assert!((get.avg_millis_change.unwrap() - 10.0 / 10.2).abs() < 1e-9);
+        assert!((get.p95_millis_change.unwrap() - 10.0 / 10.4).abs() < 1e-9);
+        // an item that (dis)appeared has no changes and is not tested
+        let put = &cmp.lines[1];
+        assert_eq!(put.count_change, None);
+        assert_eq!(put.p_value, None);
+        assert!(!put.significant_regression);
+    }
+
+    #[test]
+    fn regressions_are_ranked_on_the_median_increase() {
+        let cmp = comparison();
+        assert_eq!(cmp.num_significant_regressions, 2);
+        let ranks: Vec<_> = cmp
+            .lines
+            .iter()
+            .map(|l| (l.significant_regression, l.regression_rank))
+            .collect();
+        assert_eq!(
+            ranks,
+            [
+                (true, Some(1)),
+                (false, None),
+                (false, None),
+                (true, Some(2))
+            ]
+        );
+        assert!(cmp.lines[0].p_value.unwrap() < 0.01);
+        assert!(cmp.lines[0].effect_size.unwrap() > 0.99);
+    }
+
+    #[test]
+    fn csv_lists_the_regressions_first() {
+        let csv = comparison().to_csv_string(&AnalysisContext::default());
+        let lines: Vec<_> = csv.lines().collect();
+        let header_idx = lines
+            .iter()
+            .position(|l| *l == ComparisonLine::report_stats_line_header_str())
+            .unwrap();
+        assert_eq!(lines[0], "before:; before.json");
+        assert_eq!(lines[3], "num_significant_regressions:; 2");
+
+        let rows = &lines[header_idx + 1..header_idx + 5];
+        assert!(rows[0].starts_with("service_oper; a/get; "));
+        assert!(rows[1].starts_with(&format!("call_chain; {CHAIN}; ")));
+        let num_columns = lines[header_idx].split(';').count();
+        rows.iter()
+            .for_each(|row| assert_eq!(row.split(';').count(), num_columns, "{row}"));
+    }
+
+    #[test]
+    fn json_fields() {
+        let json = serde_json::to_value(comparison()).unwrap();
+        assert_eq!(json["before_file"], "before.json");
+        assert_eq!(json["alpha"], 0.01);
+        assert_eq!(json["num_significant_regressions"], 2);
+        let line = &json["lines"][2];
+        assert_eq!(line["kind"], "service_oper");
+        assert_eq!(line["key"], "c/post");
+        assert!(line["before"].is_null());
+        assert_eq!(line["after"]["count"], 10);
+        assert_eq!(line["after"]["frac_not_http_ok"], 0.1);
+        // the raw samples are not part of the json
+        assert!(line["after"].get("duration_micros").is_none());
+        assert_eq!(json["lines"][3]["regression_rank"], 2);
+    }
+}
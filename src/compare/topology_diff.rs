@@ -0,0 +1,462 @@
+use crate::{
+    context::AnalysisContext,
+    stats::{
+        call_chain::{CChainStatsKey, CallDirection},
+        StatsRec,
+    },
+    utils, Metric, Stitched, StitchedSet,
+};
+use serde::Serialize;
+use std::{collections::BTreeMap, error::Error, ffi::OsString, path::Path};
+
+/// The kind of element of the topology (the landscape of services and their calls)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TopologyKind {
+    EndPoint,
+    Service,
+    ServiceOper,
+    Edge,
+    CallChain,
+}
+
+impl TopologyKind {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::EndPoint => "end_point",
+            Self::Service => "service",
+            Self::ServiceOper => "service_oper",
+            Self::Edge => "edge",
+            Self::CallChain => "call_chain",
+        }
+    }
+}
+
+/// The traffic that flows over an element of the topology
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct Traffic {
+    pub count: f64,
+    /// estimated rate (requests per second), which is the sum of the rates of the call-chains involved
+    pub rate: Option<f64>,
+}
+
+impl Traffic {
+    fn add(&mut self, count: f64, rate: Option<f64>) {
+        self.count += count;
+        self.rate = match (self.rate, rate) {
+            (Some(r1), Some(r2)) => Some(r1 + r2),
+            (r1, r2) => r1.or(r2),
+        };
+    }
+}
+
+/// The topology of a dataset derived from the call-chain keys. Each element is keyed on kind and label.
+#[derive(Debug, Default)]
+pub struct Topology(pub BTreeMap<(TopologyKind, String), Traffic>);
+
+impl Topology {
+    fn add(&mut self, kind: TopologyKind, key: String, count: f64, rate: Option<f64>) {
+        self.0.entry((kind, key)).or_default().add(count, rate);
+    }
+
+    /// Add a call-chain with its traffic. The traffic of a call-chain is the traffic of its last call, so the last call determines
+    /// the traffic of the service, the service/operation and the edge from its caller (outbound calls are skipped when determining the caller).
+    pub fn add_call_chain(
+        &mut self,
+        cck: &CChainStatsKey,
+        rooted: bool,
+        count: f64,
+        rate: Option<f64>,
+    ) {
+        if count <= 0.0 {
+            return;
+        }
+        self.add(TopologyKind::CallChain, cck.call_chain_key(), count, rate);
+
+        let calls: Vec<_> = cck
+            .call_chain
+            .iter()
+            .filter(|call| call.call_direction != CallDirection::Outbound)
+            .collect();
+        // an outbound call is counted via the inbound call of the callee
+        if cck
+            .call_chain
+            .last()
+            .is_none_or(|call| call.call_direction == CallDirection::Outbound)
+        {
+            return;
+        }
+        let Some(last) = calls.last() else {
+            return;
+        };
+        self.add(TopologyKind::Service, last.get_process(), count, rate);
+        self.add(
+            TopologyKind::ServiceOper,
+            last.get_process_method(),
+            count,
+            rate,
+        );
+        match calls.len() {
+            1 if rooted => self.add(
+                TopologyKind::EndPoint,
+                last.get_process_method(),
+                count,
+                rate,
+            ),
+            1 => (),
+            len => self.add(
+                TopologyKind::Edge,
+                format!(
+                    "{} -> {}",
+                    calls[len - 2].get_process_method(),
+                    last.get_process_method()
+                ),
+                count,
+                rate,
+            ),
+        }
+    }
+
+    /// Derive the topology of an analysis
+    pub fn from_stats_rec(sr: &StatsRec) -> Self {
+        let mut topology = Self::default();
+        sr.stats.values().for_each(|oper_stats| {
+            oper_stats.call_chain.0.iter().for_each(|(cck, ccv)| {
+                topology.add_call_chain(
+                    cck,
+                    ccv.rooted,
+                    ccv.count as f64,
+                    ccv.get_avg_rate(sr.num_files),
+                )
+            })
+        });
+        topology
+    }
+
+    /// Derive the topology of a single column (input-file) of a stitched dataset
+    pub fn from_stitched_column(stitched: &Stitched, column: usize) -> Self {
+        let mut topology = Self::default();
+        let value = |ccd_data: &StitchedSet, metric| {
+            ccd_data
+                .get_metric_stitched_line(metric)
+                .and_then(|line| line.data.get(column).copied().flatten())
+        };
+        stitched.call_chain.iter().for_each(|(_, call_chains)| {
+            call_chains.iter().for_each(|ccd| {
+                let Some(count) = value(&ccd.data, Metric::Count) else {
+                    return;
+                };
+                match CChainStatsKey::parse(&ccd.full_key) {
+                    Ok(cck) => topology.add_call_chain(
+                        &cck,
+                        ccd.rooted,
+                        count,
+                        value(&ccd.data, Metric::Rate),
+                    ),
+                    Err(err) => println!("Skipped call-chain '{}': {err}", ccd.full_key),
+                }
+            })
+        });
+        topology
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TopologyChangeType {
+    Added,
+    Removed,
+}
+
+/// An element of the topology that only exists in one of both datasets
+#[derive(Debug, Clone, Serialize)]
+pub struct TopologyChange {
+    pub kind: TopologyKind,
+    pub key: String,
+    pub change: TopologyChangeType,
+    /// traffic after the change for an added element and traffic before the change for a removed element
+    pub traffic: Traffic,
+}
+
+/// The topology changes between two datasets
+#[derive(Debug, Serialize)]
+pub struct TopologyDiff {
+    pub before: String,
+    pub after: String,
+    pub changes: Vec<TopologyChange>,
+}
+
+impl TopologyDiff {
+    pub fn new(before: &Topology, after: &Topology, before_label: &str, after_label: &str) -> Self {
+        let added = after
+            .0
+            .iter()
+            .filter(|(k, _)| !before.0.contains_key(k))
+            .map(|(k, traffic)| (k, TopologyChangeType::Added, traffic));
+        let removed = before
+            .0
+            .iter()
+            .filter(|(k, _)| !after.0.contains_key(k))
+            .map(|(k, traffic)| (k, TopologyChangeType::Removed, traffic));
+        let mut changes: Vec<_> = added
+            .chain(removed)
+            .map(|((kind, key), change, traffic)| TopologyChange {
+                kind: *kind,
+                key: key.to_owned(),
+                change,
+                traffic: *traffic,
+            })
+            .collect();
+        // per kind the elements with the highest traffic first
+        changes.sort_by(|a, b| {
+            a.kind
+                .cmp(&b.kind)
+                .then(b.traffic.count.total_cmp(&a.traffic.count))
+                .then(a.key.cmp(&b.key))
+        });
+        Self {
+            before: before_label.to_owned(),
+            after: after_label.to_owned(),
+            changes,
+        }
+    }
+
+    /// count the changes of a kind
+    fn num_changes(&self, kind: TopologyKind, change: TopologyChangeType) -> usize {
+        self.changes
+            .iter()
+            .filter(|c| c.kind == kind && c.change == change)
+            .count()
+    }
+
+    /// A one-line summary of the number of added and removed elements per kind
+    pub fn summary(&self) -> String {
+        [
+            TopologyKind::EndPoint,
+            TopologyKind::Service,
+            TopologyKind::ServiceOper,
+            TopologyKind::Edge,
+            TopologyKind::CallChain,
+        ]
+        .iter()
+        .map(|kind| {
+            format!(
+                "{}: +{}/-{}",
+                kind.as_str(),
+                self.num_changes(*kind, TopologyChangeType::Added),
+                self.num_changes(*kind, TopologyChangeType::Removed)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+    }
+
+    /// header for report_stats_lines output in ';'-separated csv-format
+    pub fn report_stats_line_header_str() -> &'static str {
+        "Before; After; Change; Kind; Key; Count; Rate"
+    }
+
+    /// reports the changes in ';'-separated csv-format
+    pub fn report_stats_lines(&self, ctx: &AnalysisContext) -> Vec<String> {
+        self.changes
+            .iter()
+            .map(|c| {
+                format!(
+                    "{}; {}; {}; {}; {}; {}; {}",
+                    self.before,
+                    self.after,
+                    match c.change {
+                        TopologyChangeType::Added => "added",
+                        TopologyChangeType::Removed => "removed",
+                    },
+                    c.kind.as_str(),
+                    c.key,
+                    utils::format_float(ctx, c.traffic.count),
+                    utils::format_float_opt(ctx, c.traffic.rate)
+                )
+            })
+            .collect()
+    }
+}
+
+/// The topology drift over a series of datasets, i.e. the diffs between each pair of subsequent datasets.
+#[derive(Debug, Default, Serialize)]
+pub struct TopologyDrift(pub Vec<TopologyDiff>);
+
+impl TopologyDrift {
+    /// read two StatsRec files and compute the topology diff
+    pub fn from_files(
+        ctx: &AnalysisContext,
+        before_file: &str,
+        after_file: &str,
+    ) -> Result<Self, Box<dyn Error>> {
+        let before = StatsRec::read_file(ctx, &OsString::from(before_file))?;
+        let after = StatsRec::read_file(ctx, &OsString::from(after_file))?;
+        Ok(Self(vec![TopologyDiff::new(
+            &Topology::from_stats_rec(&before),
+            &Topology::from_stats_rec(&after),
+            before_file,
+            after_file,
+        )]))
+    }
+
+    /// compute the topology diff between the subsequent columns of a stitched dataset. Empty columns (missing input-files) are skipped.
+    pub fn from_stitched(stitched: &Stitched) -> Self {
+        let labels: BTreeMap<_, _> = stitched
+            .sources
+            .0
+            .iter()
+            .filter_map(|src| {
+                src.column
+                    .map(|col| (col as usize, src.description.as_str()))
+            })
+            .collect();
+        let num_columns = stitched
+            .basic
+            .0
+            .first()
+            .map_or(labels.len(), |line| line.data.len());
+        let label = |col: usize| {
+            labels
+                .get(&col)
+                .map_or(format!("column {col}"), |descr| format!("{col}: {descr}"))
+        };
+
+        let mut diffs = Vec::new();
+        let mut prev: Option<(usize, Topology)> = None;
+        for col in 0..num_columns {
+            let topology = Topology::from_stitched_column(stitched, col);
+            if topology.is_empty() {
+                continue;
+            }
+            if let Some((prev_col, prev_topology)) = &prev {
+                diffs.push(TopologyDiff::new(
+                    prev_topology,
+                    &topology,
+                    &label(*prev_col),
+                    &label(col),
+                ));
+            }
+            prev = Some((col, topology));
+        }
+        Self(diffs)
+    }
+
+    pub fn num_changes(&self) -> usize {
+        self.0.iter().map(|diff| diff.changes.len()).sum()
+    }
+
+    /// Show the drift as a ';'-separated csv-string, starting with a summary line per diff.
+    pub fn to_csv_string(&self, ctx: &AnalysisContext) -> String {
+        let mut s = vec!["Before; After; Summary".to_owned()];
+        self.0.iter().for_each(|diff| {
+            s.push(format!(
+                "{}; {}; {}",
+                diff.before,
+                diff.after,
+                diff.summary()
+            ))
+        });
+        s.push("\n".to_owned());
+        s.push(TopologyDiff::report_stats_line_header_str().to_owned());
+        self.0
+            .iter()
+            .for_each(|diff| s.extend(diff.report_stats_lines(ctx)));
+        s.push("\n".to_owned());
+        s.join("\n")
+    }
+
+    pub fn write_csv(&self, ctx: &AnalysisContext, path: &Path) {
+        let file_name = path.to_str().unwrap();
+        if let Err(err) = utils::write_string_to_file(file_name, self.to_csv_string(ctx)) {
+            panic!("Writing to file '{file_name}' failed with error: {err:?}");
+        };
+    }
+
+    pub fn write_json(&self, path: &Path) {
+        let file_name = path.to_str().unwrap();
+        let json =
+            serde_json::to_string_pretty(self).expect("Failed to serialize the topology drift");
+        if let Err(err) = utils::write_string_to_file(file_name, json) {
+            panic!("Writing to file '{file_name}' failed with error: {err:?}");
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Topology, TopologyChangeType, TopologyDiff, TopologyKind};
+    use crate::stats::call_chain::CChainStatsKey;
+
+    fn topology(chains: &[(&str, f64)]) -> Topology {
+        let mut topology = Topology::default();
+        chains.iter().for_each(|(key, count)| {
+            let cck = CChainStatsKey::parse(key).unwrap();
+            topology.add_call_chain(&cck, true, *count, None)
+        });
+        topology
+    }
+
+    #[test]
+    fn edges_skip_outbound_calls() {
+        let t = topology(&[
+            ("gw/GET:/x [Unknown] & &", 10.0),
+            ("gw/GET:/x [Unknown] | gw/GET [Outbound] & &", 10.0),
+            (
+                "gw/GET:/x [Unknown] | gw/GET [Outbound] | a/get [Inbound] & & *LEAF*",
+                10.0,
+            ),
+        ]);
+        let edge = (TopologyKind::Edge, "gw/GET:/x -> a/get".to_owned());
+        assert_eq!(t.0.get(&edge).unwrap().count, 10.0);
+        assert_eq!(
+            t.0.get(&(TopologyKind::EndPoint, "gw/GET:/x".to_owned()))
+                .unwrap()
+                .count,
+            10.0
+        );
+        // the outbound call is not a service/operation of its own
+        assert!(!t
+            .0
+            .contains_key(&(TopologyKind::ServiceOper, "gw/GET".to_owned())));
+        assert_eq!(
+            t.0.keys()
+                .filter(|(kind, _)| *kind == TopologyKind::CallChain)
+                .count(),
+            3
+        );
+    }
+
+    #[test]
+    fn diff_reports_added_and_removed() {
+        let before = topology(&[
+            ("gw/GET:/x [Unknown] & &", 10.0),
+            ("gw/GET:/x [Unknown] | a/get [Inbound] & & *LEAF*", 10.0),
+        ]);
+        let after = topology(&[
+            ("gw/GET:/x [Unknown] & &", 12.0),
+            ("gw/GET:/x [Unknown] | b/get [Inbound] & & *LEAF*", 8.0),
+        ]);
+        let diff = TopologyDiff::new(&before, &after, "before", "after");
+        let find = |kind, key: &str| {
+            diff.changes
+                .iter()
+                .find(|c| c.kind == kind && c.key == key)
+                .map(|c| (c.change, c.traffic.count))
+        };
+        assert_eq!(
+            find(TopologyKind::Service, "b"),
+            Some((TopologyChangeType::Added, 8.0))
+        );
+        assert_eq!(
+            find(TopologyKind::Edge, "gw/GET:/x -> a/get"),
+            Some((TopologyChangeType::Removed, 10.0))
+        );
+        assert_eq!(find(TopologyKind::EndPoint, "gw/GET:/x"), None);
+        assert_eq!(diff.changes.len(), 8);
+    }
+}
@@ -0,0 +1,146 @@
+//! The AnalysisContext holds the settings of an analysis (formatting of floats, timezone, ...) and collects the report and the input-files.
+//! Each context is independent, so multiple analyses can run in one process (for example in a server, or tests running in parallel).
+//!
+//! The context is passed explicitly to the library-calls that read, format or report, such as 'analyze_file_or_folder', 'read_file_or_folder', 'write_stats' and 'Stitched::build'.
+use crate::{
+    raw::FileTracker,
+    utils::{Report, Timezone},
+};
+use regex::Regex;
+use std::{error::Error, fs, io, mem, path::Path, str::FromStr};
+
+/// A rule that replaces all matches of the pattern by the replacement, for example to normalise an id in an operation-name.
+#[derive(Debug, Clone)]
+pub struct ReplaceRule {
+    pub replacement: String,
+    pub pattern: Regex,
+}
+
+/// Parse a rule of the form '<replacement>=<regex>', for example '/{ORDER}=/orders/\d+'
+impl FromStr for ReplaceRule {
+    type Err = Box<dyn Error>;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let Some((replacement, pattern)) = s.split_once('=') else {
+            Err(format!(
+                "Rule '{s}' should have format '<replacement>=<regex>'"
+            ))?
+        };
+        Ok(Self {
+            replacement: replacement.to_owned(),
+            pattern: Regex::new(pattern)?,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AnalysisContext {
+    /// Replace the decimal point of floats by a comma in the csv-output (for a Dutch Excel)
+    pub comma_float: bool,
+    /// The timezone used to show the UTC-timestamps of the traces as local date-times
+    pub timezone: Timezone,
+    /// Log-messages are truncated to this length
+    pub max_log_msg_length: usize,
+    /// The mermaid-library that is inlined in the html-reports to render the diagrams (no diagram rendering if None)
+    pub mermaid_js: Option<String>,
+    /// Additional rules to normalise operation-names (applied after the built-in rules)
+    pub normalisation_rules: Vec<ReplaceRule>,
+    /// Additional rules to derive templates of error-messages (applied before the built-in rules)
+    pub error_rules: Vec<ReplaceRule>,
+    pub(crate) report: Report,
+    pub(crate) file_tracker: FileTracker,
+}
+
+impl Default for AnalysisContext {
+    fn default() -> Self {
+        Self {
+            comma_float: false,
+            timezone: Timezone::default(),
+            max_log_msg_length: 100,
+            mermaid_js: None,
+            normalisation_rules: Vec::new(),
+            error_rules: Vec::new(),
+            report: Report::default(),
+            file_tracker: FileTracker::default(),
+        }
+    }
+}
+
+impl AnalysisContext {
+    pub fn new(comma_float: bool, timezone: Timezone) -> Self {
+        Self {
+            comma_float,
+            timezone,
+            ..Default::default()
+        }
+    }
+
+    /// Read a local copy of 'mermaid.min.js' which will be inlined in the html-pages, such that the diagrams are rendered without external assets.
+    pub fn set_mermaid_js(&mut self, path: &Path) -> io::Result<()> {
+        self.mermaid_js = Some(fs::read_to_string(path)?);
+        Ok(())
+    }
+
+    /// The report collected so far
+    pub fn report(&self) -> &Report {
+        &self.report
+    }
+
+    /// Take the report collected so far, leaving an empty report in the context.
+    pub fn take_report(&mut self) -> Report {
+        mem::take(&mut self.report)
+    }
+
+    /// The input-files read in this context
+    pub fn files(&self) -> &[String] {
+        self.file_tracker.files()
+    }
+
+    /// A context with the same settings but without comma-floats and with an empty report, which is used to produce typed output (such as xlsx) that does not depend on the locale.
+    pub(crate) fn without_comma_float(&self) -> Self {
+        Self {
+            comma_float: false,
+            timezone: self.timezone,
+            max_log_msg_length: self.max_log_msg_length,
+            mermaid_js: self.mermaid_js.clone(),
+            normalisation_rules: self.normalisation_rules.clone(),
+            error_rules: self.error_rules.clone(),
+            report: Report::default(),
+            file_tracker: FileTracker::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::AnalysisContext;
+    use crate::utils::{self, Chapter, Timezone};
+
+    #[test]
+    fn contexts_are_independent() {
+        let mut dutch = AnalysisContext::new(true, Timezone::default());
+        let mut plain = AnalysisContext::default();
+
+        utils::report(
+            &mut dutch.report,
+            Chapter::Issues,
+            "test",
+            "first".to_owned(),
+        );
+        utils::report(
+            &mut plain.report,
+            Chapter::Issues,
+            "test",
+            "second".to_owned(),
+        );
+
+        assert_eq!(utils::format_float(&dutch, 1.5), "1,5");
+        assert_eq!(utils::format_float(&plain, 1.5), "1.5");
+        assert_eq!(
+            utils::format_float(&dutch.without_comma_float(), 1.5),
+            "1.5"
+        );
+        assert_eq!(dutch.report().lines(Chapter::Issues), ["first"]);
+        assert_eq!(plain.report().lines(Chapter::Issues), ["second"]);
+    }
+}
@@ -1,3 +1,5 @@
+mod compare;
+mod context;
 mod mermaid;
 mod processed;
 mod raw;
@@ -9,24 +11,33 @@ mod view_api;
 mod graph;
 mod stitch;
 
+pub use compare::{ComparisonParameters, StatsComparison, TopologyDrift};
+pub use context::{AnalysisContext, ReplaceRule};
 pub use graph::build_graph;
 pub use raw::{
     read_file_or_folder, read_jaeger_trace_file, write_traces, JaegerItem, JaegerLog, JaegerSpan,
     JaegerTags, JaegerTrace,
 };
-pub use stats::{chained_stats, file::StatsRecJson, CChainEndPointCache, StatsRec};
+pub use stats::{
+    call_chain::{CChainCatalog, CChainRepair, RepairMode},
+    chained_stats,
+    file::{read_sqlite, to_openmetrics, write_sqlite, write_stats, StatsRecJson},
+    CChainEndPointCache, StatsRec,
+};
 pub use utils::{
     current_folder, datetime_micros_str, datetime_millis_str, datetime_to_micros, hash,
-    micros_to_datetime, report, set_comma_float, set_tz_offset_minutes, string_hash, write_report,
+    micros_to_datetime, report, string_hash, Chapter, Report, ReportCounters, ReportEntry,
+    Severity, Timezone,
 };
 
 pub use stitch::{
-    AnomalyParameters, BestFit, StitchList, StitchParameters, Stitched, StitchedDataSet,
-    StitchedLine, StitchedSet,
+    AnomalyParameters, AnomalyReport, AnomalySeverity, BestFit, ForecastThreshold, SeverityRule,
+    SeverityRules, StitchList, StitchParameters, Stitched, StitchedDataSet, StitchedLine,
+    StitchedSet,
 };
 pub use trace_analysis::{analyze_file_or_folder, TraceDataSet};
 pub use view_api::{
     load_viewer,
     types::{self, ServiceOperString},
-    MermaidScope, Metric, TraceScope, ViewError, Viewer,
+    HtmlParameters, MermaidScope, Metric, TraceScope, Version, ViewError, Viewer,
 };
@@ -0,0 +1,110 @@
+use chrono::NaiveDate;
+use clap::{Parser, Subcommand};
+use jaeger_stats::CChainCatalog;
+use std::{fs, path::Path, process};
+
+/// Inspection and maintenance of the catalog of call-chains (a folder with '.cchain' files)
+#[derive(Parser, Debug)]
+#[command(author, version, about, long_about = None)]
+struct Args {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// List the end-points with the number of call-chains
+    List { folder: String },
+    /// Show the differences between two catalogs ('-' only in the first, '+' only in the second)
+    Diff { folder: String, other: String },
+    /// Remove the call-chains that have not been seen since a date (format yyyy-mm-dd)
+    Prune {
+        folder: String,
+        #[arg(short, long)]
+        since: NaiveDate,
+        /// Also remove the call-chains that do not have a last-seen date (written by older versions)
+        #[arg(long, default_value_t = false)]
+        include_undated: bool,
+        /// Only report the number of chains that would be removed
+        #[arg(long, default_value_t = false)]
+        dry_run: bool,
+    },
+    /// Merge the call-chains of one or more source catalogs into the target catalog
+    Merge {
+        target: String,
+        #[arg(required = true)]
+        sources: Vec<String>,
+    },
+    /// Check whether every line of the cchain-files can be parsed
+    Validate { folder: String },
+}
+
+fn read_catalog(folder: &str) -> CChainCatalog {
+    CChainCatalog::read(Path::new(folder)).unwrap_or_else(|err| {
+        eprintln!("Failed to read the catalog '{folder}': {err}");
+        process::exit(1);
+    })
+}
+
+fn main() {
+    let args = Args::parse();
+
+    match args.command {
+        Command::List { folder } => {
+            let catalog = read_catalog(&folder);
+            println!("{}", CChainCatalog::list_header_str());
+            catalog.list().iter().for_each(|line| println!("{line}"));
+        }
+        Command::Diff { folder, other } => {
+            let catalog = read_catalog(&folder);
+            let other = read_catalog(&other);
+            let diff = catalog.diff(&other);
+            if diff.is_empty() {
+                println!("No differences found.");
+            } else {
+                diff.iter().for_each(|line| println!("{line}"));
+            }
+        }
+        Command::Prune {
+            folder,
+            since,
+            include_undated,
+            dry_run,
+        } => {
+            let mut catalog = read_catalog(&folder);
+            let num_removed = catalog.prune(since, include_undated);
+            if dry_run {
+                println!("Would remove {num_removed} call-chains not seen since {since}.");
+            } else {
+                let num_files = catalog.write();
+                println!("Removed {num_removed} call-chains not seen since {since} ({num_files} files updated).");
+            }
+        }
+        Command::Merge { target, sources } => {
+            // the target catalog can be a new (empty) folder
+            if let Err(err) = fs::create_dir_all(&target) {
+                eprintln!("Failed to create the catalog '{target}': {err}");
+                process::exit(1);
+            }
+            let mut catalog = read_catalog(&target);
+            sources.iter().for_each(|source| {
+                let num_added = catalog.merge(read_catalog(source));
+                println!("Added {num_added} call-chains from '{source}'.");
+            });
+            let num_files = catalog.write();
+            println!("Updated {num_files} files in '{target}'.");
+        }
+        Command::Validate { folder } => {
+            let issues = CChainCatalog::validate(Path::new(&folder)).unwrap_or_else(|err| {
+                eprintln!("Failed to validate the catalog '{folder}': {err}");
+                process::exit(1);
+            });
+            if issues.is_empty() {
+                println!("All call-chains in '{folder}' are valid.");
+            } else {
+                issues.iter().for_each(|issue| println!("{issue}"));
+                process::exit(1);
+            }
+        }
+    }
+}
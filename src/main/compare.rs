@@ -0,0 +1,60 @@
+use clap::{ArgAction, Parser};
+use jaeger_stats::{AnalysisContext, ComparisonParameters, StatsComparison, Timezone};
+use std::{path::Path, process};
+
+/// Compare two analyses (for example 'cummulative_trace_stats.json' from before and after a release) and rank the significant regressions
+#[derive(Parser, Debug)]
+#[command(author, version, about, long_about = None)]
+struct Args {
+    /// The statistics file of the analysis before the change
+    before: String,
+
+    /// The statistics file of the analysis after the change
+    after: String,
+
+    #[arg(short, long, default_value_t = String::from("comparison.csv"))]
+    output: String,
+
+    #[arg(short, long, default_value_t = String::from("comparison.json"))]
+    json: String,
+
+    /// Replace the decimal point by a comma in the csv-output (switch off via '--comma-float=false')
+    #[arg(short, long, default_value_t = true, action = ArgAction::Set, num_args = 0..=1, default_missing_value = "true")]
+    comma_float: bool,
+
+    /// Significance level of the Mann-Whitney U test on the durations
+    #[arg(long, default_value_t = 0.01)]
+    alpha: f64,
+
+    /// Minimal number of duration samples in both analyses to apply the test
+    #[arg(long, default_value_t = 8)]
+    min_samples: usize,
+}
+
+fn main() {
+    let args = Args::parse();
+
+    let ctx = AnalysisContext::new(args.comma_float, Timezone::default());
+
+    let pars = ComparisonParameters {
+        alpha: args.alpha,
+        min_samples: args.min_samples,
+    };
+
+    let comparison = StatsComparison::from_files(&ctx, &args.before, &args.after, &pars)
+        .unwrap_or_else(|err| {
+            eprintln!("Failed to read the statistics files: {err}");
+            process::exit(1);
+        });
+
+    comparison.write_csv(&ctx, Path::new(&args.output));
+    comparison.write_json(Path::new(&args.json));
+
+    println!(
+        "Compared {} items and found {} significant regressions.\n\tFor further information check files '{}' and '{}'",
+        comparison.lines.len(),
+        comparison.num_significant_regressions,
+        args.output,
+        args.json
+    );
+}
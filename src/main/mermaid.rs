@@ -1,5 +1,7 @@
 use clap::Parser;
-use jaeger_stats::{load_viewer, utils, MermaidScope, Metric, TraceDataSet, Viewer};
+use jaeger_stats::{
+    load_viewer, utils, AnalysisContext, MermaidScope, Metric, TraceDataSet, Viewer,
+};
 
 /// Parsing and analyzin}g Jaeger traces
 
@@ -50,8 +52,9 @@ fn get_numbered_lines(data: Vec<String>) -> String {
 
 fn main() {
     let args = Args::parse();
+    let ctx = AnalysisContext::default();
 
-    match load_viewer(&args.input) {
+    match load_viewer(&ctx, &args.input) {
         Ok(viewer) => {
             println!(
                 "Read the file '{}' as generic.  has time-series {}",
@@ -62,7 +65,7 @@ fn main() {
         Err(err) => panic!("Reading '{}' failed with error: {err:?}", args.input),
     }
 
-    match TraceDataSet::from_file(&args.input) {
+    match TraceDataSet::from_file(&ctx, &args.input) {
         Ok(trace_data_set) => {
             println!("Successfully read a TraceDataSet from file {}", args.input);
 
@@ -0,0 +1,130 @@
+use clap::Parser;
+use jaeger_stats::{
+    utils::{self, FileHeader, FileKind},
+    write_stats, AnalysisContext, StatsRec, Stitched,
+};
+use std::{error::Error, ffi::OsString, fs, path::Path, process};
+
+/// Upgrade statistics files and stitched files (json or bincode) in place to the current file-format version
+#[derive(Parser, Debug)]
+#[command(author, version, about, long_about = None)]
+struct Args {
+    /// The files to be migrated
+    #[arg(required = true)]
+    files: Vec<String>,
+
+    /// Keep a copy of the original file as '<file>.bak'
+    #[arg(short, long, default_value_t = false)]
+    backup: bool,
+
+    /// Only report the kind and version of the files without rewriting them
+    #[arg(short, long, default_value_t = false)]
+    dry_run: bool,
+}
+
+/// Determine the kind and version of the file. Bincode files written before the version-header was introduced return None.
+fn detect_header(path: &Path) -> Result<Option<FileHeader>, Box<dyn Error>> {
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some("json") => Ok(Some(utils::peek_json_header(path)?)),
+        Some("bincode") => Ok(utils::read_bincode(path)?.0),
+        _ => Err(format!(
+            "'{}' is not a json or bincode file",
+            path.display()
+        ))?,
+    }
+}
+
+/// Read the file, which migrates it to the current version in memory
+enum Data {
+    Stats(StatsRec),
+    Stitched(Stitched),
+}
+
+impl Data {
+    fn load(file_name: &str, kind: Option<FileKind>) -> Result<Self, Box<dyn Error>> {
+        let stats = || {
+            StatsRec::read_file(&AnalysisContext::default(), &OsString::from(file_name))
+                .map(Data::Stats)
+        };
+        let stitched = || Stitched::from_file(file_name).map(Data::Stitched);
+        match kind {
+            Some(FileKind::Stats) => stats(),
+            Some(FileKind::Stitched) => stitched(),
+            // a bincode-file without header is either a statistics or a stitched file
+            None => stats().or_else(|_| stitched()),
+        }
+    }
+
+    fn kind(&self) -> FileKind {
+        match self {
+            Data::Stats(_) => FileKind::Stats,
+            Data::Stitched(_) => FileKind::Stitched,
+        }
+    }
+
+    fn write(self, file_name: &str, ext: &str) {
+        match self {
+            Data::Stats(stats) => write_stats(&AnalysisContext::default(), file_name, stats, ext),
+            Data::Stitched(stitched) => stitched.to_json(file_name),
+        }
+    }
+}
+
+fn migrate(file_name: &str, args: &Args) -> Result<(), Box<dyn Error>> {
+    let path = Path::new(file_name);
+    let header = detect_header(path)?;
+    let found = match header {
+        Some(header) => {
+            header.check(path, header.kind)?;
+            if header.version == header.kind.current_version() {
+                println!(
+                    "'{file_name}': {} file of version {} is already current",
+                    header.kind, header.version
+                );
+                return Ok(());
+            }
+            header.version.to_string()
+        }
+        None => "(no header)".to_owned(),
+    };
+
+    let data = Data::load(file_name, header.map(|h| h.kind))?;
+    let kind = data.kind();
+    println!(
+        "'{file_name}': {kind} file of version {found} -> {}",
+        kind.current_version()
+    );
+    if args.dry_run {
+        return Ok(());
+    }
+
+    // write to a temporary file first, such that a failure does not damage the original file
+    let ext = path.extension().and_then(|ext| ext.to_str()).unwrap();
+    let tmp_file = format!("{file_name}.tmp.{ext}");
+    data.write(&tmp_file, ext);
+    if args.backup {
+        fs::copy(file_name, format!("{file_name}.bak"))?;
+    }
+    fs::rename(&tmp_file, file_name)?;
+    Ok(())
+}
+
+fn main() {
+    let args = Args::parse();
+
+    let failures = args
+        .files
+        .iter()
+        .filter(|file_name| match migrate(file_name, &args) {
+            Ok(()) => false,
+            Err(err) => {
+                eprintln!("Failed to migrate '{file_name}': {err}");
+                true
+            }
+        })
+        .count();
+
+    if failures > 0 {
+        process::exit(1);
+    }
+}
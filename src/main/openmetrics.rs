@@ -0,0 +1,94 @@
+use clap::Parser;
+use jaeger_stats::{to_openmetrics, AnalysisContext, StatsRec};
+use std::{ffi::OsString, fs, process, time::SystemTime};
+use tiny_http::{Header, Response, Server};
+
+const CONTENT_TYPE: &str = "application/openmetrics-text; version=1.0.0; charset=utf-8";
+
+/// Export the Rate/Errors/Duration metrics per service/operation and per edge of an analysis in OpenMetrics text-format (for Prometheus)
+#[derive(Parser, Debug)]
+#[command(author, version, about, long_about = None)]
+struct Args {
+    /// The statistics file of the analysis (for example 'cummulative_trace_stats.json')
+    stats_file: String,
+
+    #[arg(short, long, default_value_t = String::from("metrics.prom"))]
+    output: String,
+
+    /// Serve the metrics on 'http://<address>/metrics' (for example '127.0.0.1:9464'). The statistics file is reloaded when it changes, so a scrape always returns the latest analysis.
+    #[arg(short, long)]
+    serve: Option<String>,
+}
+
+/// The metrics of a statistics file and the modification-time of the file they were derived from
+struct Metrics {
+    path: OsString,
+    modified: Option<SystemTime>,
+    text: String,
+}
+
+impl Metrics {
+    fn load(path: &str) -> Result<Self, String> {
+        let path = OsString::from(path);
+        let modified = fs::metadata(&path).and_then(|md| md.modified()).ok();
+        let stats = StatsRec::read_file(&AnalysisContext::default(), &path)
+            .map_err(|err| format!("Failed to read '{}': {err}", path.to_string_lossy()))?;
+        Ok(Self {
+            path,
+            modified,
+            text: to_openmetrics(&stats),
+        })
+    }
+
+    /// Reload the metrics if the statistics file has been modified. On failure the previous metrics are kept.
+    fn refresh(&mut self) {
+        let modified = fs::metadata(&self.path).and_then(|md| md.modified()).ok();
+        if modified.is_some() && modified != self.modified {
+            match Metrics::load(&self.path.to_string_lossy()) {
+                Ok(metrics) => *self = metrics,
+                Err(err) => eprintln!("{err}"),
+            }
+        }
+    }
+}
+
+fn serve(address: &str, mut metrics: Metrics) {
+    let server = Server::http(address).unwrap_or_else(|err| {
+        eprintln!("Failed to listen on '{address}': {err}");
+        process::exit(1);
+    });
+    let content_type = Header::from_bytes("Content-Type", CONTENT_TYPE).unwrap();
+    println!("Serving metrics on 'http://{address}/metrics'");
+
+    for request in server.incoming_requests() {
+        let response = match request.url() {
+            "/metrics" => {
+                metrics.refresh();
+                Response::from_string(metrics.text.clone()).with_header(content_type.clone())
+            }
+            _ => Response::from_string("Not found (use '/metrics')").with_status_code(404),
+        };
+        if let Err(err) = request.respond(response) {
+            eprintln!("Failed to send response: {err}");
+        }
+    }
+}
+
+fn main() {
+    let args = Args::parse();
+
+    let metrics = Metrics::load(&args.stats_file).unwrap_or_else(|err| {
+        eprintln!("{err}");
+        process::exit(1);
+    });
+
+    if let Err(err) = fs::write(&args.output, &metrics.text) {
+        eprintln!("Failed to write '{}': {err}", args.output);
+        process::exit(1);
+    }
+    println!("Metrics written to: '{}'", args.output);
+
+    if let Some(address) = args.serve {
+        serve(&address, metrics);
+    }
+}
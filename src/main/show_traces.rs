@@ -1,4 +1,5 @@
 use clap::Parser;
+use jaeger_stats::{AnalysisContext, Timezone};
 use std::path::Path;
 
 /// Show the Jaeger-traces, or a selection of jaeger-traces, as Pretty-printed JSON in UTF-8 format.
@@ -18,12 +19,23 @@ struct Args {
     // call_chain_folder: String,
     #[arg(short = 'z', long, default_value_t = 2*60)]
     timezone_minutes: i64,
+
+    /// An IANA timezone such as 'Europe/Amsterdam' to show the timestamps with daylight-saving time. This overrides the fixed offset of '--timezone-minutes'.
+    #[arg(long)]
+    timezone: Option<Timezone>,
 }
 
 fn main() {
     let args = Args::parse();
 
-    let (traces, num_files, path) = jaeger_stats::read_file_or_folder(Path::new(&args.input));
+    let mut ctx = AnalysisContext::new(
+        false,
+        args.timezone
+            .unwrap_or(Timezone::Offset(args.timezone_minutes)),
+    );
+
+    let (traces, num_files, path) =
+        jaeger_stats::read_file_or_folder(&mut ctx, Path::new(&args.input));
 
     println!("Extracted {} traces from {num_files} files.", traces.len());
 
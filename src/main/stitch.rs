@@ -1,11 +1,20 @@
-use clap::Parser;
-use jaeger_stats::{set_comma_float, AnomalyParameters, StitchList, StitchParameters, Stitched};
-use std::path::Path;
+use clap::{ArgAction, Parser};
+use jaeger_stats::{
+    utils, AnalysisContext, AnomalyParameters, AnomalySeverity, ForecastThreshold, HtmlParameters,
+    SeverityRule, SeverityRules, StitchList, StitchParameters, Stitched, StitchedDataSet, Timezone,
+};
+use serde::Serialize;
+use std::{path::Path, process};
 
 /// Stitching results of different runs of trace_analysis into a single CSV for visualization in Excel
-#[derive(Parser, Debug)]
-#[command(author, version, about, long_about = None)]
+#[derive(Parser, Debug, Serialize)]
+#[command(author, version, about, long_about = None, args_override_self = true)]
 struct Args {
+    /// A TOML config-file with values for the flags below (flags on the command-line override the values of the config-file)
+    #[arg(long)]
+    #[serde(skip)]
+    config: Option<String>,
+
     // List of files to be stitched
     #[arg(short, long, default_value_t = String::from("input.stitch"))]
     stitch_list: String,
@@ -13,15 +22,44 @@ struct Args {
     #[arg(short, long, default_value_t = String::from("stitched.csv"))]
     output: String,
 
+    /// An existing stitched dataset ('.bincode' or '.json') to which the analyses of the stitch-list are appended as new columns
+    #[arg(long)]
+    append: Option<String>,
+
+    /// The file ('.bincode' or '.json') to which the stitched dataset is written (by default the '--append' file, or else 'stitched.bincode')
+    #[arg(long)]
+    stitched_output: Option<String>,
+
     #[arg(short, long, default_value_t = String::from("anomalies.csv"))]
     anomalies: String,
 
-    #[arg(short, long, default_value_t = true)]
+    /// Also write the anomalies as a json-report with the key, metric, kind, score, severity and recent values of each anomaly
+    #[arg(long)]
+    anomalies_json: Option<String>,
+
+    /// Rule '<severity>=<metric>[@<key-regex>]' that sets the severity of anomalies, for example 'critical=p95-millis@^gateway/' or 'low=*@health' (can be repeated, the first matching rule applies)
+    #[arg(long)]
+    severity_rule: Vec<String>,
+
+    /// The severity of anomalies that do not match any severity-rule
+    #[arg(long, value_enum, default_value_t = AnomalySeverity::Medium)]
+    default_severity: AnomalySeverity,
+
+    /// Exit with code 2 when anomalies of at least this severity are detected (for use in a CI-pipeline)
+    #[arg(long, value_enum)]
+    fail_on: Option<AnomalySeverity>,
+
+    /// Replace the decimal point by a comma in the csv-output (switch off via '--comma-float=false')
+    #[arg(short, long, default_value_t = true, action = ArgAction::Set, num_args = 0..=1, default_missing_value = "true")]
     comma_float: bool,
 
     #[arg(short, long, default_value_t = 0)]
     drop_count: usize,
 
+    /// The number of threads used to read the analyses (each thread holds one analysis in memory at a time)
+    #[arg(long, default_value_t = 1)]
+    read_threads: usize,
+
     #[arg(long, default_value_t = 0.05)]
     scaled_slope_bound: f64,
 
@@ -33,45 +71,181 @@ struct Args {
 
     #[arg(long, default_value_t = 2.0)]
     l1_dev_bound: f64,
+
+    /// The length of a season in columns (for example 7 for daily analyses with a weekly pattern). Anomalies are then detected on the residuals of a seasonal model (0 = no seasonal model).
+    #[arg(long, default_value_t = 0)]
+    seasonal_period: usize,
+
+    /// The minimal score (shift of the level divided by its standard error) of a change-point (0.0 = no change-point detection).
+    #[arg(long, default_value_t = 4.0)]
+    change_point_bound: f64,
+
+    /// Threshold '<metric>=<value>' for the capacity forecast, for example 'rate=500' or 'p95-millis=800' (can be repeated)
+    #[arg(long)]
+    threshold: Vec<String>,
+
+    /// The output-file of the capacity forecast (only written when thresholds are given)
+    #[arg(long, default_value_t = String::from("forecast.csv"))]
+    forecast: String,
+
+    /// Also write the stitched data as a long-format parquet-table 'stitched.parquet'
+    #[arg(long, default_value_t = false, action = ArgAction::Set, num_args = 0..=1, default_missing_value = "true")]
+    parquet: bool,
+
+    /// Also write the stitched data to the SQLite database 'stitched.sqlite'
+    #[arg(long, default_value_t = false, action = ArgAction::Set, num_args = 0..=1, default_missing_value = "true")]
+    sqlite: bool,
+
+    /// Also write the stitched data and the anomalies as xlsx-workbooks (next to the csv-files)
+    #[arg(long, default_value_t = false, action = ArgAction::Set, num_args = 0..=1, default_missing_value = "true")]
+    xlsx: bool,
+
+    /// Also write a self-contained html-report next to the csv-file (by default 'stitched.html')
+    #[arg(long, default_value_t = false, action = ArgAction::Set, num_args = 0..=1, default_missing_value = "true")]
+    html: bool,
+
+    /// A local copy of 'mermaid.min.js' that is inlined in the html-report to render the diagrams
+    #[arg(long)]
+    mermaid_js: Option<String>,
 }
 
 fn main() {
-    let args = Args::parse();
+    let args: Args = utils::parse_args_with_config();
 
     let stitch_list_path = Path::new(&args.stitch_list);
 
-    set_comma_float(args.comma_float);
+    // the effective configuration allows to reproduce this run
+    utils::write_config(
+        &Path::new(&args.output).with_file_name("stitch_config.toml"),
+        &args,
+    );
+
+    let mut ctx = AnalysisContext::new(args.comma_float, Timezone::default());
+    if let Some(mermaid_js) = &args.mermaid_js {
+        if let Err(err) = ctx.set_mermaid_js(Path::new(mermaid_js)) {
+            eprintln!("Failed to read '{mermaid_js}': {err}");
+            process::exit(1);
+        }
+    }
 
     let stitch_pars = {
         let scaled_slope_bound = args.scaled_slope_bound;
         let st_num_points = args.st_num_points;
         let scaled_st_slope_bound = args.scaled_st_slope_bound;
         let l1_dev_bound = args.l1_dev_bound;
+        let seasonal_period = args.seasonal_period;
+        let change_point_bound = args.change_point_bound;
         StitchParameters {
             drop_count: args.drop_count,
+            read_threads: args.read_threads,
             anomaly_pars: AnomalyParameters {
                 scaled_slope_bound,
                 st_num_points,
                 scaled_st_slope_bound,
                 l1_dev_bound,
+                seasonal_period,
+                change_point_bound,
             },
         }
     };
 
+    let thresholds: Vec<ForecastThreshold> = args
+        .threshold
+        .iter()
+        .map(|threshold| {
+            threshold.parse().unwrap_or_else(|err| {
+                eprintln!("Invalid threshold '{threshold}': {err}");
+                process::exit(1);
+            })
+        })
+        .collect();
+
+    let severity_rules = SeverityRules {
+        rules: args
+            .severity_rule
+            .iter()
+            .map(|rule| {
+                rule.parse::<SeverityRule>().unwrap_or_else(|err| {
+                    eprintln!("Invalid severity-rule '{rule}': {err}");
+                    process::exit(1);
+                })
+            })
+            .collect(),
+        default: args.default_severity,
+    };
+
     let stitch_list =
         StitchList::read_stitch_list(stitch_list_path).expect("Failed to read stitchlist-file");
-    let stitched = Stitched::build(stitch_list, &stitch_pars);
+    let stitched = match &args.append {
+        Some(existing) => {
+            let mut stitched = Stitched::from_file(existing).unwrap_or_else(|err| {
+                eprintln!("Failed to read stitched dataset '{existing}': {err}");
+                process::exit(1);
+            });
+            if let Err(err) = stitched.append(&ctx, stitch_list, &stitch_pars) {
+                eprintln!("Failed to append to '{existing}': {err}");
+                process::exit(1);
+            }
+            stitched
+        }
+        None => Stitched::build(&ctx, stitch_list, &stitch_pars),
+    };
+    let num_failing = write_output(
+        &ctx,
+        &args,
+        &stitch_pars,
+        &thresholds,
+        &severity_rules,
+        stitched,
+    );
+    if num_failing > 0 {
+        eprintln!("Detected {num_failing} anomalies at or above the '--fail-on' severity");
+        process::exit(2);
+    }
+}
 
+/// write the stitched data, the anomalies and the forecast in the requested formats.
+/// Returns the number of anomalies with at least the severity of '--fail-on' (0 if not set).
+fn write_output(
+    ctx: &AnalysisContext,
+    args: &Args,
+    stitch_pars: &StitchParameters,
+    thresholds: &[ForecastThreshold],
+    severity_rules: &SeverityRules,
+    stitched: Stitched,
+) -> usize {
     let path = Path::new(&args.output);
-    stitched.write_csv(path);
-    stitched.to_json("stitched.bincode");
+    stitched.write_csv(ctx, path);
+    let stitched_output = args
+        .stitched_output
+        .as_deref()
+        .or(args.append.as_deref())
+        .unwrap_or("stitched.bincode");
+    stitched.to_json(stitched_output);
+    if args.parquet {
+        stitched.to_parquet("stitched.parquet");
+    }
+    if args.sqlite {
+        if let Err(err) = stitched.write_sqlite("stitched.sqlite") {
+            panic!("Failed to write 'stitched.sqlite': {err:?}");
+        }
+    }
+    if args.xlsx {
+        stitched.write_xlsx(ctx, &path.with_extension("xlsx"));
+    }
 
-    println!("Stitched output written to: '{}'", path.display());
+    println!(
+        "Stitched output written to: '{}' and '{stitched_output}'",
+        path.display()
+    );
 
     let path = Path::new(&args.anomalies);
 
     println!("Using anomaly parameters: {:?}", stitch_pars.anomaly_pars);
-    let num_anomalies = stitched.write_anomalies_csv(path, &stitch_pars.anomaly_pars);
+    let num_anomalies = stitched.write_anomalies_csv(ctx, path, &stitch_pars.anomaly_pars);
+    if args.xlsx {
+        stitched.write_anomalies_xlsx(ctx, &path.with_extension("xlsx"), &stitch_pars.anomaly_pars);
+    }
     if num_anomalies > 0 {
         println!(
             "Detected {num_anomalies}.\n\tFor further information check file '{}'",
@@ -80,4 +254,61 @@ fn main() {
     } else {
         println!("NO anomalies detected");
     }
+
+    let anomaly_report = stitched.anomaly_report(&stitch_pars.anomaly_pars, severity_rules);
+    if let Some(json_path) = &args.anomalies_json {
+        anomaly_report.write_json(Path::new(json_path));
+        println!("Anomaly report written to: '{json_path}'");
+    }
+
+    if !thresholds.is_empty() {
+        let path = Path::new(&args.forecast);
+        let num_breaches = stitched.write_forecast_csv(ctx, path, thresholds);
+        println!(
+            "Forecasted {num_breaches} threshold-breaches, written to: '{}'",
+            path.display()
+        );
+    }
+
+    if args.html {
+        let path = Path::new(&args.output).with_extension("html");
+        StitchedDataSet::new(stitched).write_html(ctx, &path, &HtmlParameters::default());
+        println!("Html-report written to: '{}'", path.display());
+    }
+
+    args.fail_on
+        .map_or(0, |severity| anomaly_report.count_at_least(severity))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Args;
+    use clap::Parser;
+    use jaeger_stats::utils;
+    use std::{env, ffi::OsString, fs};
+
+    #[test]
+    fn command_line_overrides_config() {
+        let config = env::temp_dir().join("jaeger_stats_stitch_precedence.toml");
+        fs::write(
+            &config,
+            "stitch_list = \"list.toml\"\ndrop_count = 3\n\n[anomaly_pars]\nl1_dev_bound = 2.5\n",
+        )
+        .unwrap();
+        let cli_args = [
+            "stitch",
+            "--config",
+            config.to_str().unwrap(),
+            "--drop-count",
+            "5",
+        ]
+        .iter()
+        .map(OsString::from)
+        .collect();
+
+        let args = Args::parse_from(utils::args_with_config::<Args>(cli_args).unwrap());
+        assert_eq!(args.stitch_list, "list.toml");
+        assert_eq!(args.drop_count, 5);
+        assert_eq!(args.l1_dev_bound, 2.5);
+    }
 }
@@ -0,0 +1,60 @@
+use clap::{ArgAction, Parser, Subcommand};
+use jaeger_stats::{AnalysisContext, Stitched, Timezone, TopologyDrift};
+use std::{path::Path, process};
+
+/// Report changes in the topology (end-points, services, service/operations, caller-callee edges and call-chains) between datasets
+#[derive(Parser, Debug)]
+#[command(author, version, about, long_about = None)]
+struct Args {
+    #[command(subcommand)]
+    command: Command,
+
+    #[arg(short, long, default_value_t = String::from("topology_diff.csv"))]
+    output: String,
+
+    #[arg(short, long, default_value_t = String::from("topology_diff.json"))]
+    json: String,
+
+    /// Replace the decimal point by a comma in the csv-output (switch off via '--comma-float=false')
+    #[arg(short, long, default_value_t = true, action = ArgAction::Set, num_args = 0..=1, default_missing_value = "true")]
+    comma_float: bool,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Compare the statistics files of two analyses (for example 'cummulative_trace_stats.json')
+    Diff { before: String, after: String },
+    /// Compare the subsequent columns (input-files) of a stitched dataset
+    Drift { stitched: String },
+}
+
+fn main() {
+    let args = Args::parse();
+
+    let ctx = AnalysisContext::new(args.comma_float, Timezone::default());
+
+    let drift = match args.command {
+        Command::Diff { before, after } => TopologyDrift::from_files(&ctx, &before, &after),
+        Command::Drift { stitched } => {
+            Stitched::from_file(&stitched).map(|stitched| TopologyDrift::from_stitched(&stitched))
+        }
+    }
+    .unwrap_or_else(|err| {
+        eprintln!("Failed to read the input: {err}");
+        process::exit(1);
+    });
+
+    drift.write_csv(&ctx, Path::new(&args.output));
+    drift.write_json(Path::new(&args.json));
+
+    drift
+        .0
+        .iter()
+        .for_each(|diff| println!("{} -> {}: {}", diff.before, diff.after, diff.summary()));
+    println!(
+        "Found {} topology changes.\n\tFor further information check files '{}' and '{}'",
+        drift.num_changes(),
+        args.output,
+        args.json
+    );
+}
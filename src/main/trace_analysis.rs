@@ -1,17 +1,25 @@
-use clap::Parser;
-use jaeger_stats::{analyze_file_or_folder, set_comma_float, set_tz_offset_minutes, write_report};
-use std::path::Path;
+use clap::{ArgAction, Parser};
+use jaeger_stats::{
+    analyze_file_or_folder, utils, AnalysisContext, CChainRepair, RepairMode, ReplaceRule, Timezone,
+};
+use serde::Serialize;
+use std::{path::Path, process};
 
 /// Parsing and analyzing Jaeger traces
 
 const EMPTY_ARG: &str = "--";
 
-#[derive(Parser, Debug)]
-#[command(author, version, about, long_about = None)]
+#[derive(Parser, Debug, Serialize)]
+#[command(author, version, about, long_about = None, args_override_self = true)]
 struct Args {
     // file of folder to parse
     input: String,
 
+    /// A TOML config-file with values for the flags below (flags on the command-line override the values of the config-file)
+    #[arg(long)]
+    #[serde(skip)]
+    config: Option<String>,
+
     #[arg(long)]
     caching_process: Option<String>,
 
@@ -22,13 +30,19 @@ struct Args {
     #[arg(short = 'z', long, default_value_t = 2*60)]
     timezone_minutes: i64,
 
-    #[arg(short = 'f', long, default_value_t = true)]
+    /// An IANA timezone such as 'Europe/Amsterdam' to show the timestamps with daylight-saving time. This overrides the fixed offset of '--timezone-minutes'.
+    #[arg(long)]
+    timezone: Option<Timezone>,
+
+    /// Replace the decimal point by a comma in the csv-output (switch off via '--comma-float=false')
+    #[arg(short = 'f', long, default_value_t = true, action = ArgAction::Set, num_args = 0..=1, default_missing_value = "true")]
     comma_float: bool,
 
-    #[arg(short, long, default_value_t = false)]
+    #[arg(short, long, default_value_t = false, action = ArgAction::Set, num_args = 0..=1, default_missing_value = "true")]
     trace_output: bool,
 
-    /// The output-extension determines the output-types are 'json' and 'bincode' (which is also used as the file-extension).
+    /// The output-extension determines the output-types are 'json', 'bincode' and 'sqlite' (which is also used as the file-extension).
+    /// Add ',parquet' (for example 'json,parquet') to also write the statistics as parquet-tables, ',xlsx' for an Excel-workbook, ',html' for a self-contained report or ',prom' for the RED-metrics in OpenMetrics-format.
     #[arg(short, long, default_value_t = String::from("json"))]
     output_ext: String,
 
@@ -37,6 +51,39 @@ struct Args {
 
     #[arg(long, default_value_t = String::from(EMPTY_ARG))]
     display_call_chain: String,
+
+    /// The repair-mode for non-rooted call-chains that match multiple known call-chains ('exact' only repairs unambiguous matches)
+    #[arg(long, value_enum, default_value_t = RepairMode::Exact)]
+    repair_mode: RepairMode,
+
+    /// The minimal share of the observed traffic a call-chain needs to be selected in the 'dominant' repair-mode
+    #[arg(long, default_value_t = 0.8)]
+    dominance_threshold: f64,
+
+    /// A local copy of 'mermaid.min.js' that is inlined in the html-reports to render the diagrams
+    #[arg(long)]
+    mermaid_js: Option<String>,
+
+    /// Additional rule '<replacement>=<regex>' to normalise operation-names, for example '/{ORDER}=/orders/\d+' (can be repeated)
+    #[arg(long)]
+    normalisation_rule: Vec<String>,
+
+    /// Additional rule '<replacement>=<regex>' to derive templates of error-messages, for example '<ORDER>=order \d+' (can be repeated)
+    #[arg(long)]
+    error_rule: Vec<String>,
+}
+
+/// Parse the rules passed via the flag 'name'
+fn parse_rules(rules: &[String], name: &str) -> Vec<ReplaceRule> {
+    rules
+        .iter()
+        .map(|rule| {
+            rule.parse().unwrap_or_else(|err| {
+                eprintln!("Invalid {name} '{rule}': {err}");
+                process::exit(1);
+            })
+        })
+        .collect()
 }
 
 fn to_opt_str(s: &str) -> Option<&str> {
@@ -48,19 +95,36 @@ fn to_opt_str(s: &str) -> Option<&str> {
 }
 
 fn main() {
-    let args = Args::parse();
+    let args: Args = utils::parse_args_with_config();
 
-    let caching_processes = if let Some(cache_proc) = args.caching_process {
+    let caching_processes = if let Some(cache_proc) = &args.caching_process {
         cache_proc.split(',').map(|s| s.to_owned()).collect()
     } else {
         Vec::new()
     };
 
-    set_tz_offset_minutes(args.timezone_minutes);
+    let mut ctx = AnalysisContext::new(
+        args.comma_float,
+        args.timezone
+            .unwrap_or(Timezone::Offset(args.timezone_minutes)),
+    );
+    ctx.normalisation_rules = parse_rules(&args.normalisation_rule, "normalisation-rule");
+    ctx.error_rules = parse_rules(&args.error_rule, "error-rule");
+
+    if let Some(mermaid_js) = &args.mermaid_js {
+        if let Err(err) = ctx.set_mermaid_js(Path::new(mermaid_js)) {
+            eprintln!("Failed to read '{mermaid_js}': {err}");
+            process::exit(1);
+        }
+    }
 
-    set_comma_float(args.comma_float);
+    let repair = CChainRepair {
+        mode: args.repair_mode,
+        dominance_threshold: args.dominance_threshold,
+    };
 
     let mut path = analyze_file_or_folder(
+        &mut ctx,
         Path::new(&args.input),
         caching_processes,
         &args.call_chain_folder,
@@ -68,8 +132,66 @@ fn main() {
         &args.output_ext,
         to_opt_str(&args.display_service_oper),
         to_opt_str(&args.display_call_chain),
+        &repair,
     );
     println!("{:?}", args.display_service_oper);
+    let report = ctx.take_report();
     path.push("report.txt");
-    write_report(path.to_str().unwrap());
+    report.write_file(path.to_str().unwrap());
+    path.set_file_name("report.json");
+    report.write_json(path.to_str().unwrap());
+    // the effective configuration allows to reproduce this run
+    path.set_file_name("trace_analysis_config.toml");
+    utils::write_config(&path, &args);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Args;
+    use clap::Parser;
+    use jaeger_stats::utils;
+    use std::{env, ffi::OsString, fs, path::Path};
+
+    fn parse_with_config(config: &Path, cli_args: &[&str]) -> Args {
+        let cli_args = ["trace_analysis", "--config", config.to_str().unwrap()]
+            .iter()
+            .chain(cli_args)
+            .map(OsString::from)
+            .collect();
+        Args::parse_from(utils::args_with_config::<Args>(cli_args).unwrap())
+    }
+
+    #[test]
+    fn command_line_overrides_config() {
+        let config = env::temp_dir().join("jaeger_stats_trace_analysis_precedence.toml");
+        fs::write(
+            &config,
+            "input = \"traces\"\ncomma_float = false\noutput_ext = \"bincode\"\n",
+        )
+        .unwrap();
+
+        let args = parse_with_config(&config, &[]);
+        assert_eq!(args.input, "traces");
+        assert!(!args.comma_float);
+        assert_eq!(args.output_ext, "bincode");
+
+        let args = parse_with_config(&config, &["--comma-float", "-o", "json", "other"]);
+        assert_eq!(args.input, "other");
+        assert!(args.comma_float);
+        assert_eq!(args.output_ext, "json");
+    }
+
+    #[test]
+    fn effective_config_reproduces_the_run() {
+        let args = Args::parse_from(["trace_analysis", "traces", "--caching-process", "cache"]);
+        let config = env::temp_dir().join("jaeger_stats_trace_analysis_effective.toml");
+        utils::write_config(&config, &args);
+
+        let reproduced = parse_with_config(&config, &[]);
+        assert_eq!(reproduced.input, "traces");
+        assert_eq!(
+            toml::to_string(&reproduced).unwrap(),
+            toml::to_string(&args).unwrap()
+        );
+    }
 }
@@ -1,6 +1,6 @@
 use crate::{
     raw::JaegerItem,
-    utils::{self, Chapter},
+    utils::{self, Chapter, Report, ReportEntry, Severity},
 };
 use serde_json::Value;
 use std::collections::HashMap;
@@ -27,8 +27,8 @@ impl Process {
         self.name = name.to_owned();
     }
 
-    /// Extend a Process with tags data
-    fn with_tags(&mut self, proc_key: &String, data: &Value) {
+    /// Extend a Process with tags data, where the ignored tags are added to 'report'
+    fn with_tags(&mut self, report: &mut Report, proc_key: &String, data: &Value) {
         match data {
             // expect an array of tags
             Value::Array(val) => {
@@ -48,10 +48,20 @@ impl Process {
                         "telemetry.sdk.language" => self.telemetry_sdk_language = val,
                         "telemetry.sdk.name" => self.telemetry_sdk_name = val,
                         "telemetry.sdk.version" => self.telemetry_sdk_version = val,
-                        _ => utils::report(
-                            Chapter::Issues,
-                            format!("Ignored Unknown key {key}='{val}' for process {proc_key}"),
-                        ),
+                        _ => {
+                            utils::report_entry(
+                                report,
+                                ReportEntry::new(
+                                    Chapter::Issues,
+                                    Severity::Warning,
+                                    "ignored_tag",
+                                    format!(
+                                        "Ignored Unknown key {key}='{val}' for process {proc_key}"
+                                    ),
+                                ),
+                            );
+                            report.counters.ignored_tags += 1;
+                        }
                     }
                 }
             }
@@ -66,7 +76,7 @@ pub type ProcessMap = HashMap<String, Process>;
 /// The nested structure of JSON items with flexible key-value pairs is flattened to simple Struct for convenient access downstream (during processing)
 /// (This is the imperative version, next version is in functional style)
 #[allow(dead_code)]
-fn build_process_map_imperative(item: &JaegerItem) -> ProcessMap {
+fn build_process_map_imperative(report: &mut Report, item: &JaegerItem) -> ProcessMap {
     let mut proc_map = HashMap::new();
 
     for (proc_key, val) in &item.processes {
@@ -78,7 +88,7 @@ fn build_process_map_imperative(item: &JaegerItem) -> ProcessMap {
                 for (key2, val2) in val {
                     match &key2[..] {
                         "serviceName" => proc.with_servername(val2),
-                        "tags" => proc.with_tags(proc_key, val2),
+                        "tags" => proc.with_tags(report, proc_key, val2),
                         _ => panic!("Unexpected key for process {proc_key}: '{key2}'"),
                     }
                 }
@@ -98,7 +108,7 @@ fn build_process_map_imperative(item: &JaegerItem) -> ProcessMap {
 /// Build_process takes a JaegerItem and extract a mapping from keys like 'p2' to a Process-structs.
 /// The nested structure of JSON items with flexible key-value pairs is flattened to simple Struct for convenient access downstream (during processing)
 /// (this is the functional version, the imperative version is called 'build_process_map_imperative')
-pub fn build_process_map(item: &JaegerItem) -> ProcessMap {
+pub fn build_process_map(report: &mut Report, item: &JaegerItem) -> ProcessMap {
     item.processes
         .iter()
         .map(|(proc_key, val)| {
@@ -110,7 +120,7 @@ pub fn build_process_map(item: &JaegerItem) -> ProcessMap {
                     for (key2, val2) in val {
                         match &key2[..] {
                             "serviceName" => proc.with_servername(val2),
-                            "tags" => proc.with_tags(proc_key, val2),
+                            "tags" => proc.with_tags(report, proc_key, val2),
                             _ => panic!("Unexpected key for process {proc_key}: '{key2}'"),
                         }
                     }
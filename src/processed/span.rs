@@ -4,21 +4,13 @@ use super::{
     unify_operation::unified_operation_name,
 };
 use crate::{
-    micros_to_datetime,
+    context::AnalysisContext,
     raw::{JaegerItem, JaegerLog, JaegerSpan, JaegerTags},
-    utils,
+    utils::{self, Chapter, ReportEntry, Severity},
 };
 
-use chrono::NaiveDateTime;
 use serde_json::Value;
-use std::{collections::HashMap, iter, sync::Mutex};
-
-static MAX_LOG_MSG_LENGTH: Mutex<usize> = Mutex::new(100);
-
-pub fn set_max_log_msg_length(val: usize) {
-    let mut guard = MAX_LOG_MSG_LENGTH.lock().unwrap();
-    *guard = val
-}
+use std::{collections::HashMap, iter};
 
 #[derive(Debug, Default)]
 pub enum Position {
@@ -43,7 +35,8 @@ pub struct Span {
     pub span_id: String,
     pub operation_name: String,
     pub full_operation_name: Option<String>,
-    pub start_dt: NaiveDateTime,
+    /// the start of the span in micro-seconds since epoch (UTC)
+    pub start_dt_micros: i64,
     pub duration_micros: i64,
     // optional parameters from tags
     // to see statistics on all tags run:
@@ -64,12 +57,12 @@ pub struct Span {
 }
 
 impl Span {
-    fn new(js: &JaegerSpan, proc_map: &ProcessMap) -> Self {
+    fn new(ctx: &AnalysisContext, js: &JaegerSpan, proc_map: &ProcessMap) -> Self {
         let position = Default::default();
         let span_id = js.spanID.to_owned();
-        let (operation_name, full_operation_name) = unified_operation_name(&js.operationName);
+        let (operation_name, full_operation_name) = unified_operation_name(ctx, &js.operationName);
 
-        let start_dt = micros_to_datetime(js.startTime);
+        let start_dt_micros = js.startTime;
         let duration_micros = js.duration;
         let process = proc_map.get(&js.processID).map(|proc| proc.to_owned());
         let mut span = Span {
@@ -77,13 +70,13 @@ impl Span {
             span_id,
             operation_name,
             full_operation_name,
-            start_dt,
+            start_dt_micros,
             duration_micros,
             process,
             ..Default::default()
         };
         span.add_tags(&js.tags);
-        span.add_logs(&js.logs);
+        span.add_logs(&js.logs, ctx.max_log_msg_length);
         span
     }
 
@@ -108,14 +101,13 @@ impl Span {
         // })
     }
 
-    fn add_logs(&mut self, logs: &[JaegerLog]) {
+    /// add the logs, where messages are truncated to 'max_msg_len'
+    fn add_logs(&mut self, logs: &[JaegerLog], max_msg_len: usize) {
         let unpack_serde_str = |v: &Value| match v {
             Value::String(s) => s.to_owned(),
             _ => panic!("Invalid type of string-field {:?}", v),
         };
 
-        let max_msg_len = *MAX_LOG_MSG_LENGTH.lock().unwrap();
-
         self.logs = logs
             .iter()
             .map(|log| {
@@ -250,13 +242,13 @@ impl Spans {
     }
 
     /// build the list of spans (including parent links and proces-mapping)
-    pub fn build_spans(item: &JaegerItem) -> (Spans, Vec<String>) {
-        let proc_map = build_process_map(item);
+    pub fn build_spans(ctx: &mut AnalysisContext, item: &JaegerItem) -> (Spans, Vec<String>) {
+        let proc_map = build_process_map(&mut ctx.report, item);
 
         let mut spans: Vec<_> = item
             .spans
             .iter()
-            .map(|jspan| Span::new(jspan, &proc_map))
+            .map(|jspan| Span::new(ctx, jspan, &proc_map))
             .collect();
 
         let missing_span_ids = add_parents(&mut spans, &item.spans);
@@ -281,7 +273,11 @@ impl Spans {
                 roots.len(),
                 item.spans.len(),
             );
-            utils::report(crate::utils::Chapter::Issues, issue);
+            utils::report_entry(
+                &mut ctx.report,
+                ReportEntry::new(Chapter::Issues, Severity::Warning, "multiple_roots", issue)
+                    .trace_id(&item.traceID),
+            );
             Spans {
                 items: spans,
                 root_idx: None, // assume a default
@@ -1,9 +1,8 @@
 use super::span::Spans;
 use crate::{
-    micros_to_datetime,
-    raw::{JaegerItem, JaegerTrace, FILE_TRACKER},
+    context::AnalysisContext,
+    raw::{JaegerItem, JaegerTrace},
 };
-use chrono::NaiveDateTime;
 use std::{ffi::OsString, path::Path};
 
 #[derive(Debug)]
@@ -11,8 +10,9 @@ pub struct Trace {
     pub trace_id: String,
     pub source_file_id: usize,
     pub root_call: String,
-    pub start_dt: NaiveDateTime,
-    pub end_dt: NaiveDateTime,
+    /// the start and end of the trace in micro-seconds since epoch (UTC)
+    pub start_dt_micros: i64,
+    pub end_dt_micros: i64,
     pub duration_micros: i64,
     pub time_to_respond_micros: i64,
     pub missing_span_ids: Vec<String>,
@@ -21,18 +21,21 @@ pub struct Trace {
 
 impl Trace {
     /// build a Trace based upon a JaegerTrace
-    pub fn new(jt: &JaegerTrace, idx: usize, source_file_id: usize) -> Self {
+    pub fn new(
+        ctx: &mut AnalysisContext,
+        jt: &JaegerTrace,
+        idx: usize,
+        source_file_id: usize,
+    ) -> Self {
         let item = &jt.data[idx];
         let trace_id = item.traceID.to_owned();
 
-        let (spans, missing_span_ids) = Spans::build_spans(item);
+        let (spans, missing_span_ids) = Spans::build_spans(ctx, item);
 
         let root_call = get_root_call(&spans);
 
-        let (start_dt, end_dt) = find_full_duration(item);
-        let duration_micros = end_dt - start_dt;
-        let start_dt = micros_to_datetime(start_dt);
-        let end_dt = micros_to_datetime(end_dt);
+        let (start_dt_micros, end_dt_micros) = find_full_duration(item);
+        let duration_micros = end_dt_micros - start_dt_micros;
 
         let time_to_respond_micros = get_response_duration(&spans, item);
 
@@ -40,8 +43,8 @@ impl Trace {
             trace_id,
             source_file_id,
             root_call,
-            start_dt,
-            end_dt,
+            start_dt_micros,
+            end_dt_micros,
             duration_micros,
             time_to_respond_micros,
             missing_span_ids,
@@ -67,10 +70,11 @@ impl Trace {
 // }
 
 /// Transform a raw JaegerTrace to a vector of Traces. A single JaegerTrace file can contain many traces, and these will be split out.
-pub fn extract_traces(jt: JaegerTrace) -> Vec<Trace> {
+pub fn extract_traces(ctx: &mut AnalysisContext, jt: JaegerTrace) -> Vec<Trace> {
     let num_traces = jt.data.len();
+    let source_file_id = ctx.file_tracker.get_last_idx();
     (0..num_traces)
-        .map(|idx| Trace::new(&jt, idx, FILE_TRACKER.lock().unwrap().get_last_idx()))
+        .map(|idx| Trace::new(ctx, &jt, idx, source_file_id))
         .collect()
 }
 
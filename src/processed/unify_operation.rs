@@ -1,3 +1,4 @@
+use crate::context::AnalysisContext;
 use lazy_static::lazy_static;
 use regex::Regex;
 
@@ -18,7 +19,10 @@ fn replace_regex(s: String, re: &Regex, replacement: &str) -> (String, bool) {
     }
 }
 
-pub fn unified_operation_name(js_operation: &str) -> (String, Option<String>) {
+pub fn unified_operation_name(
+    ctx: &AnalysisContext,
+    js_operation: &str,
+) -> (String, Option<String>) {
     lazy_static! {
         static ref REPLACEMENTS: Vec<(&'static str, Regex)> = {
             vec![("/{TIME}", Regex::new(r"(?x)
@@ -54,6 +58,15 @@ pub fn unified_operation_name(js_operation: &str) -> (String, Option<String>) {
         .iter()
         .fold((js_operation.to_owned(), false), chained_update);
 
+    // the additional normalisation-rules of the analysis-context are applied after the built-in rules
+    let (oper_name, replaced) = ctx.normalisation_rules.iter().fold(
+        (oper_name, replaced),
+        |(oper_name, any_replaced), rule| {
+            let (oper_name, replaced) = replace_regex(oper_name, &rule.pattern, &rule.replacement);
+            (oper_name, any_replaced || replaced)
+        },
+    );
+
     if replaced {
         (oper_name, Some(js_operation.to_owned()))
     } else {
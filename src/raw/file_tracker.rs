@@ -1,7 +1,5 @@
-//use std::fs::File;
-use std::sync::Mutex;
-
-/// Keeping track of all file-names in used and mapping them to an index
+/// Keeping track of all file-names in used and mapping them to an index (part of the AnalysisContext)
+#[derive(Debug, Default, Clone)]
 pub struct FileTracker {
     files: Vec<String>,
 }
@@ -20,11 +18,13 @@ impl FileTracker {
         self.files.len() - 1
     }
 
+    pub fn files(&self) -> &[String] {
+        &self.files
+    }
+
     #[allow(dead_code)]
     pub fn get_file_name(&self, idx: usize) -> String {
         assert!(idx < self.files.len(), "Index out of bounds");
         self.files[idx].to_owned()
     }
 }
-
-pub static FILE_TRACKER: Mutex<FileTracker> = Mutex::new(FileTracker { files: Vec::new() });
@@ -1,35 +1,36 @@
 //! Reading raw json-formatted Jaeger-traces from file
 use super::JaegerTrace;
 use crate::{
-    raw::{self, FILE_TRACKER},
+    context::AnalysisContext,
+    raw,
     utils::{self, Chapter},
 };
 use std::{error::Error, ffi::OsStr, fs, path::Path};
 
 // this fails as this type is not Clone (even though it is an Fn). Clone is needed to call is within an FnMut that is passed to filter_map in read_trace_folder
 //type JaegerTraceProcessor<T> = Box<dyn Fn(JaegerTrace) -> Vec<T>>;
-type JaegerTraceProcessor<T> = fn(JaegerTrace) -> Vec<T>;
+type JaegerTraceProcessor<T> = fn(&mut AnalysisContext, JaegerTrace) -> Vec<T>;
 
 /// read a single file and process it to get clean Tcaecs. Returns a set of traces, or an error
 /// TODO: extend to keep track of a file-label
 fn read_trace_file<T>(
+    ctx: &mut AnalysisContext,
     input_file: &Path,
     process_traces: JaegerTraceProcessor<T>,
 ) -> Result<Vec<T>, Box<dyn Error>> {
     println!("Reading a Jaeger-trace from '{}'", input_file.display());
 
     // Add the file to the list, such that it's index is available from the FileTracker
-    FILE_TRACKER
-        .lock()
-        .unwrap()
+    ctx.file_tracker
         .add_file(input_file.to_string_lossy().to_string());
 
-    let jt = raw::read_jaeger_trace_file(input_file).unwrap();
+    let jt = raw::read_jaeger_trace_file(&mut ctx.report, input_file).unwrap();
 
-    Ok(process_traces(jt))
+    Ok(process_traces(ctx, jt))
 }
 
 fn read_trace_folder<T>(
+    ctx: &mut AnalysisContext,
     folder: &Path,
     process_traces: JaegerTraceProcessor<T>,
 ) -> Result<(Vec<T>, i32), Box<dyn Error>> {
@@ -46,7 +47,7 @@ fn read_trace_folder<T>(
                 let file_name = path.to_str().expect("path-string").to_owned();
                 if file_name.ends_with(".json") {
                     num_files += 1;
-                    read_trace_file(&path, process_traces).ok()
+                    read_trace_file(ctx, &path, process_traces).ok()
                 } else {
                     println!("Ignore '{file_name} as it does not have suffix '.json'.");
                     None // Not .json file
@@ -61,17 +62,20 @@ fn read_trace_folder<T>(
 }
 
 ///Check whether path is a file or folder and read all traces.
-pub fn read_process_file_or_folder<T>(
-    path: &Path,
+pub fn read_process_file_or_folder<'a, T>(
+    ctx: &mut AnalysisContext,
+    path: &'a Path,
     process_traces: JaegerTraceProcessor<T>,
-) -> (Vec<T>, i32, &Path) {
+) -> (Vec<T>, i32, &'a Path) {
     utils::report(
+        &mut ctx.report,
         Chapter::Summary,
+        "read_folder",
         format!("Reading all traces from folder: {}", path.display()),
     );
     let (traces, num_files, folder) =
         if path.is_file() && path.extension() == Some(OsStr::new("json")) {
-            let traces = read_trace_file(path, process_traces).unwrap();
+            let traces = read_trace_file(ctx, path, process_traces).unwrap();
             (
                 traces,
                 1,
@@ -79,7 +83,7 @@ pub fn read_process_file_or_folder<T>(
                     .expect("Could not extract parent of input_file"),
             )
         } else if path.is_dir() {
-            let (traces, num_files) = read_trace_folder(path, process_traces).unwrap();
+            let (traces, num_files) = read_trace_folder(ctx, path, process_traces).unwrap();
             (traces, num_files, path)
         } else {
             panic!(
@@ -88,7 +92,9 @@ pub fn read_process_file_or_folder<T>(
             );
         };
     utils::report(
+        &mut ctx.report,
         Chapter::Summary,
+        "read_folder",
         format!(
             "Read {} traces in total from {} files.",
             traces.len(),
@@ -100,7 +106,7 @@ pub fn read_process_file_or_folder<T>(
 }
 
 /// change a single Jaeger-trace, possibly containing many traces to a Vector of JaegerTraces each containing a single file.
-fn extract_jaeger_traces(jt: JaegerTrace) -> Vec<JaegerTrace> {
+fn extract_jaeger_traces(_ctx: &mut AnalysisContext, jt: JaegerTrace) -> Vec<JaegerTrace> {
     match &jt.errors {
         None => (),
         Some(err) if err.is_empty() => (),
@@ -113,7 +119,10 @@ fn extract_jaeger_traces(jt: JaegerTrace) -> Vec<JaegerTrace> {
     jt.data.into_iter().map(JaegerTrace::new).collect()
 }
 
-/// read a series of raw Jaeger-traces from a file or a folder
-pub fn read_file_or_folder(path: &Path) -> (Vec<JaegerTrace>, i32, &Path) {
-    read_process_file_or_folder(path, extract_jaeger_traces)
+/// read a series of raw Jaeger-traces from a file or a folder. The files read are registered in the analysis-context 'ctx'.
+pub fn read_file_or_folder<'a>(
+    ctx: &mut AnalysisContext,
+    path: &'a Path,
+) -> (Vec<JaegerTrace>, i32, &'a Path) {
+    read_process_file_or_folder(ctx, path, extract_jaeger_traces)
 }
@@ -7,7 +7,7 @@ use std::{
 };
 
 use super::jaeger::JaegerTrace;
-use crate::utils::{self, Chapter};
+use crate::utils::{self, Chapter, Report, ReportEntry, Severity};
 
 use encoding_rs::Encoding;
 
@@ -28,7 +28,9 @@ fn check_bom<P: AsRef<Path>>(path: P) -> Result<&'static Encoding, Box<dyn Error
     }
 }
 
+/// read a file of Jaeger-traces, where the issues of reading are added to 'report'
 pub fn read_jaeger_trace_file<P: AsRef<Path> + Copy + Debug>(
+    report: &mut Report,
     path: P,
 ) -> Result<JaegerTrace, Box<dyn Error>> {
     let jt = match check_bom(path) {
@@ -37,7 +39,9 @@ pub fn read_jaeger_trace_file<P: AsRef<Path> + Copy + Debug>(
             // beware, this consumes quite a bit of memory as the data is present 3 times (raw, decoded and as json)
             let file_size = fs::metadata(path)?.len();
             utils::report(
+                report,
                 Chapter::Details,
+                "file_encoding",
                 format!(
                     "File {path:?}: Found encoding {encoding:?} for a file with size: {file_size}"
                 ),
@@ -48,16 +52,24 @@ pub fn read_jaeger_trace_file<P: AsRef<Path> + Copy + Debug>(
             reader.read_to_end(&mut buffer)?;
             let (s, malformed) = encoding.decode_with_bom_removal(buffer.as_slice());
             if malformed {
-                utils::report(
-                    Chapter::Issues,
-                    format!("File {:?} returned a signal Malformed", path),
+                utils::report_entry(
+                    report,
+                    ReportEntry::new(
+                        Chapter::Issues,
+                        Severity::Warning,
+                        "malformed_encoding",
+                        format!("File {:?} returned a signal Malformed", path),
+                    )
+                    .file(&path.as_ref().to_string_lossy()),
                 );
             }
             serde_json::from_str(&s)?
         }
         Err(err) => {
             utils::report(
+                report,
                 Chapter::Details,
+                "file_encoding",
                 format!("File {path:?}: Failed to find encoding: {err:?}"),
             );
             // Open the file in read-only mode with buffer.
@@ -0,0 +1,372 @@
+//! Cache hit-ratio statistics for the caching processes provided to the analysis.
+//! A call to a caching process that does not have any downstream calls (so it is a leaf) is counted as a cache-hit,
+//! while a call that results in downstream calls is counted as a cache-miss.
+use super::{
+    call_chain::{CChainStatsKey, CallDirection},
+    StatsRec,
+};
+use crate::{
+    context::AnalysisContext,
+    utils::{self, TimeStats},
+};
+use std::collections::{HashMap, HashSet};
+
+/// Hit/miss statistics of a caching process, observed via a single call-chain prefix (the path up to and including the call to the caching process).
+#[derive(Debug, Default, Clone)]
+pub struct CacheStatsValue {
+    /// The caching process that is called at the end of the call-chain prefix
+    pub caching_process: String,
+    /// Number of calls that were answered without downstream calls (leaf-calls)
+    pub hit_count: usize,
+    /// Number of calls that resulted in downstream calls
+    pub miss_count: usize,
+    pub hit_duration_micros: Vec<i64>,
+    pub miss_duration_micros: Vec<i64>,
+    /// Number of downstream calls observed below this call-chain prefix (only produced by cache-misses)
+    pub downstream_count: usize,
+    /// The downstream calls per (leaf-)process, such that the 'downstream_count' can be corrected when processes are dropped
+    pub downstream_per_process: HashMap<String, usize>,
+}
+
+impl CacheStatsValue {
+    pub fn total_count(&self) -> usize {
+        self.hit_count + self.miss_count
+    }
+
+    pub fn get_hit_ratio(&self) -> Option<f64> {
+        match self.total_count() {
+            0 => None,
+            total => Some(self.hit_count as f64 / total as f64),
+        }
+    }
+
+    pub fn get_hit_avg_millis(&self) -> Option<f64> {
+        if self.hit_duration_micros.is_empty() {
+            None
+        } else {
+            Some(TimeStats(&self.hit_duration_micros).get_avg_millis())
+        }
+    }
+
+    pub fn get_miss_avg_millis(&self) -> Option<f64> {
+        if self.miss_duration_micros.is_empty() {
+            None
+        } else {
+            Some(TimeStats(&self.miss_duration_micros).get_avg_millis())
+        }
+    }
+
+    /// The average latency that is saved by a cache-hit (avg miss-duration minus avg hit-duration)
+    pub fn get_hit_miss_diff_millis(&self) -> Option<f64> {
+        match (self.get_miss_avg_millis(), self.get_hit_avg_millis()) {
+            (Some(miss), Some(hit)) => Some(miss - hit),
+            _ => None,
+        }
+    }
+
+    /// Estimate of the number of additional downstream calls when the cache is flushed and all hits turn into misses.
+    /// The estimate assumes that each miss results in the same (average) number of downstream calls.
+    pub fn get_flush_load_increase(&self) -> Option<f64> {
+        if self.miss_count == 0 {
+            None
+        } else {
+            Some(self.downstream_count as f64 * self.hit_count as f64 / self.miss_count as f64)
+        }
+    }
+
+    /// The relative increase of the downstream load when the cache is flushed.
+    pub fn get_flush_load_factor(&self) -> Option<f64> {
+        if self.miss_count == 0 {
+            None
+        } else {
+            Some(self.hit_count as f64 / self.miss_count as f64)
+        }
+    }
+
+    /// header for report_stats_line output in ';'-separated csv-format
+    pub fn report_stats_line_header_str() -> &'static str {
+        "Call_chain_prefix; Caching_process; Hit_count; Miss_count; Hit_ratio; Hit_avg_millis; Miss_avg_millis; Hit_miss_diff_millis; Downstream_count; Flush_load_increase; Flush_load_factor"
+    }
+
+    /// reports the statistics for a single line in ';'-separated csv-format
+    pub fn report_stats_line(&self, ctx: &AnalysisContext, prefix: &str) -> String {
+        format!(
+            "{prefix}; {}; {}; {}; {}; {}; {}; {}; {}; {}; {}",
+            self.caching_process,
+            self.hit_count,
+            self.miss_count,
+            utils::format_float_opt(ctx, self.get_hit_ratio()),
+            utils::format_float_opt(ctx, self.get_hit_avg_millis()),
+            utils::format_float_opt(ctx, self.get_miss_avg_millis()),
+            utils::format_float_opt(ctx, self.get_hit_miss_diff_millis()),
+            self.downstream_count,
+            utils::format_float_opt(ctx, self.get_flush_load_increase()),
+            utils::format_float_opt(ctx, self.get_flush_load_factor())
+        )
+    }
+}
+
+/// Cache statistics keyed by the call-chain prefix that ends in the call to the caching process.
+#[derive(Debug, Default, Clone)]
+pub struct CacheStats(pub HashMap<String, CacheStatsValue>);
+
+impl CacheStats {
+    /// Check whether the call-chain ends in an (inbound) call to one of the caching processes and return that process.
+    fn get_caching_process<'a>(
+        cck: &CChainStatsKey,
+        caching_processes: &'a [String],
+    ) -> Option<&'a String> {
+        cck.call_chain.last().and_then(|call| {
+            if call.call_direction == CallDirection::Outbound {
+                None
+            } else {
+                caching_processes.iter().find(|cp| **cp == call.service)
+            }
+        })
+    }
+
+    /// Derive the cache statistics from the call-chains of a StatsRec, using the caching_processes of this StatsRec.
+    pub fn new(stats_rec: &StatsRec) -> Self {
+        let caching_processes = &stats_rec.caching_processes;
+        if caching_processes.is_empty() {
+            return Self::default();
+        }
+
+        let call_chains: Vec<_> = stats_rec
+            .stats
+            .iter()
+            .flat_map(|(proc, st)| {
+                st.call_chain
+                    .0
+                    .iter()
+                    .map(move |(cck, ccv)| (proc, cck, ccv))
+            })
+            .collect();
+
+        // first collect the hits and misses, keyed by the call-chain of the call to the caching process
+        let mut cache_stats = HashMap::new();
+        call_chains.iter().for_each(|(_proc, cck, ccv)| {
+            if let Some(caching_process) = Self::get_caching_process(cck, caching_processes) {
+                let csv = cache_stats
+                    .entry(cck.call_chain.clone())
+                    .or_insert_with(|| CacheStatsValue {
+                        caching_process: caching_process.to_owned(),
+                        ..Default::default()
+                    });
+                if cck.is_leaf {
+                    csv.hit_count += ccv.count;
+                    csv.hit_duration_micros
+                        .extend_from_slice(&ccv.duration_micros);
+                } else {
+                    csv.miss_count += ccv.count;
+                    csv.miss_duration_micros
+                        .extend_from_slice(&ccv.duration_micros);
+                }
+            }
+        });
+
+        // next count the calls that are downstream of each of the cached calls.
+        // A call shows up as an outbound and an inbound call-chain, so only the non-inbound chains are counted.
+        call_chains
+            .iter()
+            .filter(|(_proc, cck, _)| {
+                cck.call_chain
+                    .last()
+                    .is_some_and(|call| call.call_direction != CallDirection::Inbound)
+            })
+            .for_each(|(proc, cck, ccv)| {
+                (1..cck.call_chain.len()).for_each(|len| {
+                    if let Some(csv) = cache_stats.get_mut(&cck.call_chain[..len]) {
+                        csv.downstream_count += ccv.count;
+                        *csv.downstream_per_process
+                            .entry(proc.to_string())
+                            .or_default() += ccv.count;
+                    }
+                })
+            });
+
+        Self(
+            cache_stats
+                .into_iter()
+                .map(|(call_chain, csv)| {
+                    let cck = CChainStatsKey {
+                        call_chain,
+                        caching_process: String::new(),
+                        is_leaf: false,
+                    };
+                    (cck.call_chain_key(), csv)
+                })
+                .collect(),
+        )
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Remove the statistics that originate from the 'dropped' processes, as if these processes were not part of the analysis.
+    /// The hits and misses are recorded by the caching process, while the downstream calls are recorded by the process that performs the call.
+    pub fn drop_processes(&mut self, dropped: &HashSet<String>) {
+        self.0
+            .retain(|_prefix, csv| !dropped.contains(&csv.caching_process));
+        self.0.values_mut().for_each(|csv| {
+            csv.downstream_per_process.retain(|proc, count| {
+                if dropped.contains(proc) {
+                    csv.downstream_count -= *count;
+                    false
+                } else {
+                    true
+                }
+            })
+        });
+    }
+
+    /// Show the cache-statistics as a ';'-separated csv-string, ordered on the call-chain prefix.
+    pub fn to_csv_string(&self, ctx: &AnalysisContext) -> String {
+        let mut data: Vec<_> = self.0.iter().collect();
+        data.sort_by(|a, b| a.0.cmp(b.0));
+
+        let mut s = vec![CacheStatsValue::report_stats_line_header_str().to_owned()];
+        data.into_iter()
+            .for_each(|(prefix, csv)| s.push(csv.report_stats_line(ctx, prefix)));
+        s.push("\n".to_owned());
+        s.join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CacheStats;
+    use crate::stats::{
+        call_chain::{CChainStats, CChainStatsKey, CChainStatsValue, Call, CallDirection},
+        OperationStats, StatsRec,
+    };
+    use std::collections::HashSet;
+
+    fn call(service: &str, operation: &str, call_direction: CallDirection) -> Call {
+        Call {
+            service: service.to_owned(),
+            operation: operation.to_owned(),
+            call_direction,
+        }
+    }
+
+    fn add_chain(stats_rec: &mut StatsRec, call_chain: Vec<Call>, is_leaf: bool, count: usize) {
+        let leaf = call_chain.last().unwrap().service.clone();
+        let cck = CChainStatsKey {
+            call_chain,
+            caching_process: String::new(),
+            is_leaf,
+        };
+        let ccv = CChainStatsValue {
+            count,
+            duration_micros: vec![1000; count],
+            ..Default::default()
+        };
+        stats_rec
+            .stats
+            .entry(leaf)
+            .or_insert_with(|| OperationStats {
+                call_chain: CChainStats::new(),
+                ..Default::default()
+            })
+            .call_chain
+            .0
+            .insert(cck, ccv);
+    }
+
+    #[test]
+    fn hits_misses_and_downstream_calls() {
+        use CallDirection::{Inbound, Outbound};
+        let mut stats_rec = StatsRec {
+            caching_processes: vec!["cache".to_owned()],
+            ..Default::default()
+        };
+        let root = call("gateway", "get", Inbound);
+        let to_cache = call("gateway", "get_cache", Outbound);
+        let cache = call("cache", "get", Inbound);
+        let to_db = call("cache", "query", Outbound);
+        let db = call("db", "query", Inbound);
+
+        // 3 calls are answered by the cache (hits), 1 call results in a downstream call to the db (miss)
+        add_chain(
+            &mut stats_rec,
+            vec![root.clone(), to_cache.clone(), cache.clone()],
+            true,
+            3,
+        );
+        add_chain(
+            &mut stats_rec,
+            vec![root.clone(), to_cache.clone(), cache.clone()],
+            false,
+            1,
+        );
+        add_chain(
+            &mut stats_rec,
+            vec![root.clone(), to_cache.clone(), cache.clone(), to_db.clone()],
+            false,
+            1,
+        );
+        add_chain(
+            &mut stats_rec,
+            vec![root, to_cache, cache, to_db, db],
+            true,
+            1,
+        );
+
+        let cache_stats = CacheStats::new(&stats_rec);
+
+        assert_eq!(cache_stats.0.len(), 1);
+        let csv = cache_stats.0.values().next().unwrap();
+        assert_eq!(csv.caching_process, "cache");
+        assert_eq!((csv.hit_count, csv.miss_count), (3, 1));
+        assert_eq!(csv.get_hit_ratio(), Some(0.75));
+        // the call to the db is counted once, although it shows up as an outbound and an inbound call-chain
+        assert_eq!(csv.downstream_count, 1);
+        assert_eq!(csv.get_flush_load_increase(), Some(3.0));
+    }
+
+    #[test]
+    fn no_caching_processes() {
+        let mut stats_rec = StatsRec::default();
+        add_chain(
+            &mut stats_rec,
+            vec![call("cache", "get", CallDirection::Inbound)],
+            true,
+            1,
+        );
+        assert!(CacheStats::new(&stats_rec).is_empty());
+    }
+
+    #[test]
+    fn drop_processes() {
+        use CallDirection::{Inbound, Outbound};
+        let mut stats_rec = StatsRec {
+            caching_processes: vec!["cache".to_owned()],
+            ..Default::default()
+        };
+        let cache = vec![call("cache", "get", Inbound)];
+        let to_db = [cache.clone(), vec![call("cache", "query", Outbound)]].concat();
+        let to_disk = [
+            to_db.clone(),
+            vec![call("db", "query", Inbound), call("db", "read", Outbound)],
+        ]
+        .concat();
+        add_chain(&mut stats_rec, cache.clone(), false, 2);
+        add_chain(&mut stats_rec, to_db, false, 2);
+        add_chain(&mut stats_rec, to_disk, true, 1);
+
+        let cache_stats = CacheStats::new(&stats_rec);
+        assert_eq!(cache_stats.0.values().next().unwrap().downstream_count, 3);
+
+        // the call to the disk is recorded by the db, the call to the db by the cache
+        let mut without_db = cache_stats.clone();
+        without_db.drop_processes(&HashSet::from(["db".to_owned()]));
+        let csv = without_db.0.values().next().unwrap();
+        assert_eq!((csv.miss_count, csv.downstream_count), (2, 2));
+
+        let mut without_cache = cache_stats;
+        without_cache.drop_processes(&HashSet::from(["cache".to_owned()]));
+        assert!(without_cache.is_empty());
+    }
+}
@@ -1,6 +1,7 @@
-use crate::utils::{self, Chapter};
+use crate::utils::{self, Chapter, Report, ReportEntry, Severity};
 use regex::Regex;
 use serde::{Deserialize, Serialize};
+use std::str::FromStr;
 
 #[derive(
     Serialize, Deserialize, Hash, PartialEq, Eq, PartialOrd, Ord, Debug, Default, Clone, Copy,
@@ -12,44 +13,66 @@ pub enum CallDirection {
     Unknown,
 }
 
-impl From<&str> for CallDirection {
-    fn from(s: &str) -> Self {
+impl FromStr for CallDirection {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s {
-            "Inbound" => CallDirection::Inbound, // would be nice if "Inbound" could be taken from 'CallDirection::Inbound.as_str()'
-            "Outbound" => CallDirection::Outbound,
-            "Unknown" => CallDirection::Unknown,
-            _ => {
-                let msg = format!("Invalid value for CallDirection. Observed: {s}");
-                let ingest_msg = "Issue might be ingest issue: ".to_string() + &msg;
-                utils::report(Chapter::Details, msg);
-                utils::report(Chapter::Ingest, ingest_msg);
-                CallDirection::Unknown
-            }
+            "Inbound" => Ok(CallDirection::Inbound), // would be nice if "Inbound" could be taken from 'CallDirection::Inbound.as_str()'
+            "Outbound" => Ok(CallDirection::Outbound),
+            "Unknown" => Ok(CallDirection::Unknown),
+            _ => Err(format!("Invalid value for CallDirection. Observed: {s}")),
         }
     }
 }
 
+/// The call-direction of a span-kind. Invalid span-kinds map to Unknown (see 'check_span_kind').
 impl From<Option<&String>> for CallDirection {
     fn from(s: Option<&String>) -> Self {
         match s {
             Some(s) => match &s[..] {
                 "server" | "consumer" => CallDirection::Inbound,
                 "client" | "producer" => CallDirection::Outbound,
-                _ => {
-                    let msg = format!("Invalid value for CallDirection. Observed: {s:?}");
-                    let ingest_msg = "Issue might be ingest issue: ".to_string() + &msg;
-                    utils::report(Chapter::Details, msg);
-                    utils::report(Chapter::Ingest, ingest_msg);
-                    CallDirection::Unknown
-                }
+                _ => CallDirection::Unknown,
             },
             None => CallDirection::Unknown,
         }
     }
 }
 
+/// report an invalid span-kind in 'report', both as detail and as potential ingest issue
+pub fn check_span_kind(report: &mut Report, span_kind: Option<&String>) {
+    let Some(s) = span_kind else {
+        return;
+    };
+    if CallDirection::from(span_kind) != CallDirection::Unknown {
+        return;
+    }
+    let msg = format!("Invalid value for CallDirection. Observed: {s:?}");
+    let ingest_msg = "Issue might be ingest issue: ".to_string() + &msg;
+    utils::report_entry(
+        report,
+        ReportEntry::new(
+            Chapter::Details,
+            Severity::Warning,
+            "unknown_call_direction",
+            msg,
+        ),
+    );
+    utils::report_entry(
+        report,
+        ReportEntry::new(
+            Chapter::Ingest,
+            Severity::Warning,
+            "unknown_call_direction",
+            ingest_msg,
+        ),
+    );
+    report.counters.unknown_call_directions += 1;
+}
+
 impl CallDirection {
-    fn as_str(&self) -> &'static str {
+    pub fn as_str(&self) -> &'static str {
         match self {
             CallDirection::Inbound => "Inbound",
             CallDirection::Outbound => "Outbound",
@@ -0,0 +1,187 @@
+//! Detection of loops (recursive calls) in a call-chain.
+//! A loop is a subsequence of Service/Operations that is entered again further down the call-chain. The detection
+//! is performed on the inbound calls only, as an outbound call is always followed by the inbound call it triggers.
+//! Spans might be missing (UDP transport), so a repetition of a loop is also accepted when a single step is missing.
+use super::{
+    call::{Call, CallDirection},
+    call_chain::CallChain,
+};
+use std::fmt;
+
+/// A loop in a call-chain represented by the repeating sequence of Service/Operations.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CallLoop {
+    /// the sequence of service/operations that is repeated
+    pub pattern: Vec<String>,
+    /// index of the first step of the loop (counted over the inbound calls of the call-chain)
+    pub start: usize,
+    /// number of times the start of the pattern is entered (so a recursion A -> B -> A has depth 2)
+    pub depth: usize,
+    /// number of repetitions where a step was missing (most likely due to a missing span)
+    pub num_missing: usize,
+}
+
+impl fmt::Display for CallLoop {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let missing = if self.num_missing > 0 {
+            format!(", {} incomplete", self.num_missing)
+        } else {
+            String::new()
+        };
+        write!(
+            f,
+            "[{}] depth {}{missing}",
+            self.pattern.join(" -> "),
+            self.depth
+        )
+    }
+}
+
+/// reduce the call-chain to the steps used for loop-detection, i.e. the inbound calls.
+/// If the call-direction is not available at all we fall back to using all calls.
+fn get_steps(call_chain: &CallChain) -> Vec<String> {
+    let has_direction = call_chain
+        .iter()
+        .any(|call| call.call_direction != CallDirection::Unknown);
+    call_chain
+        .iter()
+        .filter(|call| !has_direction || call.call_direction != CallDirection::Outbound)
+        .map(Call::get_process_method)
+        .collect()
+}
+
+/// check whether the steps at 'pos' match the pattern exactly, and return the number of steps consumed.
+fn match_exact(steps: &[String], pos: usize, pattern: &[String]) -> Option<usize> {
+    let end = pos + pattern.len();
+    if end <= steps.len() && steps[pos..end] == *pattern {
+        Some(pattern.len())
+    } else {
+        None
+    }
+}
+
+/// check whether the steps at 'pos' match the pattern when a single step (not the first) is missing.
+/// The match is only accepted when the loop is entered again afterwards, as otherwise we can not distinguish
+/// a missing step from a call-chain that leaves the loop halfway.
+fn match_with_missing(steps: &[String], pos: usize, pattern: &[String]) -> Option<usize> {
+    let len = pattern.len();
+    if len < 2
+        || pos + len > steps.len()
+        || steps[pos] != pattern[0]
+        || steps[pos + len - 1] != pattern[0]
+    {
+        return None;
+    }
+    (1..len)
+        .find(|&skip| {
+            pattern
+                .iter()
+                .enumerate()
+                .filter(|(idx, _)| *idx != skip)
+                .zip(&steps[pos..(pos + len - 1)])
+                .all(|((_, p), s)| p == s)
+        })
+        .map(|_| len - 1)
+}
+
+/// Detect all loops in a sequence of steps.
+/// Scanning from the start, a loop starts at the first step that re-appears further down the sequence. The repeating pattern is
+/// the sequence up to that re-appearance, and it is followed as long as the sequence repeats this pattern (allowing for a missing step).
+pub fn detect_step_loops(steps: &[String]) -> Vec<CallLoop> {
+    let mut loops = Vec::new();
+    let mut idx = 0;
+    while idx < steps.len() {
+        let Some(next) = (idx + 1..steps.len()).find(|&j| steps[j] == steps[idx]) else {
+            idx += 1;
+            continue;
+        };
+        let pattern = &steps[idx..next];
+        let mut pos = next;
+        let mut depth = 1;
+        let mut num_missing = 0;
+        loop {
+            if let Some(len) = match_exact(steps, pos, pattern) {
+                pos += len;
+            } else if let Some(len) = match_with_missing(steps, pos, pattern) {
+                pos += len;
+                num_missing += 1;
+            } else {
+                break;
+            }
+            depth += 1;
+        }
+        // the loop is entered once more when the sequence ends in (part of) the pattern
+        if pos < steps.len() && steps[pos] == pattern[0] {
+            depth += 1;
+            pos += 1;
+        }
+        loops.push(CallLoop {
+            pattern: pattern.to_vec(),
+            start: idx,
+            depth,
+            num_missing,
+        });
+        idx = pos;
+    }
+    loops
+}
+
+/// Detect all loops (recursive calls) in a call-chain
+pub fn detect_loops(call_chain: &CallChain) -> Vec<CallLoop> {
+    detect_step_loops(&get_steps(call_chain))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::detect_step_loops;
+
+    fn to_steps(steps: &[&str]) -> Vec<String> {
+        steps.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn no_loop() {
+        let steps = to_steps(&["a/x", "b/y", "c/z"]);
+        assert!(detect_step_loops(&steps).is_empty());
+    }
+
+    #[test]
+    fn direct_recursion() {
+        let steps = to_steps(&["gw/get", "a/x", "a/x", "a/x"]);
+        let loops = detect_step_loops(&steps);
+        assert_eq!(loops.len(), 1);
+        assert_eq!(loops[0].pattern, to_steps(&["a/x"]));
+        assert_eq!(loops[0].start, 1);
+        assert_eq!(loops[0].depth, 3);
+        assert_eq!(loops[0].num_missing, 0);
+    }
+
+    #[test]
+    fn mutual_recursion() {
+        let steps = to_steps(&["gw/get", "a/x", "b/y", "a/x", "b/y", "a/x", "c/z"]);
+        let loops = detect_step_loops(&steps);
+        assert_eq!(loops.len(), 1);
+        assert_eq!(loops[0].pattern, to_steps(&["a/x", "b/y"]));
+        assert_eq!(loops[0].depth, 3);
+    }
+
+    #[test]
+    fn recursion_with_missing_span() {
+        let steps = to_steps(&["a/x", "b/y", "c/z", "a/x", "c/z", "a/x", "b/y", "c/z"]);
+        let loops = detect_step_loops(&steps);
+        assert_eq!(loops.len(), 1);
+        assert_eq!(loops[0].pattern, to_steps(&["a/x", "b/y", "c/z"]));
+        assert_eq!(loops[0].depth, 3);
+        assert_eq!(loops[0].num_missing, 1);
+    }
+
+    #[test]
+    fn two_separate_loops() {
+        let steps = to_steps(&["a/x", "a/x", "b/y", "c/z", "b/y"]);
+        let loops = detect_step_loops(&steps);
+        assert_eq!(loops.len(), 2);
+        assert_eq!(loops[0].pattern, to_steps(&["a/x"]));
+        assert_eq!(loops[1].pattern, to_steps(&["b/y", "c/z"]));
+        assert_eq!(loops[1].depth, 2);
+    }
+}
@@ -0,0 +1,168 @@
+//! The catalog of known call-chains, i.e. a folder containing a '.cchain' file for each end-point.
+//! The catalog is used for inspection and maintenance of these files, while the CChainEndPointCache is used during analysis.
+use super::{
+    cchain_cache::EndPointCChains,
+    file::{read_cchain_file, validate_cchain_file},
+};
+use chrono::NaiveDate;
+use std::{
+    collections::{BTreeMap, HashSet},
+    error::Error,
+    fs,
+    path::{Path, PathBuf},
+};
+
+const CCHAIN_EXT: &str = "cchain";
+
+/// get the end-point keys and paths of all cchain-files in a folder (ordered on the key)
+fn cchain_files(folder: &Path) -> Result<Vec<(String, PathBuf)>, Box<dyn Error>> {
+    let mut files: Vec<_> = fs::read_dir(folder)?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.is_file() && path.extension().is_some_and(|ext| ext == CCHAIN_EXT))
+        .filter_map(|path| {
+            path.file_stem()
+                .and_then(|stem| stem.to_str())
+                .map(|stem| (stem.to_owned(), path.clone()))
+        })
+        .collect();
+    files.sort();
+    Ok(files)
+}
+
+pub struct CChainCatalog {
+    pub folder: PathBuf,
+    pub end_points: BTreeMap<String, EndPointCChains>,
+}
+
+impl CChainCatalog {
+    /// read all cchain-files in the folder
+    pub fn read(folder: &Path) -> Result<Self, Box<dyn Error>> {
+        let end_points = cchain_files(folder)?
+            .into_iter()
+            .map(|(key, path)| {
+                read_cchain_file(&path)
+                    .map(|epcc| (key, epcc))
+                    .map_err(|err| format!("Failed to read '{}': {err}", path.display()).into())
+            })
+            .collect::<Result<_, Box<dyn Error>>>()?;
+        Ok(Self {
+            folder: folder.to_path_buf(),
+            end_points,
+        })
+    }
+
+    /// Validate all cchain-files in the folder and return the list of issues found (each as file-name, line-number and message).
+    pub fn validate(folder: &Path) -> Result<Vec<String>, Box<dyn Error>> {
+        let mut issues = Vec::new();
+        for (_, path) in cchain_files(folder)? {
+            validate_cchain_file(&path)?
+                .into_iter()
+                .for_each(|(line, msg)| issues.push(format!("{}:{line}: {msg}", path.display())));
+        }
+        Ok(issues)
+    }
+
+    /// header for the list output in ';'-separated csv-format
+    pub fn list_header_str() -> &'static str {
+        "End_point; Num_chains; Num_leaf_chains; Num_undated; First_last_seen; Last_last_seen"
+    }
+
+    /// list the end-points with the number of chains in ';'-separated csv-format
+    pub fn list(&self) -> Vec<String> {
+        self.end_points
+            .iter()
+            .map(|(key, epcc)| {
+                let num_leafs = epcc.chains.iter().filter(|cck| cck.is_leaf).count();
+                let num_undated = epcc.chains.len() - epcc.last_seen.len();
+                let first = epcc.last_seen.values().min();
+                let last = epcc.last_seen.values().max();
+                let fmt_date = |d: Option<&NaiveDate>| d.map_or(String::new(), |d| d.to_string());
+                format!(
+                    "{key}; {}; {num_leafs}; {num_undated}; {}; {}",
+                    epcc.chains.len(),
+                    fmt_date(first),
+                    fmt_date(last)
+                )
+            })
+            .collect()
+    }
+
+    /// Show the differences with another catalog. Lines starting with '-' only exist in this catalog
+    /// and lines starting with '+' only exist in the other catalog.
+    pub fn diff(&self, other: &CChainCatalog) -> Vec<String> {
+        let keys: HashSet<_> = self
+            .end_points
+            .keys()
+            .chain(other.end_points.keys())
+            .collect();
+        let mut keys: Vec<_> = keys.into_iter().collect();
+        keys.sort();
+
+        let mut lines = Vec::new();
+        keys.into_iter().for_each(|key| {
+            match (self.end_points.get(key), other.end_points.get(key)) {
+                (Some(epcc), None) => lines.push(format!(
+                    "- end-point '{key}' ({} chains)",
+                    epcc.chains.len()
+                )),
+                (None, Some(epcc)) => lines.push(format!(
+                    "+ end-point '{key}' ({} chains)",
+                    epcc.chains.len()
+                )),
+                (Some(epcc), Some(other_epcc)) => {
+                    let removed: Vec<_> = epcc
+                        .chains
+                        .iter()
+                        .filter(|cck| !other_epcc.chains.contains(cck))
+                        .collect();
+                    let added: Vec<_> = other_epcc
+                        .chains
+                        .iter()
+                        .filter(|cck| !epcc.chains.contains(cck))
+                        .collect();
+                    if !removed.is_empty() || !added.is_empty() {
+                        lines.push(format!(
+                            "~ end-point '{key}' ({} removed, {} added)",
+                            removed.len(),
+                            added.len()
+                        ));
+                        removed
+                            .iter()
+                            .for_each(|cck| lines.push(format!("-    {}", cck.call_chain_key())));
+                        added
+                            .iter()
+                            .for_each(|cck| lines.push(format!("+    {}", cck.call_chain_key())));
+                    }
+                }
+                (None, None) => (),
+            }
+        });
+        lines
+    }
+
+    /// remove all chains that have not been seen since 'since' and return the number of chains removed
+    pub fn prune(&mut self, since: NaiveDate, include_undated: bool) -> usize {
+        self.end_points
+            .values_mut()
+            .map(|epcc| epcc.prune(since, include_undated))
+            .sum()
+    }
+
+    /// merge another catalog into this one and return the number of chains added
+    pub fn merge(&mut self, other: CChainCatalog) -> usize {
+        other
+            .end_points
+            .into_iter()
+            .map(|(key, other_epcc)| self.end_points.entry(key).or_default().merge(other_epcc))
+            .sum()
+    }
+
+    /// write all modified end-points to the folder of the catalog and return the number of files written
+    pub fn write(&self) -> usize {
+        self.end_points
+            .iter()
+            .filter(|(_, epcc)| epcc.is_dirty())
+            .map(|(key, epcc)| epcc.write_call_chain(self.folder.clone(), key))
+            .count()
+    }
+}
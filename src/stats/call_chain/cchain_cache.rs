@@ -1,39 +1,105 @@
 use super::{
     cchain_stats::CChainStatsKey,
-    file::{cchain_filename, read_cchain_file},
+    file::{cchain_filename, cchain_line, read_cchain_file},
 };
-use crate::utils;
-
+use crate::utils::{self, Chapter, Report, ReportEntry, Severity};
+use chrono::NaiveDate;
 use std::{collections::HashMap, mem, path::PathBuf};
 
 /// An end-point has a set of call-chains that originate from this endpoint (each represented by a CChainStatsKey)
 //pub type EndPointCChain = Vec<CChainStatsKey>;
+#[derive(Debug, Default)]
 pub struct EndPointCChains {
     dirty: bool,
     pub chains: Vec<CChainStatsKey>,
+    /// The date at which a chain was last observed. Chains from files written before this date was tracked do not have an entry.
+    pub last_seen: HashMap<CChainStatsKey, NaiveDate>,
 }
 
 impl EndPointCChains {
     pub fn new(chains: Vec<CChainStatsKey>) -> Self {
+        Self::new_with_last_seen(chains, HashMap::new())
+    }
+
+    pub fn new_with_last_seen(
+        chains: Vec<CChainStatsKey>,
+        last_seen: HashMap<CChainStatsKey, NaiveDate>,
+    ) -> Self {
         let dirty = false;
-        Self { dirty, chains }
+        Self {
+            dirty,
+            chains,
+            last_seen,
+        }
     }
 
-    pub fn new_dirty(chains: Vec<CChainStatsKey>) -> Option<Self> {
-        let dirty = true;
-        Some(Self { dirty, chains })
+    pub fn new_dirty(chains: Vec<CChainStatsKey>, seen: Option<NaiveDate>) -> Option<Self> {
+        let mut epcc = Self::default();
+        epcc.update_chains(chains, seen);
+        Some(epcc)
     }
 
-    /// Update the entry with the provided chains. If updates are needed set dirty-flag to false
-    pub fn update_chains(&mut self, check_chains: Vec<CChainStatsKey>) {
-        let new_entries: Vec<_> = check_chains
-            .into_iter()
-            .filter(|check_key| self.chains.iter().any(|curr_key| *curr_key == *check_key))
-            .collect();
-        if !new_entries.is_empty() {
-            self.chains.extend(new_entries);
+    /// Update the entry with the provided chains, and return the number of chains that were new for this end-point.
+    /// If 'seen' is provided the last-seen date of the provided chains is moved forward to this date.
+    /// The dirty-flag is set when the entry is modified.
+    pub fn update_chains(
+        &mut self,
+        check_chains: Vec<CChainStatsKey>,
+        seen: Option<NaiveDate>,
+    ) -> usize {
+        let mut num_new = 0;
+        check_chains.into_iter().for_each(|check_key| {
+            if let Some(seen) = seen {
+                match self.last_seen.get_mut(&check_key) {
+                    Some(last_seen) if *last_seen >= seen => (),
+                    Some(last_seen) => {
+                        *last_seen = seen;
+                        self.dirty = true;
+                    }
+                    None => {
+                        self.last_seen.insert(check_key.clone(), seen);
+                        self.dirty = true;
+                    }
+                }
+            }
+            if !self.chains.contains(&check_key) {
+                self.chains.push(check_key);
+                num_new += 1;
+                self.dirty = true;
+            }
+        });
+        num_new
+    }
+
+    /// Merge the chains of another end-point into this one and return the number of chains that were added.
+    pub fn merge(&mut self, other: EndPointCChains) -> usize {
+        let num_before = self.chains.len();
+        let EndPointCChains {
+            chains, last_seen, ..
+        } = other;
+        chains.into_iter().for_each(|cck| {
+            let seen = last_seen.get(&cck).cloned();
+            self.update_chains(vec![cck], seen);
+        });
+        self.chains.len() - num_before
+    }
+
+    /// Remove the chains that have not been seen since 'since' and return the number of chains removed.
+    /// Chains without a last-seen date are only removed when 'include_undated' is set.
+    pub fn prune(&mut self, since: NaiveDate, include_undated: bool) -> usize {
+        let num_before = self.chains.len();
+        let last_seen = &self.last_seen;
+        self.chains.retain(|cck| match last_seen.get(cck) {
+            Some(seen) => *seen >= since,
+            None => !include_undated,
+        });
+        let chains = &self.chains;
+        self.last_seen.retain(|cck, _| chains.contains(cck));
+        let num_removed = num_before - self.chains.len();
+        if num_removed > 0 {
             self.dirty = true;
         }
+        num_removed
     }
 
     pub fn is_dirty(&self) -> bool {
@@ -47,7 +113,7 @@ impl EndPointCChains {
         let cchain_str = self
             .chains
             .iter()
-            .map(|cc| cc.call_chain_key())
+            .map(|cc| cchain_line(cc, self.last_seen.get(cc)))
             .collect::<Vec<_>>()
             .join("\n");
         utils::write_string_to_file(file_name, cchain_str).expect("Failed to write cchain-files.");
@@ -68,8 +134,12 @@ impl CChainEndPointCache {
         }
     }
 
-    /// internatal function that returns a mutable reference
-    fn get_cchain_key_aux(&mut self, key: &str) -> &mut Option<EndPointCChains> {
+    /// internatal function that returns a mutable reference. Failures to load an entry are added to 'report'.
+    fn get_cchain_key_aux(
+        &mut self,
+        report: &mut Report,
+        key: &str,
+    ) -> &mut Option<EndPointCChains> {
         self.cache.entry(key.to_string()).or_insert_with(|| {
             let mut path = self.path.clone();
             path.push(cchain_filename(key));
@@ -77,20 +147,34 @@ impl CChainEndPointCache {
                 match read_cchain_file(&path) {
                     Ok(cchain_key) => Some(cchain_key),
                     Err(err) => {
-                        utils::report(
-                            utils::Chapter::Issues,
-                            format!("Loading of entry '{key}' failed with error: {err:?}"),
+                        utils::report_entry(
+                            report,
+                            ReportEntry::new(
+                                Chapter::Issues,
+                                Severity::Error,
+                                "cchain_load_failed",
+                                format!("Loading of entry '{key}' failed with error: {err:?}"),
+                            )
+                            .endpoint(key)
+                            .file(&path.to_string_lossy()),
                         );
                         None
                     }
                 }
             } else {
-                utils::report(
-                    utils::Chapter::Details,
-                    format!(
-                        "Could not find '{}' so no call-chain available",
-                        path.display()
-                    ),
+                utils::report_entry(
+                    report,
+                    ReportEntry::new(
+                        Chapter::Details,
+                        Severity::Info,
+                        "cchain_missing",
+                        format!(
+                            "Could not find '{}' so no call-chain available",
+                            path.display()
+                        ),
+                    )
+                    .endpoint(key)
+                    .file(&path.to_string_lossy()),
                 );
                 None
             }
@@ -101,18 +185,26 @@ impl CChainEndPointCache {
         s.replace(&['/', '\\', ';', ':'][..], "_")
     }
     /// extract a reference to an EndPointCChains
-    pub fn get_cchain_key(&mut self, key: &str) -> Option<&EndPointCChains> {
-        self.get_cchain_key_aux(key).as_ref()
+    pub fn get_cchain_key(&mut self, report: &mut Report, key: &str) -> Option<&EndPointCChains> {
+        self.get_cchain_key_aux(report, key).as_ref()
     }
 
-    /// Create a new entry of update an existing entry with the provided cchains
-    pub fn create_update_entry(&mut self, key: &str, cchains: Vec<CChainStatsKey>) {
-        match self.get_cchain_key_aux(key) {
-            Some(entry) => entry.update_chains(cchains),
+    /// Create a new entry of update an existing entry with the provided cchains that have been observed at date 'seen'
+    pub fn create_update_entry(
+        &mut self,
+        report: &mut Report,
+        key: &str,
+        cchains: Vec<CChainStatsKey>,
+        seen: Option<NaiveDate>,
+    ) {
+        match self.get_cchain_key_aux(report, key) {
+            Some(entry) => {
+                entry.update_chains(cchains, seen);
+            }
             None => {
                 _ = self
                     .cache
-                    .insert(key.to_string(), EndPointCChains::new_dirty(cchains));
+                    .insert(key.to_string(), EndPointCChains::new_dirty(cchains, seen));
             }
         }
     }
@@ -130,3 +222,74 @@ impl Drop for CChainEndPointCache {
         });
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn to_key(chain: &str) -> CChainStatsKey {
+        CChainStatsKey::parse(chain).unwrap()
+    }
+
+    fn date(d: u32) -> Option<NaiveDate> {
+        NaiveDate::from_ymd_opt(2023, 10, d)
+    }
+
+    #[test]
+    fn update_adds_new_chains_only() {
+        let mut epcc = EndPointCChains::new(vec![to_key("a/x [Inbound] & & *LEAF*")]);
+        assert!(!epcc.is_dirty());
+
+        // an existing chain does not change the entry
+        let num_new = epcc.update_chains(vec![to_key("a/x [Inbound] & & *LEAF*")], None);
+        assert_eq!(num_new, 0);
+        assert!(!epcc.is_dirty());
+
+        // new chains are added once
+        let num_new = epcc.update_chains(
+            vec![
+                to_key("a/x [Inbound] & & *LEAF*"),
+                to_key("a/x [Inbound] | b/y [Inbound] & & *LEAF*"),
+                to_key("a/x [Inbound] | b/y [Inbound] & & *LEAF*"),
+            ],
+            None,
+        );
+        assert_eq!(num_new, 1);
+        assert_eq!(epcc.chains.len(), 2);
+        assert!(epcc.is_dirty());
+    }
+
+    #[test]
+    fn update_last_seen_and_prune() {
+        let old = to_key("a/x [Inbound] & & *LEAF*");
+        let recent = to_key("a/x [Inbound] | b/y [Inbound] & & *LEAF*");
+        let undated = to_key("a/x [Inbound] | c/z [Inbound] & & *LEAF*");
+        let mut epcc = EndPointCChains::new(vec![undated.clone()]);
+        epcc.update_chains(vec![old.clone(), recent.clone()], date(1));
+        epcc.update_chains(vec![recent.clone()], date(20));
+        assert_eq!(epcc.last_seen.get(&old).cloned(), date(1));
+        assert_eq!(epcc.last_seen.get(&recent).cloned(), date(20));
+
+        assert_eq!(epcc.prune(date(10).unwrap(), false), 1);
+        assert_eq!(epcc.chains, vec![undated, recent.clone()]);
+        assert_eq!(epcc.prune(date(10).unwrap(), true), 1);
+        assert_eq!(epcc.chains, vec![recent]);
+    }
+
+    #[test]
+    fn merge_end_points() {
+        let shared = to_key("a/x [Inbound] & & *LEAF*");
+        let mut epcc = EndPointCChains::new(vec![shared.clone()]);
+        let mut other = EndPointCChains::default();
+        other.update_chains(
+            vec![
+                shared.clone(),
+                to_key("a/x [Inbound] | b/y [Inbound] & & *LEAF*"),
+            ],
+            date(5),
+        );
+        assert_eq!(epcc.merge(other), 1);
+        assert_eq!(epcc.chains.len(), 2);
+        assert_eq!(epcc.last_seen.get(&shared).cloned(), date(5));
+    }
+}
@@ -0,0 +1,414 @@
+//! Repair of non-rooted call-chains, i.e. call-chains that miss a prefix (or some calls) due to missing spans.
+//! A non-rooted chain is matched against the known call-chains of its end-point. A known chain is a candidate when the
+//! non-rooted chain equals its tail, or (as fall-back) when it matches the tail with a single call missing between
+//! some of the subsequent calls (a gap in the middle of the chain).
+//! When multiple candidates remain, the observed counts of the rooted chains are used to pick the dominant candidate,
+//! or to distribute the traffic of the non-rooted chain proportionally over the candidates.
+use super::{call::Call, cchain_cache::EndPointCChains, cchain_stats::CChainStatsKey, CallChain};
+use crate::utils::{self, Chapter, Report, ReportEntry, Severity};
+use clap::ValueEnum;
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// Maximal number of calls that can be missing between two subsequent calls of a non-rooted chain.
+const MAX_GAP: usize = 1;
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, ValueEnum, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RepairMode {
+    /// Only repair when a single candidate matches (after selecting on is_leaf)
+    #[default]
+    Exact,
+    /// Assign an ambiguous chain to the dominant candidate, provided its share exceeds the dominance-threshold
+    Dominant,
+    /// Distribute an ambiguous chain over all candidates proportional to the observed counts of these candidates
+    Proportional,
+}
+
+/// The parameters for the repair of non-rooted call-chains
+#[derive(Copy, Clone, Debug)]
+pub struct CChainRepair {
+    pub mode: RepairMode,
+    /// minimal share of the observed counts a candidate needs to be dominant
+    pub dominance_threshold: f64,
+}
+
+impl Default for CChainRepair {
+    fn default() -> Self {
+        Self {
+            mode: RepairMode::Exact,
+            dominance_threshold: 0.8,
+        }
+    }
+}
+
+/// The way the traffic of a non-rooted chain is assigned to the known chains.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum RepairKind {
+    Exact,
+    Dominant,
+    Proportional,
+}
+
+/// The outcome of a successful repair
+#[derive(Debug)]
+pub struct Repair {
+    pub kind: RepairKind,
+    /// the repair is based on a match with a gap in the middle of the chain
+    pub with_gap: bool,
+    /// the new keys with the count that is assigned to each of these keys
+    pub assignments: Vec<(CChainStatsKey, usize)>,
+}
+
+/// Observed counts of the rooted chains, used as weights to resolve ambiguous matches.
+pub type ObservedCounts = HashMap<(CallChain, bool), usize>;
+
+/// check whether the fragment equals the tail of the call-chain
+fn tail_match(fragment: &[Call], call_chain: &[Call]) -> bool {
+    fragment.len() <= call_chain.len() && call_chain.ends_with(fragment)
+}
+
+/// check whether the fragment matches the tail of the call-chain when at most MAX_GAP calls are missing between subsequent calls of the fragment.
+fn gap_match(fragment: &[Call], call_chain: &[Call]) -> bool {
+    match (fragment.split_last(), call_chain.split_last()) {
+        (None, _) => true,
+        (Some(_), None) => false,
+        (Some((f_last, f_rest)), Some((c_last, c_rest))) => {
+            f_last == c_last
+                && (0..=MAX_GAP.min(c_rest.len())).any(|skip| {
+                    // the first call of the fragment can be preceded by any prefix, so a skip is only needed in between calls.
+                    f_rest.is_empty() || gap_match(f_rest, &c_rest[..(c_rest.len() - skip)])
+                })
+        }
+    }
+}
+
+/// distribute 'count' over the weights using the method of the largest remainder, such that the sum of the parts equals 'count'
+pub(super) fn distribute(count: usize, weights: &[usize]) -> Vec<usize> {
+    let total: usize = weights.iter().sum();
+    let weights: Vec<usize> = if total == 0 {
+        vec![1; weights.len()]
+    } else {
+        weights.to_vec()
+    };
+    let total: usize = weights.iter().sum();
+    let mut parts: Vec<_> = weights.iter().map(|w| count * w / total).collect();
+    let mut remainders: Vec<_> = weights
+        .iter()
+        .enumerate()
+        .map(|(idx, w)| (count * w % total, idx))
+        .collect();
+    remainders.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+    let missing = count - parts.iter().sum::<usize>();
+    remainders
+        .iter()
+        .take(missing)
+        .for_each(|(_, idx)| parts[*idx] += 1);
+    parts
+}
+
+impl CChainRepair {
+    /// find the candidates for a non-rooted chain and return whether a gap-match was needed.
+    fn get_candidates<'a>(
+        cck: &CChainStatsKey,
+        expected_cc: &'a EndPointCChains,
+    ) -> (Vec<&'a CChainStatsKey>, bool) {
+        let select = |matcher: fn(&[Call], &[Call]) -> bool| {
+            let mut candidates: Vec<&CChainStatsKey> = Vec::new();
+            expected_cc
+                .chains
+                .iter()
+                .filter(|ecc| matcher(&cck.call_chain, &ecc.call_chain))
+                .for_each(|ecc| {
+                    // the cchain-files can contain the same chain with different caching-labels
+                    if !candidates
+                        .iter()
+                        .any(|c| c.call_chain == ecc.call_chain && c.is_leaf == ecc.is_leaf)
+                    {
+                        candidates.push(ecc)
+                    }
+                });
+            candidates
+        };
+        let (candidates, with_gap) = match select(tail_match) {
+            candidates if candidates.is_empty() => (select(gap_match), true),
+            candidates => (candidates, false),
+        };
+        // prefer the candidates with the same leaf-status
+        if candidates.iter().any(|c| c.is_leaf == cck.is_leaf) {
+            (
+                candidates
+                    .into_iter()
+                    .filter(|c| c.is_leaf == cck.is_leaf)
+                    .collect(),
+                with_gap,
+            )
+        } else {
+            (candidates, with_gap)
+        }
+    }
+
+    /// try to repair a non-rooted call-chain with 'count' observations based on the expected call-chains of the end-point.
+    /// An ambiguous chain that can not be repaired is added to 'report'.
+    pub fn repair(
+        &self,
+        report: &mut Report,
+        cck: &CChainStatsKey,
+        count: usize,
+        expected_cc: &EndPointCChains,
+        observed: &ObservedCounts,
+    ) -> Option<Repair> {
+        let (candidates, with_gap) = Self::get_candidates(cck, expected_cc);
+        let new_key = |c: &CChainStatsKey| CChainStatsKey {
+            call_chain: c.call_chain.clone(),
+            caching_process: cck.caching_process.clone(),
+            is_leaf: c.is_leaf,
+        };
+        let weights: Vec<usize> = candidates
+            .iter()
+            .map(|c| {
+                *observed
+                    .get(&(c.call_chain.clone(), c.is_leaf))
+                    .unwrap_or(&0)
+            })
+            .collect();
+        let total: usize = weights.iter().sum();
+
+        let (kind, assignments) = match (candidates.len(), self.mode) {
+            (0, _) => return None,
+            (1, _) => (RepairKind::Exact, vec![(new_key(candidates[0]), count)]),
+            (n, RepairMode::Exact) => {
+                utils::report_entry(
+                    report,
+                    ReportEntry::new(
+                        Chapter::Details,
+                        Severity::Warning,
+                        "no_fix_ambiguous",
+                        format!(
+                            "NO FIX: {n} matches found for non-rooted '{:?}'",
+                            cck.call_chain
+                        ),
+                    ),
+                );
+                return None;
+            }
+            (_, RepairMode::Dominant) => {
+                let (idx, max) = weights
+                    .iter()
+                    .enumerate()
+                    .max_by(|a, b| a.1.cmp(b.1).then(b.0.cmp(&a.0)))
+                    .unwrap();
+                if total == 0 || (*max as f64 / total as f64) < self.dominance_threshold {
+                    return None;
+                }
+                (
+                    RepairKind::Dominant,
+                    vec![(new_key(candidates[idx]), count)],
+                )
+            }
+            (_, RepairMode::Proportional) => {
+                let assignments = distribute(count, &weights)
+                    .into_iter()
+                    .zip(candidates.iter())
+                    .filter(|(part, _)| *part > 0)
+                    .map(|(part, c)| (new_key(c), part))
+                    .collect();
+                (RepairKind::Proportional, assignments)
+            }
+        };
+        Some(Repair {
+            kind,
+            with_gap,
+            assignments,
+        })
+    }
+}
+
+/// Statistics on the repairs applied, counted in chains (keys) and in calls (traffic).
+#[derive(Debug, Default, Clone)]
+pub struct RepairStats {
+    pub exact_chains: usize,
+    pub exact_calls: usize,
+    pub dominant_chains: usize,
+    pub dominant_calls: usize,
+    pub proportional_chains: usize,
+    pub proportional_calls: usize,
+    pub gap_chains: usize,
+    pub gap_calls: usize,
+    pub failed_chains: usize,
+    pub failed_calls: usize,
+}
+
+impl RepairStats {
+    /// register the outcome of a repair of a non-rooted chain with 'count' calls
+    pub fn add(&mut self, repair: Option<&Repair>, count: usize) {
+        match repair {
+            None => {
+                self.failed_chains += 1;
+                self.failed_calls += count;
+            }
+            Some(repair) => {
+                match repair.kind {
+                    RepairKind::Exact => {
+                        self.exact_chains += 1;
+                        self.exact_calls += count;
+                    }
+                    RepairKind::Dominant => {
+                        self.dominant_chains += 1;
+                        self.dominant_calls += count;
+                    }
+                    RepairKind::Proportional => {
+                        self.proportional_chains += 1;
+                        self.proportional_calls += count;
+                    }
+                }
+                if repair.with_gap {
+                    self.gap_chains += 1;
+                    self.gap_calls += count;
+                }
+            }
+        }
+    }
+
+    /// extend with the repair statistics of another set
+    pub fn extend(&mut self, other: &RepairStats) {
+        self.exact_chains += other.exact_chains;
+        self.exact_calls += other.exact_calls;
+        self.dominant_chains += other.dominant_chains;
+        self.dominant_calls += other.dominant_calls;
+        self.proportional_chains += other.proportional_chains;
+        self.proportional_calls += other.proportional_calls;
+        self.gap_chains += other.gap_chains;
+        self.gap_calls += other.gap_calls;
+        self.failed_chains += other.failed_chains;
+        self.failed_calls += other.failed_calls;
+    }
+
+    /// the number of non-rooted chains that have been repaired
+    pub fn num_fixes(&self) -> usize {
+        self.exact_chains + self.dominant_chains + self.proportional_chains
+    }
+
+    /// a one-line summary that shows which part of the traffic is assigned exactly and which part is assigned probabilistically
+    pub fn summary(&self) -> String {
+        format!("Repair of non-rooted call-chains: {} calls ({} chains) assigned exactly, {} calls ({} chains) assigned to a dominant chain, {} calls ({} chains) distributed proportionally and {} calls ({} chains) not repaired. Of the repaired chains {} calls ({} chains) were matched with a gap in the middle of the chain.",
+            self.exact_calls, self.exact_chains,
+            self.dominant_calls, self.dominant_chains,
+            self.proportional_calls, self.proportional_chains,
+            self.failed_calls, self.failed_chains,
+            self.gap_calls, self.gap_chains)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn to_key(chain: &str, is_leaf: bool) -> CChainStatsKey {
+        let leaf = if is_leaf { " *LEAF*" } else { "" };
+        CChainStatsKey::parse(&format!("{chain} & & {leaf}")).unwrap()
+    }
+
+    fn expected() -> EndPointCChains {
+        EndPointCChains::new(vec![
+            to_key("gw/get [Inbound] | a/x [Inbound] | c/z [Inbound]", true),
+            to_key("gw/get [Inbound] | b/y [Inbound] | c/z [Inbound]", true),
+            to_key(
+                "gw/get [Inbound] | a/x [Inbound] | d/w [Inbound] | e/v [Inbound]",
+                true,
+            ),
+        ])
+    }
+
+    fn observed(a: usize, b: usize) -> ObservedCounts {
+        let ecc = expected();
+        HashMap::from([
+            ((ecc.chains[0].call_chain.clone(), true), a),
+            ((ecc.chains[1].call_chain.clone(), true), b),
+        ])
+    }
+
+    #[test]
+    fn distribute_counts() {
+        assert_eq!(distribute(10, &[3, 1]), vec![8, 2]);
+        assert_eq!(distribute(3, &[1, 1, 1]), vec![1, 1, 1]);
+        assert_eq!(distribute(5, &[0, 0]), vec![3, 2]);
+        assert_eq!(distribute(7, &[2, 3, 5]).iter().sum::<usize>(), 7);
+    }
+
+    #[test]
+    fn ambiguous_fragment() {
+        let fragment = to_key("c/z [Inbound]", true);
+        let exact = CChainRepair::default();
+        assert!(exact
+            .repair(
+                &mut Report::default(),
+                &fragment,
+                10,
+                &expected(),
+                &observed(90, 10)
+            )
+            .is_none());
+
+        let dominant = CChainRepair {
+            mode: RepairMode::Dominant,
+            ..Default::default()
+        };
+        let repair = dominant
+            .repair(
+                &mut Report::default(),
+                &fragment,
+                10,
+                &expected(),
+                &observed(90, 10),
+            )
+            .unwrap();
+        assert_eq!(repair.kind, RepairKind::Dominant);
+        assert_eq!(repair.assignments.len(), 1);
+        assert_eq!(repair.assignments[0].0.call_chain[1].service, "a");
+        assert!(dominant
+            .repair(
+                &mut Report::default(),
+                &fragment,
+                10,
+                &expected(),
+                &observed(60, 40)
+            )
+            .is_none());
+
+        let proportional = CChainRepair {
+            mode: RepairMode::Proportional,
+            ..Default::default()
+        };
+        let repair = proportional
+            .repair(
+                &mut Report::default(),
+                &fragment,
+                10,
+                &expected(),
+                &observed(60, 40),
+            )
+            .unwrap();
+        assert_eq!(repair.kind, RepairKind::Proportional);
+        let counts: Vec<_> = repair.assignments.iter().map(|(_, c)| *c).collect();
+        assert_eq!(counts, vec![6, 4]);
+    }
+
+    #[test]
+    fn gap_in_middle() {
+        // the call to d/w is missing
+        let fragment = to_key("a/x [Inbound] | e/v [Inbound]", true);
+        let repair = CChainRepair::default()
+            .repair(
+                &mut Report::default(),
+                &fragment,
+                2,
+                &expected(),
+                &HashMap::new(),
+            )
+            .unwrap();
+        assert!(repair.with_gap);
+        assert_eq!(repair.kind, RepairKind::Exact);
+        assert_eq!(repair.assignments[0].0.call_chain.len(), 4);
+    }
+}
@@ -1,23 +1,25 @@
 use super::{
     call::{Call, CallDirection},
     call_chain::CallChain,
-    cchain_cache::EndPointCChains,
+    cchain_repair::distribute,
     expected_roots::ExpectedRoots,
     file::{call_chain_key, LEAF_LABEL},
 };
 use crate::{
+    context::AnalysisContext,
+    stats::ErrorTemplates,
     string_hash,
-    utils::{self, Chapter, Counted, TimeStats},
+    utils::{self, Counted, TimeStats},
 };
 use serde::{Deserialize, Serialize};
-use std::{cmp::Ordering, collections::HashMap, error::Error};
+use std::{collections::HashMap, error::Error, mem};
 
 #[derive(Debug, Default, Serialize, Deserialize, Clone)]
 pub struct CChainStatsValue {
     pub count: usize,
     pub depth: usize,
     pub duration_micros: Vec<i64>,
-    pub start_dt_micros: Vec<i64>, // represented via start_dt.timestamp_micros()
+    pub start_dt_micros: Vec<i64>, // micro-seconds since epoch (UTC)
     pub looped: Vec<String>,
     pub rooted: bool, //does this call-chain originate from the root of this trace.
     pub expect_root: ExpectedRoots,
@@ -104,7 +106,7 @@ impl CChainStatsKey {
             Some(s) => match s {
                 LEAF_LABEL => true,
                 "" => false,
-                s => panic!("Expected {LEAF_LABEL} or empty string. Found {s}"),
+                s => Err(format!("Expected {LEAF_LABEL} or empty string. Found {s}"))?,
             },
             None => false,
         };
@@ -113,76 +115,32 @@ impl CChainStatsKey {
             .split('|')
             .map(|s| {
                 let Some((proc, meth_dir)) = s.trim().split_once('/') else {
-                    panic!("Failed to unpack '{s}' in a process/operation pair.");
+                    return Err(format!(
+                        "Failed to unpack '{s}' in a process/operation pair."
+                    ));
                 };
                 let (meth, call_direction) = match meth_dir.split_once('[') {
                     Some((meth, dir)) => {
-                        let dir = &dir[0..(dir.len() - 1)];
-                        (meth, dir.into())
+                        let Some(dir) = dir.trim().strip_suffix(']') else {
+                            return Err(format!("Missing ']' after call-direction in '{s}'."));
+                        };
+                        (meth, dir.parse()?)
                     }
                     None => (meth_dir, CallDirection::Unknown),
                 };
-                Call {
+                Ok(Call {
                     service: proc.trim().to_owned(),
                     operation: meth.trim().to_owned(),
                     call_direction,
-                }
+                })
             })
-            .collect();
+            .collect::<Result<_, _>>()?;
         Ok(Self {
             call_chain,
             caching_process,
             is_leaf,
         })
     }
-
-    /// try to remap a non-rooted call-chain based on expected call chains and return whether the remapping succeeded.
-    /// TODO: also return the missing prefix such that it can be corrected later in the overall stats?
-    pub fn remap_callchain(&mut self, expected_cc: &EndPointCChains) -> bool {
-        let cc_len = self.call_chain.len();
-        let matches: Vec<_> = expected_cc
-            .chains
-            .iter()
-            .filter(|ecc| {
-                let ecc_len = ecc.call_chain.len();
-                if cc_len > ecc_len {
-                    false // the chain is longer than the expected chain currently under investigation
-                } else {
-                    let ecc_iter = ecc.call_chain.iter().skip(ecc_len - cc_len);
-                    // compare the call-chains and only return true when these are equal (other fields, such as 'is_leaf' can still differ)
-                    self.call_chain.iter().cmp(ecc_iter) == Ordering::Equal
-                }
-            })
-            .collect();
-        let the_match = match matches.len() {
-            0 => None,
-            1 => Some(matches[0]),
-            2 => {
-                if matches[0].is_leaf == self.is_leaf {
-                    Some(matches[0])
-                } else {
-                    Some(matches[1])
-                }
-            }
-            n => {
-                utils::report(
-                    Chapter::Details,
-                    format!(
-                        "NO FIX: {n} matches found for non-rooted '{:?}'",
-                        self.call_chain
-                    ),
-                );
-                None
-            }
-        };
-        if let Some(the_match) = the_match {
-            self.is_leaf = the_match.is_leaf;
-            self.call_chain = the_match.call_chain.clone();
-            true
-        } else {
-            false
-        }
-    }
 }
 
 impl ToString for CChainStatsKey {
@@ -205,24 +163,24 @@ impl CChainStatsValue {
         TimeStats(&self.duration_micros).get_min_millis()
     }
 
-    pub fn get_min_millis_str(&self) -> String {
-        TimeStats(&self.duration_micros).get_min_millis_str()
+    pub fn get_min_millis_str(&self, ctx: &AnalysisContext) -> String {
+        TimeStats(&self.duration_micros).get_min_millis_str(ctx)
     }
 
     pub fn get_avg_millis(&self) -> f64 {
         TimeStats(&self.duration_micros).get_avg_millis()
     }
 
-    pub fn get_avg_millis_str(&self) -> String {
-        TimeStats(&self.duration_micros).get_avg_millis_str()
+    pub fn get_avg_millis_str(&self, ctx: &AnalysisContext) -> String {
+        TimeStats(&self.duration_micros).get_avg_millis_str(ctx)
     }
 
     pub fn get_median_millis(&self) -> Option<f64> {
         TimeStats(&self.duration_micros).get_median_millis()
     }
 
-    pub fn get_median_millis_str(&self) -> String {
-        TimeStats(&self.duration_micros).get_median_millis_str()
+    pub fn get_median_millis_str(&self, ctx: &AnalysisContext) -> String {
+        TimeStats(&self.duration_micros).get_median_millis_str(ctx)
     }
 
     /// get the P-percentile over the values
@@ -230,39 +188,85 @@ impl CChainStatsValue {
         TimeStats(&self.duration_micros).get_p_millis(p)
     }
 
-    pub fn get_p_millis_str(&self, p: f64) -> String {
-        TimeStats(&self.duration_micros).get_p_millis_str(p)
+    pub fn get_p_millis_str(&self, ctx: &AnalysisContext, p: f64) -> String {
+        TimeStats(&self.duration_micros).get_p_millis_str(ctx, p)
     }
 
     pub fn get_max_millis(&self) -> f64 {
         TimeStats(&self.duration_micros).get_max_millis()
     }
 
-    pub fn get_max_millis_str(&self) -> String {
-        TimeStats(&self.duration_micros).get_max_millis_str()
+    pub fn get_max_millis_str(&self, ctx: &AnalysisContext) -> String {
+        TimeStats(&self.duration_micros).get_max_millis_str(ctx)
     }
 
     pub fn get_avg_rate(&self, num_files: i32) -> Option<f64> {
         TimeStats(&self.start_dt_micros).get_avg_rate(num_files)
     }
 
-    pub fn get_avg_rate_str(&self, num_files: i32) -> String {
-        TimeStats(&self.start_dt_micros).get_avg_rate_str(num_files)
+    pub fn get_avg_rate_str(&self, ctx: &AnalysisContext, num_files: i32) -> String {
+        TimeStats(&self.start_dt_micros).get_avg_rate_str(ctx, num_files)
+    }
+
+    /// split this value in parts with the provided counts (which should add up to self.count).
+    /// The observations (durations and start-times) are divided sequentially over the parts, while
+    /// the error-counts are distributed proportionally, such that the parts add up to the original counts.
+    /// The counted http-codes and error-logs are retained in the first part only.
+    pub fn split(mut self, counts: &[usize]) -> Vec<CChainStatsValue> {
+        let mut not_http_ok = distribute(self.cc_not_http_ok.max(0) as usize, counts).into_iter();
+        let mut with_error_logs =
+            distribute(self.cc_with_error_logs.max(0) as usize, counts).into_iter();
+        let mut duration_micros = mem::take(&mut self.duration_micros).into_iter();
+        let mut start_dt_micros = mem::take(&mut self.start_dt_micros).into_iter();
+        let http_not_ok = mem::take(&mut self.http_not_ok);
+        let error_logs = mem::take(&mut self.error_logs);
+        let mut parts: Vec<_> = counts
+            .iter()
+            .map(|&part| CChainStatsValue {
+                count: part,
+                duration_micros: duration_micros.by_ref().take(part).collect(),
+                start_dt_micros: start_dt_micros.by_ref().take(part).collect(),
+                cc_not_http_ok: not_http_ok.next().unwrap_or_default() as i32,
+                cc_with_error_logs: with_error_logs.next().unwrap_or_default() as i32,
+                ..self.clone()
+            })
+            .collect();
+        if let Some(first) = parts.first_mut() {
+            first.http_not_ok = http_not_ok;
+            first.error_logs = error_logs;
+        }
+        parts
+    }
+
+    /// add the observations and error-information of 'other' (a value for the same call-chain) to this value
+    pub fn extend(&mut self, mut other: CChainStatsValue) {
+        self.count += other.count;
+        self.duration_micros.append(&mut other.duration_micros);
+        self.start_dt_micros.append(&mut other.start_dt_micros);
+        self.cc_not_http_ok += other.cc_not_http_ok;
+        self.cc_with_error_logs += other.cc_with_error_logs;
+        self.http_not_ok.extend(other.http_not_ok);
+        self.error_logs.extend(other.error_logs);
+    }
+
+    /// group the error-messages observed along this call-chain in templates
+    pub fn get_error_templates(&self, ctx: &AnalysisContext) -> ErrorTemplates {
+        ErrorTemplates::new(ctx, &self.error_logs)
     }
 
     pub fn get_frac_not_http_ok(&self) -> f64 {
         self.cc_not_http_ok as f64 / self.count as f64
     }
-    pub fn get_frac_not_http_ok_str(&self) -> String {
-        utils::format_float(self.get_frac_not_http_ok())
+    pub fn get_frac_not_http_ok_str(&self, ctx: &AnalysisContext) -> String {
+        utils::format_float(ctx, self.get_frac_not_http_ok())
     }
 
     pub fn get_frac_error_log(&self) -> f64 {
         self.cc_with_error_logs as f64 / self.count as f64
     }
 
-    pub fn get_frac_error_log_str(&self) -> String {
-        utils::format_float(self.get_frac_error_log())
+    pub fn get_frac_error_log_str(&self, ctx: &AnalysisContext) -> String {
+        utils::format_float(ctx, self.get_frac_error_log())
     }
 
     /// header for report_stats_line output in ';'-separated csv-format
@@ -273,6 +277,7 @@ impl CChainStatsValue {
     /// reports the statistics for a single line in ';'-separated csv-format
     pub fn report_stats_line(
         &self,
+        ctx: &AnalysisContext,
         process_key: &str,
         ps_key: &CChainStatsKey,
         n: f64,
@@ -303,16 +308,16 @@ impl CChainStatsValue {
             self.count,
             !self.looped.is_empty(),
             self.looped,
-            self.get_min_millis_str(),
-            self.get_median_millis_str(),
-            self.get_avg_millis_str(),
-            self.get_max_millis_str(),
-            utils::format_float(percentage),
-            self.get_avg_rate_str(num_files),
-            utils::format_float(expect_duration),
-            utils::format_float(expect_contribution),
-            self.get_frac_not_http_ok_str(),
-            self.get_frac_error_log_str()
+            self.get_min_millis_str(ctx),
+            self.get_median_millis_str(ctx),
+            self.get_avg_millis_str(ctx),
+            self.get_max_millis_str(ctx),
+            utils::format_float(ctx, percentage),
+            self.get_avg_rate_str(ctx, num_files),
+            utils::format_float(ctx, expect_duration),
+            utils::format_float(ctx, expect_contribution),
+            self.get_frac_not_http_ok_str(ctx),
+            self.get_frac_error_log_str(ctx)
         );
         line
     }
@@ -333,3 +338,50 @@ impl CChainStats {
         todo!()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::CChainStatsValue;
+
+    #[test]
+    fn split_and_merge_keep_the_error_counts() {
+        let mut non_rooted = CChainStatsValue::new(2, Vec::new(), false);
+        non_rooted.count = 10;
+        non_rooted.duration_micros = (0..10).collect();
+        non_rooted.cc_not_http_ok = 3;
+        non_rooted.cc_with_error_logs = 5;
+        non_rooted.http_not_ok.add_item_count(500, 3);
+        non_rooted
+            .error_logs
+            .add_item_count("timeout".to_owned(), 5);
+
+        let parts = non_rooted.split(&[7, 3]);
+        assert_eq!(
+            parts.iter().map(|p| p.cc_not_http_ok).collect::<Vec<_>>(),
+            [2, 1]
+        );
+        assert_eq!(
+            parts
+                .iter()
+                .map(|p| p.cc_with_error_logs)
+                .collect::<Vec<_>>(),
+            [4, 1]
+        );
+
+        // a repaired part merges into the observed rooted chain
+        let mut rooted = CChainStatsValue::new(2, Vec::new(), true);
+        rooted.count = 20;
+        rooted.duration_micros = vec![100; 20];
+        rooted.cc_not_http_ok = 2;
+        rooted.http_not_ok.add_item_count(500, 2);
+        rooted.extend(parts.into_iter().next().unwrap());
+
+        assert_eq!(rooted.count, 27);
+        assert_eq!(rooted.duration_micros.len(), 27);
+        assert_eq!(rooted.cc_not_http_ok, 4);
+        assert_eq!(rooted.cc_with_error_logs, 4);
+        assert_eq!(rooted.http_not_ok.get_count(500), 5);
+        assert_eq!(rooted.error_logs.get_count("timeout".to_owned()), 5);
+        assert!((rooted.get_frac_not_http_ok() - 4.0 / 27.0).abs() < 1e-12);
+    }
+}
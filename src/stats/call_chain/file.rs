@@ -2,7 +2,11 @@ use super::{
     call::Call, call_chain::CallChain, cchain_cache::EndPointCChains, cchain_stats::CChainStatsKey,
 };
 use crate::utils::read_lines;
-use std::{error::Error, path::Path};
+use chrono::NaiveDate;
+use std::{collections::HashMap, error::Error, path::Path};
+
+/// format of the date at which a call-chain was last seen (stored as an optional fourth '&'-separated part of a line in a cchain-file)
+const LAST_SEEN_FORMAT: &str = "%Y-%m-%d";
 
 /// get the file-name for a specific key (excluding path)
 pub fn cchain_filename(key: &str) -> String {
@@ -24,20 +28,64 @@ pub fn call_chain_key(call_chain: &CallChain, caching_process: &str, is_leaf: bo
     call_chain_str + " & " + caching_process + "& " + leaf_str // using '&' as separator as a ';' separator would break the CSV-files
 }
 
+/// build the line of a cchain-file, which consists of the call_chain_key and optionally the date the chain was last seen.
+/// Readers that are not aware of the last-seen date ignore this fourth part of the line.
+pub fn cchain_line(cck: &CChainStatsKey, last_seen: Option<&NaiveDate>) -> String {
+    match last_seen {
+        Some(last_seen) => format!(
+            "{} & {}",
+            cck.call_chain_key(),
+            last_seen.format(LAST_SEEN_FORMAT)
+        ),
+        None => cck.call_chain_key(),
+    }
+}
+
+/// parse a line of a cchain-file in the key and the optional last-seen date.
+pub fn parse_cchain_line(
+    line: &str,
+) -> Result<(CChainStatsKey, Option<NaiveDate>), Box<dyn Error>> {
+    let cck = CChainStatsKey::parse(line)?;
+    let last_seen = match line.split('&').nth(3).map(|s| s.trim()) {
+        Some(s) if !s.is_empty() => Some(NaiveDate::parse_from_str(s, LAST_SEEN_FORMAT)?),
+        _ => None,
+    };
+    Ok((cck, last_seen))
+}
+
 /// read a cchain-file and parse it
 pub fn read_cchain_file(path: &Path) -> Result<EndPointCChains, Box<dyn Error>> {
-    let epcc = read_lines(path)?
-        .filter_map(|l| {
-            let l = l.unwrap();
-            let l = l.trim();
-            if l.is_empty() || l.starts_with('#') {
-                None
-            } else {
-                Some(CChainStatsKey::parse(l).unwrap())
-            }
-        })
-        .collect();
-    Ok(EndPointCChains::new(epcc))
+    let mut chains = Vec::new();
+    let mut last_seen = HashMap::new();
+    for l in read_lines(path)? {
+        let l = l?;
+        let l = l.trim();
+        if l.is_empty() || l.starts_with('#') {
+            continue;
+        }
+        let (cck, seen) = parse_cchain_line(l)?;
+        if let Some(seen) = seen {
+            last_seen.insert(cck.clone(), seen);
+        }
+        chains.push(cck);
+    }
+    Ok(EndPointCChains::new_with_last_seen(chains, last_seen))
+}
+
+/// check that all lines of a cchain-file can be parsed and return the errors as a list of (line_number, message)
+pub fn validate_cchain_file(path: &Path) -> Result<Vec<(usize, String)>, Box<dyn Error>> {
+    let mut errors = Vec::new();
+    for (idx, l) in read_lines(path)?.enumerate() {
+        let l = l?;
+        let l = l.trim();
+        if l.is_empty() || l.starts_with('#') {
+            continue;
+        }
+        if let Err(err) = parse_cchain_line(l) {
+            errors.push((idx + 1, format!("{err}")));
+        }
+    }
+    Ok(errors)
 }
 
 /// the label shows whether cached processes are in the call-chain and if so returns a suffix to represent it.
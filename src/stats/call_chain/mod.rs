@@ -1,15 +1,21 @@
 //! Build statistics and call-chains out of a set of traces.
 mod call;
 mod call_chain;
+mod call_loop;
+mod catalog;
 mod cchain_cache;
+mod cchain_repair;
 mod cchain_stats;
 mod expected_roots;
 mod file;
 
 pub use self::{
-    call::{Call, CallDirection},
+    call::{check_span_kind, Call, CallDirection},
     call_chain::{get_call_chain, CallChain},
+    call_loop::detect_loops,
+    catalog::CChainCatalog,
     cchain_cache::CChainEndPointCache,
+    cchain_repair::{CChainRepair, ObservedCounts, RepairMode, RepairStats},
     cchain_stats::{CChainStats, CChainStatsKey, CChainStatsValue},
     file::{caching_process_label, call_chain_key},
 };
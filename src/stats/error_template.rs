@@ -0,0 +1,158 @@
+//! Templating of error-messages, such that messages that only differ in ids, numbers, timestamps or quoted values are grouped.
+use crate::{context::AnalysisContext, utils::Counted};
+use lazy_static::lazy_static;
+use regex::Regex;
+use std::collections::HashMap;
+
+/// Maximal number of raw examples retained per template
+const MAX_EXAMPLES: usize = 3;
+
+/// Translate a raw message into a template by masking the variable parts of the message.
+/// The order of the replacements matters, as for example a timestamp or uuid also contains numbers.
+pub fn message_template(ctx: &AnalysisContext, msg: &str) -> String {
+    lazy_static! {
+        static ref REPLACEMENTS: Vec<(&'static str, Regex)> = {
+            vec![("<TIME>", Regex::new(r"(?x)
+                    \d{4}-\d{2}-\d{2}
+                    ([T\s]\d{2}:\d{2}(:\d{2}([.,]\d+)?)?)?
+                    (Z|[+-]\d{2}:?\d{2})?").unwrap() ),
+                ("<TIME>", Regex::new(r"\b\d{2}:\d{2}:\d{2}([.,]\d+)?\b").unwrap() ),
+                ("<UUID>", Regex::new(r"(?x)
+                    \b[0-9a-fA-F]{8}-
+                    [0-9a-fA-F]{4}-
+                    [0-9a-fA-F]{4}-
+                    [0-9a-fA-F]{4}-
+                    [0-9a-fA-F]{12}\b").unwrap() ),
+                ("<STR>", Regex::new(r#""[^"]*"|'[^']*'"#).unwrap() ),
+                ("<HEX>", Regex::new(r"\b(0x[0-9a-fA-F]+|[0-9a-fA-F]*[0-9][0-9a-fA-F]*[a-fA-F][0-9a-fA-F]*|[0-9a-fA-F]*[a-fA-F][0-9a-fA-F]*[0-9][0-9a-fA-F]*)\b").unwrap() ),
+                ("<NUM>", Regex::new(r"[-+]?\b\d+([.,]\d+)?\b").unwrap() ),
+                ]
+        };
+    }
+
+    // the additional error-rules of the analysis-context are applied first, as these are more specific than the built-in rules
+    let msg = ctx.error_rules.iter().fold(msg.to_owned(), |msg, rule| {
+        rule.pattern
+            .replace_all(&msg, rule.replacement.as_str())
+            .into_owned()
+    });
+
+    REPLACEMENTS.iter().fold(msg, |msg, (label, pattern)| {
+        pattern.replace_all(&msg, *label).into_owned()
+    })
+}
+
+/// A group of messages that share the same template
+#[derive(Debug, Default, Clone)]
+pub struct ErrorTemplate {
+    pub template: String,
+    pub count: usize,
+    /// a few of the raw messages that match this template
+    pub examples: Vec<String>,
+}
+
+/// The error-templates ordered on descending count.
+#[derive(Debug, Default, Clone)]
+pub struct ErrorTemplates(pub Vec<ErrorTemplate>);
+
+impl ErrorTemplates {
+    /// header for report_stats_line output in ';'-separated csv-format
+    pub fn report_stats_line_header_str() -> &'static str {
+        "Process/Oper; Error_template; Count; Examples"
+    }
+
+    /// reports the templates of a process/operation as lines in ';'-separated csv-format. The ';' is removed from the messages.
+    pub fn report_stats_lines(&self, proc_oper: &str) -> Vec<String> {
+        self.0
+            .iter()
+            .map(|et| {
+                let examples: Vec<_> = et.examples.iter().map(|ex| ex.replace(';', ",")).collect();
+                format!(
+                    "{proc_oper}; {}; {}; {}",
+                    et.template.replace(';', ","),
+                    et.count,
+                    examples.join(" | ")
+                )
+            })
+            .collect()
+    }
+}
+
+impl ErrorTemplates {
+    /// group the raw messages in templates, where the error-rules of 'ctx' are applied first
+    pub fn new(ctx: &AnalysisContext, error_logs: &Counted<String>) -> Self {
+        // sort the raw messages first to get a deterministic set of examples.
+        let mut raw: Vec<_> = error_logs.iter().collect();
+        raw.sort_by(|a, b| b.1.cmp(a.1).then(a.0.cmp(b.0)));
+
+        let mut templates: HashMap<String, ErrorTemplate> = HashMap::new();
+        raw.into_iter().for_each(|(msg, count)| {
+            let template = message_template(ctx, msg);
+            let et = templates
+                .entry(template.clone())
+                .or_insert_with(|| ErrorTemplate {
+                    template,
+                    ..Default::default()
+                });
+            et.count += count;
+            if et.examples.len() < MAX_EXAMPLES {
+                et.examples.push(msg.to_owned());
+            }
+        });
+
+        let mut templates: Vec<_> = templates.into_values().collect();
+        templates.sort_by(|a, b| b.count.cmp(&a.count).then(a.template.cmp(&b.template)));
+        Self(templates)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mask_variable_parts() {
+        let ctx = AnalysisContext::default();
+        assert_eq!(
+            message_template(
+                &ctx,
+                "Order 12345 not found for customer 'jan' at 2023-10-01T12:00:01.123Z"
+            ),
+            "Order <NUM> not found for customer <STR> at <TIME>"
+        );
+        assert_eq!(
+            message_template(
+                &ctx,
+                "Request 3f2b8c1e-0d4a-4b7e-9a6f-1c2d3e4f5a6b failed with code 0x1F, hash ab12cd34"
+            ),
+            "Request <UUID> failed with code <HEX>, hash <HEX>"
+        );
+        assert_eq!(
+            message_template(&ctx, "Timeout after 1.5 seconds"),
+            "Timeout after <NUM> seconds"
+        );
+        // plain words that happen to consist of hex-characters are not masked
+        assert_eq!(message_template(&ctx, "bad face"), "bad face");
+    }
+
+    #[test]
+    fn group_messages() {
+        let mut error_logs = Counted::new();
+        error_logs.add_item("Order 1 not found".to_owned());
+        error_logs.add_item("Order 2 not found".to_owned());
+        error_logs.add_item("Order 2 not found".to_owned());
+        error_logs.add_item("Connection refused".to_owned());
+        let templates = ErrorTemplates::new(&AnalysisContext::default(), &error_logs);
+        assert_eq!(templates.0.len(), 2);
+        assert_eq!(templates.0[0].template, "Order <NUM> not found");
+        assert_eq!(templates.0[0].count, 3);
+        assert_eq!(
+            templates.0[0].examples,
+            vec![
+                "Order 2 not found".to_owned(),
+                "Order 1 not found".to_owned()
+            ]
+        );
+        assert_eq!(templates.0[1].count, 1);
+    }
+}
@@ -1,16 +1,19 @@
 //!  Write the statistics to a JSON file and read them back in memory
 //!
 use super::super::StatsRec;
-use std::{fs, io};
+use crate::{
+    utils::{self, FileKind},
+    view_api::Version,
+};
+use std::path::Path;
 
 use super::StatsRecJson;
 
+/// Write the statistics as bincode preceded by a version-header (see 'utils::write_bincode')
 pub fn dump_file(file_name: &str, stats: StatsRec) {
-    let f = fs::File::create(file_name).expect("Failed to open file");
-    let writer = io::BufWriter::new(f);
-
-    let srj: StatsRecJson = stats.into();
-    if let Err(err) = bincode::serialize_into(writer, &srj) {
+    let mut srj: StatsRecJson = stats.into();
+    srj.version = Version::STATS;
+    if let Err(err) = utils::write_bincode(Path::new(file_name), FileKind::Stats, &srj) {
         panic!("Dump of data to file {file_name} failed.\n\tError: {err:?}");
     };
 }
@@ -0,0 +1,53 @@
+//!  The chain of migrations that upgrade statistics of an older file-version to the current version ('Version::STATS').
+//!
+use super::StatsRecJson;
+use crate::view_api::Version;
+
+type Migration = fn(StatsRecJson) -> StatsRecJson;
+
+/// The migrations ordered on version. A migration is applied when the data has a version below the version of the migration.
+const MIGRATIONS: [(Version, Migration); 3] = [
+    // 0.2: the version-field was added. The renamed fields of a Call ('process' and 'method') are handled via serde-aliases.
+    (Version::new(0, 2), |srj| srj),
+    // 0.3: the bincode-files start with a version-header. The layout of the data itself is unchanged.
+    (Version::new(0, 3), |srj| srj),
+    // 0.4: the timezone-field is added. Without a timezone the timestamps are shown in the timezone of the analysis-context.
+    (Version::new(0, 4), |srj| srj),
+];
+
+/// Bincode is not self-describing, so the payload of a file of an older version is extended with the encoding of the fields added since.
+pub fn upgrade_bincode_payload(version: Version, mut payload: Vec<u8>) -> Vec<u8> {
+    if version < Version::new(0, 4) {
+        // the trailing 'timezone: None'
+        payload.push(0);
+    }
+    payload
+}
+
+/// Apply all migrations needed to upgrade the 'srj' to the current version.
+pub fn migrate(mut srj: StatsRecJson) -> StatsRecJson {
+    for (version, migration) in MIGRATIONS.iter() {
+        if srj.version < *version {
+            srj = migration(srj);
+            srj.version = *version;
+        }
+    }
+    srj
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{migrate, StatsRecJson, MIGRATIONS};
+    use crate::view_api::Version;
+
+    #[test]
+    fn migrations_reach_current_version() {
+        assert_eq!(MIGRATIONS.last().unwrap().0, Version::STATS);
+        assert!(MIGRATIONS.windows(2).all(|w| w[0].0 < w[1].0));
+        let srj = StatsRecJson {
+            version: Version::UNVERSIONED,
+            ..Default::default()
+        };
+        assert_eq!(migrate(srj).version, Version::STATS);
+    }
+}
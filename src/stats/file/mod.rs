@@ -2,18 +2,57 @@
 //!
 mod bincode;
 mod json;
+mod migrate;
+mod openmetrics;
 mod operation_stats_json;
+mod parquet;
+mod sqlite;
 
 use super::StatsRec;
+use crate::{context::AnalysisContext, view_api::HtmlParameters, TraceDataSet};
+use std::path::Path;
 
+pub use openmetrics::to_openmetrics;
 pub use operation_stats_json::{OperationStatsJson, StatsRecJson};
+pub use sqlite::{read_file as read_sqlite, write_file as write_sqlite};
 
-/// write the (complete) statistics to either '.json'  or '.bincode'
-pub fn write_stats(file_name: &str, stats: StatsRec, ext: &str) {
+/// write the (complete) statistics to either '.json', '.bincode', '.sqlite' or '.xlsx', a self-contained report to '.html', or write the RED-metrics in OpenMetrics text-format to '.prom'.
+/// The ext can be a comma-separated list, for example 'json,parquet' also writes the statistics as a set of parquet-files.
+/// The files are formatted according to the settings of the analysis-context 'ctx'.
+pub fn write_stats(ctx: &AnalysisContext, file_name: &str, stats: StatsRec, ext: &str) {
+    let (parquet, exts): (Vec<_>, Vec<_>) = ext
+        .split(',')
+        .map(|ext| ext.trim())
+        .partition(|ext| *ext == "parquet");
+    if !parquet.is_empty() {
+        parquet::dump_files(&file_name.replace(".csv", ""), &stats);
+    }
+    match exts[..] {
+        [] => (),
+        [ext] => write_stats_ext(ctx, file_name, stats, ext),
+        _ => exts
+            .iter()
+            .for_each(|ext| write_stats_ext(ctx, file_name, stats.clone(), ext)),
+    }
+}
+
+fn write_stats_ext(ctx: &AnalysisContext, file_name: &str, stats: StatsRec, ext: &str) {
     let file_name = file_name.replace(".csv", &format!(".{ext}"));
     match ext {
         "json" => json::dump_file(&file_name, stats),
         "bincode" => bincode::dump_file(&file_name, stats),
+        "sqlite" => sqlite::dump_file(&file_name, &stats),
+        "prom" => openmetrics::dump_file(&file_name, &stats),
+        "html" => TraceDataSet::new(stats).write_html(
+            ctx,
+            Path::new(&file_name),
+            &HtmlParameters::default(),
+        ),
+        "xlsx" => {
+            if let Err(err) = stats.write_xlsx(ctx, Path::new(&file_name)) {
+                panic!("Failed to write '{file_name}'. Error: {err:?}");
+            }
+        }
         unknown => panic!("Unknown output format: '{unknown}'"),
     }
 }
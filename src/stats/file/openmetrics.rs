@@ -0,0 +1,374 @@
+//!  Export of the Rate/Errors/Duration (RED) metrics of the statistics in the OpenMetrics text-format (as scraped by Prometheus).
+//!  Metrics are reported per service/operation and per edge, where an edge is the last call of a call-chain (labelled by end-point, caller and call-direction).
+//!
+use crate::{
+    stats::{call_chain::CChainStatsKey, StatsRec},
+    utils::TimeStats,
+};
+use std::{collections::BTreeMap, fmt::Write, fs};
+
+/// Prefix of all metric-families
+const PREFIX: &str = "jaeger_stats";
+/// The quantiles reported for the durations
+const QUANTILES: [f64; 5] = [0.5, 0.75, 0.9, 0.95, 0.99];
+
+type Labels = Vec<(&'static str, String)>;
+
+/// The aggregated RED-values of a single series (a service/operation or an edge)
+#[derive(Default)]
+struct Red {
+    count: usize,
+    num_not_http_ok: i64,
+    num_with_error_logs: i64,
+    duration_micros: Vec<i64>,
+    start_dt_micros: Vec<i64>,
+}
+
+impl Red {
+    fn frac(&self, num: i64) -> f64 {
+        if self.count == 0 {
+            0.0
+        } else {
+            num as f64 / self.count as f64
+        }
+    }
+}
+
+/// Escape a label-value (backslash, double-quote and line-feed)
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+fn format_labels(labels: &Labels, extra: Option<(&str, String)>) -> String {
+    let labels: Vec<_> = labels
+        .iter()
+        .map(|(k, v)| (*k, v.as_str()))
+        .chain(extra.iter().map(|(k, v)| (*k, v.as_str())))
+        .map(|(k, v)| format!("{k}=\"{}\"", escape_label(v)))
+        .collect();
+    if labels.is_empty() {
+        String::new()
+    } else {
+        format!("{{{}}}", labels.join(","))
+    }
+}
+
+/// Writer of metric-families. All samples of a family are written directly after its metadata.
+struct OpenMetrics(String);
+
+impl OpenMetrics {
+    fn family(&mut self, name: &str, metric_type: &str, unit: Option<&str>, help: &str) {
+        writeln!(self.0, "# TYPE {name} {metric_type}").unwrap();
+        if let Some(unit) = unit {
+            writeln!(self.0, "# UNIT {name} {unit}").unwrap();
+        }
+        writeln!(self.0, "# HELP {name} {help}").unwrap();
+    }
+
+    fn sample(&mut self, name: &str, labels: &str, value: f64) {
+        writeln!(self.0, "{name}{labels} {value}").unwrap();
+    }
+
+    /// Write the RED-families for all series using 'kind' as part of the family-names
+    fn red_families(&mut self, kind: &str, descr: &str, series: &[(Labels, Red)], num_files: i32) {
+        let name = format!("{PREFIX}_{kind}_calls");
+        self.family(
+            &name,
+            "counter",
+            None,
+            &format!("Number of calls per {descr}."),
+        );
+        series.iter().for_each(|(labels, red)| {
+            self.sample(
+                &format!("{name}_total"),
+                &format_labels(labels, None),
+                red.count as f64,
+            )
+        });
+
+        let name = format!("{PREFIX}_{kind}_rate");
+        self.family(
+            &name,
+            "gauge",
+            None,
+            &format!("Average request-rate (req/sec) per {descr}."),
+        );
+        series.iter().for_each(|(labels, red)| {
+            if let Some(rate) = TimeStats(&red.start_dt_micros).get_avg_rate(num_files) {
+                self.sample(&name, &format_labels(labels, None), rate)
+            }
+        });
+
+        let name = format!("{PREFIX}_{kind}_not_http_ok_ratio");
+        self.family(
+            &name,
+            "gauge",
+            Some("ratio"),
+            &format!("Fraction of the calls with a HTTP-error per {descr}."),
+        );
+        series.iter().for_each(|(labels, red)| {
+            self.sample(
+                &name,
+                &format_labels(labels, None),
+                red.frac(red.num_not_http_ok),
+            )
+        });
+
+        let name = format!("{PREFIX}_{kind}_error_logs_ratio");
+        self.family(
+            &name,
+            "gauge",
+            Some("ratio"),
+            &format!("Fraction of the calls with ERROR log-lines per {descr}."),
+        );
+        series.iter().for_each(|(labels, red)| {
+            self.sample(
+                &name,
+                &format_labels(labels, None),
+                red.frac(red.num_with_error_logs),
+            )
+        });
+
+        let name = format!("{PREFIX}_{kind}_duration_seconds");
+        self.family(
+            &name,
+            "summary",
+            Some("seconds"),
+            &format!("Duration of the calls per {descr}."),
+        );
+        series
+            .iter()
+            .filter(|(_, red)| !red.duration_micros.is_empty())
+            .for_each(|(labels, red)| {
+                let time_stats = TimeStats(&red.duration_micros);
+                QUANTILES.iter().for_each(|q| {
+                    if let Some(millis) = time_stats.get_p_millis(*q) {
+                        let labels = format_labels(labels, Some(("quantile", q.to_string())));
+                        self.sample(&name, &labels, millis / 1000.0)
+                    }
+                });
+                let labels = format_labels(labels, None);
+                let sum = red.duration_micros.iter().sum::<i64>() as f64 / 1_000_000.0;
+                self.sample(&format!("{name}_sum"), &labels, sum);
+                self.sample(
+                    &format!("{name}_count"),
+                    &labels,
+                    red.duration_micros.len() as f64,
+                );
+            });
+    }
+}
+
+/// The RED-values per service/operation
+fn service_oper_series(stats: &StatsRec) -> Vec<(Labels, Red)> {
+    let mut series = BTreeMap::new();
+    stats.stats.iter().for_each(|(service, oper_stats)| {
+        oper_stats.operation.0.iter().for_each(|(oper, pos)| {
+            let red = Red {
+                count: pos.count,
+                num_not_http_ok: pos.num_not_http_ok as i64,
+                num_with_error_logs: pos.num_with_error_logs as i64,
+                duration_micros: pos.duration_micros.clone(),
+                start_dt_micros: pos.start_dt_micros.clone(),
+            };
+            series.insert((service.to_owned(), oper.to_owned()), red);
+        })
+    });
+    series
+        .into_iter()
+        .map(|((service, oper), red)| (vec![("service", service), ("operation", oper)], red))
+        .collect()
+}
+
+/// The labels of the edge formed by the last call of a call-chain: the end-point, the calling service, the called service/operation and the call-direction
+fn edge_labels(key: &CChainStatsKey) -> [String; 5] {
+    let call_chain = &key.call_chain;
+    let last = call_chain.last().expect("Call chain is empty!");
+    let caller = if call_chain.len() > 1 {
+        call_chain[call_chain.len() - 2].service.to_owned()
+    } else {
+        String::new()
+    };
+    [
+        key.get_endpoint(),
+        caller,
+        last.service.to_owned(),
+        last.operation.to_owned(),
+        last.call_direction.as_str().to_owned(),
+    ]
+}
+
+/// The RED-values per edge, aggregated over all call-chains that end in the same edge
+fn edge_series(stats: &StatsRec) -> Vec<(Labels, Red)> {
+    let mut series: BTreeMap<_, Red> = BTreeMap::new();
+    stats.stats.values().for_each(|oper_stats| {
+        oper_stats.call_chain.0.iter().for_each(|(key, ccv)| {
+            let red = series.entry(edge_labels(key)).or_default();
+            red.count += ccv.count;
+            red.num_not_http_ok += ccv.cc_not_http_ok as i64;
+            red.num_with_error_logs += ccv.cc_with_error_logs as i64;
+            red.duration_micros.extend(ccv.duration_micros.iter());
+            red.start_dt_micros.extend(ccv.start_dt_micros.iter());
+        })
+    });
+    series
+        .into_iter()
+        .map(|([endpoint, caller, service, oper, direction], red)| {
+            let labels = vec![
+                ("endpoint", endpoint),
+                ("caller", caller),
+                ("service", service),
+                ("operation", oper),
+                ("direction", direction),
+            ];
+            (labels, red)
+        })
+        .collect()
+}
+
+/// Render the statistics in the OpenMetrics text-format (terminated by '# EOF')
+pub fn to_openmetrics(stats: &StatsRec) -> String {
+    let mut om = OpenMetrics(String::new());
+
+    let name = format!("{PREFIX}_traces");
+    om.family(&name, "gauge", None, "Number of traces in the analysis.");
+    om.sample(&name, "", stats.trace_id.len() as f64);
+    let name = format!("{PREFIX}_files");
+    om.family(
+        &name,
+        "gauge",
+        None,
+        "Number of input-files of the analysis.",
+    );
+    om.sample(&name, "", stats.num_files as f64);
+
+    om.red_families(
+        "operation",
+        "service/operation",
+        &service_oper_series(stats),
+        stats.num_files,
+    );
+    om.red_families(
+        "edge",
+        "edge (last call of the call-chains starting at an end-point)",
+        &edge_series(stats),
+        stats.num_files,
+    );
+
+    om.0.push_str("# EOF\n");
+    om.0
+}
+
+pub fn dump_file(file_name: &str, stats: &StatsRec) {
+    if let Err(err) = fs::write(file_name, to_openmetrics(stats)) {
+        panic!("Dump of data to file {file_name} failed.\n\tError: {err:?}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{escape_label, format_labels, to_openmetrics, OpenMetrics};
+    use crate::stats::{
+        call_chain::{CChainStatsKey, CChainStatsValue},
+        ProcOperStatsValue, StatsRec,
+    };
+
+    #[test]
+    fn labels_are_escaped() {
+        assert_eq!(escape_label(r#"a"b\c"#), r#"a\"b\\c"#);
+        assert_eq!(escape_label("a\nb"), "a\\nb");
+        let labels = vec![
+            ("service", "gw".to_owned()),
+            ("operation", "GET /x".to_owned()),
+        ];
+        assert_eq!(
+            format_labels(&labels, Some(("quantile", "0.5".to_owned()))),
+            r#"{service="gw",operation="GET /x",quantile="0.5"}"#
+        );
+        assert_eq!(format_labels(&Vec::new(), None), "");
+    }
+
+    #[test]
+    fn family_metadata_precedes_samples() {
+        let mut om = OpenMetrics(String::new());
+        om.family(
+            "x_duration_seconds",
+            "summary",
+            Some("seconds"),
+            "Duration.",
+        );
+        om.sample("x_duration_seconds_count", "", 3.0);
+        assert_eq!(
+            om.0,
+            "# TYPE x_duration_seconds summary\n# UNIT x_duration_seconds seconds\n# HELP x_duration_seconds Duration.\nx_duration_seconds_count 3\n"
+        );
+    }
+
+    #[test]
+    fn red_metrics_of_a_stats_rec() {
+        // 20 calls of 10, 20, ..., 200 milli-seconds
+        let durations: Vec<_> = (1..=20).map(|i| i * 10_000).collect();
+        let mut sr = StatsRec {
+            trace_id: vec!["t1".to_owned(), "t2".to_owned()],
+            num_files: 1,
+            ..Default::default()
+        };
+        let oper_stats = sr.stats.entry("a".to_owned()).or_default();
+        oper_stats.operation.0.insert(
+            "get".to_owned(),
+            ProcOperStatsValue {
+                count: 20,
+                duration_micros: durations.clone(),
+                num_not_http_ok: 1,
+                ..Default::default()
+            },
+        );
+        let mut ccv = CChainStatsValue::new(2, Vec::new(), true);
+        ccv.count = 20;
+        ccv.duration_micros = durations;
+        oper_stats.call_chain.0.insert(
+            CChainStatsKey::parse("gw/GET:/x [Inbound] | a/get [Inbound] & &  *LEAF*").unwrap(),
+            ccv,
+        );
+
+        let om = to_openmetrics(&sr);
+        let lines: Vec<_> = om.lines().collect();
+        let has = |line: &str| lines.contains(&line);
+
+        assert!(has("jaeger_stats_traces 2"));
+        assert!(has(
+            r#"jaeger_stats_operation_calls_total{service="a",operation="get"} 20"#
+        ));
+        assert!(has(
+            r#"jaeger_stats_operation_not_http_ok_ratio{service="a",operation="get"} 0.05"#
+        ));
+        assert!(has(
+            r#"jaeger_stats_operation_duration_seconds{service="a",operation="get",quantile="0.5"} 0.1"#
+        ));
+        assert!(has(
+            r#"jaeger_stats_operation_duration_seconds{service="a",operation="get",quantile="0.95"} 0.19"#
+        ));
+        assert!(has(
+            r#"jaeger_stats_operation_duration_seconds_sum{service="a",operation="get"} 2.1"#
+        ));
+        assert!(has(
+            r#"jaeger_stats_operation_duration_seconds_count{service="a",operation="get"} 20"#
+        ));
+
+        let edge =
+            r#"endpoint="gw/GET:/x",caller="gw",service="a",operation="get",direction="Inbound""#;
+        assert!(has(&format!("jaeger_stats_edge_calls_total{{{edge}}} 20")));
+        assert!(has(&format!(
+            "jaeger_stats_edge_duration_seconds{{{edge},quantile=\"0.9\"}} 0.18"
+        )));
+        // without start-times no rate is known
+        assert!(!om.contains("jaeger_stats_edge_rate{"));
+
+        assert_eq!(lines.last(), Some(&"# EOF"));
+        assert_eq!(om.matches("# EOF").count(), 1);
+        assert!(om.ends_with("# EOF\n"));
+    }
+}
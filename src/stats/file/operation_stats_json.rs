@@ -2,16 +2,17 @@
 //! The StatsRec can not be stored in JSON as:
 //!    1. It contains a HashMap (call-chain) with non-string keys
 //!    2. It contains date-times which can not represented in JSON (we will store them as a i64, just like we had in the Jaeger-JSON file)
+use super::migrate;
 use crate::{
     stats::{
         call_chain::{CChainStatsKey, CChainStatsValue},
         OperationStats, ProcOperStats, StatsRec,
     },
-    utils,
+    utils::{self, FileFormatError, FileHeader, FileKind, Timezone},
     view_api::Version,
 };
 use serde::{Deserialize, Serialize};
-use std::{collections::HashMap, error::Error, ffi::OsString, fs::File, io, path::Path};
+use std::{collections::HashMap, error::Error, ffi::OsString, path::Path};
 
 /// The OperationStatsJson is used as an intermediate value for storage as JSON does not allow compound hashmap-keys.
 /// Thus Hashmap is flattened to a vector of key-value pairs. For more details on the fields see OperationStats.
@@ -41,6 +42,8 @@ impl From<OperationStats> for OperationStatsJson {
 
 #[derive(Debug, Default, Serialize, Deserialize)]
 pub struct StatsRecJson {
+    /// The version of the file-layout (a file without version-field is migrated from 'Version::UNVERSIONED')
+    #[serde(default = "Version::unversioned")]
     pub version: Version,
     pub trace_id: Vec<String>,
     pub root_call: Vec<String>,
@@ -64,6 +67,9 @@ pub struct StatsRecJson {
     pub time_to_respond_micros: Vec<i64>,
     pub caching_processes: Vec<String>,
     pub stats: HashMap<String, OperationStatsJson>, // hashmap base on the leaf process (as that is the initial level of reporting)
+    /// The timezone used to show the timestamps, which are stored as UTC micro-seconds (None for files before version 0.4)
+    #[serde(default)]
+    pub timezone: Option<Timezone>,
 }
 
 impl From<StatsRec> for StatsRecJson {
@@ -82,45 +88,90 @@ impl From<StatsRec> for StatsRecJson {
             init_num_unrooted_cc: sr.init_num_unrooted_cc,
             num_fixes: sr.num_fixes,
             num_unrooted_cc_after_fixes: sr.num_unrooted_cc_after_fixes,
-            start_dt: sr
-                .start_dt
-                .into_iter()
-                .map(utils::datetime_to_micros)
-                .collect(),
-            end_dt: sr
-                .end_dt
-                .into_iter()
-                .map(utils::datetime_to_micros)
-                .collect(),
+            start_dt: sr.start_dt_micros,
+            end_dt: sr.end_dt_micros,
             duration_micros: sr.duration_micros,
             time_to_respond_micros: sr.time_to_respond_micros,
             caching_processes: sr.caching_processes,
             stats,
+            timezone: Some(sr.timezone),
         }
     }
 }
 
 impl StatsRecJson {
-    /// StatsJson file and parse it
+    /// Read a StatsJson (or bincode) file, check its version and migrate it to the current version.
     pub fn read_file(path: &OsString) -> Result<Self, Box<dyn Error>> {
         let keep = path.clone().into_string().unwrap();
         let path_str = Path::new(&keep);
-        let f = File::open(path)?;
-        let reader = io::BufReader::new(f);
 
         let Some(ext) = path_str.extension() else {
             panic!("Failed to find extension of '{}'", path_str.display());
         };
         let ext = ext.to_str().unwrap();
 
-        let sj = match ext {
-            "json" => serde_json::from_reader(reader)?,
-            "bincode" => bincode::deserialize_from(reader)?,
+        let sj: Self = match ext {
+            "json" => {
+                let (header, value) = utils::read_json_with_header(path_str)?;
+                header.check(path_str, FileKind::Stats)?;
+                serde_json::from_value(value).map_err(|err| {
+                    FileFormatError::corrupt(path_str, FileKind::Stats, header.version, err)
+                })?
+            }
+            "bincode" => {
+                let (header, payload) = utils::read_bincode(path_str)?;
+                // files without header have the layout of version 0.2 (and contain a version-field)
+                let header = header.unwrap_or(FileHeader {
+                    kind: FileKind::Stats,
+                    version: Version::new(0, 2),
+                });
+                header.check(path_str, FileKind::Stats)?;
+                let payload = migrate::upgrade_bincode_payload(header.version, payload);
+                bincode::deserialize(&payload).map_err(|err| {
+                    FileFormatError::corrupt(path_str, FileKind::Stats, header.version, err)
+                })?
+            }
             ext => panic!(
                 "Unknown extension '{ext}'of inputfile {}",
                 path_str.display()
             ),
         };
-        Ok(sj)
+        Ok(migrate::migrate(sj))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::StatsRecJson;
+    use crate::{context::AnalysisContext, stats::StatsRec};
+    use chrono::NaiveDate;
+
+    #[test]
+    fn round_trip_of_the_repeated_autumn_hour() {
+        // 29 October 2023 00:30 and 01:30 UTC are both 02:30 in Amsterdam (summer- and winter-time)
+        let summer_time = 1_698_539_400_000_000;
+        let winter_time = summer_time + 3_600_000_000;
+        let sr = StatsRec {
+            trace_id: vec!["t1".to_owned(), "t2".to_owned()],
+            start_dt_micros: vec![summer_time, winter_time],
+            end_dt_micros: vec![summer_time + 1_000, winter_time + 1_000],
+            timezone: "Europe/Amsterdam".parse().unwrap(),
+            ..Default::default()
+        };
+
+        let json = serde_json::to_string(&StatsRecJson::from(sr.clone())).unwrap();
+        let srj: StatsRecJson = serde_json::from_str(&json).unwrap();
+        assert_eq!(srj.start_dt, [summer_time, winter_time]);
+        let sr2 = StatsRec::from_json(&AnalysisContext::default(), srj);
+        assert_eq!(sr2.start_dt_micros, sr.start_dt_micros);
+        assert_eq!(sr2.end_dt_micros, sr.end_dt_micros);
+        assert_eq!(sr2.timezone, sr.timezone);
+
+        // both traces are shown at the same local time
+        let local = NaiveDate::from_ymd_opt(2023, 10, 29)
+            .unwrap()
+            .and_hms_opt(2, 30, 0)
+            .unwrap();
+        assert_eq!(sr2.start_dt(), [local, local]);
     }
 }
@@ -0,0 +1,228 @@
+//!  Write the statistics as a set of parquet-files (one file per table) for analysis in pandas, polars or DuckDB.
+//!
+use super::super::StatsRec;
+use crate::utils::{self, bool_column, float_column, int_column, string_column, timestamp_column};
+use arrow::record_batch::RecordBatch;
+use std::error::Error;
+
+/// table with a row per trace
+fn traces_table(stats: &StatsRec) -> Result<RecordBatch, Box<dyn Error>> {
+    Ok(RecordBatch::try_from_iter(vec![
+        ("trace_id", string_column(&stats.trace_id)),
+        ("root_call", string_column(&stats.root_call)),
+        (
+            "num_spans",
+            int_column(stats.num_spans.iter().map(|n| *n as i64)),
+        ),
+        (
+            "start_dt",
+            timestamp_column(stats.start_dt_micros.iter().map(|micros| Some(*micros))),
+        ),
+        (
+            "end_dt",
+            timestamp_column(stats.end_dt_micros.iter().map(|micros| Some(*micros))),
+        ),
+        (
+            "duration_micros",
+            int_column(stats.duration_micros.iter().copied()),
+        ),
+        (
+            "time_to_respond_micros",
+            int_column(stats.time_to_respond_micros.iter().copied()),
+        ),
+    ])?)
+}
+
+/// table with a row per service/operation
+fn service_oper_table(stats: &StatsRec) -> Result<RecordBatch, Box<dyn Error>> {
+    let mut rows: Vec<_> = stats
+        .stats
+        .iter()
+        .flat_map(|(service, oper_stats)| {
+            oper_stats
+                .operation
+                .0
+                .iter()
+                .map(move |(oper, posv)| (service, oper, posv))
+        })
+        .collect();
+    rows.sort_by(|a, b| (a.0, a.1).cmp(&(b.0, b.1)));
+
+    Ok(RecordBatch::try_from_iter(vec![
+        ("service", string_column(rows.iter().map(|r| r.0))),
+        ("operation", string_column(rows.iter().map(|r| r.1))),
+        ("count", int_column(rows.iter().map(|r| r.2.count as i64))),
+        (
+            "num_traces",
+            int_column(rows.iter().map(|r| r.2.num_traces as i64)),
+        ),
+        (
+            "rate",
+            float_column(rows.iter().map(|r| r.2.get_avg_rate(stats.num_files))),
+        ),
+        (
+            "min_millis",
+            float_column(rows.iter().map(|r| Some(r.2.get_min_millis()))),
+        ),
+        (
+            "avg_millis",
+            float_column(rows.iter().map(|r| Some(r.2.get_avg_millis()))),
+        ),
+        (
+            "median_millis",
+            float_column(rows.iter().map(|r| r.2.get_median_millis())),
+        ),
+        (
+            "p95_millis",
+            float_column(rows.iter().map(|r| r.2.get_p_millis(0.95))),
+        ),
+        (
+            "p99_millis",
+            float_column(rows.iter().map(|r| r.2.get_p_millis(0.99))),
+        ),
+        (
+            "max_millis",
+            float_column(rows.iter().map(|r| Some(r.2.get_max_millis()))),
+        ),
+        (
+            "num_not_http_ok",
+            int_column(rows.iter().map(|r| r.2.num_not_http_ok as i64)),
+        ),
+        (
+            "num_with_error_logs",
+            int_column(rows.iter().map(|r| r.2.num_with_error_logs as i64)),
+        ),
+    ])?)
+}
+
+/// table with a row per call-chain
+fn call_chain_table(stats: &StatsRec) -> Result<RecordBatch, Box<dyn Error>> {
+    let mut rows: Vec<_> = stats
+        .stats
+        .values()
+        .flat_map(|oper_stats| oper_stats.call_chain.0.iter())
+        .map(|(cck, ccv)| (cck.call_chain_key(), cck, ccv))
+        .collect();
+    rows.sort_by(|a, b| a.0.cmp(&b.0));
+
+    Ok(RecordBatch::try_from_iter(vec![
+        ("call_chain", string_column(rows.iter().map(|r| &r.0))),
+        (
+            "cc_hash",
+            string_column(rows.iter().map(|r| utils::string_hash(&r.0))),
+        ),
+        (
+            "end_point",
+            string_column(rows.iter().map(|r| r.1.get_endpoint())),
+        ),
+        (
+            "service_oper",
+            string_column(rows.iter().map(|r| r.1.get_leaf())),
+        ),
+        (
+            "caching_process",
+            string_column(rows.iter().map(|r| &r.1.caching_process)),
+        ),
+        ("is_leaf", bool_column(rows.iter().map(|r| r.1.is_leaf))),
+        ("rooted", bool_column(rows.iter().map(|r| r.2.rooted))),
+        ("depth", int_column(rows.iter().map(|r| r.2.depth as i64))),
+        ("count", int_column(rows.iter().map(|r| r.2.count as i64))),
+        (
+            "rate",
+            float_column(rows.iter().map(|r| r.2.get_avg_rate(stats.num_files))),
+        ),
+        (
+            "min_millis",
+            float_column(rows.iter().map(|r| Some(r.2.get_min_millis()))),
+        ),
+        (
+            "avg_millis",
+            float_column(rows.iter().map(|r| Some(r.2.get_avg_millis()))),
+        ),
+        (
+            "median_millis",
+            float_column(rows.iter().map(|r| r.2.get_median_millis())),
+        ),
+        (
+            "p95_millis",
+            float_column(rows.iter().map(|r| r.2.get_p_millis(0.95))),
+        ),
+        (
+            "p99_millis",
+            float_column(rows.iter().map(|r| r.2.get_p_millis(0.99))),
+        ),
+        (
+            "max_millis",
+            float_column(rows.iter().map(|r| Some(r.2.get_max_millis()))),
+        ),
+        (
+            "num_not_http_ok",
+            int_column(rows.iter().map(|r| r.2.cc_not_http_ok as i64)),
+        ),
+        (
+            "num_with_error_logs",
+            int_column(rows.iter().map(|r| r.2.cc_with_error_logs as i64)),
+        ),
+        (
+            "looped",
+            string_column(rows.iter().map(|r| r.2.looped.join(", "))),
+        ),
+    ])?)
+}
+
+/// table with the raw duration samples of all service/operations and call-chains (long format)
+fn durations_table(stats: &StatsRec) -> Result<RecordBatch, Box<dyn Error>> {
+    let mut kind = Vec::new();
+    let mut key = Vec::new();
+    let mut start_dt = Vec::new();
+    let mut duration = Vec::new();
+    let mut add_samples = |k: &'static str, label: String, durs: &[i64], starts: &[i64]| {
+        durs.iter().enumerate().for_each(|(idx, d)| {
+            kind.push(k);
+            key.push(label.clone());
+            start_dt.push(starts.get(idx).copied());
+            duration.push(*d);
+        })
+    };
+    stats.stats.iter().for_each(|(service, oper_stats)| {
+        oper_stats.operation.0.iter().for_each(|(oper, posv)| {
+            add_samples(
+                "service_oper",
+                format!("{service}/{oper}"),
+                &posv.duration_micros,
+                &posv.start_dt_micros,
+            )
+        });
+        oper_stats.call_chain.0.iter().for_each(|(cck, ccv)| {
+            add_samples(
+                "call_chain",
+                cck.call_chain_key(),
+                &ccv.duration_micros,
+                &ccv.start_dt_micros,
+            )
+        });
+    });
+
+    Ok(RecordBatch::try_from_iter(vec![
+        ("kind", string_column(kind)),
+        ("key", string_column(key)),
+        ("start_dt", timestamp_column(start_dt)),
+        ("duration_micros", int_column(duration)),
+    ])?)
+}
+
+/// Write the tables 'traces', 'service_oper', 'call_chains' and 'durations' to the parquet-files '<base_name>_<table>.parquet'.
+pub fn dump_files(base_name: &str, stats: &StatsRec) {
+    let tables = [
+        ("traces", traces_table(stats)),
+        ("service_oper", service_oper_table(stats)),
+        ("call_chains", call_chain_table(stats)),
+        ("durations", durations_table(stats)),
+    ];
+    tables.into_iter().for_each(|(table, batch)| {
+        let file_name = format!("{base_name}_{table}.parquet");
+        if let Err(err) = batch.and_then(|batch| utils::write_parquet(&file_name, &batch)) {
+            panic!("Dump of data to file {file_name} failed.\n\tError: {err:?}");
+        }
+    });
+}
@@ -0,0 +1,671 @@
+//!  Write the statistics to a SQLite database with normalised tables and rebuild the statistics from such a database.
+//!  The database allows ad-hoc SQL-queries and can act as a long-term store, as the StatsRec can be fully reconstructed.
+//!
+use crate::{
+    context::AnalysisContext,
+    stats::{
+        call_chain::{CChainStats, CChainStatsKey, CChainStatsValue, Call, CallDirection},
+        OperationStats, ProcOperStats, ProcOperStatsValue, StatsRec,
+    },
+    utils::Counted,
+    view_api::Version,
+};
+use rusqlite::{params, Connection, OptionalExtension, Transaction};
+use std::{collections::HashMap, error::Error, fs, path::Path};
+
+/// version of the database schema (stored in the meta-table)
+const SCHEMA_VERSION: &str = "1";
+
+const SCHEMA: &str = "
+CREATE TABLE meta (
+    key TEXT PRIMARY KEY,
+    value TEXT NOT NULL
+);
+CREATE TABLE traces (
+    id INTEGER PRIMARY KEY,
+    trace_id TEXT,
+    root_call TEXT,
+    num_spans INTEGER,
+    start_dt_micros INTEGER,
+    end_dt_micros INTEGER,
+    duration_micros INTEGER,
+    time_to_respond_micros INTEGER
+);
+CREATE INDEX traces_trace_id ON traces (trace_id);
+CREATE TABLE services (
+    service TEXT PRIMARY KEY,
+    num_traces INTEGER NOT NULL,
+    num_received_calls INTEGER NOT NULL,
+    num_outbound_calls INTEGER NOT NULL,
+    num_unknown_calls INTEGER NOT NULL
+);
+CREATE TABLE service_opers (
+    id INTEGER PRIMARY KEY,
+    service TEXT NOT NULL REFERENCES services (service),
+    operation TEXT NOT NULL,
+    count INTEGER NOT NULL,
+    num_traces INTEGER NOT NULL,
+    num_not_http_ok INTEGER NOT NULL,
+    num_with_error_logs INTEGER NOT NULL,
+    UNIQUE (service, operation)
+);
+CREATE TABLE call_chains (
+    id INTEGER PRIMARY KEY,
+    service TEXT NOT NULL REFERENCES services (service),
+    service_oper_id INTEGER REFERENCES service_opers (id),
+    call_chain_key TEXT NOT NULL,
+    caching_process TEXT NOT NULL,
+    is_leaf INTEGER NOT NULL,
+    rooted INTEGER NOT NULL,
+    depth INTEGER NOT NULL,
+    count INTEGER NOT NULL,
+    cc_not_http_ok INTEGER NOT NULL,
+    cc_with_error_logs INTEGER NOT NULL
+);
+CREATE INDEX call_chains_key ON call_chains (call_chain_key);
+CREATE INDEX call_chains_service_oper_id ON call_chains (service_oper_id);
+CREATE TABLE calls (
+    call_chain_id INTEGER NOT NULL REFERENCES call_chains (id),
+    position INTEGER NOT NULL,
+    service TEXT NOT NULL,
+    operation TEXT NOT NULL,
+    call_direction TEXT NOT NULL,
+    PRIMARY KEY (call_chain_id, position)
+);
+CREATE INDEX calls_service_operation ON calls (service, operation);
+CREATE TABLE call_chain_loops (
+    call_chain_id INTEGER NOT NULL REFERENCES call_chains (id),
+    position INTEGER NOT NULL,
+    looped TEXT NOT NULL,
+    PRIMARY KEY (call_chain_id, position)
+);
+CREATE TABLE expected_roots (
+    call_chain_id INTEGER NOT NULL REFERENCES call_chains (id),
+    position INTEGER NOT NULL,
+    proc_oper TEXT NOT NULL,
+    count INTEGER NOT NULL,
+    PRIMARY KEY (call_chain_id, position)
+);
+CREATE TABLE samples (
+    service_oper_id INTEGER REFERENCES service_opers (id),
+    call_chain_id INTEGER REFERENCES call_chains (id),
+    position INTEGER NOT NULL,
+    start_dt_micros INTEGER,
+    duration_micros INTEGER,
+    CHECK ((service_oper_id IS NULL) <> (call_chain_id IS NULL))
+);
+CREATE INDEX samples_service_oper_id ON samples (service_oper_id, position);
+CREATE INDEX samples_call_chain_id ON samples (call_chain_id, position);
+CREATE TABLE errors (
+    service_oper_id INTEGER REFERENCES service_opers (id),
+    call_chain_id INTEGER REFERENCES call_chains (id),
+    error_type TEXT NOT NULL CHECK (error_type IN ('http', 'log')),
+    value TEXT NOT NULL,
+    count INTEGER NOT NULL,
+    CHECK ((service_oper_id IS NULL) <> (call_chain_id IS NULL))
+);
+CREATE INDEX errors_service_oper_id ON errors (service_oper_id);
+CREATE INDEX errors_call_chain_id ON errors (call_chain_id);
+";
+
+/// The owner of a set of samples or errors, which is either a service/operation or a call-chain
+#[derive(Clone, Copy)]
+enum Owner {
+    ServiceOper(i64),
+    CallChain(i64),
+}
+
+impl Owner {
+    fn ids(&self) -> (Option<i64>, Option<i64>) {
+        match self {
+            Owner::ServiceOper(id) => (Some(*id), None),
+            Owner::CallChain(id) => (None, Some(*id)),
+        }
+    }
+
+    fn condition(&self) -> &'static str {
+        match self {
+            Owner::ServiceOper(_) => "service_oper_id = ?1",
+            Owner::CallChain(_) => "call_chain_id = ?1",
+        }
+    }
+
+    fn id(&self) -> i64 {
+        match self {
+            Owner::ServiceOper(id) | Owner::CallChain(id) => *id,
+        }
+    }
+}
+
+fn insert_meta(tx: &Transaction, stats: &StatsRec) -> Result<(), Box<dyn Error>> {
+    let meta = [
+        ("schema_version", SCHEMA_VERSION.to_owned()),
+        ("version_major", stats.version.major.to_string()),
+        ("version_minor", stats.version.minor.to_string()),
+        ("num_files", stats.num_files.to_string()),
+        ("num_endpoints", stats.num_endpoints.to_string()),
+        (
+            "num_incomplete_traces",
+            stats.num_incomplete_traces.to_string(),
+        ),
+        ("num_call_chains", stats.num_call_chains.to_string()),
+        (
+            "init_num_unrooted_cc",
+            stats.init_num_unrooted_cc.to_string(),
+        ),
+        ("num_fixes", stats.num_fixes.to_string()),
+        (
+            "num_unrooted_cc_after_fixes",
+            stats.num_unrooted_cc_after_fixes.to_string(),
+        ),
+        ("caching_processes", stats.caching_processes.join(",")),
+        ("timezone", stats.timezone.to_string()),
+    ];
+    let mut stmt = tx.prepare("INSERT INTO meta (key, value) VALUES (?1, ?2)")?;
+    for (key, value) in meta {
+        stmt.execute(params![key, value])?;
+    }
+    Ok(())
+}
+
+fn insert_traces(tx: &Transaction, stats: &StatsRec) -> Result<(), Box<dyn Error>> {
+    let mut stmt = tx.prepare(
+        "INSERT INTO traces (id, trace_id, root_call, num_spans, start_dt_micros, end_dt_micros, duration_micros, time_to_respond_micros)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+    )?;
+    // the vectors are expected to have the same length, however the longest vector determines the number of rows
+    let num_rows = [
+        stats.trace_id.len(),
+        stats.root_call.len(),
+        stats.num_spans.len(),
+        stats.start_dt_micros.len(),
+        stats.end_dt_micros.len(),
+        stats.duration_micros.len(),
+        stats.time_to_respond_micros.len(),
+    ]
+    .into_iter()
+    .max()
+    .unwrap_or(0);
+    for idx in 0..num_rows {
+        stmt.execute(params![
+            idx as i64,
+            stats.trace_id.get(idx),
+            stats.root_call.get(idx),
+            stats.num_spans.get(idx).map(|n| *n as i64),
+            stats.start_dt_micros.get(idx),
+            stats.end_dt_micros.get(idx),
+            stats.duration_micros.get(idx),
+            stats.time_to_respond_micros.get(idx),
+        ])?;
+    }
+    Ok(())
+}
+
+fn insert_samples(
+    tx: &Transaction,
+    owner: Owner,
+    duration_micros: &[i64],
+    start_dt_micros: &[i64],
+) -> Result<(), Box<dyn Error>> {
+    let mut stmt = tx.prepare_cached(
+        "INSERT INTO samples (service_oper_id, call_chain_id, position, start_dt_micros, duration_micros) VALUES (?1, ?2, ?3, ?4, ?5)",
+    )?;
+    let (so_id, cc_id) = owner.ids();
+    for idx in 0..duration_micros.len().max(start_dt_micros.len()) {
+        stmt.execute(params![
+            so_id,
+            cc_id,
+            idx as i64,
+            start_dt_micros.get(idx),
+            duration_micros.get(idx)
+        ])?;
+    }
+    Ok(())
+}
+
+fn insert_errors(
+    tx: &Transaction,
+    owner: Owner,
+    http_not_ok: &Counted<i16>,
+    error_logs: &Counted<String>,
+) -> Result<(), Box<dyn Error>> {
+    let mut stmt = tx.prepare_cached(
+        "INSERT INTO errors (service_oper_id, call_chain_id, error_type, value, count) VALUES (?1, ?2, ?3, ?4, ?5)",
+    )?;
+    let (so_id, cc_id) = owner.ids();
+    for (code, count) in http_not_ok.iter() {
+        stmt.execute(params![
+            so_id,
+            cc_id,
+            "http",
+            code.to_string(),
+            *count as i64
+        ])?;
+    }
+    for (msg, count) in error_logs.iter() {
+        stmt.execute(params![so_id, cc_id, "log", msg, *count as i64])?;
+    }
+    Ok(())
+}
+
+fn insert_call_chain(
+    tx: &Transaction,
+    service: &str,
+    service_oper_id: Option<i64>,
+    cck: &CChainStatsKey,
+    ccv: &CChainStatsValue,
+) -> Result<(), Box<dyn Error>> {
+    tx.prepare_cached(
+        "INSERT INTO call_chains (service, service_oper_id, call_chain_key, caching_process, is_leaf, rooted, depth, count, cc_not_http_ok, cc_with_error_logs)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+    )?
+    .execute(params![
+        service,
+        service_oper_id,
+        cck.call_chain_key(),
+        cck.caching_process,
+        cck.is_leaf,
+        ccv.rooted,
+        ccv.depth as i64,
+        ccv.count as i64,
+        ccv.cc_not_http_ok,
+        ccv.cc_with_error_logs
+    ])?;
+    let cc_id = tx.last_insert_rowid();
+
+    let mut stmt = tx.prepare_cached(
+        "INSERT INTO calls (call_chain_id, position, service, operation, call_direction) VALUES (?1, ?2, ?3, ?4, ?5)",
+    )?;
+    for (pos, call) in cck.call_chain.iter().enumerate() {
+        stmt.execute(params![
+            cc_id,
+            pos as i64,
+            call.service,
+            call.operation,
+            call.call_direction.as_str()
+        ])?;
+    }
+    let mut stmt = tx.prepare_cached(
+        "INSERT INTO call_chain_loops (call_chain_id, position, looped) VALUES (?1, ?2, ?3)",
+    )?;
+    for (pos, looped) in ccv.looped.iter().enumerate() {
+        stmt.execute(params![cc_id, pos as i64, looped])?;
+    }
+    let mut stmt = tx.prepare_cached(
+        "INSERT INTO expected_roots (call_chain_id, position, proc_oper, count) VALUES (?1, ?2, ?3, ?4)",
+    )?;
+    for (pos, root) in ccv.expect_root.0.iter().enumerate() {
+        stmt.execute(params![
+            cc_id,
+            pos as i64,
+            root.proc_oper,
+            root.count as i64
+        ])?;
+    }
+
+    let owner = Owner::CallChain(cc_id);
+    insert_samples(tx, owner, &ccv.duration_micros, &ccv.start_dt_micros)?;
+    insert_errors(tx, owner, &ccv.http_not_ok, &ccv.error_logs)
+}
+
+fn insert_stats(tx: &Transaction, stats: &StatsRec) -> Result<(), Box<dyn Error>> {
+    let mut services: Vec<_> = stats.stats.iter().collect();
+    services.sort_by(|a, b| a.0.cmp(b.0));
+    for (service, oper_stats) in services {
+        tx.execute(
+            "INSERT INTO services (service, num_traces, num_received_calls, num_outbound_calls, num_unknown_calls) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                service,
+                oper_stats.num_traces as i64,
+                oper_stats.num_received_calls as i64,
+                oper_stats.num_outbound_calls as i64,
+                oper_stats.num_unknown_calls as i64
+            ],
+        )?;
+
+        let mut so_ids = HashMap::new();
+        for (oper, posv) in oper_stats.operation.0.iter() {
+            tx.prepare_cached(
+                "INSERT INTO service_opers (service, operation, count, num_traces, num_not_http_ok, num_with_error_logs) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            )?
+            .execute(params![
+                service,
+                oper,
+                posv.count as i64,
+                posv.num_traces as i64,
+                posv.num_not_http_ok,
+                posv.num_with_error_logs
+            ])?;
+            let so_id = tx.last_insert_rowid();
+            so_ids.insert(oper.as_str(), so_id);
+            let owner = Owner::ServiceOper(so_id);
+            insert_samples(tx, owner, &posv.duration_micros, &posv.start_dt_micros)?;
+            insert_errors(tx, owner, &posv.http_not_ok_codes, &posv.error_logs)?;
+        }
+
+        for (cck, ccv) in oper_stats.call_chain.0.iter() {
+            let so_id = so_ids.get(cck.get_operation()).copied();
+            insert_call_chain(tx, service, so_id, cck, ccv)?;
+        }
+    }
+    Ok(())
+}
+
+/// Write the statistics to a new SQLite database (an existing file is replaced).
+pub fn write_file(file_name: &str, stats: &StatsRec) -> Result<(), Box<dyn Error>> {
+    if Path::new(file_name).exists() {
+        fs::remove_file(file_name)?;
+    }
+    let mut conn = Connection::open(file_name)?;
+    conn.pragma_update(None, "foreign_keys", "ON")?;
+    let tx = conn.transaction()?;
+    tx.execute_batch(SCHEMA)?;
+    insert_meta(&tx, stats)?;
+    insert_traces(&tx, stats)?;
+    insert_stats(&tx, stats)?;
+    tx.commit()?;
+    Ok(())
+}
+
+pub fn dump_file(file_name: &str, stats: &StatsRec) {
+    if let Err(err) = write_file(file_name, stats) {
+        panic!("Dump of data to file {file_name} failed.\n\tError: {err:?}");
+    }
+}
+
+fn read_samples(conn: &Connection, owner: Owner) -> Result<(Vec<i64>, Vec<i64>), Box<dyn Error>> {
+    let mut stmt = conn.prepare_cached(&format!(
+        "SELECT start_dt_micros, duration_micros FROM samples WHERE {} ORDER BY position",
+        owner.condition()
+    ))?;
+    let mut start_dt_micros = Vec::new();
+    let mut duration_micros = Vec::new();
+    let mut rows = stmt.query([owner.id()])?;
+    while let Some(row) = rows.next()? {
+        if let Some(start) = row.get::<_, Option<i64>>(0)? {
+            start_dt_micros.push(start);
+        }
+        if let Some(duration) = row.get::<_, Option<i64>>(1)? {
+            duration_micros.push(duration);
+        }
+    }
+    Ok((duration_micros, start_dt_micros))
+}
+
+fn read_errors(
+    conn: &Connection,
+    owner: Owner,
+) -> Result<(Counted<i16>, Counted<String>), Box<dyn Error>> {
+    let mut stmt = conn.prepare_cached(&format!(
+        "SELECT error_type, value, count FROM errors WHERE {}",
+        owner.condition()
+    ))?;
+    let mut http_not_ok = Counted::new();
+    let mut error_logs = Counted::new();
+    let mut rows = stmt.query([owner.id()])?;
+    while let Some(row) = rows.next()? {
+        let error_type: String = row.get(0)?;
+        let value: String = row.get(1)?;
+        let count = row.get::<_, i64>(2)? as usize;
+        match &error_type[..] {
+            "http" => {
+                http_not_ok.add_item_count(value.parse()?, count);
+            }
+            _ => {
+                error_logs.add_item_count(value, count);
+            }
+        }
+    }
+    Ok((http_not_ok, error_logs))
+}
+
+fn read_call_chain(conn: &Connection, cc_id: i64) -> Result<Vec<Call>, Box<dyn Error>> {
+    let mut stmt = conn.prepare_cached(
+        "SELECT service, operation, call_direction FROM calls WHERE call_chain_id = ?1 ORDER BY position",
+    )?;
+    let calls = stmt
+        .query_map([cc_id], |row| {
+            Ok((row.get(0)?, row.get(1)?, row.get::<_, String>(2)?))
+        })?
+        .map(|row| -> Result<Call, Box<dyn Error>> {
+            let (service, operation, call_direction) = row?;
+            Ok(Call {
+                service,
+                operation,
+                call_direction: call_direction.parse::<CallDirection>()?,
+            })
+        })
+        .collect::<Result<_, _>>()?;
+    Ok(calls)
+}
+
+fn read_service(
+    conn: &Connection,
+    service: &str,
+    oper_stats: &mut OperationStats,
+) -> Result<(), Box<dyn Error>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, operation, count, num_traces, num_not_http_ok, num_with_error_logs FROM service_opers WHERE service = ?1",
+    )?;
+    let mut rows = stmt.query([service])?;
+    while let Some(row) = rows.next()? {
+        let owner = Owner::ServiceOper(row.get(0)?);
+        let (duration_micros, start_dt_micros) = read_samples(conn, owner)?;
+        let (http_not_ok_codes, error_logs) = read_errors(conn, owner)?;
+        oper_stats.operation.0.insert(
+            row.get(1)?,
+            ProcOperStatsValue {
+                count: row.get::<_, i64>(2)? as usize,
+                num_traces: row.get::<_, i64>(3)? as usize,
+                duration_micros,
+                start_dt_micros,
+                num_not_http_ok: row.get(4)?,
+                num_with_error_logs: row.get(5)?,
+                http_not_ok_codes,
+                error_logs,
+            },
+        );
+    }
+
+    let mut stmt = conn.prepare(
+        "SELECT id, caching_process, is_leaf, rooted, depth, count, cc_not_http_ok, cc_with_error_logs FROM call_chains WHERE service = ?1",
+    )?;
+    let mut rows = stmt.query([service])?;
+    while let Some(row) = rows.next()? {
+        let cc_id: i64 = row.get(0)?;
+        let owner = Owner::CallChain(cc_id);
+        let cck = CChainStatsKey {
+            call_chain: read_call_chain(conn, cc_id)?,
+            caching_process: row.get(1)?,
+            is_leaf: row.get(2)?,
+        };
+        let looped = conn
+            .prepare_cached(
+                "SELECT looped FROM call_chain_loops WHERE call_chain_id = ?1 ORDER BY position",
+            )?
+            .query_map([cc_id], |row| row.get(0))?
+            .collect::<Result<_, _>>()?;
+        let mut ccv = CChainStatsValue::new(row.get::<_, i64>(4)? as usize, looped, row.get(3)?);
+        let mut roots = conn.prepare_cached(
+            "SELECT proc_oper, count FROM expected_roots WHERE call_chain_id = ?1 ORDER BY position",
+        )?;
+        let mut root_rows = roots.query([cc_id])?;
+        while let Some(root) = root_rows.next()? {
+            let proc_oper: String = root.get(0)?;
+            (0..root.get::<_, i64>(1)?).for_each(|_| ccv.expect_root.add_root(&proc_oper));
+        }
+        (ccv.duration_micros, ccv.start_dt_micros) = read_samples(conn, owner)?;
+        (ccv.http_not_ok, ccv.error_logs) = read_errors(conn, owner)?;
+        ccv.count = row.get::<_, i64>(5)? as usize;
+        ccv.cc_not_http_ok = row.get(6)?;
+        ccv.cc_with_error_logs = row.get(7)?;
+        oper_stats.call_chain.0.insert(cck, ccv);
+    }
+    Ok(())
+}
+
+/// Rebuild the statistics from a SQLite database written by 'write_file'
+pub fn read_file(ctx: &AnalysisContext, file_name: &str) -> Result<StatsRec, Box<dyn Error>> {
+    let conn = Connection::open(file_name)?;
+
+    let meta = |key: &str| -> Result<String, Box<dyn Error>> {
+        conn.query_row("SELECT value FROM meta WHERE key = ?1", [key], |row| {
+            row.get(0)
+        })
+        .optional()?
+        .ok_or_else(|| format!("Missing key '{key}' in the meta-table of '{file_name}'").into())
+    };
+    let schema_version = meta("schema_version")?;
+    if schema_version != SCHEMA_VERSION {
+        Err(format!(
+            "Unsupported schema-version '{schema_version}' in '{file_name}' (expected {SCHEMA_VERSION})"
+        ))?
+    }
+
+    let mut sr = StatsRec {
+        version: Version::new(
+            meta("version_major")?.parse()?,
+            meta("version_minor")?.parse()?,
+        ),
+        num_files: meta("num_files")?.parse()?,
+        num_endpoints: meta("num_endpoints")?.parse()?,
+        num_incomplete_traces: meta("num_incomplete_traces")?.parse()?,
+        num_call_chains: meta("num_call_chains")?.parse()?,
+        init_num_unrooted_cc: meta("init_num_unrooted_cc")?.parse()?,
+        num_fixes: meta("num_fixes")?.parse()?,
+        num_unrooted_cc_after_fixes: meta("num_unrooted_cc_after_fixes")?.parse()?,
+        caching_processes: meta("caching_processes")?
+            .split(',')
+            .filter(|s| !s.is_empty())
+            .map(|s| s.to_owned())
+            .collect(),
+        // databases without a timezone are shown in the timezone of the analysis-context
+        timezone: match meta("timezone") {
+            Ok(timezone) => timezone.parse()?,
+            Err(_) => ctx.timezone,
+        },
+        ..Default::default()
+    };
+
+    let mut stmt = conn.prepare(
+        "SELECT trace_id, root_call, num_spans, start_dt_micros, end_dt_micros, duration_micros, time_to_respond_micros FROM traces ORDER BY id",
+    )?;
+    let mut rows = stmt.query([])?;
+    while let Some(row) = rows.next()? {
+        if let Some(trace_id) = row.get(0)? {
+            sr.trace_id.push(trace_id);
+        }
+        if let Some(root_call) = row.get(1)? {
+            sr.root_call.push(root_call);
+        }
+        if let Some(num_spans) = row.get::<_, Option<i64>>(2)? {
+            sr.num_spans.push(num_spans as usize);
+        }
+        if let Some(start_dt) = row.get(3)? {
+            sr.start_dt_micros.push(start_dt);
+        }
+        if let Some(end_dt) = row.get(4)? {
+            sr.end_dt_micros.push(end_dt);
+        }
+        if let Some(duration) = row.get(5)? {
+            sr.duration_micros.push(duration);
+        }
+        if let Some(time_to_respond) = row.get(6)? {
+            sr.time_to_respond_micros.push(time_to_respond);
+        }
+    }
+
+    let services: Vec<(String, OperationStats)> = conn
+        .prepare("SELECT service, num_traces, num_received_calls, num_outbound_calls, num_unknown_calls FROM services")?
+        .query_map([], |row| {
+            let oper_stats = OperationStats {
+                num_traces: row.get::<_, i64>(1)? as usize,
+                num_received_calls: row.get::<_, i64>(2)? as usize,
+                num_outbound_calls: row.get::<_, i64>(3)? as usize,
+                num_unknown_calls: row.get::<_, i64>(4)? as usize,
+                operation: ProcOperStats(HashMap::new()),
+                call_chain: CChainStats::new(),
+            };
+            Ok((row.get(0)?, oper_stats))
+        })?
+        .collect::<Result<_, _>>()?;
+    for (service, mut oper_stats) in services {
+        read_service(&conn, &service, &mut oper_stats)?;
+        sr.stats.insert(service, oper_stats);
+    }
+    Ok(sr)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{read_file, write_file};
+    use crate::context::AnalysisContext;
+    use crate::stats::{
+        call_chain::{CChainStatsKey, CChainStatsValue},
+        OperationStats, ProcOperStatsValue, StatsRec,
+    };
+    use std::fs;
+
+    #[test]
+    fn write_and_read_back() {
+        let mut sr = StatsRec {
+            trace_id: vec!["t1".to_owned(), "t2".to_owned()],
+            root_call: vec!["gw/GET:/x".to_owned(); 2],
+            num_spans: vec![3, 4],
+            num_files: 1,
+            start_dt_micros: vec![1_698_539_400_000_000, 1_698_543_000_000_000],
+            end_dt_micros: vec![1_698_539_401_000_000, 1_698_543_001_000_000],
+            duration_micros: vec![100, 200],
+            time_to_respond_micros: vec![90, 180],
+            caching_processes: vec!["cache".to_owned()],
+            ..Default::default()
+        };
+        let mut oper_stats = OperationStats::default();
+        let mut posv = ProcOperStatsValue {
+            count: 2,
+            duration_micros: vec![10, 20],
+            start_dt_micros: vec![1, 2],
+            num_not_http_ok: 1,
+            ..Default::default()
+        };
+        posv.http_not_ok_codes.add_item(404);
+        posv.error_logs.add_item("Order 12 not found".to_owned());
+        oper_stats.operation.0.insert("get".to_owned(), posv);
+        let cck =
+            CChainStatsKey::parse("gw/GET:/x [Inbound] | a/get [Inbound] & & *LEAF*").unwrap();
+        let mut ccv = CChainStatsValue::new(2, vec!["loop".to_owned()], true);
+        ccv.count = 2;
+        ccv.duration_micros = vec![10, 20];
+        ccv.start_dt_micros = vec![1, 2];
+        ccv.expect_root.add_root("gw/GET:/x");
+        oper_stats.call_chain.0.insert(cck.clone(), ccv);
+        sr.stats.insert("a".to_owned(), oper_stats);
+
+        let path = std::env::temp_dir().join("jaeger_stats_sqlite_write_and_read_back.sqlite");
+        let file_name = path.to_str().unwrap();
+        write_file(file_name, &sr).unwrap();
+        let sr2 = read_file(&AnalysisContext::default(), file_name).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(sr2.trace_id, sr.trace_id);
+        assert_eq!(sr2.num_spans, sr.num_spans);
+        assert_eq!(sr2.start_dt_micros, sr.start_dt_micros);
+        assert_eq!(sr2.end_dt_micros, sr.end_dt_micros);
+        assert_eq!(sr2.time_to_respond_micros, sr.time_to_respond_micros);
+        assert_eq!(sr2.caching_processes, sr.caching_processes);
+        let oper_stats = sr2.stats.get("a").unwrap();
+        let posv = oper_stats.operation.0.get("get").unwrap();
+        assert_eq!(posv.duration_micros, vec![10, 20]);
+        assert_eq!(posv.http_not_ok_codes.get_count(404), 1);
+        assert_eq!(
+            posv.error_logs.get_count("Order 12 not found".to_owned()),
+            1
+        );
+        let ccv = oper_stats.call_chain.0.get(&cck).unwrap();
+        assert_eq!(ccv.count, 2);
+        assert!(ccv.rooted);
+        assert_eq!(ccv.looped, vec!["loop".to_owned()]);
+        assert_eq!(ccv.start_dt_micros, vec![1, 2]);
+        assert_eq!(ccv.expect_root.0[0].count, 1);
+    }
+}
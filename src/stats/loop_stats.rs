@@ -0,0 +1,111 @@
+//! Report of the call-chains that contain loops (recursive calls).
+//! Only the complete call-chains (ending in a leaf-span) are reported, as each prefix of a looping call-chain
+//! contains the same loop.
+use super::{
+    call_chain::{call_chain_key, detect_loops, get_call_chain},
+    TraceExt,
+};
+use crate::{context::AnalysisContext, utils::TimeStats};
+use std::collections::HashMap;
+
+/// Maximal number of example trace-ids retained per looping call-chain
+const MAX_EXAMPLE_TRACES: usize = 3;
+
+#[derive(Debug, Default, Clone)]
+pub struct LoopStatsValue {
+    /// Description of the loops detected in this call-chain
+    pub loops: Vec<String>,
+    /// Maximal depth over the loops in this call-chain
+    pub max_depth: usize,
+    pub count: usize,
+    pub num_traces: usize,
+    /// Durations of the traces that contain this call-chain
+    pub trace_duration_micros: Vec<i64>,
+    pub example_trace_ids: Vec<String>,
+    /// trace_id of the last trace that contained this call-chain (traces are processed one by one)
+    last_trace_id: String,
+}
+
+impl LoopStatsValue {
+    /// header for report_stats_line output in ';'-separated csv-format
+    pub fn report_stats_line_header_str() -> &'static str {
+        "Call_chain; Loops; Max_depth; Count; Num_traces; Trace_min_millis; Trace_avg_millis; Trace_max_millis; Example_trace_ids"
+    }
+
+    /// reports the statistics for a single line in ';'-separated csv-format
+    pub fn report_stats_line(&self, ctx: &AnalysisContext, call_chain: &str) -> String {
+        let ts = TimeStats(&self.trace_duration_micros);
+        format!(
+            "{call_chain}; {}; {}; {}; {}; {}; {}; {}; {}",
+            self.loops.join(" & "),
+            self.max_depth,
+            self.count,
+            self.num_traces,
+            ts.get_min_millis_str(ctx),
+            ts.get_avg_millis_str(ctx),
+            ts.get_max_millis_str(ctx),
+            self.example_trace_ids.join(", ")
+        )
+    }
+}
+
+/// Statistics of looping call-chains keyed by the call-chain.
+#[derive(Debug, Default, Clone)]
+pub struct LoopStats(pub HashMap<String, LoopStatsValue>);
+
+impl LoopStats {
+    /// Collect all complete call-chains that contain a loop.
+    pub fn new(traces: &[TraceExt]) -> Self {
+        let mut loop_stats: HashMap<String, LoopStatsValue> = HashMap::new();
+        traces.iter().for_each(|tr| {
+            let trace = &tr.trace;
+            let spans = &trace.spans;
+            spans
+                .items
+                .iter()
+                .enumerate()
+                .filter(|(_, span)| span.is_leaf)
+                .for_each(|(idx, _)| {
+                    let call_chain = get_call_chain(idx, spans);
+                    let loops = detect_loops(&call_chain);
+                    if loops.is_empty() {
+                        return;
+                    }
+                    let lsv = loop_stats
+                        .entry(call_chain_key(&call_chain, "", true))
+                        .or_insert_with(|| LoopStatsValue {
+                            loops: loops.iter().map(|l| l.to_string()).collect(),
+                            ..Default::default()
+                        });
+                    lsv.count += 1;
+                    lsv.max_depth = loops.iter().fold(lsv.max_depth, |md, l| md.max(l.depth));
+                    // a trace can contain the same looping call-chain many times, so only count it once.
+                    if lsv.last_trace_id != trace.trace_id {
+                        lsv.last_trace_id = trace.trace_id.to_owned();
+                        lsv.num_traces += 1;
+                        lsv.trace_duration_micros.push(trace.duration_micros);
+                        if lsv.example_trace_ids.len() < MAX_EXAMPLE_TRACES {
+                            lsv.example_trace_ids.push(trace.trace_id.to_owned());
+                        }
+                    }
+                })
+        });
+        Self(loop_stats)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Show the looping call-chains as a ';'-separated csv-string, ordered on descending count.
+    pub fn to_csv_string(&self, ctx: &AnalysisContext) -> String {
+        let mut data: Vec<_> = self.0.iter().collect();
+        data.sort_by(|a, b| b.1.count.cmp(&a.1.count).then(a.0.cmp(b.0)));
+
+        let mut s = vec![LoopStatsValue::report_stats_line_header_str().to_owned()];
+        data.into_iter()
+            .for_each(|(call_chain, lsv)| s.push(lsv.report_stats_line(ctx, call_chain)));
+        s.push("\n".to_owned());
+        s.join("\n")
+    }
+}
@@ -1,8 +1,11 @@
 //!  Computing statistics and call-chains over the traces.
 
+mod cache_stats;
 pub mod call_chain; // already defines its public interface
 mod error_stats;
+mod error_template;
 pub mod file;
+mod loop_stats;
 mod operation_stats;
 mod proc_oper_stats;
 mod stats_rec;
@@ -10,7 +13,10 @@ mod traceext; // already defines its public interface
 mod traceextvec;
 
 pub use {
+    cache_stats::{CacheStats, CacheStatsValue},
     call_chain::{CChainEndPointCache, CChainStatsKey},
+    error_template::ErrorTemplates,
+    loop_stats::LoopStats,
     operation_stats::OperationStats,
     proc_oper_stats::{ProcOperStats, ProcOperStatsValue},
     stats_rec::{chained_stats, BasicStatsRec, LeafService, StatsRec},
@@ -1,15 +1,16 @@
 use super::{
     call_chain::{
-        caching_process_label, get_call_chain, CChainStats, CChainStatsKey, CChainStatsValue,
-        CallChain,
+        caching_process_label, check_span_kind, detect_loops, get_call_chain, CChainStats,
+        CChainStatsKey, CChainStatsValue,
     },
     error_stats::{get_cchain_error_information, get_span_error_information},
     file::OperationStatsJson,
     proc_oper_stats::{ProcOperStats, ProcOperStatsValue},
 };
 use crate::{
+    context::AnalysisContext,
     processed::{Span, Spans},
-    utils,
+    utils::{self, Report},
 };
 
 #[derive(Debug, Default, Clone)]
@@ -53,8 +54,10 @@ impl OperationStats {
     // The update_stat closure is the actual update operation
     // This closure is later applied to the newly inserted record for this process, or is used to update an existing record,
     // such that both processes share exactly the same code.
+    // An invalid span-kind is added to 'report'.
     pub fn update(
         &mut self,
+        report: &mut Report,
         idx: usize,
         span: &Span,
         spans: &Spans,
@@ -69,9 +72,10 @@ impl OperationStats {
             },
             None => self.num_unknown_calls += 1,
         }
+        check_span_kind(report, span.span_kind.as_ref());
 
         let duration_micros = span.duration_micros;
-        let start_dt_micros = span.start_dt.timestamp_micros();
+        let start_dt_micros = span.start_dt_micros;
         let (http_not_ok_vec, error_logs_vec) = get_span_error_information(span);
 
         let update_proc_oper_value = |oper_stat_val: &mut ProcOperStatsValue| {
@@ -104,7 +108,10 @@ impl OperationStats {
 
         // add call-chain stats
         let depth = call_chain.len();
-        let looped = get_duplicates(&call_chain);
+        let looped = detect_loops(&call_chain)
+            .iter()
+            .map(|l| l.to_string())
+            .collect();
         let is_leaf = span.is_leaf;
         //TODO: if get_call_chain returned whether it is rooted we do not need 'span.rooted'. However span.rooted is also used in filtering of reported spans.
         let rooted = span.rooted;
@@ -147,7 +154,12 @@ impl OperationStats {
     }
 
     /// reports the statistics for a single line in ';'-separated csv-format
-    pub fn report_stats_line(&self, process: &str, num_traces: f64) -> String {
+    pub fn report_stats_line(
+        &self,
+        ctx: &AnalysisContext,
+        process: &str,
+        num_traces: f64,
+    ) -> String {
         let freq_rc = self.num_received_calls as f64 / num_traces;
         let freq_oc = self.num_outbound_calls as f64 / num_traces;
         let freq_uc = self.num_outbound_calls as f64 / num_traces;
@@ -156,43 +168,9 @@ impl OperationStats {
             self.num_received_calls,
             self.num_outbound_calls,
             self.num_unknown_calls,
-            utils::format_float(freq_rc),
-            utils::format_float(freq_oc),
-            utils::format_float(freq_uc)
+            utils::format_float(ctx, freq_rc),
+            utils::format_float(ctx, freq_oc),
+            utils::format_float(ctx, freq_uc)
         )
     }
 }
-
-/// get all values that appear more than once in the list of strings, while being none-adjacent.
-/// TODO: this is part of a procedure to detect loops, which is not completely correct I guess (in case spans are missing)
-fn get_duplicates(names: &CallChain) -> Vec<String> {
-    let mut duplicates = Vec::new();
-    for idx in 0..names.len() {
-        let proc = &names[idx].service;
-        let mut j = 0;
-        loop {
-            if j >= duplicates.len() {
-                break;
-            }
-            if duplicates[j] == *proc {
-                break;
-            }
-            j += 1;
-        }
-        if j < duplicates.len() {
-            continue;
-        }
-        //  nme does not exist in duplicates yet, so find it in names
-        let mut j = idx + 2; // Step by 2 as we want to prevent matching sub-sequent GET calls
-        loop {
-            if j >= names.len() || names[j].service == *proc {
-                break;
-            }
-            j += 1;
-        }
-        if j < names.len() {
-            duplicates.push(proc.to_owned());
-        }
-    }
-    duplicates
-}
@@ -1,4 +1,8 @@
-use crate::utils::{self, Counted, TimeStats};
+use super::ErrorTemplates;
+use crate::{
+    context::AnalysisContext,
+    utils::{self, Counted, TimeStats},
+};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
@@ -28,16 +32,16 @@ impl ProcOperStatsValue {
         TimeStats(&self.duration_micros).get_min_millis()
     }
 
-    pub fn get_min_millis_str(&self) -> String {
-        TimeStats(&self.duration_micros).get_min_millis_str()
+    pub fn get_min_millis_str(&self, ctx: &AnalysisContext) -> String {
+        TimeStats(&self.duration_micros).get_min_millis_str(ctx)
     }
 
     pub fn get_avg_millis(&self) -> f64 {
         TimeStats(&self.duration_micros).get_avg_millis()
     }
 
-    pub fn get_avg_millis_str(&self) -> String {
-        TimeStats(&self.duration_micros).get_avg_millis_str()
+    pub fn get_avg_millis_str(&self, ctx: &AnalysisContext) -> String {
+        TimeStats(&self.duration_micros).get_avg_millis_str(ctx)
     }
 
     pub fn get_median_millis(&self) -> Option<f64> {
@@ -49,39 +53,44 @@ impl ProcOperStatsValue {
         TimeStats(&self.duration_micros).get_p_millis(p)
     }
 
-    pub fn get_median_millis_str(&self) -> String {
-        TimeStats(&self.duration_micros).get_median_millis_str()
+    pub fn get_median_millis_str(&self, ctx: &AnalysisContext) -> String {
+        TimeStats(&self.duration_micros).get_median_millis_str(ctx)
     }
 
     pub fn get_max_millis(&self) -> f64 {
         TimeStats(&self.duration_micros).get_max_millis()
     }
 
-    pub fn get_max_millis_str(&self) -> String {
-        TimeStats(&self.duration_micros).get_max_millis_str()
+    pub fn get_max_millis_str(&self, ctx: &AnalysisContext) -> String {
+        TimeStats(&self.duration_micros).get_max_millis_str(ctx)
     }
 
     pub fn get_avg_rate(&self, num_files: i32) -> Option<f64> {
         TimeStats(&self.start_dt_micros).get_avg_rate(num_files)
     }
 
-    pub fn get_avg_rate_str(&self, num_files: i32) -> String {
-        TimeStats(&self.start_dt_micros).get_avg_rate_str(num_files)
+    pub fn get_avg_rate_str(&self, ctx: &AnalysisContext, num_files: i32) -> String {
+        TimeStats(&self.start_dt_micros).get_avg_rate_str(ctx, num_files)
     }
 
     pub fn get_frac_not_http_ok(&self) -> f64 {
         self.num_not_http_ok as f64 / self.count as f64
     }
-    pub fn get_frac_not_http_ok_str(&self) -> String {
-        utils::format_float(self.get_frac_not_http_ok())
+    pub fn get_frac_not_http_ok_str(&self, ctx: &AnalysisContext) -> String {
+        utils::format_float(ctx, self.get_frac_not_http_ok())
     }
 
     pub fn get_frac_error_log(&self) -> f64 {
         self.num_with_error_logs as f64 / self.count as f64
     }
 
-    pub fn get_frac_error_log_str(&self) -> String {
-        utils::format_float(self.get_frac_error_log())
+    pub fn get_frac_error_log_str(&self, ctx: &AnalysisContext) -> String {
+        utils::format_float(ctx, self.get_frac_error_log())
+    }
+
+    /// group the error-messages in templates
+    pub fn get_error_templates(&self, ctx: &AnalysisContext) -> ErrorTemplates {
+        ErrorTemplates::new(ctx, &self.error_logs)
     }
 
     /// header for report_stats_line output in ';'-separated csv-format
@@ -92,6 +101,7 @@ impl ProcOperStatsValue {
     /// reports the statistics for a single line in ';'-separated csv-format
     pub fn report_stats_line(
         &self,
+        ctx: &AnalysisContext,
         process_key: &str,
         operation: &str,
         n: f64,
@@ -104,15 +114,15 @@ impl ProcOperStatsValue {
             "{process_key}/{operation}; {}; {}; {}; {}; {}; {}; {}; {}; {}; {}; {}",
             self.count,
             self.num_traces,
-            self.get_min_millis_str(),
-            self.get_median_millis_str(),
-            self.get_avg_millis_str(),
-            self.get_max_millis_str(),
-            utils::format_float(percentage),
-            self.get_avg_rate_str(num_files),
-            utils::format_float(expect_duration),
-            self.get_frac_not_http_ok_str(),
-            self.get_frac_error_log_str()
+            self.get_min_millis_str(ctx),
+            self.get_median_millis_str(ctx),
+            self.get_avg_millis_str(ctx),
+            self.get_max_millis_str(ctx),
+            utils::format_float(ctx, percentage),
+            self.get_avg_rate_str(ctx, num_files),
+            utils::format_float(ctx, expect_duration),
+            self.get_frac_not_http_ok_str(ctx),
+            self.get_frac_error_log_str(ctx)
         );
         line
     }
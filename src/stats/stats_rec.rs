@@ -1,16 +1,18 @@
 use super::{
     call_chain::{
-        call_chain_key, get_call_chain, CChainEndPointCache, CChainStats, CChainStatsKey,
-        CChainStatsValue,
+        call_chain_key, get_call_chain, CChainEndPointCache, CChainRepair, CChainStats,
+        CChainStatsKey, CChainStatsValue, ObservedCounts, RepairStats,
     },
-    file::StatsRecJson,
+    error_template::ErrorTemplates,
+    file::{self, StatsRecJson},
     operation_stats::OperationStats,
     proc_oper_stats::ProcOperStatsValue,
 };
 use crate::view_api::Version;
 use crate::{
+    context::AnalysisContext,
     processed::Trace,
-    utils::{self, micros_to_datetime, Chapter},
+    utils::{self, Chapter, Report, ReportEntry, Severity, Timezone, XlsxSheet},
 };
 use chrono::NaiveDateTime;
 use std::{
@@ -18,6 +20,7 @@ use std::{
     error::Error,
     ffi::OsString,
     mem,
+    path::Path,
 };
 
 #[derive(Default, Clone)]
@@ -42,7 +45,7 @@ pub type LeafService = String;
 #[derive(Debug, Default, Clone)]
 pub struct StatsRec {
     /// version numbering to handle diversity of analyzed data-sets.
-    /// Currently we do not have the code that handles or checks versions. So only present for post-mortem analysis or future usage.
+    /// Files of an older version are migrated on load (see 'StatsRecJson::read_file'), so this is 'Version::STATS' for data read from json or bincode.
     pub version: Version,
     /// Ordered list of all the trace_ids present in this
     pub trace_id: Vec<String>,
@@ -64,11 +67,11 @@ pub struct StatsRec {
     pub num_fixes: usize,
     // Number of Call-chains that do not start at the root of the full trace after fixes based on call-chains
     pub num_unrooted_cc_after_fixes: usize,
-    /// Start date-time per trace in a Naive format as the encoding in the source-files is based on Epoch-micros and does not contain time-zone information
-    pub start_dt: Vec<NaiveDateTime>,
-    /// End date-time for each trace
-    pub end_dt: Vec<NaiveDateTime>,
-    /// The duration in microseconds is added for convenience. This information is aligned with 'end_dt - start_dt'.
+    /// Start of each trace in micro-seconds since epoch (UTC), which is shown as a local date-time via 'timezone'
+    pub start_dt_micros: Vec<i64>,
+    /// End of each trace in micro-seconds since epoch (UTC)
+    pub end_dt_micros: Vec<i64>,
+    /// The duration in microseconds is added for convenience. This information is aligned with 'end_dt_micros - start_dt_micros'.
     pub duration_micros: Vec<i64>,
     /// The Time_to_respond_micros measures when a response is returned, as a some background computation, or writing of data might happen after this time.
     pub time_to_respond_micros: Vec<i64>,
@@ -76,12 +79,16 @@ pub struct StatsRec {
     pub caching_processes: Vec<String>,
     /// Statistis per leaf-process (end-point of the chain of processes)
     pub stats: HashMap<LeafService, OperationStats>, // hashmap based on the leaf process (as that is the initial level of reporting)
+    /// The timezone used to show 'start_dt_micros' and 'end_dt_micros' as local date-times
+    pub timezone: Timezone,
 }
 
-impl From<StatsRecJson> for StatsRec {
-    fn from(srj: StatsRecJson) -> Self {
+impl StatsRec {
+    /// Turn a StatsRecJson into a StatsRec, where files without a timezone are shown in the timezone of the analysis-context
+    pub fn from_json(ctx: &AnalysisContext, srj: StatsRecJson) -> Self {
         let stats: HashMap<String, OperationStats> =
             srj.stats.into_iter().map(|(k, v)| (k, v.into())).collect();
+        let timezone = srj.timezone.unwrap_or(ctx.timezone);
         Self {
             version: srj.version,
             trace_id: srj.trace_id,
@@ -94,18 +101,17 @@ impl From<StatsRecJson> for StatsRec {
             init_num_unrooted_cc: srj.init_num_unrooted_cc,
             num_fixes: srj.num_fixes,
             num_unrooted_cc_after_fixes: srj.num_unrooted_cc_after_fixes,
-            start_dt: srj.start_dt.into_iter().map(micros_to_datetime).collect(),
-            end_dt: srj.end_dt.into_iter().map(micros_to_datetime).collect(),
+            start_dt_micros: srj.start_dt,
+            end_dt_micros: srj.end_dt,
             duration_micros: srj.duration_micros,
             time_to_respond_micros: srj.time_to_respond_micros,
             caching_processes: srj.caching_processes,
             stats,
+            timezone,
         }
     }
-}
 
-impl StatsRec {
-    pub fn new(mut bsr: BasicStatsRec) -> Self {
+    pub fn new(ctx: &AnalysisContext, mut bsr: BasicStatsRec) -> Self {
         let caching_process = mem::take(&mut bsr.caching_processes);
         let num_files = bsr.num_files;
         let num_endpoints = bsr.num_endpoints;
@@ -114,6 +120,7 @@ impl StatsRec {
         let num_fixes = bsr.num_fixes;
         let num_unrooted_cc_after_fixes = bsr.num_unrooted_cc_after_fixes;
         StatsRec {
+            version: Version::STATS,
             caching_processes: caching_process,
             num_files,
             num_endpoints,
@@ -121,14 +128,37 @@ impl StatsRec {
             init_num_unrooted_cc,
             num_fixes,
             num_unrooted_cc_after_fixes,
+            timezone: ctx.timezone,
             ..Default::default()
         }
     }
 
-    /// Read a StatsRecJson file and turn it into a StatsRec
-    pub fn read_file(path: &OsString) -> Result<Self, Box<dyn Error>> {
+    /// The start of each trace as a local date-time in the timezone of this StatsRec
+    pub fn start_dt(&self) -> Vec<NaiveDateTime> {
+        self.start_dt_micros
+            .iter()
+            .map(|micros| self.timezone.to_local(*micros))
+            .collect()
+    }
+
+    /// The end of each trace as a local date-time in the timezone of this StatsRec
+    pub fn end_dt(&self) -> Vec<NaiveDateTime> {
+        self.end_dt_micros
+            .iter()
+            .map(|micros| self.timezone.to_local(*micros))
+            .collect()
+    }
+
+    /// Read a StatsRecJson file and turn it into a StatsRec. A '.sqlite' file is read as a database written via 'file::write_sqlite'.
+    pub fn read_file(ctx: &AnalysisContext, path: &OsString) -> Result<Self, Box<dyn Error>> {
+        if Path::new(path)
+            .extension()
+            .is_some_and(|ext| ext == "sqlite")
+        {
+            return file::read_sqlite(ctx, path.to_str().ok_or("Invalid file-name")?);
+        }
         let srj = StatsRecJson::read_file(path)?;
-        Ok(srj.into())
+        Ok(Self::from_json(ctx, srj))
     }
 
     /// Calculate the contents of the call-chain-file
@@ -151,7 +181,13 @@ impl StatsRec {
 
     /// extend the statistics of this StatsRec with the spans of a provided trace.
     /// The rooted_spans_only parameter determined the filtering. When set to true only spans that trace back to root are included in the analysis (parameter always false in current code)
-    pub fn extend_statistics(&mut self, trace: &Trace, rooted_spans_only: bool) {
+    /// Invalid span-kinds are added to 'report'.
+    pub fn extend_statistics(
+        &mut self,
+        report: &mut Report,
+        trace: &Trace,
+        rooted_spans_only: bool,
+    ) {
         //println!("Extend statistics for trace: {}", trace.trace_id);
 
         let spans = &trace.spans;
@@ -159,8 +195,8 @@ impl StatsRec {
         self.trace_id.push(trace.trace_id.to_owned());
         self.root_call.push(trace.root_call.to_owned());
         self.num_spans.push(trace.spans.items.len());
-        self.start_dt.push(trace.start_dt);
-        self.end_dt.push(trace.end_dt);
+        self.start_dt_micros.push(trace.start_dt_micros);
+        self.end_dt_micros.push(trace.end_dt_micros);
         self.duration_micros.push(trace.duration_micros);
         self.time_to_respond_micros
             .push(trace.time_to_respond_micros);
@@ -188,19 +224,15 @@ impl StatsRec {
                 let _ = proc_oper_used.insert((proc, &span.operation_name));
                 let proc = proc.to_owned();
 
-                let update_stat = |stat: &mut OperationStats| {
-                    stat.update(idx, span, spans, &self.caching_processes, &trace.root_call);
-                };
-
-                // This is the actual insert or update based on the 'update_stats'.
-                self.stats
-                    .entry(proc)
-                    .and_modify(update_stat)
-                    .or_insert_with(|| {
-                        let mut stat = OperationStats::new();
-                        update_stat(&mut stat);
-                        stat
-                    });
+                // This is the actual insert or update of the statistics of the process.
+                self.stats.entry(proc).or_default().update(
+                    report,
+                    idx,
+                    span,
+                    spans,
+                    &self.caching_processes,
+                    &trace.root_call,
+                );
             });
 
         // Update num_traces such that each process is uniquely counted.
@@ -219,7 +251,8 @@ impl StatsRec {
         });
     }
 
-    pub fn to_csv_string(&self) -> String {
+    /// The key/value lines that summarize the traces
+    pub(crate) fn summary_lines(&self) -> Vec<String> {
         let mut s = Vec::new();
         let num_traces: i64 = self.trace_id.len().try_into().unwrap();
 
@@ -229,8 +262,8 @@ impl StatsRec {
                 s.push(format!("trace_id:; {}", self.trace_id[0]));
                 s.push(format!("root_call:; {}", self.root_call[0]));
                 s.push(format!("num_spans:; {}", self.num_spans[0]));
-                s.push(format!("start_dt; {:?}", self.start_dt[0]));
-                s.push(format!("end_dt:; {:?}", self.end_dt[0]));
+                s.push(format!("start_dt; {:?}", self.start_dt()[0]));
+                s.push(format!("end_dt:; {:?}", self.end_dt()[0]));
                 s.push(format!("duration_micros:; {}", self.duration_micros[0]));
                 s.push(format!(
                     "time_to_respond_micros:; {}",
@@ -275,8 +308,8 @@ impl StatsRec {
                     "num_unrooted_cc_after_fixes:; {}",
                     self.num_unrooted_cc_after_fixes
                 ));
-                s.push(format!("start_dt; {:?}", self.start_dt));
-                s.push(format!("end_dt:; {:?}", self.end_dt));
+                s.push(format!("start_dt; {:?}", self.start_dt()));
+                s.push(format!("end_dt:; {:?}", self.end_dt()));
                 s.push(format!(
                     "MIN(duration_micros):; {:?}",
                     self.duration_micros.iter().min().unwrap()
@@ -308,33 +341,48 @@ impl StatsRec {
                 ));
             }
         }
-        s.push("\n".to_owned());
+        s
+    }
 
+    /// The stats sorted by key
+    fn sorted_stats(&self) -> Vec<(&LeafService, &OperationStats)> {
         let mut data: Vec<_> = self.stats.iter().collect();
         data.sort_by(|a, b| a.0.cmp(b.0));
+        data
+    }
 
-        s.push(OperationStats::report_stats_line_header_str().to_owned());
-        data.iter()
-            .for_each(|(k, stat)| s.push(stat.report_stats_line(k, num_traces as f64)));
-        s.push("\n".to_owned());
+    /// The header and lines of the statistics per process
+    pub(crate) fn process_lines(&self, ctx: &AnalysisContext) -> Vec<String> {
+        let num_traces = self.trace_id.len() as f64;
+        let mut s = vec![OperationStats::report_stats_line_header_str().to_owned()];
+        self.sorted_stats()
+            .iter()
+            .for_each(|(k, stat)| s.push(stat.report_stats_line(ctx, k, num_traces)));
+        s
+    }
 
-        let num_traces = num_traces as f64;
-        s.push(ProcOperStatsValue::report_stats_line_header_str().to_owned());
-        data.iter().for_each(|(k, stat)| {
+    /// The header and lines of the statistics per process/operation
+    pub(crate) fn service_oper_lines(&self, ctx: &AnalysisContext) -> Vec<String> {
+        let num_traces = self.trace_id.len() as f64;
+        let mut s = vec![ProcOperStatsValue::report_stats_line_header_str().to_owned()];
+        self.sorted_stats().iter().for_each(|(k, stat)| {
             stat.operation.0.iter().for_each(|(method, meth_stat)| {
-                let line = meth_stat.report_stats_line(k, method, num_traces, self.num_files);
+                let line = meth_stat.report_stats_line(ctx, k, method, num_traces, self.num_files);
                 s.push(line);
             })
         });
-        s.push("\n".to_owned());
-
-        s.push("#The unique key of the next table is 'Call_Chain' (which includes full path and the leaf-marker). So the Process column contains duplicates".to_owned());
+        s
+    }
 
-        s.push(CChainStatsValue::report_stats_line_header_str().to_owned());
+    /// The header and lines of the statistics per call-chain
+    pub(crate) fn call_chain_lines(&self, ctx: &AnalysisContext) -> Vec<String> {
+        let num_traces = self.trace_id.len() as f64;
+        let mut s = vec![CChainStatsValue::report_stats_line_header_str().to_owned()];
 
         // reorder data based on the full call-chain
         //  key is the ProcessKey and ps_key is the PathStatsKey (a.o. call-chain)
-        let mut ps_data = data
+        let mut ps_data = self
+            .sorted_stats()
             .into_iter()
             .flat_map(|(key, stat)| {
                 stat.call_chain
@@ -345,13 +393,68 @@ impl StatsRec {
             .collect::<Vec<_>>();
         ps_data.sort_by(|a, b| a.0.cmp(b.0));
         ps_data.into_iter().for_each(|(ps_key, key, cchain_stats)| {
-            s.push(cchain_stats.report_stats_line(&key, ps_key, num_traces, self.num_files))
+            s.push(cchain_stats.report_stats_line(ctx, &key, ps_key, num_traces, self.num_files))
         });
+        s
+    }
+
+    /// The header and lines of the error-templates per process/operation
+    pub(crate) fn error_template_lines(&self, ctx: &AnalysisContext) -> Vec<String> {
+        let mut s = vec![ErrorTemplates::report_stats_line_header_str().to_owned()];
+        let mut error_data: Vec<_> = self
+            .stats
+            .iter()
+            .flat_map(|(key, stat)| {
+                stat.operation
+                    .0
+                    .iter()
+                    .filter(|(_, meth_stat)| !meth_stat.error_logs.is_empty())
+                    .map(move |(method, meth_stat)| (format!("{key}/{method}"), meth_stat))
+            })
+            .collect();
+        error_data.sort_by(|a, b| a.0.cmp(&b.0));
+        error_data.into_iter().for_each(|(proc_oper, meth_stat)| {
+            s.extend(
+                meth_stat
+                    .get_error_templates(ctx)
+                    .report_stats_lines(&proc_oper),
+            )
+        });
+        s
+    }
+
+    pub fn to_csv_string(&self, ctx: &AnalysisContext) -> String {
+        let mut s = self.summary_lines();
+        s.push("\n".to_owned());
+        s.extend(self.process_lines(ctx));
+        s.push("\n".to_owned());
+        s.extend(self.service_oper_lines(ctx));
+        s.push("\n".to_owned());
+        s.push("#The unique key of the next table is 'Call_Chain' (which includes full path and the leaf-marker). So the Process column contains duplicates".to_owned());
+        s.extend(self.call_chain_lines(ctx));
+        s.push("\n".to_owned());
+        s.push("#Error-messages grouped in templates (numbers, ids, timestamps and quoted values are masked)".to_owned());
+        s.extend(self.error_template_lines(ctx));
         s.push("\n".to_owned());
 
         s.join("\n")
     }
 
+    /// Write the statistics as an xlsx-workbook with a sheet per table of the csv-output.
+    pub fn write_xlsx(&self, ctx: &AnalysisContext, path: &Path) -> Result<(), Box<dyn Error>> {
+        let ctx = &ctx.without_comma_float();
+        let mut summary = vec!["Key; Value".to_owned()];
+        summary.extend(self.summary_lines());
+        let sheets = vec![
+            XlsxSheet::new("Summary", summary),
+            XlsxSheet::new("Processes", self.process_lines(ctx)),
+            XlsxSheet::new("Service-Oper", self.service_oper_lines(ctx)),
+            XlsxSheet::new("Call-chains", self.call_chain_lines(ctx)),
+            XlsxSheet::new("Error templates", self.error_template_lines(ctx)),
+        ];
+        utils::write_xlsx(path, &sheets)
+    }
+
     /// internal function
     fn call_chain_list(&self) -> Vec<String> {
         self.stats
@@ -409,56 +512,70 @@ impl StatsRec {
             .collect()
     }
 
-    pub fn fix_call_chain(&mut self, cchain_cache: &mut CChainEndPointCache) -> usize {
-        let mut num_fixes = 0;
+    /// Repair the non-rooted call-chains based on the known call-chains in the cchain_cache and return the statistics of the repairs.
+    pub fn fix_call_chain(
+        &mut self,
+        report: &mut Report,
+        cchain_cache: &mut CChainEndPointCache,
+        repair: &CChainRepair,
+    ) -> RepairStats {
+        let mut repair_stats = RepairStats::default();
 
         //        if let Some(expect_cc) = cchain_cache.get_cchain_key(&self.get_endpoint_key()) {
         let new_stats: HashMap<_, _> = mem::take(&mut self.stats)
             .into_iter()
             .map(|(key, mut stats)| {
-                    let (rooted, mut non_rooted): (Vec<_>, Vec<_>) = stats.call_chain.0
+                    let (rooted, non_rooted): (Vec<_>, Vec<_>) = stats.call_chain.0
                         .into_iter()
                         .partition(|(_k2, v2)| v2.rooted);
 
                     if !non_rooted.is_empty() {
                         let depths: Vec<_> = non_rooted.iter().map(|(_k,v)| v.depth).collect();
-                        utils::report(Chapter::Details, format!("For key '{key}'  found {} non-rooted out of {} traces with call-chain depths {depths:?}", non_rooted.len(), non_rooted.len() + rooted.len()));
+                        utils::report(report, Chapter::Details, "non_rooted_chains", format!("For key '{key}'  found {} non-rooted out of {} traces with call-chain depths {depths:?}", non_rooted.len(), non_rooted.len() + rooted.len()));
                     }
 
+                    // the observed counts of the rooted chains are the weights for ambiguous repairs
+                    let observed: ObservedCounts = rooted.iter().fold(HashMap::new(), |mut observed, (k, v)| {
+                        *observed.entry((k.call_chain.clone(), k.is_leaf)).or_insert(0) += v.count;
+                        observed
+                    });
+
                     // fix the non-rooted paths by a rewrite of the key
-                    let mut fix_failed = 0;
-                    non_rooted.iter_mut()
-                        .for_each(|(cck, v)| {
-                            if let Some(expect_cc) = v
+                    let mut key_stats = RepairStats::default();
+                    let num_non_rooted = non_rooted.len();
+                    let non_rooted: Vec<_> = non_rooted.into_iter()
+                        .flat_map(|(cck, mut v)| {
+                            let the_repair = v
                                 .expect_root
                                 .get_frequent_endpoint()
-                                .and_then(|end_point| cchain_cache.get_cchain_key(&CChainEndPointCache::str_to_cache_key(&end_point)))
-                            {
-                                if cck.remap_callchain(expect_cc) {
+                                .and_then(|end_point| cchain_cache.get_cchain_key(report, &CChainEndPointCache::str_to_cache_key(&end_point)))
+                                .and_then(|expect_cc| repair.repair(report, &cck, v.count, expect_cc, &observed));
+                            key_stats.add(the_repair.as_ref(), v.count);
+                            match the_repair {
+                                Some(the_repair) => {
                                     assert!(!v.rooted);  // should be false
-                                    num_fixes += 1;
                                     v.rooted = true;
-                                } else {
-                                    fix_failed += 1;
+                                    let (keys, counts): (Vec<_>, Vec<_>) = the_repair.assignments.into_iter().unzip();
+                                    keys.into_iter().zip(v.split(&counts)).collect::<Vec<_>>()
                                 }
-                            } else {
-                                println!("Failed to find call-chain");
-                                fix_failed += 1;
+                                None => vec![(cck, v)],
                             }
-                    });
-                    if fix_failed > 0 {
-                        utils::report(Chapter::Details, format!("Failed to fix {fix_failed} chains out of {} non-rooted chains. ({num_fixes} fixes applied succesful)", non_rooted.len()));
+                    })
+                    .collect();
+                    if key_stats.failed_chains > 0 {
+                        utils::report_entry(report, ReportEntry::new(Chapter::Details, Severity::Warning, "failed_fixes", format!("Failed to fix {} chains out of {num_non_rooted} non-rooted chains. ({} fixes applied succesful)", key_stats.failed_chains, key_stats.num_fixes())));
                     }
+                    repair_stats.extend(&key_stats);
 
                     let new_call_chain = rooted.into_iter()
                         .chain(non_rooted)
-                        .fold(HashMap::new(), |mut cc, (k, mut v_new)| {
-                            cc.entry(k)
-                                .and_modify(|v_curr: &mut CChainStatsValue| {
-                                    v_curr.count += v_new.count;
-                                    v_curr.duration_micros.append(&mut v_new.duration_micros);
-                                })
-                                .or_insert(v_new);
+                        .fold(HashMap::new(), |mut cc: HashMap<CChainStatsKey, CChainStatsValue>, (k, v_new)| {
+                            match cc.get_mut(&k) {
+                                Some(v_curr) => v_curr.extend(v_new),
+                                None => {
+                                    cc.insert(k, v_new);
+                                }
+                            }
                             cc
                         });
                     stats.call_chain = CChainStats( new_call_chain );
@@ -467,7 +584,7 @@ impl StatsRec {
             .collect();
         self.stats = new_stats;
 
-        num_fixes
+        repair_stats
     }
 }
 
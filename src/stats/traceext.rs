@@ -1,10 +1,10 @@
-use crate::{processed::Trace, stats::stats_rec::StatsRec, utils};
+use crate::{context::AnalysisContext, processed::Trace, stats::stats_rec::StatsRec, utils};
 use std::path::Path;
 
 /// Collect statistics as a string and write it to a textfile in CSV format
-pub fn write_stats_to_csv_file(csv_file: &str, stats: &StatsRec) {
+pub fn write_stats_to_csv_file(ctx: &AnalysisContext, csv_file: &str, stats: &StatsRec) {
     //println!("Now writing the trace statistics to {csv_file}");
-    let stats_csv_str = stats.to_csv_string();
+    let stats_csv_str = stats.to_csv_string(ctx);
     if let Err(err) = utils::write_string_to_file(csv_file, stats_csv_str) {
         panic!("Writing to file '{csv_file}' failed with error: {err:?}");
     };
@@ -1,6 +1,6 @@
-use crate::utils;
+use crate::{context::AnalysisContext, utils};
 
-use super::stitched_line::StitchedLine;
+use super::{change_point::ChangePoint, stitched_line::StitchedLine};
 
 #[derive(Debug)]
 pub struct AnomalyParameters {
@@ -8,6 +8,8 @@ pub struct AnomalyParameters {
     pub st_num_points: usize,       // default 5
     pub scaled_st_slope_bound: f64, // default 0.05
     pub l1_dev_bound: f64,          // default 2.0
+    pub seasonal_period: usize,     // default 0 (no seasonal model)
+    pub change_point_bound: f64,    // default 4.0 (0.0 disables change-point detection)
 }
 
 pub const DEFAULT_ANOMALY_PARS: AnomalyParameters = AnomalyParameters {
@@ -15,12 +17,16 @@ pub const DEFAULT_ANOMALY_PARS: AnomalyParameters = AnomalyParameters {
     st_num_points: 5,
     scaled_st_slope_bound: 0.05,
     l1_dev_bound: 2.0,
+    seasonal_period: 0,
+    change_point_bound: 4.0,
 };
 
 pub struct Anomalies {
     pub scaled_slope: Option<f64>,
     pub st_scaled_slope: Option<f64>,
     pub l1_deviation: Option<f64>,
+    /// the most recent change-point of the line
+    pub change_point: Option<ChangePoint>,
 }
 
 impl Anomalies {
@@ -48,11 +54,18 @@ impl Anomalies {
                 }
             });
 
-            if scaled_slope.is_some() || st_scaled_slope.is_some() || l1_deviation.is_some() {
+            let change_point = line.change_points(pars.change_point_bound).pop();
+
+            if scaled_slope.is_some()
+                || st_scaled_slope.is_some()
+                || l1_deviation.is_some()
+                || change_point.is_some()
+            {
                 Some(Anomalies {
                     scaled_slope,
                     st_scaled_slope,
                     l1_deviation,
+                    change_point,
                 })
             } else {
                 None
@@ -62,15 +75,35 @@ impl Anomalies {
 
     /// header for report_stats_line output in ';'-separated csv-format
     pub fn report_stats_line_header_str() -> &'static str {
-        "Process; Scaled_slope; Short-term scaled_slope; L1-deviation"
+        "Process; Scaled_slope; Short-term scaled_slope; L1-deviation; Change-point column; Change-point label; Level before; Level after; Change-point score"
     }
 
-    pub fn report_stats_line(&self, key: &str, extra_key: &str) -> String {
+    /// The anomalies as a ';'-separated csv-line, where 'labels' are the labels of the columns (used to show where a change-point occurred)
+    pub fn report_stats_line(
+        &self,
+        ctx: &AnalysisContext,
+        key: &str,
+        extra_key: &str,
+        labels: &[String],
+    ) -> String {
         let data = [self.scaled_slope, self.st_scaled_slope, self.l1_deviation].to_vec();
+        let change_point = match &self.change_point {
+            Some(cp) => format!(
+                "{}; {}; {}",
+                cp.idx,
+                labels.get(cp.idx).map(|l| &l[..]).unwrap_or(""),
+                utils::floats_to_string(
+                    ctx,
+                    vec![Some(cp.before), Some(cp.after), Some(cp.score)],
+                    "; "
+                )
+            ),
+            None => "; ; ; ; ".to_owned(),
+        };
 
         format!(
-            "{key}; {}; {}",
-            utils::floats_to_string(data, "; "),
+            "{key}; {}; {change_point}; {}",
+            utils::floats_to_string(ctx, data, "; "),
             extra_key
         )
     }
@@ -0,0 +1,229 @@
+//! A machine-readable report of the anomalies of a stitched dataset, with a severity per anomaly derived from rules per metric and per key-pattern.
+//! The report is intended for automated pipelines, for example to fail a nightly job when serious anomalies appear.
+use super::{anomalies::Anomalies, stitched_line::StitchedLine};
+use crate::Metric;
+use clap::ValueEnum;
+use regex::Regex;
+use serde::Serialize;
+use std::{error::Error, fs::File, path::Path, str::FromStr};
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, ValueEnum, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AnomalySeverity {
+    Low,
+    #[default]
+    Medium,
+    High,
+    Critical,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AnomalyKind {
+    /// the scaled slope over the full period exceeds the bound
+    Slope,
+    /// the scaled slope over the last points exceeds the bound
+    ShortTermSlope,
+    /// the deviation of the last point exceeds the bound
+    L1Deviation,
+    /// a shift of the level of the line
+    ChangePoint,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AnomalyLevel {
+    ProcessOperation,
+    CallChain,
+}
+
+/// A rule that assigns a severity to the anomalies of a metric and/or of the keys matching a pattern.
+#[derive(Debug, Clone)]
+pub struct SeverityRule {
+    pub severity: AnomalySeverity,
+    /// None matches all metrics
+    pub metric: Option<Metric>,
+    /// None matches all keys
+    pub key_pattern: Option<Regex>,
+}
+
+/// Parse a rule of the form '<severity>=<metric>[@<key-regex>]', where '*' matches any metric, for example 'critical=p95-millis@^gateway/' or 'low=*@health'
+impl FromStr for SeverityRule {
+    type Err = Box<dyn Error>;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let Some((severity, selector)) = s.split_once('=') else {
+            Err(format!(
+                "Rule '{s}' should have format '<severity>=<metric>[@<key-regex>]'"
+            ))?
+        };
+        let severity = AnomalySeverity::from_str(severity.trim(), true)?;
+        let (metric, key_pattern) = match selector.split_once('@') {
+            Some((metric, pattern)) => (metric, Some(Regex::new(pattern)?)),
+            None => (selector, None),
+        };
+        let metric = match metric.trim() {
+            "*" | "" => None,
+            metric => Some(Metric::from_str(metric, true)?),
+        };
+        Ok(Self {
+            severity,
+            metric,
+            key_pattern,
+        })
+    }
+}
+
+impl SeverityRule {
+    fn matches(&self, metric: Metric, key: &str) -> bool {
+        self.metric.is_none_or(|m| m == metric)
+            && self
+                .key_pattern
+                .as_ref()
+                .is_none_or(|pattern| pattern.is_match(key))
+    }
+}
+
+/// The severity rules, where the first matching rule determines the severity of an anomaly.
+#[derive(Debug, Clone, Default)]
+pub struct SeverityRules {
+    pub rules: Vec<SeverityRule>,
+    /// the severity of anomalies that do not match any rule
+    pub default: AnomalySeverity,
+}
+
+impl SeverityRules {
+    pub fn severity(&self, metric: Metric, key: &str) -> AnomalySeverity {
+        self.rules
+            .iter()
+            .find(|rule| rule.matches(metric, key))
+            .map_or(self.default, |rule| rule.severity)
+    }
+}
+
+/// A single anomaly of a stitched line
+#[derive(Debug, Clone, Serialize)]
+pub struct AnomalyRecord {
+    pub level: AnomalyLevel,
+    /// the Process/Operation, or the full call-chain
+    pub key: String,
+    /// the Process/Operation in which a call-chain ends
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub process_operation: Option<String>,
+    pub metric: Metric,
+    pub kind: AnomalyKind,
+    /// the value that exceeded the bound (scaled slope, scaled deviation or change-point score)
+    pub score: f64,
+    pub severity: AnomalySeverity,
+    /// the label of the column where the level changed (only for a change-point)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub change_point_label: Option<String>,
+    /// the values of the last columns (null for a missing value)
+    pub recent_values: Vec<Option<f64>>,
+}
+
+/// The anomalies of a stitched dataset, ordered on decreasing severity
+#[derive(Debug, Default, Serialize)]
+pub struct AnomalyReport {
+    pub anomalies: Vec<AnomalyRecord>,
+}
+
+impl AnomalyReport {
+    /// Add a record per kind of anomaly of 'line', where 'labels' are the labels of the columns and 'num_recent' the number of recent values to include.
+    #[allow(clippy::too_many_arguments)]
+    pub(super) fn add_line(
+        &mut self,
+        level: AnomalyLevel,
+        key: &str,
+        process_operation: Option<&str>,
+        line: &StitchedLine,
+        anomalies: Anomalies,
+        rules: &SeverityRules,
+        labels: &[String],
+        num_recent: usize,
+    ) {
+        let severity = rules.severity(line.metric, key);
+        let recent_values = line.data[line.data.len().saturating_sub(num_recent)..].to_vec();
+        let record = |kind, score, change_point_label| AnomalyRecord {
+            level,
+            key: key.to_owned(),
+            process_operation: process_operation.map(|po| po.to_owned()),
+            metric: line.metric,
+            kind,
+            score,
+            severity,
+            change_point_label,
+            recent_values: recent_values.clone(),
+        };
+
+        [
+            (AnomalyKind::Slope, anomalies.scaled_slope),
+            (AnomalyKind::ShortTermSlope, anomalies.st_scaled_slope),
+            (AnomalyKind::L1Deviation, anomalies.l1_deviation),
+        ]
+        .into_iter()
+        .filter_map(|(kind, score)| score.map(|score| record(kind, score, None)))
+        .for_each(|rec| self.anomalies.push(rec));
+        if let Some(cp) = anomalies.change_point {
+            let label = labels.get(cp.idx).cloned();
+            self.anomalies
+                .push(record(AnomalyKind::ChangePoint, cp.score, label));
+        }
+    }
+
+    /// order the anomalies on decreasing severity (the order within a severity is kept)
+    pub(super) fn sort(&mut self) {
+        self.anomalies
+            .sort_by_key(|rec| std::cmp::Reverse(rec.severity));
+    }
+
+    /// The number of anomalies with at least severity 'min_severity'
+    pub fn count_at_least(&self, min_severity: AnomalySeverity) -> usize {
+        self.anomalies
+            .iter()
+            .filter(|rec| rec.severity >= min_severity)
+            .count()
+    }
+
+    pub fn write_json(&self, path: &Path) {
+        let f = File::create(path).expect("Failed to create json anomaly-file");
+        serde_json::to_writer_pretty(f, self).expect("Failed to write json anomalies.");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AnomalySeverity, SeverityRule, SeverityRules};
+    use crate::Metric;
+
+    #[test]
+    fn first_matching_rule_sets_severity() {
+        let rules = SeverityRules {
+            rules: ["critical=p95-millis@^gateway/", "low=*@health", "high=rate"]
+                .iter()
+                .map(|rule| rule.parse::<SeverityRule>().unwrap())
+                .collect(),
+            default: AnomalySeverity::Medium,
+        };
+
+        let severity = |metric, key| rules.severity(metric, key);
+        assert_eq!(
+            severity(Metric::P95Millis, "gateway/GET:/orders"),
+            AnomalySeverity::Critical
+        );
+        assert_eq!(
+            severity(Metric::P95Millis, "orders/getOrder"),
+            AnomalySeverity::Medium
+        );
+        assert_eq!(
+            severity(Metric::Rate, "gateway/GET:/health"),
+            AnomalySeverity::Low
+        );
+        assert_eq!(
+            severity(Metric::Rate, "orders/getOrder"),
+            AnomalySeverity::High
+        );
+        assert!("urgent=rate".parse::<SeverityRule>().is_err());
+        assert!("high".parse::<SeverityRule>().is_err());
+    }
+}
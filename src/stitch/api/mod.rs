@@ -4,3 +4,4 @@ mod stitched_data_set;
 mod utils;
 
 pub use stitched_data_set::StitchedDataSet;
+pub(super) use utils::get_label_list;
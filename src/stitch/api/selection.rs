@@ -3,9 +3,15 @@ use crate::{
     view_api::types::{SelectLabel, Selection},
 };
 
-use super::super::{stitch_list::StitchSources, stitched_set::StitchedSet, Stitched};
+use super::super::{
+    anomalies::{AnomalyParameters, DEFAULT_ANOMALY_PARS},
+    stitch_list::StitchSources,
+    stitched_set::StitchedSet,
+    time_axis::TimeAxis,
+    Stitched,
+};
 use super::utils;
-use std::{iter, sync::Arc};
+use std::{collections::BTreeMap, iter, sync::Arc};
 
 impl SelectLabel {
     pub fn new(idx: i64, label: String) -> Self {
@@ -13,16 +19,24 @@ impl SelectLabel {
             idx,
             label,
             selected: true,
+            tags: BTreeMap::new(),
         }
     }
 }
 
-/// get a numbered and labeled selection with all item selected (the default)
+/// get a numbered and labeled selection with all item selected (the default), including the tags of the columns of a structured stitch-list
 pub fn get_full_selection(data: &Stitched) -> Selection {
     utils::get_label_list(data)
         .into_iter()
         .enumerate()
-        .map(|(idx, label)| SelectLabel::new(idx as i64, label))
+        .map(|(idx, label)| SelectLabel {
+            tags: data
+                .columns
+                .get(idx)
+                .map(|col| col.tags.clone())
+                .unwrap_or_default(),
+            ..SelectLabel::new(idx as i64, label)
+        })
         .collect()
 }
 
@@ -36,13 +50,18 @@ fn get_stitch_sources(original: &Stitched, selection: &Vec<bool>) -> StitchSourc
 }
 
 /// get a copy of the process_operation data for a specific selection
-fn get_proc_oper_selection(original: &Stitched, selection: &[bool]) -> Vec<(String, StitchedSet)> {
+fn get_proc_oper_selection(
+    original: &Stitched,
+    selection: &[bool],
+    time_axis: &TimeAxis,
+    pars: &AnomalyParameters,
+) -> Vec<(String, StitchedSet)> {
     original
         .service_operation
         .iter()
         .filter_map(|(k, stitched_set)| {
             stitched_set
-                .get_selection(selection)
+                .get_selection(selection, time_axis, pars)
                 .map(|selection| (k.to_owned(), selection))
         })
         .collect()
@@ -52,6 +71,8 @@ fn get_proc_oper_selection(original: &Stitched, selection: &[bool]) -> Vec<(Stri
 fn get_call_chain_selection(
     original: &Stitched,
     selection: &[bool],
+    time_axis: &TimeAxis,
+    pars: &AnomalyParameters,
 ) -> Vec<(String, Vec<CallChainData>)> {
     original
         .call_chain
@@ -59,7 +80,7 @@ fn get_call_chain_selection(
         .filter_map(|(k, ccd_vec)| {
             let data: Vec<_> = ccd_vec
                 .iter()
-                .filter_map(|ccd| ccd.get_selection(selection))
+                .filter_map(|ccd| ccd.get_selection(selection, time_axis, pars))
                 .collect();
             if !data.is_empty() {
                 Some((k.to_owned(), data))
@@ -70,17 +91,80 @@ fn get_call_chain_selection(
         .collect()
 }
 
-/// get a derived dataset that only contains the selected columns
+/// get a copy of the cache data for a specific selection
+fn get_cache_selection(
+    original: &Stitched,
+    selection: &[bool],
+    time_axis: &TimeAxis,
+    pars: &AnomalyParameters,
+) -> Vec<(String, StitchedSet)> {
+    original
+        .cache
+        .iter()
+        .filter_map(|(k, stitched_set)| {
+            stitched_set
+                .get_selection(selection, time_axis, pars)
+                .map(|selection| (k.to_owned(), selection))
+        })
+        .collect()
+}
+
+/// The selected columns form a single block, such that the phase of a column within the selection matches its phase in the original dataset
+/// (up to a constant shift).
+fn is_contiguous(selection: &[bool]) -> bool {
+    let first = selection.iter().position(|sel| *sel);
+    let last = selection.iter().rposition(|sel| *sel);
+    match (first, last) {
+        (Some(first), Some(last)) => selection[first..=last].iter().all(|sel| *sel),
+        _ => true,
+    }
+}
+
+/// get a derived dataset that only contains the selected columns.
+/// The seasonal model is only refitted on a contiguous selection, as otherwise the phases of the selected columns are mixed up.
 pub fn get_derived_stitched(original: &Stitched, selection: &Vec<bool>) -> Arc<Stitched> {
-    let process_operation = get_proc_oper_selection(original, selection);
-    let call_chain = get_call_chain_selection(original, selection);
+    let time_axis = original.time_axis().select(selection);
+    let seasonal_period = if is_contiguous(selection) {
+        original.seasonal_period
+    } else {
+        0
+    };
+    let pars = AnomalyParameters {
+        seasonal_period,
+        change_point_bound: original.change_point_bound,
+        ..DEFAULT_ANOMALY_PARS
+    };
+    let process_operation = get_proc_oper_selection(original, selection, &time_axis, &pars);
+    let call_chain = get_call_chain_selection(original, selection, &time_axis, &pars);
+    let cache = get_cache_selection(original, selection, &time_axis, &pars);
     let sources = get_stitch_sources(original, selection);
     let version = original.version;
+    let columns = iter::zip(selection, &original.columns)
+        .filter(|(sel, _col)| **sel)
+        .map(|(_sel, col)| col.clone())
+        .collect();
     Arc::new(Stitched {
         sources,
         version,
         basic: StitchedSet(Vec::new()), // exluded from copy
         service_operation: process_operation,
         call_chain,
+        cache,
+        columns,
+        time_axis: time_axis.to_vec(),
+        seasonal_period,
+        change_point_bound: original.change_point_bound,
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::is_contiguous;
+
+    #[test]
+    fn contiguous_selection() {
+        assert!(is_contiguous(&[false, true, true, false]));
+        assert!(is_contiguous(&[false, false]));
+        assert!(!is_contiguous(&[true, false, true]));
+    }
+}
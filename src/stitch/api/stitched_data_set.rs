@@ -4,12 +4,16 @@ use super::{
     utils,
 };
 use crate::{
+    context::AnalysisContext,
     mermaid,
-    view_api::types::{ChartDataParameters, ProcessList, Selection, Table},
+    view_api::{
+        types::{ChartDataParameters, ProcessList, Selection, Table},
+        HtmlParameters, HtmlReport,
+    },
     MermaidScope, Metric, TraceScope, ViewError, Viewer,
 };
 use log::{error, info};
-use std::{path::Path, sync::Arc};
+use std::{collections::HashMap, path::Path, sync::Arc};
 
 pub struct StitchedDataSet {
     /// current dataset used for most of the operations
@@ -40,10 +44,62 @@ impl StitchedDataSet {
             .map(|label_item| label_item.label.to_owned())
             .collect()
     }
+
+    /// Sections of the csv-output (sheet-name) that are included as tables in the html-report, and their titles
+    const HTML_TABLES: [(&'static str, &'static str); 6] = [
+        ("Summary", "Averages per Service/Operation"),
+        ("Slopes", "Slopes per Service/Operation"),
+        ("Scaled slopes", "Scaled slopes per Service/Operation"),
+        ("Last deviation", "Last deviation per Service/Operation"),
+        ("Basic", "Basic statistics per input file"),
+        ("Call-chain summary", "Averages per Call-chain"),
+    ];
+
+    /// Write a self-contained html-report with the stitched sources, the summary tables, the diagrams and the time-series charts of the busiest service/operations.
+    pub fn write_html(&self, ctx: &AnalysisContext, path: &Path, pars: &HtmlParameters) {
+        let summary = self
+            .current
+            .sources
+            .0
+            .iter()
+            .map(|src| {
+                let column = src.column.map(|col| col.to_string()).unwrap_or_default();
+                // the tags of a structured stitch-list are shown behind the description
+                let tags = src
+                    .column
+                    .and_then(|col| self.current.columns.get(col as usize))
+                    .map(|col| col.tags_str())
+                    .unwrap_or_default();
+                if tags.is_empty() {
+                    (column, src.description.to_owned())
+                } else {
+                    (column, format!("{} [{tags}]", src.description))
+                }
+            })
+            .collect();
+        let tables = self
+            .current
+            .csv_buffer(&ctx.without_comma_float())
+            .into_sections()
+            .into_iter()
+            .filter_map(|(sheet, lines)| {
+                Self::HTML_TABLES
+                    .iter()
+                    .find(|(name, _)| *name == sheet)
+                    .map(|(_, title)| (title.to_string(), lines))
+            })
+            .collect();
+        let report = HtmlReport {
+            title: format!("Stitched data: {}", path.display()),
+            summary,
+            tables,
+        };
+        report.write_file(ctx, self, path, pars);
+    }
 }
 
 impl Viewer for StitchedDataSet {
-    fn from_file(file_name: &str) -> Result<Box<Self>, ViewError> {
+    fn from_file(_ctx: &AnalysisContext, file_name: &str) -> Result<Box<Self>, ViewError> {
         if Path::new(file_name).exists() {
             info!("Trying to load the file {file_name}");
 
@@ -106,47 +162,7 @@ impl Viewer for StitchedDataSet {
         scope: MermaidScope,
         compact: bool,
     ) -> String {
-        let trace_tree = self
-            .current
-            .call_chain
-            .iter()
-            .map(|(k, ccd)| {
-                let trace_data = ccd
-                    .iter()
-                    .map(|ccd| {
-                        let count: u64 = ccd
-                            .data
-                            .0
-                            .first()
-                            .and_then(|data| data.data_avg)
-                            .unwrap()
-                            .round() as u64;
-                        let avg_duration_millis = ccd
-                            .data
-                            .0
-                            .iter()
-                            .find(|x| x.metric == Metric::AvgDurationMillis)
-                            .and_then(|data| data.data_avg)
-                            .expect("avg-duration missing");
-                        mermaid::TraceData::new(
-                            &ccd.full_key,
-                            ccd.rooted,
-                            ccd.is_leaf,
-                            count,
-                            //TODO: some more parameters need to be passed.
-                            None,
-                            avg_duration_millis,
-                            None,
-                            None,
-                            None,
-                            None,
-                        )
-                    })
-                    .collect();
-                (k.clone(), trace_data)
-            })
-            .collect();
-        mermaid::TracePaths(trace_tree).get_diagram(
+        trace_paths(&self.current).get_diagram(
             service_oper,
             call_chain_key,
             edge_value,
@@ -200,3 +216,135 @@ impl Viewer for StitchedDataSet {
         }
     }
 }
+
+/// The trace-paths of the call-chains of a stitched dataset, grouped per (leaf) service
+fn trace_paths(stitched: &Stitched) -> mermaid::TracePaths {
+    // The TracePaths are grouped per (leaf) service, while the call-chains are stored per service/operation
+    let mut trace_tree: HashMap<String, Vec<_>> = HashMap::new();
+    stitched
+        .call_chain
+        .iter()
+        .map(|(k, ccd)| {
+            let trace_data = ccd
+                .iter()
+                .map(|ccd| {
+                    let count: u64 = ccd
+                        .data
+                        .0
+                        .first()
+                        .and_then(|data| data.data_avg)
+                        .unwrap()
+                        .round() as u64;
+                    let avg_duration_millis = ccd
+                        .data
+                        .0
+                        .iter()
+                        .find(|x| x.metric == Metric::AvgDurationMillis)
+                        .and_then(|data| data.data_avg)
+                        .expect("avg-duration missing");
+                    mermaid::TraceData::new(
+                        &ccd.full_key,
+                        ccd.rooted,
+                        ccd.is_leaf,
+                        count,
+                        //TODO: some more parameters need to be passed.
+                        None,
+                        avg_duration_millis,
+                        None,
+                        None,
+                        None,
+                        None,
+                    )
+                })
+                .collect::<Vec<_>>();
+            (mermaid::split_service(k), trace_data)
+        })
+        .for_each(|(service, trace_data)| {
+            trace_tree
+                .entry(service.to_owned())
+                .or_default()
+                .extend(trace_data)
+        });
+    mermaid::TracePaths(trace_tree)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::trace_paths;
+    use crate::{
+        stats::call_chain::{CChainStatsKey, Call, CallDirection},
+        stitch::{
+            anomalies::DEFAULT_ANOMALY_PARS, call_chain_data::CallChainData, time_axis::TimeAxis,
+            StitchedLine, StitchedSet,
+        },
+        MermaidScope, Metric, Stitched,
+    };
+
+    fn call_chain_data(operation: &str, count: f64) -> CallChainData {
+        let cck = CChainStatsKey {
+            call_chain: vec![
+                Call {
+                    service: "gateway".to_owned(),
+                    operation: "GET".to_owned(),
+                    call_direction: CallDirection::Inbound,
+                },
+                Call {
+                    service: "orders".to_owned(),
+                    operation: operation.to_owned(),
+                    call_direction: CallDirection::Inbound,
+                },
+            ],
+            caching_process: String::new(),
+            is_leaf: true,
+        };
+        let line = |metric, value| {
+            StitchedLine::compute_new(
+                metric,
+                vec![Some(value); 3],
+                &DEFAULT_ANOMALY_PARS,
+                &TimeAxis::default(),
+            )
+        };
+        CallChainData {
+            full_key: cck.call_chain_key(),
+            inbound_process_key: cck.inbound_call_chain_key(),
+            rooted: true,
+            is_leaf: true,
+            data: StitchedSet(vec![
+                line(Metric::Count, count),
+                line(Metric::AvgDurationMillis, 10.0),
+            ]),
+        }
+    }
+
+    #[test]
+    fn trace_paths_are_grouped_per_service() {
+        let stitched = Stitched {
+            call_chain: vec![
+                (
+                    "orders/getOrder".to_owned(),
+                    vec![call_chain_data("getOrder", 4.0)],
+                ),
+                (
+                    "orders/listOrders".to_owned(),
+                    vec![call_chain_data("listOrders", 2.0)],
+                ),
+            ],
+            ..Default::default()
+        };
+
+        let paths = trace_paths(&stitched);
+
+        assert_eq!(paths.0.keys().collect::<Vec<_>>(), ["orders"]);
+        assert_eq!(paths.0["orders"].len(), 2);
+        // the diagram of a service/operation is found via its service
+        let diagram = paths.get_diagram(
+            "orders/getOrder",
+            None,
+            Metric::Count,
+            MermaidScope::Full,
+            false,
+        );
+        assert!(diagram.contains("getOrder"), "{diagram}");
+    }
+}
@@ -1,15 +1,18 @@
-use super::inbound_prefix_idx::InboundPrefixIdx;
+use super::{super::forecast::Projection, inbound_prefix_idx::InboundPrefixIdx};
 use crate::{
     view_api::{
         reorder_and_renumber,
-        types::{ChartDataParameters, ChartLine, ProcessList, ProcessListItem, Table},
+        types::{ChartDataParameters, ChartLine, ChartMarker, ProcessList, ProcessListItem, Table},
     },
     BestFit, Metric, Stitched, StitchedLine, StitchedSet, TraceScope,
 };
 use log::error;
 use regex::{self, Regex};
+use std::iter;
 
 const DEFAULT_RANK: f64 = -1.0; // indicates growth not defined
+/// The number of columns the best-fit model is projected beyond the last column in the charts
+const FORECAST_HORIZON: usize = 7;
 
 /// Map a numberal string like for example "02" to the string "Febr".
 fn get_month_description(month: &str) -> &str {
@@ -30,9 +33,12 @@ fn get_month_description(month: &str) -> &str {
     }
 }
 
-/// find the list of labels for the graphs by extracting them from the source-list descriptions.
+/// find the list of labels for the graphs. The labels of a structured stitch-list are used when available, otherwise they are extracted from the source-list descriptions.
 /// The description is assume to contain a sub-string yyyymmdd, so for example "20231008". For this string the output Sept 8 is produced.
 pub fn get_label_list(data: &Stitched) -> Vec<String> {
+    if !data.columns.is_empty() {
+        return data.columns.iter().map(|col| col.label.clone()).collect();
+    }
     // TODO: we could guard against multiple matches being present
     let re = Regex::new(r"(\d{4})(\d{2})(\d{2})").expect("Failed to create regexp for dates");
 
@@ -237,7 +243,14 @@ pub fn get_call_chain_list(
 }
 
 impl ChartDataParameters {
-    pub fn new(process: &str, metric: &str, labels: Vec<String>, st_line: &StitchedLine) -> Self {
+    /// The chart of 'st_line', where the change-points with a score of at least 'change_point_bound' are shown as markers
+    pub fn new(
+        process: &str,
+        metric: &str,
+        labels: Vec<String>,
+        st_line: &StitchedLine,
+        change_point_bound: f64,
+    ) -> Self {
         let mut lines = Vec::new();
         lines.push(ChartLine {
             label: "Observed".to_string(),
@@ -250,7 +263,7 @@ impl ChartDataParameters {
                     .data
                     .iter()
                     .enumerate()
-                    .map(|(idx, _)| Some(lin_regr.predict(idx as f64)))
+                    .map(|(idx, _)| Some(lin_regr.predict(st_line.x(idx))))
                     .collect(),
             });
         };
@@ -261,10 +274,36 @@ impl ChartDataParameters {
                     .data
                     .iter()
                     .enumerate()
-                    .map(|(idx, _)| Some(exp_regr.predict(idx as f64)))
+                    .map(|(idx, _)| Some(exp_regr.predict(st_line.x(idx))))
+                    .collect(),
+            });
+        };
+        if let Some(seasonal) = &st_line.seasonal {
+            lines.push(ChartLine {
+                label: format!("Seasonal model (period {})", seasonal.period),
+                data: (0..st_line.data.len())
+                    .map(|idx| Some(seasonal.predict(idx, st_line.x(idx))))
+                    .collect(),
+            });
+            lines.push(ChartLine {
+                label: "Seasonal component".to_string(),
+                data: (0..st_line.data.len())
+                    .map(|idx| Some(seasonal.component(idx)))
                     .collect(),
             });
         };
+        let markers: Vec<_> = st_line
+            .change_points(change_point_bound)
+            .into_iter()
+            .map(|cp| ChartMarker {
+                index: cp.idx,
+                label: labels.get(cp.idx).cloned().unwrap_or_default(),
+                description: format!(
+                    "Change-point: {:.2} -> {:.2} (score {:.1})",
+                    cp.before, cp.after, cp.score
+                ),
+            })
+            .collect();
         let description = {
             let growth = st_line.periodic_growth().map(|v| v * 100.0);
             if growth.is_none() {
@@ -297,10 +336,17 @@ impl ChartDataParameters {
                 ),
                 BestFit::None => "None".to_string(),
             };
-            vec![
+            let mut description = vec![
                 ("BestFit".to_owned(), best_fit),
                 //TODO: more items can be added. Would be nice to state if this is inbound or outbound
-            ]
+            ];
+            markers.iter().for_each(|marker| {
+                description.push((
+                    format!("Change-point at {}", marker.label),
+                    marker.description.clone(),
+                ))
+            });
+            description
         };
         ChartDataParameters {
             title: format!("{}  of {}", metric, process),
@@ -309,8 +355,53 @@ impl ChartDataParameters {
             description,
             labels,
             lines,
+            markers,
         }
     }
+
+    /// Extend the chart with a projection of the best-fit model (and its 95% confidence band) over 'FORECAST_HORIZON' columns.
+    /// The future columns have the average column-distance of the observed columns.
+    pub fn add_forecast(&mut self, st_line: &StitchedLine) {
+        let Some(projection) = Projection::new(st_line) else {
+            return;
+        };
+        let len = st_line.data.len();
+        let step = if len > 1 {
+            (projection.last_x - st_line.x(0)) / (len - 1) as f64
+        } else {
+            1.0
+        };
+        let future_x: Vec<_> = (1..=FORECAST_HORIZON)
+            .map(|i| projection.last_x + i as f64 * step)
+            .collect();
+
+        self.lines
+            .iter_mut()
+            .for_each(|line| line.data.resize(len + FORECAST_HORIZON, None));
+        self.labels.resize(len, String::new());
+        self.labels
+            .extend((1..=FORECAST_HORIZON).map(|i| format!("forecast +{i}")));
+        // the forecast-lines start at the last column, such that they connect to the observed data
+        let forecast_line = |label: &str, f: &dyn Fn(f64) -> f64| ChartLine {
+            label: label.to_owned(),
+            data: (0..len - 1)
+                .map(|_| None)
+                .chain(
+                    iter::once(projection.last_x)
+                        .chain(future_x.iter().copied())
+                        .map(|x| Some(f(x))),
+                )
+                .collect(),
+        };
+        self.lines
+            .push(forecast_line("Forecast", &|x| projection.predict(x)));
+        self.lines.push(forecast_line("Forecast upper (95%)", &|x| {
+            projection.band(x).1
+        }));
+        self.lines.push(forecast_line("Forecast lower (95%)", &|x| {
+            projection.band(x).0
+        }));
+    }
 }
 
 /// the the chart-data for a specific Service-operation combination
@@ -325,9 +416,17 @@ pub fn get_service_oper_chart_data(
         .iter()
         .find(|(proc, _)| proc == full_service_oper_key)
     {
-        Some((proc, st_set)) => st_set
-            .get_metric_stitched_line(metric)
-            .map(|sl| ChartDataParameters::new(proc, metric.to_str(), labels, sl)),
+        Some((proc, st_set)) => st_set.get_metric_stitched_line(metric).map(|sl| {
+            let mut chart_data = ChartDataParameters::new(
+                proc,
+                metric.to_str(),
+                labels,
+                sl,
+                data.change_point_bound,
+            );
+            chart_data.add_forecast(sl);
+            chart_data
+        }),
         None => {
             error!("Could not find process '{full_service_oper_key}'");
             None
@@ -394,10 +493,15 @@ pub fn get_call_chain_chart_data(
             if n > 1 {
                 error!("Observed {n} matches for key {call_chain_key}. Returning first match only");
             };
-            proc[0]
-                .data
-                .get_metric_stitched_line(metric)
-                .map(|sl| ChartDataParameters::new(call_chain_key, metric.to_str(), labels, sl))
+            proc[0].data.get_metric_stitched_line(metric).map(|sl| {
+                ChartDataParameters::new(
+                    call_chain_key,
+                    metric.to_str(),
+                    labels,
+                    sl,
+                    data.change_point_bound,
+                )
+            })
         }
     }
 }
@@ -0,0 +1,25 @@
+use crate::{stats::CacheStatsValue, Metric};
+
+type Processor = fn(&CacheStatsValue) -> Option<f64>;
+
+/// Cache hit-ratio report items
+pub struct CSReportItem {
+    pub metric: Metric,
+    processor: Processor,
+}
+
+/// this container of ReportItems is primarily used to bundle the methods that runs over a set of CacheStats.
+pub struct CSReportItems(pub Vec<CSReportItem>);
+
+impl CSReportItem {
+    pub fn new(metric: Metric, processor: Processor) -> Self {
+        Self { metric, processor }
+    }
+}
+
+impl CSReportItems {
+    /// extract the value of each of the report items for a cache-key of a single analysis
+    pub fn extract_values(&self, csv: &CacheStatsValue) -> Vec<Option<f64>> {
+        self.0.iter().map(|item| (item.processor)(csv)).collect()
+    }
+}
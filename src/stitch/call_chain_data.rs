@@ -1,4 +1,4 @@
-use super::StitchedSet;
+use super::{anomalies::AnomalyParameters, time_axis::TimeAxis, StitchedSet};
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -43,9 +43,14 @@ impl CallChainData {
 
     /// Get a subset of selected data-points for each of the stiched lines in the stitched set, or None if the selection does not contain any f64 values (only None)
     /// assume that the size of the selection was checked by the upstream process (the caller).
-    pub fn get_selection(&self, selection: &[bool]) -> Option<Self> {
+    pub fn get_selection(
+        &self,
+        selection: &[bool],
+        time_axis: &TimeAxis,
+        pars: &AnomalyParameters,
+    ) -> Option<Self> {
         self.data
-            .get_selection(selection)
+            .get_selection(selection, time_axis, pars)
             .map(|data| CallChainData {
                 full_key: self.full_key.to_owned(),
                 inbound_process_key: self.inbound_process_key.to_owned(),
@@ -1,19 +1,16 @@
-use super::stitched_line::StitchedLine;
 use crate::{
-    stats::{
-        call_chain::{CChainStatsKey, CChainStatsValue},
-        StatsRec,
-    },
-    AnomalyParameters, Metric,
+    stats::call_chain::{CChainStatsKey, CChainStatsValue},
+    Metric,
 };
 
 use std::{cmp::Ordering, collections::HashMap};
 
-/// The POData is the input for the processor (which is a series of report-closures.
-/// If the processor operated on a tuple we could extract a joined type from the next two types.
-type ProcessorInput<'a> = (&'a CChainStatsValue, i32, usize);
+/// The input for the processor (which is a series of report-closures) consists of:
+///    1. the complete CallChainValue record
+///    2. the number of files in the analysis
+///    3. the number of traces included in this analysis
+pub type ProcessorInput<'a> = (&'a CChainStatsValue, i32, usize);
 type Processor = fn(&ProcessorInput) -> Option<f64>;
-type CCData<'a> = Vec<Option<ProcessorInput<'a>>>;
 
 /// Call-chain report items are defined in this structure.
 /// TODO: as this is a copy of the POReportItem, including all code we should move this to generics
@@ -31,18 +28,9 @@ impl CCReportItem {
     pub fn new(metric: Metric, processor: Processor) -> Self {
         Self { metric, processor }
     }
-
-    pub fn extract_stitched_line(&self, data: &CCData, pars: &AnomalyParameters) -> StitchedLine {
-        let values = data
-            .iter()
-            .map(|ms| ms.as_ref().and_then(self.processor))
-            .collect::<Vec<_>>();
-
-        StitchedLine::compute_new(self.metric, values, pars)
-    }
 }
 
-type CCKey = (String, Vec<(CChainStatsKey, bool)>);
+pub type CCKey = (String, Vec<(CChainStatsKey, bool)>);
 
 impl CCReportItems {
     /// repartition the keys by grouping on the string value (proc_oper) and then on the usize decending
@@ -72,26 +60,13 @@ impl CCReportItems {
             .0
     }
 
-    /// get all the keys that are relevant for a CC-report
-    /// TODO: when using generics we could have one-codebase for POReportItems and CCReportItems
-    /// Here it is only the inner loop that generates a key that needs to be split out.
-    pub fn get_keys(data: &[Option<StatsRec>]) -> Vec<CCKey> {
-        let mut keys = HashMap::new(); // Computing all possible keys over the different datasets that need to be stitched.
-        data.iter().for_each(|stats_rec| {
-            if let Some(stats_rec) = stats_rec {
-                stats_rec.stats.iter().for_each(|(_proc_key, st)| {
-                    st.call_chain.0.iter().for_each(|(cc_key, cc_val)| {
-                        let cc_key_clone = cc_key.clone();
-                        keys.entry(cc_key_clone)
-                            .and_modify(|(cnt, rooted)| {
-                                *cnt += cc_val.count;
-                                *rooted &= cc_val.rooted;
-                            })
-                            .or_insert((cc_val.count, cc_val.rooted));
-                    })
-                })
-            }
-        });
+    /// extract the value of each of the report items for a call-chain of a single analysis
+    pub fn extract_values(&self, input: &ProcessorInput) -> Vec<Option<f64>> {
+        self.0.iter().map(|item| (item.processor)(input)).collect()
+    }
+
+    /// group the keys of all analyses, with their total count and whether they are rooted in all analyses, per Process/Operation
+    pub fn group_keys(keys: HashMap<CChainStatsKey, (usize, bool)>) -> Vec<CCKey> {
         let keys: Vec<_> = keys
             .into_iter()
             .map(|(cck, count_root)| (cck.get_leaf(), cck, count_root.0, count_root.1))
@@ -100,29 +75,4 @@ impl CCReportItems {
         // make grouping based on the Process_operation field (get_leaf)
         Self::repartition_keys(keys)
     }
-
-    pub fn extract_dataset<'a>(
-        data: &'a [Option<StatsRec>],
-        cc_key: &'a CChainStatsKey,
-    ) -> CCData<'a> {
-        // We only need to search in the subset that belongs to this process as that sub-set will contain all records for this call-chain.
-        let process = cc_key.get_leaf_process();
-
-        // a ref to the extract the three values that are needed for the analysis being:
-        //    1. the complete CallChainValue record
-        //    2. the number of files in the analysis
-        //    3. the number of traces included in this analysis
-        data.iter()
-            .map(|stats_rec| {
-                stats_rec.as_ref().and_then(|stats_rec| {
-                    stats_rec.stats.get(&process).and_then(|st| {
-                        st.call_chain
-                            .0
-                            .get(cc_key)
-                            .map(|oper| (oper, stats_rec.num_files, stats_rec.trace_id.len()))
-                    })
-                })
-            })
-            .collect()
-    }
 }
@@ -0,0 +1,131 @@
+//! Change-point detection on a stitched time-series. A regression after a deployment shows up as a shift of the level, which is diluted by a
+//! linear regression over the full window. Binary segmentation splits the series at the most significant shift of the level relative to the
+//! (local) linear trend and repeats this on both parts. The detection is applied to the residuals of the regression, so a steady trend is not a shift.
+use serde::Serialize;
+
+/// The minimal number of filled values on each side of a change-point
+pub const MIN_SEGMENT_LEN: usize = 3;
+
+/// The noise is assumed to be at least this fraction of the level of the series, such that a perfect step gets a finite score
+/// and the rounding errors of the residuals of a perfect trend are not detected as shifts.
+pub const MIN_RELATIVE_NOISE: f64 = 1e-3;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ChangePoint {
+    /// the index of the first column of the new level
+    pub idx: usize,
+    /// the level at the change-point according to the local trend before the shift
+    pub before: f64,
+    /// the level at the change-point after the shift
+    pub after: f64,
+    /// the shift relative to the local trend divided by its standard error (a t-statistic)
+    pub score: f64,
+}
+
+/// Detect all change-points in 'data' with a score of at least 'bound', ordered on column-index.
+/// The 'level' is the typical level of the original series, which determines the minimal noise.
+pub fn detect_change_points(data: &[Option<f64>], bound: f64, level: f64) -> Vec<ChangePoint> {
+    let points: Vec<_> = data
+        .iter()
+        .enumerate()
+        .filter_map(|(idx, y)| y.map(|y| (idx, y)))
+        .collect();
+    let min_noise = (MIN_RELATIVE_NOISE * level.abs()).max(1e-100);
+    let mut change_points = Vec::new();
+    segment(&points, bound, min_noise, &mut change_points);
+    change_points.sort_by_key(|cp| cp.idx);
+    change_points
+}
+
+/// Find the most significant split of 'points' and, if it exceeds 'bound', recurse on both parts.
+fn segment(
+    points: &[(usize, f64)],
+    bound: f64,
+    min_noise: f64,
+    change_points: &mut Vec<ChangePoint>,
+) {
+    if points.len() < 2 * MIN_SEGMENT_LEN {
+        return;
+    }
+    let best = (MIN_SEGMENT_LEN..=points.len() - MIN_SEGMENT_LEN)
+        .map(|split| (split, split_score(points, split, min_noise)))
+        .max_by(|a, b| a.1.score.total_cmp(&b.1.score));
+    if let Some((split, cp)) = best {
+        if cp.score >= bound {
+            change_points.push(cp);
+            segment(&points[..split], bound, min_noise, change_points);
+            segment(&points[split..], bound, min_noise, change_points);
+        }
+    }
+}
+
+/// The change-point when 'points' is split in 'points[..split]' and 'points[split..]'.
+/// The shift is the coefficient of the step in a least-squares fit of 'y = a + b * idx + shift * step', such that a remaining trend within
+/// 'points' is not mistaken for a shift.
+fn split_score(points: &[(usize, f64)], split: usize, min_noise: f64) -> ChangePoint {
+    let right = &points[split..];
+    let n = points.len() as f64;
+
+    // the centered sums of squares and cross-products of x (the index), s (the step) and y
+    let step = |pos: usize| if pos < split { 0.0 } else { 1.0 };
+    let (avg_x, avg_s, avg_y) = (
+        points.iter().map(|(x, _)| *x as f64).sum::<f64>() / n,
+        right.len() as f64 / n,
+        points.iter().map(|(_, y)| y).sum::<f64>() / n,
+    );
+    let (mut sxx, mut sxs, mut sss, mut sxy, mut ssy, mut syy) = (0.0, 0.0, 0.0, 0.0, 0.0, 0.0);
+    points.iter().enumerate().for_each(|(pos, (x, y))| {
+        let (x, s, y) = (*x as f64 - avg_x, step(pos) - avg_s, y - avg_y);
+        sxx += x * x;
+        sxs += x * s;
+        sss += s * s;
+        sxy += x * y;
+        ssy += s * y;
+        syy += y * y;
+    });
+    let det = (sxx * sss - sxs * sxs).max(1e-100);
+    let slope = (sss * sxy - sxs * ssy) / det;
+    let shift = (sxx * ssy - sxs * sxy) / det;
+    let rss = (syy - slope * sxy - shift * ssy).max(0.0);
+    let noise = (rss / (n - 3.0)).sqrt().max(min_noise);
+    let score = shift.abs() / (noise * (sxx / det).sqrt());
+    // the fitted value at the first column of the new level, without and with the shift
+    let idx = right[0].0;
+    let before = avg_y + slope * (idx as f64 - avg_x) - shift * avg_s;
+    ChangePoint {
+        idx,
+        before,
+        after: before + shift,
+        score,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::detect_change_points;
+
+    #[test]
+    fn level_shift_is_detected() {
+        let noise = [
+            0.3, -0.2, 0.1, -0.4, 0.2, 0.0, -0.1, 0.3, -0.3, 0.1, 0.2, -0.2,
+        ];
+        let data: Vec<_> = noise
+            .iter()
+            .enumerate()
+            .map(|(idx, n)| {
+                let level = if idx < 7 { 10.0 } else { 13.0 };
+                (idx != 3).then_some(level + n)
+            })
+            .collect();
+
+        let cps = detect_change_points(&data, 4.0, 10.0);
+
+        assert_eq!(cps.len(), 1, "{cps:?}");
+        assert_eq!(cps[0].idx, 7);
+        assert!((cps[0].before - 10.0).abs() < 0.2);
+        assert!((cps[0].after - 13.0).abs() < 0.2);
+        // a series without a shift has no change-points
+        let flat: Vec<_> = noise.iter().map(|n| Some(10.0 + n)).collect();
+        assert!(detect_change_points(&flat, 4.0, 10.0).is_empty());
+    }
+}
@@ -0,0 +1,151 @@
+//! The values of a single analysis (column) reduced to the values of the report items per key. Only these values are kept in memory during stitching,
+//! such that the full StatsRec of an analysis can be dropped before the next analysis is read.
+use super::{
+    dataseries::process_counts,
+    key::Key,
+    stitch_tables::{
+        BASIC_REPORT_ITEMS, CACHE_REPORT_ITEMS, CALL_CHAIN_REPORT_ITEMS, PROC_OPER_REPORT_ITEMS,
+    },
+    stitched_line::StitchedLine,
+    stitched_set::StitchedSet,
+    time_axis::TimeAxis,
+};
+use crate::{
+    stats::{call_chain::CChainStatsKey, CacheStats, StatsRec},
+    AnomalyParameters, Metric,
+};
+use std::collections::{HashMap, HashSet};
+
+/// The values of a call-chain in a single analysis.
+pub struct CallChainValues {
+    /// the count over all processes
+    pub count: usize,
+    /// the call-chain is rooted in all processes
+    pub rooted: bool,
+    /// the values of the report items, taken from the leaf-process of the call-chain
+    pub values: Vec<Option<f64>>,
+}
+
+pub struct ColumnValues {
+    /// the call-volume per process, used to determine the low-volume processes over all columns
+    pub process_counts: Vec<(String, usize)>,
+    pub basic: Vec<Option<f64>>,
+    pub service_operation: HashMap<Key, Vec<Option<f64>>>,
+    pub call_chain: HashMap<CChainStatsKey, CallChainValues>,
+    pub cache: HashMap<String, Vec<Option<f64>>>,
+    /// the cache statistics from which 'cache' is derived, kept such that 'cache' can be recomputed when processes are dropped
+    cache_stats: CacheStats,
+}
+
+impl ColumnValues {
+    pub fn new(stats_rec: &StatsRec) -> Self {
+        let num_files = stats_rec.num_files;
+        let num_traces = stats_rec.trace_id.len();
+
+        let basic = BASIC_REPORT_ITEMS
+            .iter()
+            .map(|sr| sr.extract_value(stats_rec))
+            .collect();
+
+        let mut service_operation = HashMap::new();
+        let mut call_chain = HashMap::<CChainStatsKey, CallChainValues>::new();
+        stats_rec.stats.iter().for_each(|(proc_key, st)| {
+            st.operation.0.iter().for_each(|(oper_key, oper)| {
+                let key = Key {
+                    process: proc_key.to_owned(),
+                    operation: oper_key.to_owned(),
+                };
+                let values = PROC_OPER_REPORT_ITEMS.extract_values(&(oper, num_files, num_traces));
+                service_operation.insert(key, values);
+            });
+            st.call_chain.0.iter().for_each(|(cc_key, cc_val)| {
+                // only the leaf-process holds the values of a call-chain, the other processes only contribute to the count
+                let values = (cc_key.get_leaf_process() == *proc_key).then(|| {
+                    CALL_CHAIN_REPORT_ITEMS.extract_values(&(cc_val, num_files, num_traces))
+                });
+                let ccv = call_chain
+                    .entry(cc_key.clone())
+                    .and_modify(|ccv| {
+                        ccv.count += cc_val.count;
+                        ccv.rooted &= cc_val.rooted;
+                    })
+                    .or_insert_with(|| CallChainValues {
+                        count: cc_val.count,
+                        rooted: cc_val.rooted,
+                        values: Vec::new(),
+                    });
+                if let Some(values) = values {
+                    ccv.values = values;
+                }
+            });
+        });
+
+        let cache_stats = CacheStats::new(stats_rec);
+
+        Self {
+            process_counts: process_counts(stats_rec),
+            basic,
+            service_operation,
+            call_chain,
+            cache: cache_values(&cache_stats),
+            cache_stats,
+        }
+    }
+
+    /// Drop the values of the 'dropped' processes, as if these processes were not part of the analysis, and return the number of dropped processes.
+    /// A call-chain is recorded by its leaf-process, so the call-chains that end in a dropped process are dropped.
+    pub fn drop_processes(&mut self, dropped: &HashSet<String>) -> usize {
+        let num_dropped = self
+            .process_counts
+            .iter()
+            .filter(|(proc, _count)| dropped.contains(proc))
+            .count();
+        if num_dropped == 0 {
+            return 0;
+        }
+        self.process_counts
+            .retain(|(proc, _count)| !dropped.contains(proc));
+        self.service_operation
+            .retain(|key, _values| !dropped.contains(&key.process));
+        self.call_chain
+            .retain(|cc_key, _ccv| !dropped.contains(&cc_key.get_leaf_process()));
+        self.cache_stats.drop_processes(dropped);
+        self.cache = cache_values(&self.cache_stats);
+        num_dropped
+    }
+}
+
+/// The values of the cache report items per call-chain prefix
+fn cache_values(cache_stats: &CacheStats) -> HashMap<String, Vec<Option<f64>>> {
+    cache_stats
+        .0
+        .iter()
+        .map(|(key, csv)| (key.to_owned(), CACHE_REPORT_ITEMS.extract_values(csv)))
+        .collect()
+}
+
+/// Stitch the values of a key over all columns into a set with a line per metric, where 'get' selects the values of the key in a column.
+pub fn stitch_set<'a>(
+    columns: &'a [Option<ColumnValues>],
+    metrics: impl Iterator<Item = Metric>,
+    get: impl Fn(&'a ColumnValues) -> Option<&'a [Option<f64>]>,
+    pars: &AnomalyParameters,
+    time_axis: &TimeAxis,
+) -> StitchedSet {
+    let key_values: Vec<_> = columns
+        .iter()
+        .map(|cv| cv.as_ref().and_then(&get))
+        .collect();
+    StitchedSet(
+        metrics
+            .enumerate()
+            .map(|(idx, metric)| {
+                let data = key_values
+                    .iter()
+                    .map(|values| values.and_then(|values| values.get(idx).copied().flatten()))
+                    .collect();
+                StitchedLine::compute_new(metric, data, pars, time_axis)
+            })
+            .collect(),
+    )
+}
@@ -0,0 +1,194 @@
+//! Capacity forecasting: the best-fit model (linear or exponential) of a stitched line is projected forward to find the moment a threshold is crossed.
+//! The confidence band is derived from the standard deviation of the residuals of the best-fit model.
+use crate::{Metric, StitchedLine};
+use clap::ValueEnum;
+use std::str::FromStr;
+
+use super::stitched_line::BestFit;
+
+/// The z-value of a two-sided 95% confidence band
+const Z_95: f64 = 1.96;
+
+/// A threshold for a metric, parsed from '<metric>=<value>', for example 'rate=500' or 'p95-millis=800'
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ForecastThreshold {
+    pub metric: Metric,
+    pub value: f64,
+}
+
+impl FromStr for ForecastThreshold {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (metric, value) = s
+            .split_once('=')
+            .ok_or_else(|| format!("Threshold '{s}' should have the format '<metric>=<value>'"))?;
+        let metric = Metric::from_str(metric.trim(), true)?;
+        let value = value
+            .trim()
+            .parse()
+            .map_err(|err| format!("Invalid threshold value in '{s}': {err}"))?;
+        Ok(Self { metric, value })
+    }
+}
+
+/// The projected crossing of a threshold. All moments are x-values of the time-axis (days since the first column, or the column-index without a time-axis).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Forecast {
+    /// the x-value of the last column
+    pub last_x: f64,
+    /// the expected crossing of the threshold
+    pub breach_x: f64,
+    /// the crossing of the upper bound of the confidence band
+    pub earliest_x: f64,
+    /// the crossing of the lower bound of the confidence band (None if the lower bound never reaches the threshold)
+    pub latest_x: Option<f64>,
+}
+
+impl Forecast {
+    /// the time from the last column until the expected crossing (0.0 if the threshold is already crossed)
+    pub fn time_to_breach(&self) -> f64 {
+        self.breach_x - self.last_x
+    }
+}
+
+enum Model {
+    Linear { slope: f64, y_intercept: f64 },
+    Exponential { a: f64, b: f64 },
+}
+
+/// The best-fit model of a stitched line that is used to extrapolate the line
+pub struct Projection {
+    model: Model,
+    /// the standard deviation of the residuals of the model
+    pub sigma: f64,
+    pub last_x: f64,
+}
+
+impl Projection {
+    pub fn new(line: &StitchedLine) -> Option<Self> {
+        let model = match line.best_fit {
+            BestFit::LinRegr => line.lin_regr.as_ref().map(|lr| Model::Linear {
+                slope: lr.slope,
+                y_intercept: lr.y_intercept,
+            }),
+            BestFit::ExprRegr => line
+                .exp_regr
+                .as_ref()
+                .map(|er| Model::Exponential { a: er.a, b: er.b }),
+            BestFit::None => None,
+        }?;
+        let last_x = line.x(line.data.len().checked_sub(1)?);
+        let mut projection = Self {
+            model,
+            sigma: 0.0,
+            last_x,
+        };
+        let residuals: Vec<_> = line
+            .data
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, y)| y.map(|y| y - projection.predict(line.x(idx))))
+            .collect();
+        if residuals.len() > 2 {
+            projection.sigma = (residuals.iter().map(|r| r * r).sum::<f64>()
+                / (residuals.len() - 2) as f64)
+                .sqrt();
+        }
+        Some(projection)
+    }
+
+    pub fn predict(&self, x: f64) -> f64 {
+        match self.model {
+            Model::Linear { slope, y_intercept } => slope * x + y_intercept,
+            Model::Exponential { a, b } => a * b.powf(x),
+        }
+    }
+
+    /// the lower and upper bound of the confidence band at 'x'
+    pub fn band(&self, x: f64) -> (f64, f64) {
+        let y = self.predict(x);
+        (y - Z_95 * self.sigma, y + Z_95 * self.sigma)
+    }
+
+    /// The first x-value from the last column onwards where the model reaches 'y' (None if the model does not grow towards 'y')
+    fn crossing(&self, y: f64) -> Option<f64> {
+        if self.predict(self.last_x) >= y {
+            return Some(self.last_x);
+        }
+        let x = match self.model {
+            Model::Linear { slope, y_intercept } if slope > 0.0 => (y - y_intercept) / slope,
+            Model::Exponential { a, b } if a > 0.0 && b > 1.0 && y > 0.0 => (y / a).ln() / b.ln(),
+            _ => return None,
+        };
+        x.is_finite().then_some(x.max(self.last_x))
+    }
+
+    /// Forecast when 'threshold' is crossed, where 'last_value' is the last observed value (a breach is immediate if it already exceeds the threshold).
+    pub fn forecast(&self, threshold: f64, last_value: Option<f64>) -> Option<Forecast> {
+        let band = Z_95 * self.sigma;
+        let breach_x = match last_value {
+            Some(val) if val >= threshold => self.last_x,
+            _ => self.crossing(threshold)?,
+        };
+        Some(Forecast {
+            last_x: self.last_x,
+            breach_x,
+            earliest_x: self
+                .crossing(threshold - band)
+                .map_or(breach_x, |x| x.min(breach_x)),
+            latest_x: self.crossing(threshold + band).map(|x| x.max(breach_x)),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ForecastThreshold, Model, Projection};
+    use crate::Metric;
+
+    #[test]
+    fn linear_breach_with_band() {
+        let projection = Projection {
+            model: Model::Linear {
+                slope: 10.0,
+                y_intercept: 100.0,
+            },
+            sigma: 5.0,
+            last_x: 10.0,
+        };
+        // 100 + 10*x = 500  => x = 40
+        let forecast = projection.forecast(500.0, Some(200.0)).unwrap();
+        assert!((forecast.breach_x - 40.0).abs() < 1e-9);
+        assert!((forecast.time_to_breach() - 30.0).abs() < 1e-9);
+        assert!((forecast.earliest_x - (40.0 - 0.98)).abs() < 1e-9);
+        assert!((forecast.latest_x.unwrap() - (40.0 + 0.98)).abs() < 1e-9);
+        // already breached by the last observation
+        assert_eq!(
+            projection
+                .forecast(150.0, Some(200.0))
+                .unwrap()
+                .time_to_breach(),
+            0.0
+        );
+
+        let decreasing = Projection {
+            model: Model::Linear {
+                slope: -1.0,
+                y_intercept: 100.0,
+            },
+            sigma: 1.0,
+            last_x: 10.0,
+        };
+        assert!(decreasing.forecast(500.0, Some(90.0)).is_none());
+    }
+
+    #[test]
+    fn parse_threshold() {
+        let threshold: ForecastThreshold = "p95-millis=800".parse().unwrap();
+        assert_eq!(threshold.metric, Metric::P95Millis);
+        assert_eq!(threshold.value, 800.0);
+        assert!("rate".parse::<ForecastThreshold>().is_err());
+        assert!("unknown=1".parse::<ForecastThreshold>().is_err());
+    }
+}
@@ -4,5 +4,7 @@ mod lineair_regr;
 mod stitched;
 mod stitched_line;
 mod stitched_set;
+mod stitched_v0_3;
 
 pub use stitched::LegacyStitched;
+pub use stitched_v0_3::StitchedV0_3;
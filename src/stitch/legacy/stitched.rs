@@ -1,5 +1,5 @@
 use super::{
-    super::{stitch_list::StitchSources, Stitched},
+    super::{stitch_list::StitchSources, time_axis::TimeAxis, Stitched},
     call_chain_data::{VecLegacyCallChainData, VecLegacyCallChainDataJson},
     stitched_set::{LegacyStitchedSet, LegacyStitchedSetJson},
 };
@@ -41,16 +41,6 @@ impl LegacyStitched {
             }
         }
     }
-
-    // read the legacy-stitched and return a Stiched or an error
-    pub fn from_bincode(file_name: &str) -> Result<Self, Box<dyn Error>> {
-        let path_str = Path::new(file_name);
-        let f = fs::File::open(path_str)?;
-        let reader = io::BufReader::new(f);
-
-        let legacy_stitched = bincode::deserialize_from(reader)?;
-        Ok(legacy_stitched)
-    }
 }
 
 /// Implement Into directly as we do not want or need/accept the Operation from Stitched to LegacyStitched.
@@ -63,7 +53,15 @@ impl TryInto<Stitched> for LegacyStitched {
         let service_operation = vec_try_into(self.process_operation)?;
         let call_chain = vec_try_into(self.call_chain)?;
 
-        Ok(Stitched::new(sources, basic, service_operation, call_chain))
+        Ok(Stitched::new(
+            sources,
+            basic,
+            service_operation,
+            call_chain,
+            Vec::new(),
+            Vec::new(),
+            &TimeAxis::default(),
+        ))
     }
 }
 
@@ -88,7 +86,15 @@ impl TryInto<Stitched> for LegacyStitchedJson {
         let service_operation = vec_try_into(self.process_operation)?;
         let call_chain = vec_try_into(self.call_chain)?;
 
-        Ok(Stitched::new(sources, basic, service_operation, call_chain))
+        Ok(Stitched::new(
+            sources,
+            basic,
+            service_operation,
+            call_chain,
+            Vec::new(),
+            Vec::new(),
+            &TimeAxis::default(),
+        ))
     }
 }
 
@@ -0,0 +1,31 @@
+use super::super::{
+    call_chain_data::CallChainData, stitch_list::StitchSources, time_axis::TimeAxis, Stitched,
+    StitchedSet,
+};
+use crate::{view_api::Version, ServiceOperString};
+use serde::Deserialize;
+
+/// The layout of the stitched bincode-files of version 0.3, which did not contain the cache-statistics yet.
+#[derive(Deserialize)]
+pub struct StitchedV0_3 {
+    pub version: Version,
+    pub sources: StitchSources,
+    pub basic: StitchedSet,
+    pub service_operation: Vec<(ServiceOperString, StitchedSet)>,
+    pub call_chain: Vec<(ServiceOperString, Vec<CallChainData>)>,
+}
+
+/// Migration from version 0.3 adds an empty set of cache-statistics (introduced in version 0.4)
+impl From<StitchedV0_3> for Stitched {
+    fn from(st: StitchedV0_3) -> Self {
+        Stitched::new(
+            st.sources,
+            st.basic,
+            st.service_operation,
+            st.call_chain,
+            Vec::new(),
+            Vec::new(),
+            &TimeAxis::default(),
+        )
+    }
+}
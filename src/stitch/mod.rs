@@ -5,22 +5,34 @@
 //! split these phases such that we a separation of concerns and open new options to use the data.
 
 mod anomalies;
+mod anomaly_report;
 mod api;
+mod cache_stats_reporter;
 mod call_chain_data;
 mod call_chain_reporter;
+mod change_point;
+mod column_values;
 mod dataseries;
+mod forecast;
 mod key;
 mod legacy;
 mod proc_oper_stats_reporter;
+mod seasonal;
 mod stats_rec_reporter;
 mod stitch_list;
 mod stitch_tables;
 mod stitched;
+mod stitched_append;
 mod stitched_line;
+mod stitched_parquet;
 mod stitched_set;
+mod stitched_sqlite;
+mod time_axis;
 
 pub use anomalies::AnomalyParameters;
+pub use anomaly_report::{AnomalyReport, AnomalySeverity, SeverityRule, SeverityRules};
 pub use api::StitchedDataSet;
+pub use forecast::ForecastThreshold;
 pub use stitch_list::StitchList;
 pub use stitched::{StitchParameters, Stitched};
 pub use stitched_line::{BestFit, StitchedLine};
@@ -0,0 +1,114 @@
+//! Seasonal model of a stitched time-series. Hourly or daily analyses show recurring patterns (day/night or weekday/weekend), which would
+//! otherwise be reported as anomalies. The model is an additive decomposition: value = trend + seasonal component + residual, where the trend is
+//! a linear regression on the de-seasonalised data and the seasonal component depends on the phase (column-index modulo the period).
+use crate::utils::LinearRegression;
+
+#[derive(Debug)]
+pub struct SeasonalModel {
+    /// the length of a season in columns (for example 24 for hourly analyses with a daily pattern or 7 for daily analyses with a weekly pattern)
+    pub period: usize,
+    /// the seasonal component per phase, centered around zero
+    pub seasonal: Vec<f64>,
+    /// the linear trend of the de-seasonalised data (its L1-deviation is the average absolute residual)
+    pub trend: LinearRegression,
+}
+
+impl SeasonalModel {
+    /// Fit a seasonal model on 'data' with x-values 'x_axis'. At least two filled seasons are needed and each phase should have a value.
+    /// The slope and the seasonal components are the least-squares solution, obtained by a regression on the values relative to the average of their phase.
+    pub fn new(data: &[Option<f64>], x_axis: &[f64], period: usize) -> Option<Self> {
+        let num_filled = data.iter().filter(|v| v.is_some()).count();
+        if period < 2 || num_filled < 2 * period {
+            return None;
+        }
+        let points: Vec<_> = data
+            .iter()
+            .zip(x_axis)
+            .enumerate()
+            .filter_map(|(idx, (y, x))| y.map(|y| (idx % period, *x, y)))
+            .collect();
+
+        // average x and y per phase
+        let mut sums = vec![(0.0, 0.0, 0); period];
+        points.iter().for_each(|(phase, x, y)| {
+            let sum = &mut sums[*phase];
+            sum.0 += x;
+            sum.1 += y;
+            sum.2 += 1;
+        });
+        let averages = sums
+            .into_iter()
+            .map(|(sum_x, sum_y, cnt)| (cnt > 0).then(|| (sum_x / cnt as f64, sum_y / cnt as f64)))
+            .collect::<Option<Vec<_>>>()?;
+
+        let (num, denum) = points
+            .iter()
+            .fold((0.0, 0.0), |(num, denum), (phase, x, y)| {
+                let (avg_x, avg_y) = averages[*phase];
+                (num + (x - avg_x) * (y - avg_y), denum + (x - avg_x).powi(2))
+            });
+        if denum.abs() < 1e-100 {
+            return None;
+        }
+        let slope = num / denum;
+        let levels: Vec<_> = averages
+            .iter()
+            .map(|(avg_x, avg_y)| avg_y - slope * avg_x)
+            .collect();
+        let avg_level = levels.iter().sum::<f64>() / period as f64;
+        let seasonal: Vec<_> = levels.into_iter().map(|level| level - avg_level).collect();
+
+        let trend = LinearRegression::new_on_axis(&deseasonalise(data, &seasonal, 0), x_axis)?;
+        Some(Self {
+            period,
+            seasonal,
+            trend,
+        })
+    }
+
+    /// the seasonal component of column 'idx'
+    pub fn component(&self, idx: usize) -> f64 {
+        self.seasonal[idx % self.period]
+    }
+
+    /// subtract the seasonal component, where 'offset' is the column-index of the first value of 'data'
+    pub fn deseasonalise(&self, data: &[Option<f64>], offset: usize) -> Vec<Option<f64>> {
+        deseasonalise(data, &self.seasonal, offset)
+    }
+
+    /// the value expected by the model for column 'idx' at 'x'
+    pub fn predict(&self, idx: usize, x: f64) -> f64 {
+        self.trend.predict(x) + self.component(idx)
+    }
+}
+
+fn deseasonalise(data: &[Option<f64>], seasonal: &[f64], offset: usize) -> Vec<Option<f64>> {
+    data.iter()
+        .enumerate()
+        .map(|(idx, y)| y.map(|y| y - seasonal[(offset + idx) % seasonal.len()]))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SeasonalModel;
+
+    #[test]
+    fn weekly_pattern_is_removed() {
+        // a growing series (1 per day) with a weekend-dip of 10 and a missing value
+        let data: Vec<_> = (0..21)
+            .map(|day| {
+                let weekend = if day % 7 >= 5 { -10.0 } else { 0.0 };
+                (day != 9).then_some(100.0 + day as f64 + weekend)
+            })
+            .collect();
+        let x_axis: Vec<_> = (0..21).map(|day| day as f64).collect();
+
+        let model = SeasonalModel::new(&data, &x_axis, 7).unwrap();
+
+        assert!((model.trend.slope - 1.0).abs() < 1e-6, "{model:?}");
+        assert!((model.component(5) - model.component(0) + 10.0).abs() < 1e-6);
+        assert!(model.trend.L1_deviation < 1e-6);
+        assert!(SeasonalModel::new(&data[..10], &x_axis[..10], 7).is_none());
+    }
+}
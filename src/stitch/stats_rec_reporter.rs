@@ -1,10 +1,9 @@
-use super::stitched_line::StitchedLine;
-use crate::{stats::StatsRec, AnomalyParameters, Metric};
+use crate::{stats::StatsRec, Metric};
 
 type SRProcessor = fn(&StatsRec) -> Option<f64>;
 
 pub struct SRReportItem {
-    metric: Metric,
+    pub metric: Metric,
     processor: SRProcessor,
 }
 
@@ -13,21 +12,8 @@ impl SRReportItem {
         Self { metric, processor }
     }
 
-    /// extract values
-    pub fn extract_data(&self, data: &[Option<StatsRec>]) -> Vec<Option<f64>> {
-        data.iter()
-            .map(|ms| ms.as_ref().and_then(self.processor))
-            .collect::<Vec<_>>()
-    }
-
-    /// extract a line of stitched data for the current report item.
-    pub fn extract_stitched_line(
-        &self,
-        data: &[Option<StatsRec>],
-        pars: &AnomalyParameters,
-    ) -> StitchedLine {
-        let values = self.extract_data(data);
-
-        StitchedLine::compute_new(self.metric, values, pars)
+    /// extract the value of this report item for a single analysis
+    pub fn extract_value(&self, stats_rec: &StatsRec) -> Option<f64> {
+        (self.processor)(stats_rec)
     }
 }
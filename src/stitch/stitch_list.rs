@@ -1,9 +1,22 @@
 use crate::{
+    context::AnalysisContext,
     stats::StatsRec,
     utils::{extend_with_base_path_opt, extract_base_path, read_lines},
 };
+use chrono::{NaiveDate, NaiveDateTime};
 use serde::{Deserialize, Serialize};
-use std::{error::Error, ffi::OsString, path::Path};
+use std::{
+    collections::BTreeMap,
+    error::Error,
+    ffi::OsString,
+    fs,
+    path::Path,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+    thread,
+};
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct StitchSourceItem {
@@ -50,10 +63,177 @@ impl StitchSources {
     //TODO: This list already should contain the correct list of labels, as extracted from the description based on a provided pattern.
 }
 
+/// The description of a column (time-slot) of a stitched dataset, as provided by a structured stitch-list.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct StitchColumn {
+    pub label: String,
+    /// the (local) timestamp of the column in micro-seconds since epoch
+    pub timestamp_micros: i64,
+    /// tags such as the environment or the release of the analysis (empty for a gap)
+    pub tags: BTreeMap<String, String>,
+    /// false if no analysis is available for this time-slot
+    pub filled: bool,
+}
+
+impl StitchColumn {
+    /// the tags as 'key=value' pairs separated by a comma
+    pub fn tags_str(&self) -> String {
+        self.tags
+            .iter()
+            .map(|(key, value)| format!("{key}={value}"))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// header for the csv-line of a column
+    pub fn csv_header() -> &'static str {
+        "Column; Label; Timestamp; Filled; Tags"
+    }
+
+    pub fn to_csv_string(&self, idx: usize) -> String {
+        let timestamp = chrono::DateTime::from_timestamp_micros(self.timestamp_micros)
+            .map(|dt| dt.naive_utc().format("%Y-%m-%d %H:%M").to_string())
+            .unwrap_or_default();
+        format!(
+            "{idx}; {}; {timestamp}; {}; {}",
+            self.label,
+            self.filled,
+            self.tags_str()
+        )
+    }
+}
+
+/// A timestamp in a structured stitch-list is a string, or a TOML date(-time) literal.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StitchTimestamp {
+    Str(String),
+    Toml(toml::value::Datetime),
+}
+
+impl StitchTimestamp {
+    /// parse a date ('2023-10-08') or a date-time ('2023-10-08T12:00' or '2023-10-08 12:00:00')
+    fn parse(&self) -> Result<NaiveDateTime, Box<dyn Error>> {
+        let s = match self {
+            StitchTimestamp::Str(s) => s.trim().to_owned(),
+            StitchTimestamp::Toml(dt) => dt.to_string(),
+        };
+        let dt = [
+            "%Y-%m-%dT%H:%M:%S",
+            "%Y-%m-%d %H:%M:%S",
+            "%Y-%m-%dT%H:%M",
+            "%Y-%m-%d %H:%M",
+        ]
+        .iter()
+        .find_map(|fmt| NaiveDateTime::parse_from_str(&s, fmt).ok())
+        .or_else(|| {
+            NaiveDate::parse_from_str(&s, "%Y-%m-%d")
+                .ok()
+                .and_then(|d| d.and_hms_opt(0, 0, 0))
+        })
+        .ok_or_else(|| format!("Invalid timestamp '{s}' in stitch-list"))?;
+        Ok(dt)
+    }
+}
+
+/// An entry of a structured stitch-list, which has either a 'timestamp' or a 'period'
+#[derive(Deserialize)]
+struct StitchEntry {
+    path: String,
+    timestamp: Option<StitchTimestamp>,
+    /// a period '<start>/<end>' covered by the analysis, where the end is exclusive (for example '2023-10-02/2023-10-09' for a week)
+    period: Option<String>,
+    label: Option<String>,
+    #[serde(default)]
+    tags: BTreeMap<String, String>,
+}
+
+impl StitchEntry {
+    /// the start of the entry and, for a period, its length in seconds
+    fn start_and_length(&self) -> Result<(NaiveDateTime, Option<i64>), Box<dyn Error>> {
+        match (&self.timestamp, &self.period) {
+            (Some(timestamp), None) => Ok((timestamp.parse()?, None)),
+            (None, Some(period)) => {
+                let Some((start, end)) = period.split_once('/') else {
+                    Err(format!(
+                        "Period '{period}' of '{}' should have format '<start>/<end>'",
+                        self.path
+                    ))?
+                };
+                let start = StitchTimestamp::Str(start.to_owned()).parse()?;
+                let end = StitchTimestamp::Str(end.to_owned()).parse()?;
+                let length = (end - start).num_seconds();
+                if length <= 0 {
+                    Err(format!("Period '{period}' of '{}' is empty", self.path))?;
+                }
+                Ok((start, Some(length)))
+            }
+            _ => Err(format!(
+                "Entry '{}' should have either a timestamp or a period",
+                self.path
+            ))?,
+        }
+    }
+
+    /// the default label is the date of the timestamp, or the first and last day of the period
+    fn default_label(&self, start: &NaiveDateTime, length: Option<i64>) -> String {
+        match length {
+            Some(length) => {
+                let last = *start + chrono::Duration::seconds(length - 1);
+                format!("{}/{}", default_label(start), last.format("%Y-%m-%d"))
+            }
+            None => default_label(start),
+        }
+    }
+}
+
+/// A structured stitch-list (TOML or JSON). The 'interval' between consecutive analyses (for example '1d', '7d' or '6h') is used to detect gaps.
+/// Without an interval the length of the shortest period is used, or else the smallest distance between two timestamps.
+#[derive(Deserialize)]
+struct StructuredStitchList {
+    interval: Option<String>,
+    #[serde(alias = "sources")]
+    source: Vec<StitchEntry>,
+}
+
+/// parse an interval such as '1d', '7d', '6h', '30m' or '1w' to seconds
+fn parse_interval(interval: &str) -> Result<i64, Box<dyn Error>> {
+    let interval = interval.trim();
+    let (num, unit) = interval.split_at(interval.len().saturating_sub(1));
+    let num: i64 = num
+        .parse()
+        .map_err(|_| format!("Invalid interval '{interval}' in stitch-list"))?;
+    let unit_secs = match unit {
+        "w" => 7 * 86400,
+        "d" => 86400,
+        "h" => 3600,
+        "m" => 60,
+        "s" => 1,
+        _ => Err(format!(
+            "Invalid unit of interval '{interval}' (expected w, d, h, m or s)"
+        ))?,
+    };
+    if num <= 0 {
+        Err(format!("Interval '{interval}' should be positive"))?;
+    }
+    Ok(num * unit_secs)
+}
+
+/// the default label of a column is the date, or the date-time if the timestamp is not at midnight
+pub(super) fn default_label(dt: &NaiveDateTime) -> String {
+    if dt.time() == chrono::NaiveTime::MIN {
+        dt.format("%Y-%m-%d").to_string()
+    } else {
+        dt.format("%Y-%m-%d %H:%M").to_string()
+    }
+}
+
 #[derive(Default, Debug)]
 pub struct StitchList {
     pub lines: StitchSources,         // numbered Lines including comments
     pub paths: Vec<Option<OsString>>, // a None represents a slot that is not filled (will become an empty column)
+    /// the description of each column, only available for a structured stitch-list
+    pub columns: Vec<StitchColumn>,
 }
 
 impl StitchList {
@@ -66,29 +246,76 @@ impl StitchList {
         self.paths.push(path);
     }
 
-    /// Reading all data of a stitchlist in a Vector.
-    /// This is still columnar data, with one data-set per analysis. Each column is an Option<StatsRec> as missing columns are supported during stitching.
-    /// Missing columns are needed to mimick the actual timeline (and thus gaps in the timeline) for a correct linear regression analysis.
-    pub fn read_data(&self) -> Vec<Option<StatsRec>> {
-        self.paths
-            .iter()
-            .enumerate()
-            .map(|(idx, p)| {
-                if let Some(p) = p {
-                    println!("{}: Reading file '{p:?}'", idx + 1);
-                    Some(StatsRec::read_file(p).expect("Failed to read JSON-file"))
-                } else {
-                    println!("{}: No Data", idx + 1);
-                    None
-                }
-            })
+    /// Read the analyses of the stitchlist one by one and reduce each of them via 'reduce', such that only the reduced data is kept in memory.
+    /// With 'num_threads > 1' the files are read in parallel, where each thread holds at most one StatsRec in memory.
+    /// A missing column results in a None, as missing columns are needed to mimick the actual timeline (and thus gaps in the timeline).
+    pub fn read_reduced<T: Send>(
+        &self,
+        ctx: &AnalysisContext,
+        num_threads: usize,
+        reduce: impl Fn(StatsRec) -> T + Sync,
+    ) -> Vec<Option<T>> {
+        let read = |idx: usize, p: &OsString| {
+            println!("{}: Reading file '{p:?}'", idx + 1);
+            reduce(StatsRec::read_file(ctx, p).expect("Failed to read JSON-file"))
+        };
+
+        if num_threads <= 1 {
+            return self
+                .paths
+                .iter()
+                .enumerate()
+                .map(|(idx, p)| {
+                    if p.is_none() {
+                        println!("{}: No Data", idx + 1);
+                    }
+                    p.as_ref().map(|p| read(idx, p))
+                })
+                .collect();
+        }
+
+        let next_idx = AtomicUsize::new(0);
+        let results: Vec<_> = self.paths.iter().map(|_| Mutex::new(None)).collect();
+        thread::scope(|s| {
+            for _ in 0..num_threads.min(self.paths.len()) {
+                s.spawn(|| loop {
+                    let idx = next_idx.fetch_add(1, Ordering::Relaxed);
+                    let Some(p) = self.paths.get(idx) else {
+                        break;
+                    };
+                    let Some(p) = p else {
+                        println!("{}: No Data", idx + 1);
+                        continue;
+                    };
+                    *results[idx].lock().unwrap() = Some(read(idx, p));
+                });
+            }
+        });
+
+        results
+            .into_iter()
+            .map(|result| result.into_inner().unwrap())
             .collect()
     }
 
     /// Read a stitch-list file and return a struct showing the contents.
+    /// Files with extension '.toml' or '.json' are structured stitch-lists, other files use the line-based format.
     pub fn read_stitch_list(path: &Path) -> Result<StitchList, Box<dyn Error>> {
         let base_path = extract_base_path(path);
 
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("toml") => {
+                let structured: StructuredStitchList = toml::from_str(&fs::read_to_string(path)?)?;
+                return Self::from_structured(&base_path, structured);
+            }
+            Some("json") => {
+                let structured: StructuredStitchList =
+                    serde_json::from_str(&fs::read_to_string(path)?)?;
+                return Self::from_structured(&base_path, structured);
+            }
+            _ => (),
+        }
+
         Ok(read_lines(path)?.fold(StitchList::new(), |mut sl, l| {
             let l = l.unwrap();
             let l = l.trim();
@@ -109,4 +336,158 @@ impl StitchList {
             sl
         }))
     }
+
+    /// Build the stitch-list from the entries of a structured stitch-list. The entries are sorted on timestamp and
+    /// a gap (empty column) is inserted for each interval without an analysis.
+    fn from_structured(
+        base_path: &Path,
+        structured: StructuredStitchList,
+    ) -> Result<StitchList, Box<dyn Error>> {
+        let mut entries = structured
+            .source
+            .into_iter()
+            .map(|entry| {
+                entry
+                    .start_and_length()
+                    .map(|(dt, length)| (dt, length, entry))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        entries.sort_by_key(|(dt, _, _)| *dt);
+
+        let interval_secs = match &structured.interval {
+            Some(interval) => parse_interval(interval)?,
+            None => entries
+                .iter()
+                .filter_map(|(_, length, _)| *length)
+                .min()
+                .or_else(|| {
+                    entries
+                        .windows(2)
+                        .map(|w| (w[1].0 - w[0].0).num_seconds())
+                        .filter(|secs| *secs > 0)
+                        .min()
+                })
+                .unwrap_or(86400),
+        };
+
+        let mut sl = StitchList::new();
+        let Some(first) = entries.first().map(|(dt, _, _)| *dt) else {
+            return Ok(sl);
+        };
+        for (dt, length, entry) in entries {
+            let slot = ((dt - first).num_seconds() as f64 / interval_secs as f64).round() as usize;
+            if slot < sl.paths.len() {
+                Err(format!(
+                    "Entry '{}' at {dt} falls in the same interval as the previous entry",
+                    entry.path
+                ))?;
+            }
+            // fill the gap up to this slot
+            while sl.paths.len() < slot {
+                let gap_dt =
+                    first + chrono::Duration::seconds(sl.paths.len() as i64 * interval_secs);
+                sl.add_path(base_path, None);
+                sl.lines.add_numbered(&format!("% gap at {gap_dt}"));
+                sl.columns.push(StitchColumn {
+                    label: default_label(&gap_dt),
+                    timestamp_micros: gap_dt.and_utc().timestamp_micros(),
+                    tags: BTreeMap::new(),
+                    filled: false,
+                });
+            }
+            sl.add_path(base_path, Some(&entry.path));
+            sl.lines.add_numbered(&entry.path);
+            sl.columns.push(StitchColumn {
+                label: entry
+                    .label
+                    .clone()
+                    .unwrap_or_else(|| entry.default_label(&dt, length)),
+                timestamp_micros: dt.and_utc().timestamp_micros(),
+                tags: entry.tags,
+                filled: true,
+            });
+        }
+        Ok(sl)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{StitchList, StructuredStitchList};
+    use std::path::Path;
+
+    #[test]
+    fn structured_list_with_gap() {
+        let structured: StructuredStitchList = toml::from_str(
+            r#"
+            [[source]]
+            path = "/data/d3.json"
+            timestamp = 2023-10-11
+            tags = { environment = "prod", release = "1.4" }
+
+            [[source]]
+            path = "/data/d1.json"
+            timestamp = "2023-10-08"
+            label = "Sunday"
+
+            [[source]]
+            path = "/data/d2.json"
+            timestamp = "2023-10-09"
+        "#,
+        )
+        .unwrap();
+        let sl = StitchList::from_structured(Path::new("/data"), structured).unwrap();
+        let filled: Vec<_> = sl.paths.iter().map(|p| p.is_some()).collect();
+        assert_eq!(filled, [true, true, false, true]);
+        let labels: Vec<_> = sl.columns.iter().map(|c| &c.label[..]).collect();
+        assert_eq!(labels, ["Sunday", "2023-10-09", "2023-10-10", "2023-10-11"]);
+        assert_eq!(sl.columns[3].tags["release"], "1.4");
+        assert_eq!(
+            sl.columns[3].to_csv_string(3),
+            "3; 2023-10-11; 2023-10-11 00:00; true; environment=prod, release=1.4"
+        );
+        assert_eq!(sl.lines.0.len(), 4);
+    }
+
+    #[test]
+    fn structured_list_with_periods() {
+        let structured: StructuredStitchList = toml::from_str(
+            r#"
+            [[source]]
+            path = "/data/w40.json"
+            period = "2023-10-02/2023-10-09"
+
+            [[source]]
+            path = "/data/w42.json"
+            period = "2023-10-16/2023-10-23"
+            tags = { release = "1.5" }
+        "#,
+        )
+        .unwrap();
+        let sl = StitchList::from_structured(Path::new("/data"), structured).unwrap();
+        // the length of the periods is the interval, so the missing week is a gap
+        let filled: Vec<_> = sl.columns.iter().map(|c| c.filled).collect();
+        assert_eq!(filled, [true, false, true]);
+        let labels: Vec<_> = sl.columns.iter().map(|c| &c.label[..]).collect();
+        assert_eq!(
+            labels,
+            [
+                "2023-10-02/2023-10-08",
+                "2023-10-09",
+                "2023-10-16/2023-10-22"
+            ]
+        );
+
+        let invalid = |entry: &str| {
+            let structured: StructuredStitchList =
+                toml::from_str(&format!("[[source]]\npath = \"/data/d.json\"\n{entry}")).unwrap();
+            StitchList::from_structured(Path::new("/data"), structured).is_err()
+        };
+        assert!(invalid(""));
+        assert!(invalid(
+            "timestamp = 2023-10-02\nperiod = \"2023-10-02/2023-10-09\""
+        ));
+        assert!(invalid("period = \"2023-10-09/2023-10-02\""));
+        assert!(invalid("period = \"2023-10-02\""));
+    }
 }
@@ -1,12 +1,10 @@
 use super::{
+    cache_stats_reporter::{CSReportItem, CSReportItems},
     call_chain_reporter::{CCReportItem, CCReportItems},
     proc_oper_stats_reporter::{POReportItem, POReportItems},
     stats_rec_reporter::SRReportItem,
 };
-use crate::{
-    utils::{self, TimeStats},
-    Metric,
-};
+use crate::{utils::TimeStats, Metric};
 use lazy_static::lazy_static;
 
 lazy_static! {
@@ -14,8 +12,7 @@ lazy_static! {
     pub static ref BASIC_REPORT_ITEMS: Vec<SRReportItem> = vec![
         SRReportItem::new(Metric::NumFiles, |stats_rec| Some(stats_rec.num_files as f64)),
         SRReportItem::new(Metric::Rate, |stats_rec| {
-            let dt: Vec<_> = stats_rec.start_dt.iter().map(|dt| utils::datetime_to_micros(*dt)).collect();
-            TimeStats(&dt)
+            TimeStats(&stats_rec.start_dt_micros)
                 .get_avg_rate(stats_rec.num_files)
         }),
         SRReportItem::new(Metric::NumTraces, |stats_rec| Some(
@@ -95,3 +92,15 @@ lazy_static! {
         )),
     ]);
 }
+
+lazy_static! {
+    pub static ref CACHE_REPORT_ITEMS: CSReportItems = CSReportItems(vec![
+        // The downstream analysis assumes that the first Report item is the Count measure!!
+        CSReportItem::new(Metric::Count, |csv| Some(csv.total_count() as f64)),
+        CSReportItem::new(Metric::CacheHits, |csv| Some(csv.hit_count as f64)),
+        CSReportItem::new(Metric::CacheMisses, |csv| Some(csv.miss_count as f64)),
+        CSReportItem::new(Metric::CacheHitRatio, |csv| csv.get_hit_ratio()),
+        CSReportItem::new(Metric::CacheHitMissDiffMillis, |csv| csv.get_hit_miss_diff_millis()),
+        CSReportItem::new(Metric::CacheFlushLoadIncrease, |csv| csv.get_flush_load_increase()),
+    ]);
+}
@@ -1,22 +1,34 @@
-use std::{collections::HashMap, error::Error, fs, io};
+use std::{
+    collections::{BTreeSet, HashMap},
+    error::Error,
+    fs, io,
+};
 
 use crate::{
+    context::AnalysisContext,
     string_hash,
-    utils::{self, CsvFileBuffer},
+    utils::{self, CsvFileBuffer, FileFormatError, FileKind},
     view_api::Version,
     ServiceOperString, StitchList,
 };
 
 use super::{
-    anomalies::{Anomalies, AnomalyParameters},
+    anomalies::{Anomalies, AnomalyParameters, DEFAULT_ANOMALY_PARS},
+    anomaly_report::{AnomalyLevel, AnomalyReport, SeverityRules},
+    api::get_label_list,
     call_chain_data::CallChainData,
     call_chain_reporter::CCReportItems,
-    dataseries::DataSeries,
-    legacy::LegacyStitched,
-    proc_oper_stats_reporter::POReportItems,
-    stitch_list::StitchSources,
-    stitch_tables::{BASIC_REPORT_ITEMS, CALL_CHAIN_REPORT_ITEMS, PROC_OPER_REPORT_ITEMS},
+    column_values::{stitch_set, ColumnValues},
+    dataseries::low_volume_processes,
+    forecast::{Forecast, ForecastThreshold, Projection},
+    legacy::{LegacyStitched, StitchedV0_3},
+    stitch_list::{StitchColumn, StitchSources},
+    stitch_tables::{
+        BASIC_REPORT_ITEMS, CACHE_REPORT_ITEMS, CALL_CHAIN_REPORT_ITEMS, PROC_OPER_REPORT_ITEMS,
+    },
+    stitched_line::StitchedLine,
     stitched_set::StitchedSet,
+    time_axis::{TimeAxis, MICROS_PER_DAY},
 };
 use serde::{Deserialize, Serialize};
 use serde_json::{self};
@@ -25,6 +37,8 @@ use std::{mem, path::Path};
 #[derive(Debug)]
 pub struct StitchParameters {
     pub drop_count: usize,
+    /// the number of threads used to read the input-files (1 reads the files sequentially)
+    pub read_threads: usize,
     pub anomaly_pars: AnomalyParameters,
 }
 
@@ -40,6 +54,47 @@ pub struct Stitched {
     ///  call-chain is keyed by the Service/Operation and the values is a series of call-chains that end in this process/Oper
     /// The values is a Vector call-chains represent all different paths (call-chains) that terminate in de Process/Oper of the key of this vector.
     pub call_chain: Vec<(ServiceOperString, Vec<CallChainData>)>,
+    /// Cache hit-ratio statistics keyed by the call-chain prefix that ends in a call to a caching process.
+    #[serde(default)]
+    pub cache: Vec<(String, StitchedSet)>,
+    /// The label, timestamp and tags of each column, only available when a structured stitch-list is used.
+    #[serde(default)]
+    pub columns: Vec<StitchColumn>,
+    /// The time of each column in days since the first column, which is used as x-axis of the regression (empty if the column-index is used).
+    #[serde(default)]
+    pub time_axis: Vec<f64>,
+    /// The period (in columns) of the seasonal model of the stitched lines, or 0 without a seasonal model.
+    #[serde(default)]
+    pub seasonal_period: usize,
+    /// The minimal score of the change-points shown in the charts (0.0 disables the change-points).
+    #[serde(default = "default_change_point_bound")]
+    pub change_point_bound: f64,
+}
+
+/// The change-point bound of a stitched dataset of a version before 0.9
+fn default_change_point_bound() -> f64 {
+    DEFAULT_ANOMALY_PARS.change_point_bound
+}
+
+/// Bincode is not self-describing, so the payload of a file of an older version is extended with the encoding of the fields added since.
+fn upgrade_bincode_payload(version: Version, mut payload: Vec<u8>) -> Vec<u8> {
+    if version < Version::new(0, 6) {
+        // the trailing empty 'columns' (a length of zero)
+        payload.extend_from_slice(&0u64.to_le_bytes());
+    }
+    if version < Version::new(0, 7) {
+        // the trailing empty 'time_axis'
+        payload.extend_from_slice(&0u64.to_le_bytes());
+    }
+    if version < Version::new(0, 8) {
+        // the trailing 'seasonal_period' of 0 (no seasonal model)
+        payload.extend_from_slice(&0u64.to_le_bytes());
+    }
+    if version < Version::new(0, 9) {
+        // the trailing default 'change_point_bound'
+        payload.extend_from_slice(&default_change_point_bound().to_le_bytes());
+    }
+    payload
 }
 
 impl Stitched {
@@ -48,73 +103,150 @@ impl Stitched {
         basic: StitchedSet,
         service_operation: ServiceOperList,
         call_chain: Vec<(ServiceOperString, Vec<CallChainData>)>,
+        cache: Vec<(String, StitchedSet)>,
+        columns: Vec<StitchColumn>,
+        time_axis: &TimeAxis,
     ) -> Self {
         Self {
-            version: Version::new(0, 3),
+            version: Version::STITCHED,
             sources,
             basic,
             service_operation,
             call_chain,
+            cache,
+            columns,
+            time_axis: time_axis.to_vec(),
+            seasonal_period: 0,
+            change_point_bound: default_change_point_bound(),
         }
     }
 
+    /// The x-axis used for the regression of the stitched lines
+    pub fn time_axis(&self) -> TimeAxis {
+        TimeAxis::new(self.time_axis.clone())
+    }
+
+    /// Attach the time-axis and recompute the seasonal model of all stitched lines, as these are not stored per line.
+    fn restore_lines(&mut self) {
+        let time_axis = self.time_axis();
+        let seasonal_period = self.seasonal_period;
+        self.basic
+            .0
+            .iter_mut()
+            .chain(
+                self.service_operation
+                    .iter_mut()
+                    .flat_map(|(_, ss)| ss.0.iter_mut()),
+            )
+            .chain(
+                self.call_chain
+                    .iter_mut()
+                    .flat_map(|(_, ccds)| ccds.iter_mut().flat_map(|ccd| ccd.data.0.iter_mut())),
+            )
+            .chain(self.cache.iter_mut().flat_map(|(_, ss)| ss.0.iter_mut()))
+            .for_each(|line| line.restore(&time_axis, seasonal_period));
+    }
+
     /// build a stitched dataset based on a StitchList.
     /// The contents of the Stiched dataset are defined in a series of tables:
     ///    1. `stitched_tables::BASIC_REPORT_ITEMS`: Some basic statistics for this dataset.
     ///    2. `stitched_tables::PROC_OPER_REPORT_ITEMS`:  A report on the level of Process/Operation.
     ///    3. `stitched_tables::CALL_CHAIN_REPORT_ITEMS`:  A detailed report where we compute separate statistics for each call-chain (call-path) that lead to a specific Process/Operation.
-    pub fn build(mut stitch_list: StitchList, pars: &StitchParameters) -> Self {
+    ///    4. `stitched_tables::CACHE_REPORT_ITEMS`:  The cache hit-ratios for each call-chain that ends in a call to a caching process.
+    ///
+    /// The analyses of the stitch-list are read with the settings of 'ctx'.
+    pub fn build(
+        ctx: &AnalysisContext,
+        mut stitch_list: StitchList,
+        pars: &StitchParameters,
+    ) -> Self {
         let sources = mem::take(&mut stitch_list.lines);
+        let columns = mem::take(&mut stitch_list.columns);
 
-        // this method reads the data in the original format, so data contains one column (StatsRec) per dataset
-        let mut data = stitch_list.read_data();
+        // each analysis is read and reduced to the values per key, so only one StatsRec (per thread) is in memory at a time.
+        let mut data =
+            stitch_list.read_reduced(ctx, pars.read_threads, |sr| ColumnValues::new(&sr));
 
-        //TODO: check if drop works as expected.
-        let num_dropped = DataSeries(&mut data).drop_low_volume_traces(pars.drop_count);
+        // The low-volume processes can only be determined over all analyses, so these are dropped from the reduced analyses.
+        let num_dropped: usize = if pars.drop_count > 0 {
+            let dropped = low_volume_processes(
+                data.iter().flatten().map(|cv| &cv.process_counts[..]),
+                pars.drop_count,
+            );
+            data.iter_mut()
+                .flatten()
+                .map(|cv| cv.drop_processes(&dropped))
+                .sum()
+        } else {
+            0
+        };
         println!(
             "Based on drop_count={} we have dropped {num_dropped} Processes over all datasets.",
-            pars.drop_count
+            pars.drop_count,
         );
 
+        let time_axis = TimeAxis::from_columns(&columns);
+
         // add the basic report items as defined in stitch_tables::BASIC_REPORT_ITEMS.
-        let basic = StitchedSet(
-            BASIC_REPORT_ITEMS
-                .iter()
-                .map(|sr| sr.extract_stitched_line(&data, &pars.anomaly_pars))
-                .collect(),
+        let basic = stitch_set(
+            &data,
+            BASIC_REPORT_ITEMS.iter().map(|sr| sr.metric),
+            |cv| Some(&cv.basic[..]),
+            &pars.anomaly_pars,
+            &time_axis,
         );
 
-        let service_operation = POReportItems::get_keys(&data)
+        let po_keys: BTreeSet<_> = data
+            .iter()
+            .flatten()
+            .flat_map(|cv| cv.service_operation.keys())
+            .collect();
+        let service_operation = po_keys
             .into_iter()
             .map(|po_key| {
-                let key_data = POReportItems::extract_dataset(&data, &po_key);
-                let stitched_set = PROC_OPER_REPORT_ITEMS
-                    .0
-                    .iter()
-                    .map(|por| por.extract_stitched_line(&key_data, &pars.anomaly_pars))
-                    .collect();
-                (po_key.to_string(), StitchedSet(stitched_set))
+                let stitched_set = stitch_set(
+                    &data,
+                    PROC_OPER_REPORT_ITEMS.0.iter().map(|por| por.metric),
+                    |cv| cv.service_operation.get(po_key).map(|v| &v[..]),
+                    &pars.anomaly_pars,
+                    &time_axis,
+                );
+                (po_key.to_string(), stitched_set)
             })
             .collect();
 
-        let call_chain = CCReportItems::get_keys(&data)
+        // Computing all possible keys over the different datasets that need to be stitched.
+        let mut cc_keys = HashMap::new();
+        data.iter().flatten().for_each(|cv| {
+            cv.call_chain.iter().for_each(|(cc_key, ccv)| {
+                cc_keys
+                    .entry(cc_key.clone())
+                    .and_modify(|(cnt, rooted)| {
+                        *cnt += ccv.count;
+                        *rooted &= ccv.rooted;
+                    })
+                    .or_insert((ccv.count, ccv.rooted));
+            })
+        });
+        let call_chain = CCReportItems::group_keys(cc_keys)
             .into_iter()
             .map(|(proc_oper, cc_keys)| {
                 let call_chains = cc_keys
                     .into_iter()
                     .map(|(cc_key, rooted)| {
-                        let key_data = CCReportItems::extract_dataset(&data, &cc_key);
-                        let stitched_set = CALL_CHAIN_REPORT_ITEMS
-                            .0
-                            .iter()
-                            .map(|por| por.extract_stitched_line(&key_data, &pars.anomaly_pars))
-                            .collect();
+                        let stitched_set = stitch_set(
+                            &data,
+                            CALL_CHAIN_REPORT_ITEMS.0.iter().map(|ccr| ccr.metric),
+                            |cv| cv.call_chain.get(&cc_key).map(|ccv| &ccv.values[..]),
+                            &pars.anomaly_pars,
+                            &time_axis,
+                        );
                         CallChainData {
                             full_key: cc_key.call_chain_key(),
                             inbound_process_key: cc_key.inbound_call_chain_key(),
                             rooted,
                             is_leaf: cc_key.is_leaf,
-                            data: StitchedSet(stitched_set),
+                            data: stitched_set,
                         }
                     })
                     .collect();
@@ -122,36 +254,89 @@ impl Stitched {
             })
             .collect();
 
-        Stitched::new(sources, basic, service_operation, call_chain)
+        let cache_keys: BTreeSet<_> = data
+            .iter()
+            .flatten()
+            .flat_map(|cv| cv.cache.keys())
+            .collect();
+        let cache = cache_keys
+            .into_iter()
+            .map(|key| {
+                let stitched_set = stitch_set(
+                    &data,
+                    CACHE_REPORT_ITEMS.0.iter().map(|csr| csr.metric),
+                    |cv| cv.cache.get(key).map(|v| &v[..]),
+                    &pars.anomaly_pars,
+                    &time_axis,
+                );
+                (key.to_owned(), stitched_set)
+            })
+            .collect();
+
+        let mut stitched = Stitched::new(
+            sources,
+            basic,
+            service_operation,
+            call_chain,
+            cache,
+            columns,
+            &time_axis,
+        );
+        stitched.seasonal_period = pars.anomaly_pars.seasonal_period;
+        stitched.change_point_bound = pars.anomaly_pars.change_point_bound;
+        stitched
     }
 
-    // read from file (json or bincode)
+    /// read from file (json or bincode), check the version and migrate older versions to the current version.
     pub fn from_file(file_name: &str) -> Result<Self, Box<dyn Error>> {
-        //let keep = path.clone().into_string().unwrap();
         let path_str = Path::new(file_name);
-        let f = fs::File::open(path_str)?;
-        let reader = io::BufReader::new(f);
 
         let Some(ext) = path_str.extension() else {
             panic!("Failed to find extension of '{}'", path_str.display());
         };
 
-        let stitched: Self = match ext.to_str().unwrap() {
+        let mut stitched: Self = match ext.to_str().unwrap() {
             "json" => {
-                if let Ok(stitched) = serde_json::from_reader(reader) {
-                    stitched
-                } else {
-                    println!("WARN: Fallback to Legacy-format to load data!!");
-                    LegacyStitched::from_json(file_name)?
+                let (header, value) = utils::read_json_with_header(path_str)?;
+                header.check(path_str, FileKind::Stitched)?;
+                match header.version {
+                    Version::UNVERSIONED => {
+                        println!("WARN: Migrating '{file_name}' from the Legacy-format");
+                        LegacyStitched::from_json(file_name)?
+                    }
+                    // the fields added since version 0.3 are handled via the serde-default
+                    version => match serde_json::from_value(value) {
+                        Ok(stitched) => stitched,
+                        // the versions before 0.4 could still contain data in the Legacy-format
+                        Err(_) if version < Version::new(0, 4) => {
+                            println!("WARN: Migrating '{file_name}' from the Legacy-format");
+                            LegacyStitched::from_json(file_name)?
+                        }
+                        Err(err) => Err(FileFormatError::corrupt(
+                            path_str,
+                            FileKind::Stitched,
+                            version,
+                            err,
+                        ))?,
+                    },
                 }
             }
             "bincode" => {
-                if let Ok(stitched) = bincode::deserialize_from(reader) {
-                    stitched
-                } else {
-                    println!("WARN: Fallback to Legacy-format to load data!!");
-                    let sl = LegacyStitched::from_bincode(file_name)?;
-                    sl.try_into()?
+                let (header, payload) = utils::read_bincode(path_str)?;
+                match header {
+                    Some(header) => {
+                        header.check(path_str, FileKind::Stitched)?;
+                        let payload = upgrade_bincode_payload(header.version, payload);
+                        bincode::deserialize(&payload).map_err(|err| {
+                            FileFormatError::corrupt(
+                                path_str,
+                                FileKind::Stitched,
+                                header.version,
+                                err,
+                            )
+                        })?
+                    }
+                    None => Self::from_headerless_bincode(path_str, &payload)?,
                 }
             }
             ext => panic!(
@@ -159,9 +344,40 @@ impl Stitched {
                 path_str.display()
             ),
         };
+        stitched.version = Version::STITCHED;
+        stitched.restore_lines();
         Ok(stitched)
     }
 
+    /// Bincode-files without a header are of version 0.4 (with cache-statistics), version 0.3 (without cache-statistics) or Legacy-files without a version.
+    fn from_headerless_bincode(path: &Path, payload: &[u8]) -> Result<Self, Box<dyn Error>> {
+        let upgraded = upgrade_bincode_payload(Version::new(0, 4), payload.to_vec());
+        if let Ok(stitched) = bincode::deserialize::<Self>(&upgraded) {
+            return Ok(stitched);
+        }
+        if let Ok(stitched) = bincode::deserialize::<StitchedV0_3>(payload) {
+            println!(
+                "WARN: Migrating '{}' from version {}",
+                path.display(),
+                stitched.version
+            );
+            return Ok(stitched.into());
+        }
+        println!(
+            "WARN: Migrating '{}' from the Legacy-format",
+            path.display()
+        );
+        match bincode::deserialize::<LegacyStitched>(payload) {
+            Ok(legacy) => Ok(legacy.try_into()?),
+            Err(err) => Err(FileFormatError::corrupt(
+                path,
+                FileKind::Stitched,
+                Version::UNVERSIONED,
+                err,
+            ))?,
+        }
+    }
+
     /// write the 'stitched' dataset to json
     pub fn to_json(&self, file_name: &str) {
         let path_str = Path::new(file_name);
@@ -178,10 +394,11 @@ impl Stitched {
                 Ok(()) => (),
                 Err(err) => panic!("failed to Serialize '{file_name}' to JSON!! {err:?}"),
             },
-            "bincode" => match bincode::serialize_into(writer, self) {
-                Ok(()) => (),
-                Err(err) => panic!("failed to Serialize '{file_name}' to BINCODE!! {err:?}"),
-            },
+            "bincode" => {
+                if let Err(err) = utils::write_bincode(path_str, FileKind::Stitched, self) {
+                    panic!("failed to Serialize '{file_name}' to BINCODE!! {err:?}")
+                }
+            }
             ext => panic!(
                 "Unknown extension '{ext}'of inputfile {}",
                 path_str.display()
@@ -218,70 +435,107 @@ impl Stitched {
 
     /// Read all stitched data and write it out to a CSV files
     /// TODO: refactor to separate the CSV-output phase from the actual transposition and structuring of the data.
-    pub fn write_csv(&self, path: &Path) {
+    pub fn write_csv(&self, ctx: &AnalysisContext, path: &Path) {
+        self.csv_buffer(ctx).write_file(path);
+    }
+
+    /// Write the stitched data as an xlsx-workbook with a sheet per section of the csv-file
+    pub fn write_xlsx(&self, ctx: &AnalysisContext, path: &Path) {
+        self.csv_buffer(&ctx.without_comma_float()).write_xlsx(path);
+    }
+
+    /// Collect all stitched data in a buffer with a section per table
+    pub(crate) fn csv_buffer(&self, ctx: &AnalysisContext) -> CsvFileBuffer {
         let mut csv = CsvFileBuffer::new();
 
         csv.add_empty_lines(2);
-        csv.add_toc(10);
+        csv.add_toc(11);
 
-        csv.add_section("List of stitched data-files (numbered) and comments (unnumbered):");
+        csv.add_sheet_section(
+            "Sources",
+            "List of stitched data-files (numbered) and comments (unnumbered):",
+        );
         csv.append(&mut self.sources.csv_output());
+        if !self.columns.is_empty() {
+            csv.add_empty_lines(1);
+            csv.add_line(StitchColumn::csv_header().to_owned());
+            self.columns
+                .iter()
+                .enumerate()
+                .for_each(|(idx, col)| csv.add_line(col.to_csv_string(idx)));
+        }
 
-        csv.add_section("Summary_statistics per Process/Operation");
+        csv.add_sheet_section("Summary", "Summary_statistics per Process/Operation");
         csv.add_line(self.summary_header(&["Process/Operation"], false));
         self.service_operation
             .iter()
             .for_each(|(label, stitched_set)| {
                 csv.add_line(format!(
                     "{label}; {}",
-                    utils::floats_to_string(stitched_set.summary_avg(), " ;")
+                    utils::floats_to_string(ctx, stitched_set.summary_avg(), " ;")
                 ))
             });
 
-        csv.add_section("Slope summary per Process/Operation");
+        csv.add_sheet_section("Slopes", "Slope summary per Process/Operation");
         csv.add_line(self.summary_header(&["Process/Operation"], true));
         self.service_operation
             .iter()
             .for_each(|(label, stitched_set)| {
                 csv.add_line(format!(
                     "{label}; {}",
-                    utils::floats_to_string(stitched_set.summary_slopes(), " ;")
+                    utils::floats_to_string(ctx, stitched_set.summary_slopes(), " ;")
                 ))
             });
 
-        csv.add_section("Scaled Slope summary per Process/Operation");
+        csv.add_sheet_section(
+            "Scaled slopes",
+            "Scaled Slope summary per Process/Operation",
+        );
         csv.add_line(self.summary_header(&["Process/Operation"], true));
         self.service_operation
             .iter()
             .for_each(|(label, stitched_set)| {
                 csv.add_line(format!(
                     "{label}; {}",
-                    utils::floats_to_string(stitched_set.summary_scaled_slopes(), " ;")
+                    utils::floats_to_string(ctx, stitched_set.summary_scaled_slopes(), " ;")
                 ))
             });
 
-        csv.add_section("Last-deviation-scaled summary per Process/Operation");
+        csv.add_sheet_section(
+            "Last deviation",
+            "Last-deviation-scaled summary per Process/Operation",
+        );
         csv.add_line(self.summary_header(&["Process/Operation"], true));
         self.service_operation
             .iter()
             .for_each(|(label, stitched_set)| {
                 csv.add_line(format!(
                     "{label}; {}",
-                    utils::floats_to_string(stitched_set.summary_last_deviation_scaled(), " ;")
+                    utils::floats_to_string(
+                        ctx,
+                        stitched_set.summary_last_deviation_scaled(),
+                        " ;"
+                    )
                 ))
             });
 
-        csv.add_section("Basic statistics per input file");
+        csv.add_sheet_section("Basic", "Basic statistics per input file");
         csv.add_line(self.full_data_header(&["Input-files"]));
-        csv.append(&mut self.basic.csv_output(&[""]));
+        csv.append(&mut self.basic.csv_output(ctx, &[""]));
 
-        csv.add_section("Statistics per Process/Operation combination:");
+        csv.add_sheet_section(
+            "Service-Oper",
+            "Statistics per Process/Operation combination:",
+        );
         csv.add_line(self.full_data_header(&["Process/Operation"]));
         self.service_operation
             .iter()
-            .for_each(|(label, stitched_set)| csv.append(&mut stitched_set.csv_output(&[&label])));
+            .for_each(|(label, stitched_set)| {
+                csv.append(&mut stitched_set.csv_output(ctx, &[&label]))
+            });
 
-        csv.add_section(
+        csv.add_sheet_section(
+            "Call-chain summary",
             "Summary_statistics call-chain decending on count and grouped by Process/Operation",
         );
         csv.add_line(self.summary_header(
@@ -305,12 +559,12 @@ impl Stitched {
                     if ccd.is_leaf { "leaf" } else { "" },
                     po_label,
                     ccd.inbound_process_key,
-                    utils::floats_to_string(ccd.data.summary_avg(), " ;")
+                    utils::floats_to_string(ctx, ccd.data.summary_avg(), " ;")
                 ))
             });
         });
 
-        csv.add_section("Statistics per call-chain (path from the external end-point to the actual Process/Operation (detailled information):");
+        csv.add_sheet_section("Call-chains", "Statistics per call-chain (path from the external end-point to the actual Process/Operation (detailled information):");
         csv.add_line(self.full_data_header(&[
             "Full call-chain (path)",
             "cc_hash",
@@ -321,27 +575,43 @@ impl Stitched {
         ]));
         self.call_chain.iter().for_each(|(po_label, call_chains)| {
             call_chains.iter().for_each(|ccd| {
-                csv.append(&mut ccd.data.csv_output(&[
-                    &ccd.full_key,
-                    &string_hash(&ccd.full_key),
-                    if ccd.rooted { "rooted" } else { "" },
-                    if ccd.is_leaf { "leaf" } else { "" },
-                    &po_label,
-                    &ccd.inbound_process_key,
-                ]))
+                csv.append(&mut ccd.data.csv_output(
+                    ctx,
+                    &[
+                        &ccd.full_key,
+                        &string_hash(&ccd.full_key),
+                        if ccd.rooted { "rooted" } else { "" },
+                        if ccd.is_leaf { "leaf" } else { "" },
+                        &po_label,
+                        &ccd.inbound_process_key,
+                    ],
+                ))
             });
         });
 
-        csv.write_file(path);
+        if !self.cache.is_empty() {
+            csv.add_sheet_section(
+                "Cache",
+                "Cache hit-ratio statistics per call-chain to a caching process:",
+            );
+            csv.add_line(self.full_data_header(&["Call-chain prefix"]));
+            self.cache.iter().for_each(|(label, stitched_set)| {
+                csv.append(&mut stitched_set.csv_output(ctx, &[label]))
+            });
+        }
+
+        csv
     }
 
     /// Add the anomalies on the Process/Operation-level to the 'csv'.
     fn add_process_operation_anomalies(
         &self,
+        ctx: &AnalysisContext,
         csv: &mut CsvFileBuffer,
         pars: &AnomalyParameters,
     ) -> usize {
         let mut num_anomalies = 0;
+        let labels = get_label_list(self);
 
         let metrics: Vec<_> = PROC_OPER_REPORT_ITEMS
             .0
@@ -349,7 +619,10 @@ impl Stitched {
             .map(|por| por.metric)
             .collect();
         metrics.iter().for_each(|metric| {
-            csv.add_section(&format!("{} (Proces/Operation-level)", metric.to_str()));
+            csv.add_sheet_section(
+                &format!("PO {}", metric.to_str()),
+                &format!("{} (Proces/Operation-level)", metric.to_str()),
+            );
 
             csv.add_line(Anomalies::report_stats_line_header_str().to_owned());
 
@@ -361,7 +634,7 @@ impl Stitched {
                     .for_each(|line| {
                         if let Some(anomalies) = line.anomalies(pars) {
                             num_anomalies += 1;
-                            csv.add_line(anomalies.report_stats_line(po, ""))
+                            csv.add_line(anomalies.report_stats_line(ctx, po, "", &labels))
                         }
                     })
             });
@@ -372,8 +645,14 @@ impl Stitched {
     }
 
     /// Add the anomalies on the Process/Operation-level to the 'csv'.
-    fn add_call_chain_anomalies(&self, csv: &mut CsvFileBuffer, pars: &AnomalyParameters) -> usize {
+    fn add_call_chain_anomalies(
+        &self,
+        ctx: &AnalysisContext,
+        csv: &mut CsvFileBuffer,
+        pars: &AnomalyParameters,
+    ) -> usize {
         let mut num_anomalies = 0;
+        let labels = get_label_list(self);
 
         let metrics: Vec<_> = CALL_CHAIN_REPORT_ITEMS
             .0
@@ -381,7 +660,10 @@ impl Stitched {
             .map(|ccr| ccr.metric)
             .collect();
         metrics.iter().for_each(|metric| {
-            csv.add_section(&format!("{} (Call-Chain-level)", metric.to_str()));
+            csv.add_sheet_section(
+                &format!("CC {}", metric.to_str()),
+                &format!("{} (Call-Chain-level)", metric.to_str()),
+            );
 
             self.call_chain.iter().for_each(|(po_label, call_chains)| {
                 csv.add_empty_lines(1);
@@ -395,10 +677,12 @@ impl Stitched {
                         .for_each(|line| {
                             if let Some(anomalies) = line.anomalies(pars) {
                                 num_anomalies += 1;
-                                csv.add_line(
-                                    anomalies
-                                        .report_stats_line(&ccd.full_key, &ccd.inbound_process_key),
-                                )
+                                csv.add_line(anomalies.report_stats_line(
+                                    ctx,
+                                    &ccd.full_key,
+                                    &ccd.inbound_process_key,
+                                    &labels,
+                                ))
                             }
                         })
                 })
@@ -415,7 +699,39 @@ impl Stitched {
     ///    3. The deviation for today is 2x higher than average L1-deviation
     /// The reporting happens per Measure and subsequently per Process and the most important measures are handled first.
     /// On each line all three criteria are shown (with value and with a flag which values exceed the bound)
-    pub fn write_anomalies_csv(&self, path: &Path, pars: &AnomalyParameters) -> usize {
+    pub fn write_anomalies_csv(
+        &self,
+        ctx: &AnalysisContext,
+        path: &Path,
+        pars: &AnomalyParameters,
+    ) -> usize {
+        let (csv, num_anomalies) = self.anomalies_buffer(ctx, pars);
+        if num_anomalies > 0 {
+            csv.write_file(path);
+        }
+        num_anomalies
+    }
+
+    /// Write the anomalies (see 'write_anomalies_csv') as an xlsx-workbook with a sheet per metric and level
+    pub fn write_anomalies_xlsx(
+        &self,
+        ctx: &AnalysisContext,
+        path: &Path,
+        pars: &AnomalyParameters,
+    ) -> usize {
+        let (csv, num_anomalies) = self.anomalies_buffer(&ctx.without_comma_float(), pars);
+        if num_anomalies > 0 {
+            csv.write_xlsx(path);
+        }
+        num_anomalies
+    }
+
+    /// Collect the anomalies in a buffer with a section per metric and level and return the number of anomalies
+    fn anomalies_buffer(
+        &self,
+        ctx: &AnalysisContext,
+        pars: &AnomalyParameters,
+    ) -> (CsvFileBuffer, usize) {
         let mut csv = CsvFileBuffer::new();
 
         let mut num_anomalies = 0;
@@ -423,13 +739,130 @@ impl Stitched {
         csv.add_empty_lines(2);
         csv.add_toc(PROC_OPER_REPORT_ITEMS.0.len() + CALL_CHAIN_REPORT_ITEMS.0.len() + 2);
 
-        num_anomalies += self.add_process_operation_anomalies(&mut csv, pars);
-        num_anomalies += self.add_call_chain_anomalies(&mut csv, pars);
+        num_anomalies += self.add_process_operation_anomalies(ctx, &mut csv, pars);
+        num_anomalies += self.add_call_chain_anomalies(ctx, &mut csv, pars);
 
-        if num_anomalies > 0 {
-            csv.write_file(path);
-        }
-        num_anomalies
+        (csv, num_anomalies)
+    }
+
+    /// Collect the anomalies of all Process/Operation and call-chain lines as records with a severity derived from 'rules'.
+    /// Each record holds the last 'st_num_points' values of the line and the records are ordered on decreasing severity.
+    pub fn anomaly_report(&self, pars: &AnomalyParameters, rules: &SeverityRules) -> AnomalyReport {
+        let mut report = AnomalyReport::default();
+        let labels = get_label_list(self);
+
+        self.service_operation.iter().for_each(|(po, lines)| {
+            lines.0.iter().for_each(|line| {
+                if let Some(anomalies) = line.anomalies(pars) {
+                    report.add_line(
+                        AnomalyLevel::ProcessOperation,
+                        po,
+                        None,
+                        line,
+                        anomalies,
+                        rules,
+                        &labels,
+                        pars.st_num_points,
+                    );
+                }
+            })
+        });
+        self.call_chain.iter().for_each(|(po_label, call_chains)| {
+            call_chains.iter().for_each(|ccd| {
+                ccd.data.0.iter().for_each(|line| {
+                    if let Some(anomalies) = line.anomalies(pars) {
+                        report.add_line(
+                            AnomalyLevel::CallChain,
+                            &ccd.full_key,
+                            Some(po_label),
+                            line,
+                            anomalies,
+                            rules,
+                            &labels,
+                            pars.st_num_points,
+                        );
+                    }
+                })
+            })
+        });
+
+        report.sort();
+        report
+    }
+
+    /// Forecast for each Process/Operation when the metrics cross their 'thresholds' and write these forecasts sorted on time-to-breach.
+    /// The time-to-breach is in days (or in columns when the time-axis is unknown) and the dates are only available for a structured stitch-list.
+    /// Returns the number of forecasted breaches.
+    pub fn write_forecast_csv(
+        &self,
+        ctx: &AnalysisContext,
+        path: &Path,
+        thresholds: &[ForecastThreshold],
+    ) -> usize {
+        let mut forecasts: Vec<_> = self
+            .service_operation
+            .iter()
+            .flat_map(|(po, st_set)| {
+                thresholds.iter().filter_map(move |threshold| {
+                    let line = st_set.get_metric_stitched_line(threshold.metric)?;
+                    let last_value = line.data.last().copied().flatten();
+                    Projection::new(line)?
+                        .forecast(threshold.value, last_value)
+                        .map(|forecast| (po, threshold, line, last_value, forecast))
+                })
+            })
+            .collect();
+        forecasts.sort_by(|a, b| a.4.time_to_breach().total_cmp(&b.4.time_to_breach()));
+
+        let mut csv = CsvFileBuffer::new();
+        csv.add_line("Process/Operation; Metric; Threshold; Last value; Best fit; Time to breach; Earliest; Latest; Breach date; Earliest date; Latest date".to_owned());
+        forecasts
+            .iter()
+            .for_each(|(po, threshold, line, last_value, forecast)| {
+                csv.add_line(self.forecast_line(ctx, po, threshold, line, *last_value, forecast))
+            });
+        csv.write_file(path);
+        forecasts.len()
+    }
+
+    fn forecast_line(
+        &self,
+        ctx: &AnalysisContext,
+        po: &str,
+        threshold: &ForecastThreshold,
+        line: &StitchedLine,
+        last_value: Option<f64>,
+        forecast: &Forecast,
+    ) -> String {
+        let relative = |x: f64| x - forecast.last_x;
+        format!(
+            "{po}; {}; {}; {}; {:?}; {}; {}; {}; {}; {}; {}",
+            threshold.metric.to_str(),
+            utils::format_float(ctx, threshold.value),
+            utils::format_float_opt(ctx, last_value),
+            line.best_fit,
+            utils::format_float(ctx, forecast.time_to_breach()),
+            utils::format_float(ctx, relative(forecast.earliest_x)),
+            utils::format_float_opt(ctx, forecast.latest_x.map(relative)),
+            self.x_to_date(forecast.breach_x),
+            self.x_to_date(forecast.earliest_x),
+            forecast
+                .latest_x
+                .map(|x| self.x_to_date(x))
+                .unwrap_or_default(),
+        )
+    }
+
+    /// The date of the x-value 'x' (days since the first column), which is only known for a structured stitch-list
+    fn x_to_date(&self, x: f64) -> String {
+        self.columns
+            .first()
+            .and_then(|col| {
+                let micros = col.timestamp_micros as f64 + x * MICROS_PER_DAY;
+                chrono::DateTime::from_timestamp_micros(micros as i64)
+            })
+            .map(|dt| dt.naive_utc().format("%Y-%m-%d %H:%M").to_string())
+            .unwrap_or_default()
     }
 
     /// Take the process-operation data out of the record and return as a hashmap
@@ -442,3 +875,327 @@ impl Stitched {
     //     mem::take(&mut self.call_chain).into_iter().collect()
     // }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        super::{
+            anomalies::DEFAULT_ANOMALY_PARS,
+            anomaly_report::{
+                AnomalyKind, AnomalyLevel, AnomalySeverity, SeverityRule, SeverityRules,
+            },
+            call_chain_data::CallChainData,
+            dataseries::process_counts,
+            stitch_list::{StitchSourceItem, StitchSources},
+            stitched_line::StitchedLine,
+            stitched_set::StitchedSet,
+            time_axis::TimeAxis,
+        },
+        StitchParameters, Stitched,
+    };
+    use crate::{
+        stats::call_chain::{CChainStatsKey, CChainStatsValue, Call, CallDirection},
+        view_api::Version,
+        write_stats, AnalysisContext, Metric, StatsRec, StitchList,
+    };
+    use serde::Serialize;
+    use std::{env, fs};
+
+    fn call(service: &str, operation: &str, call_direction: CallDirection) -> Call {
+        Call {
+            service: service.to_owned(),
+            operation: operation.to_owned(),
+            call_direction,
+        }
+    }
+
+    /// Add a call-chain with 'count' calls to its leaf-process, and the inbound calls to the process/operation
+    fn add_chain(stats_rec: &mut StatsRec, call_chain: Vec<Call>, is_leaf: bool, count: usize) {
+        let last = call_chain.last().unwrap().clone();
+        let st = stats_rec.stats.entry(last.service.clone()).or_default();
+        if last.call_direction == CallDirection::Inbound {
+            st.num_received_calls += count;
+            let oper = st.operation.0.entry(last.operation).or_default();
+            oper.count += count;
+            oper.duration_micros.extend(vec![1000; count]);
+        }
+        let cck = CChainStatsKey {
+            call_chain,
+            caching_process: String::new(),
+            is_leaf,
+        };
+        let ccv = CChainStatsValue {
+            count,
+            duration_micros: vec![1000; count],
+            rooted: true,
+            ..Default::default()
+        };
+        st.call_chain.0.insert(cck, ccv);
+    }
+
+    /// An analysis of a gateway that calls a cache, where cache-misses are passed to a db that reads from disk.
+    /// Only the first analysis contains a call to the 'rare' process.
+    fn analysis(idx: usize) -> StatsRec {
+        use CallDirection::{Inbound, Outbound};
+        let mut stats_rec = StatsRec {
+            trace_id: vec![format!("trace_{idx}")],
+            duration_micros: vec![10_000],
+            num_files: 1,
+            caching_processes: vec!["cache".to_owned()],
+            ..Default::default()
+        };
+        let gateway = vec![call("gateway", "get", Inbound)];
+        let to_cache = [
+            gateway.clone(),
+            vec![call("gateway", "get_cache", Outbound)],
+        ]
+        .concat();
+        let cache = [to_cache.clone(), vec![call("cache", "get", Inbound)]].concat();
+        let to_db = [cache.clone(), vec![call("cache", "query", Outbound)]].concat();
+        let db = [to_db.clone(), vec![call("db", "query", Inbound)]].concat();
+        let to_disk = [db.clone(), vec![call("db", "read", Outbound)]].concat();
+        add_chain(&mut stats_rec, gateway, false, 10);
+        add_chain(&mut stats_rec, to_cache, false, 4 + idx);
+        add_chain(&mut stats_rec, cache.clone(), true, 3 + idx);
+        add_chain(&mut stats_rec, cache, false, 1);
+        add_chain(&mut stats_rec, to_db, false, 1);
+        add_chain(&mut stats_rec, db, false, 1);
+        add_chain(&mut stats_rec, to_disk, true, 1);
+        if idx == 0 {
+            add_chain(&mut stats_rec, vec![call("rare", "ping", Inbound)], true, 1);
+        }
+        stats_rec
+    }
+
+    /// Write three analyses (and a gap) without the 'dropped' processes to 'folder' and return the stitch-list
+    fn stitch_list(folder: &str, dropped: &[&str]) -> StitchList {
+        let folder = env::temp_dir().join(folder);
+        fs::create_dir_all(&folder).unwrap();
+        let mut stitch_list = StitchList::new();
+        (0..4).for_each(|idx| {
+            let path = (idx != 2).then(|| {
+                let mut stats_rec = analysis(idx);
+                stats_rec
+                    .stats
+                    .retain(|proc, _| !dropped.contains(&&proc[..]));
+                let path = folder.join(format!("analysis_{idx}.csv"));
+                let path = path.to_str().unwrap();
+                write_stats(&AnalysisContext::default(), path, stats_rec, "json");
+                path.replace(".csv", ".json")
+            });
+            let description = path.clone().unwrap_or_else(|| "% gap".to_owned());
+            stitch_list
+                .lines
+                .0
+                .push(StitchSourceItem::new(Some(idx as u32), &description));
+            stitch_list.paths.push(path.map(|path| path.into()));
+        });
+        stitch_list
+    }
+
+    fn json(value: &impl Serialize) -> serde_json::Value {
+        serde_json::to_value(value).unwrap()
+    }
+
+    fn pars(drop_count: usize, read_threads: usize) -> StitchParameters {
+        StitchParameters {
+            drop_count,
+            read_threads,
+            anomaly_pars: DEFAULT_ANOMALY_PARS,
+        }
+    }
+
+    #[test]
+    fn drop_low_volume_processes_after_reading() {
+        let ctx = AnalysisContext::default();
+        let stitched =
+            Stitched::build(&ctx, stitch_list("jaeger_stats_drop_all", &[]), &pars(3, 1));
+        // the same result as stitching the analyses without the low-volume processes 'db' (3 calls) and 'rare' (1 call)
+        let expected = Stitched::build(
+            &ctx,
+            stitch_list("jaeger_stats_drop_expected", &["db", "rare"]),
+            &pars(0, 1),
+        );
+
+        let keys: Vec<_> = stitched
+            .service_operation
+            .iter()
+            .map(|(key, _)| &key[..])
+            .collect();
+        assert_eq!(keys, ["cache/get", "gateway/get"]);
+        assert_eq!(stitched.cache.len(), 1);
+        assert_eq!(
+            json(&stitched.service_operation),
+            json(&expected.service_operation)
+        );
+        assert_eq!(json(&stitched.call_chain), json(&expected.call_chain));
+        assert_eq!(json(&stitched.cache), json(&expected.cache));
+        assert_eq!(json(&stitched.basic), json(&expected.basic));
+
+        // reading in parallel gives the same stitched dataset
+        let parallel =
+            Stitched::build(&ctx, stitch_list("jaeger_stats_drop_all", &[]), &pars(3, 3));
+        assert_eq!(json(&parallel), json(&stitched));
+    }
+
+    #[test]
+    fn read_reduced_in_parallel() {
+        let stitch_list = stitch_list("jaeger_stats_read_reduced", &[]);
+        let ctx = AnalysisContext::default();
+        let reduce = |stats_rec: StatsRec| {
+            let mut counts = process_counts(&stats_rec);
+            counts.sort();
+            counts
+        };
+
+        let sequential = stitch_list.read_reduced(&ctx, 1, reduce);
+
+        assert_eq!(sequential.len(), 4);
+        assert!(sequential[2].is_none());
+        assert_eq!(sequential[0].as_ref().unwrap().len(), 4);
+        assert_eq!(stitch_list.read_reduced(&ctx, 3, reduce), sequential);
+    }
+
+    fn stitched_set(metric: Metric, data: &[f64]) -> StitchedSet {
+        let data = data.iter().map(|v| Some(*v)).collect();
+        StitchedSet(vec![StitchedLine::compute_new(
+            metric,
+            data,
+            &DEFAULT_ANOMALY_PARS,
+            &TimeAxis::default(),
+        )])
+    }
+
+    #[test]
+    fn anomaly_report_ordered_on_severity() {
+        let flat = [
+            100.0, 101.0, 99.0, 100.0, 102.0, 98.0, 100.0, 101.0, 99.0, 100.0, 101.0, 99.0,
+        ];
+        let mut shifted = flat;
+        shifted[8..].iter_mut().for_each(|v| *v += 50.0);
+        let mut spike = flat;
+        spike[11] = 160.0;
+        let rising: Vec<_> = (0..12).map(|i| 10.0 + 10.0 * i as f64).collect();
+
+        let stitched = Stitched {
+            sources: StitchSources(
+                (1..=12)
+                    .map(|day| StitchSourceItem::new(Some(day - 1), &format!("202310{day:02}")))
+                    .collect(),
+            ),
+            service_operation: vec![
+                (
+                    "health/check".to_owned(),
+                    stitched_set(Metric::Count, &spike),
+                ),
+                (
+                    "gateway/GET:/orders".to_owned(),
+                    stitched_set(Metric::P95Millis, &shifted),
+                ),
+                (
+                    "orders/getOrder".to_owned(),
+                    stitched_set(Metric::Count, &flat),
+                ),
+            ],
+            call_chain: vec![(
+                "db/query".to_owned(),
+                vec![CallChainData {
+                    full_key: "gateway/GET:/orders | db/query".to_owned(),
+                    inbound_process_key: "gateway/GET:/orders | db/query".to_owned(),
+                    rooted: true,
+                    is_leaf: true,
+                    data: stitched_set(Metric::Count, &rising),
+                }],
+            )],
+            ..Default::default()
+        };
+        let rules = SeverityRules {
+            rules: ["critical=p95-millis@^gateway/", "low=*@health"]
+                .iter()
+                .map(|rule| rule.parse::<SeverityRule>().unwrap())
+                .collect(),
+            default: AnomalySeverity::Medium,
+        };
+
+        let report = stitched.anomaly_report(&DEFAULT_ANOMALY_PARS, &rules);
+        let records: Vec<_> = report
+            .anomalies
+            .iter()
+            .map(|rec| (rec.key.as_str(), rec.kind, rec.severity))
+            .collect();
+        assert_eq!(
+            records,
+            [
+                (
+                    "gateway/GET:/orders",
+                    AnomalyKind::ChangePoint,
+                    AnomalySeverity::Critical
+                ),
+                (
+                    "gateway/GET:/orders | db/query",
+                    AnomalyKind::Slope,
+                    AnomalySeverity::Medium
+                ),
+                (
+                    "gateway/GET:/orders | db/query",
+                    AnomalyKind::ShortTermSlope,
+                    AnomalySeverity::Medium
+                ),
+                (
+                    "health/check",
+                    AnomalyKind::ShortTermSlope,
+                    AnomalySeverity::Low
+                ),
+                (
+                    "health/check",
+                    AnomalyKind::L1Deviation,
+                    AnomalySeverity::Low
+                ),
+            ]
+        );
+
+        let change_point = &report.anomalies[0];
+        assert_eq!(change_point.level, AnomalyLevel::ProcessOperation);
+        assert_eq!(change_point.metric, Metric::P95Millis);
+        assert_eq!(change_point.change_point_label.as_deref(), Some("Oct-9"));
+        let chain = &report.anomalies[1];
+        assert_eq!(chain.level, AnomalyLevel::CallChain);
+        assert_eq!(chain.process_operation.as_deref(), Some("db/query"));
+        let deviation = &report.anomalies[4];
+        assert!(deviation.score > DEFAULT_ANOMALY_PARS.l1_dev_bound);
+        assert_eq!(
+            deviation.recent_values,
+            [
+                Some(101.0),
+                Some(99.0),
+                Some(100.0),
+                Some(101.0),
+                Some(160.0)
+            ]
+        );
+
+        assert_eq!(report.count_at_least(AnomalySeverity::Critical), 1);
+        assert_eq!(report.count_at_least(AnomalySeverity::High), 1);
+        assert_eq!(report.count_at_least(AnomalySeverity::Medium), 3);
+        assert_eq!(report.count_at_least(AnomalySeverity::Low), 5);
+    }
+
+    #[test]
+    fn load_json_of_version_0_3() {
+        // written by the stitch-tool of version 0.3 (trimmed to a few lines)
+        let file_name = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/src/stitch/testdata/stitched_v0_3.json"
+        );
+        let stitched = Stitched::from_file(file_name).unwrap();
+
+        assert_eq!(stitched.version, Version::STITCHED);
+        assert_eq!(stitched.sources.0.len(), 1);
+        assert_eq!(stitched.basic.0.len(), 2);
+        assert_eq!(stitched.service_operation[0].0, "cache/GET");
+        assert_eq!(stitched.call_chain[0].1.len(), 1);
+        assert!(stitched.cache.is_empty());
+        assert_eq!(stitched.time_axis.len(), 0);
+        assert_eq!(stitched.change_point_bound, 4.0);
+    }
+}
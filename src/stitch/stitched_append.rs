@@ -0,0 +1,397 @@
+//! Incremental stitching: new analyses are appended as columns to an existing stitched dataset, without re-reading the analyses that were stitched before.
+use super::{
+    anomalies::AnomalyParameters,
+    call_chain_data::CallChainData,
+    stitch_list::{default_label, StitchColumn, StitchSourceItem},
+    stitched_line::StitchedLine,
+    stitched_set::StitchedSet,
+    time_axis::TimeAxis,
+    StitchList, StitchParameters, Stitched,
+};
+use crate::{context::AnalysisContext, view_api::Version, Metric};
+use std::{collections::BTreeMap, error::Error, mem};
+
+/// The number of columns of the existing data, the gap in between and the new data
+struct Layout<'a> {
+    old_len: usize,
+    num_gaps: usize,
+    new_len: usize,
+    pars: &'a AnomalyParameters,
+    time_axis: &'a TimeAxis,
+}
+
+impl Stitched {
+    /// The number of columns (analyses and gaps) of the stitched dataset
+    pub fn num_columns(&self) -> usize {
+        self.sources
+            .0
+            .iter()
+            .filter(|src| src.column.is_some())
+            .count()
+    }
+
+    /// Append the analyses of 'stitch_list' as new columns. The lines of the existing keys are extended, new keys get leading gaps and keys
+    /// that are missing in the new analyses get trailing gaps. Afterwards the regressions (and thus the anomalies) of all lines are recomputed.
+    /// A structured stitch-list can only be appended to a dataset that was built from a structured stitch-list, and its timestamps should follow
+    /// the existing columns. Missing intervals in between become gaps.
+    /// Dropping low-volume processes is not supported, as the call-volume over the existing columns is not known.
+    pub fn append(
+        &mut self,
+        ctx: &AnalysisContext,
+        stitch_list: StitchList,
+        pars: &StitchParameters,
+    ) -> Result<(), Box<dyn Error>> {
+        if pars.drop_count > 0 {
+            Err("A drop-count can not be used when appending, as the call-volume of the existing columns is not known")?;
+        }
+        if self.num_columns() > 0 && self.columns.is_empty() != stitch_list.columns.is_empty() {
+            Err("Can not append a structured stitch-list to a dataset stitched from a line-based stitch-list (or vice versa)")?;
+        }
+        let gaps = self.gap_columns(&stitch_list.columns)?;
+        let new = Self::build(ctx, stitch_list, pars);
+        self.merge(new, gaps, &pars.anomaly_pars);
+        Ok(())
+    }
+
+    /// The gap-columns between the last existing column and the first new column, based on the smallest distance between consecutive columns
+    fn gap_columns(
+        &self,
+        new_columns: &[StitchColumn],
+    ) -> Result<Vec<StitchColumn>, Box<dyn Error>> {
+        let (Some(last), Some(first)) = (self.columns.last(), new_columns.first()) else {
+            return Ok(Vec::new());
+        };
+        let distance = first.timestamp_micros - last.timestamp_micros;
+        if distance <= 0 {
+            Err(format!(
+                "The first new column '{}' should be later than the last column '{}'",
+                first.label, last.label
+            ))?;
+        }
+        let interval = self
+            .columns
+            .windows(2)
+            .chain(new_columns.windows(2))
+            .map(|w| w[1].timestamp_micros - w[0].timestamp_micros)
+            .filter(|micros| *micros > 0)
+            .min()
+            .unwrap_or(distance);
+        let num_gaps = (distance as f64 / interval as f64).round() as i64 - 1;
+        Ok((1..=num_gaps)
+            .filter_map(|i| {
+                let timestamp_micros = last.timestamp_micros + i * interval;
+                chrono::DateTime::from_timestamp_micros(timestamp_micros).map(|dt| StitchColumn {
+                    label: default_label(&dt.naive_utc()),
+                    timestamp_micros,
+                    tags: BTreeMap::new(),
+                    filled: false,
+                })
+            })
+            .collect())
+    }
+
+    /// Merge the stitched dataset 'new' (with the 'gaps' in between) into this dataset and recompute all lines
+    fn merge(&mut self, mut new: Stitched, gaps: Vec<StitchColumn>, pars: &AnomalyParameters) {
+        let old_len = self.num_columns();
+        let new_len = new.num_columns();
+        let num_gaps = gaps.len();
+
+        let offset = (old_len + num_gaps) as u32;
+        self.sources
+            .0
+            .extend(gaps.iter().enumerate().map(|(idx, gap)| {
+                let description =
+                    match chrono::DateTime::from_timestamp_micros(gap.timestamp_micros) {
+                        Some(dt) => format!("% gap at {}", dt.naive_utc()),
+                        None => format!("% gap at {}", gap.label),
+                    };
+                StitchSourceItem::new(Some((old_len + idx) as u32), &description)
+            }));
+        self.sources
+            .0
+            .extend(mem::take(&mut new.sources.0).into_iter().map(|mut src| {
+                src.column = src.column.map(|col| col + offset);
+                src
+            }));
+
+        self.columns.extend(gaps);
+        self.columns.append(&mut new.columns);
+        let time_axis = TimeAxis::from_columns(&self.columns);
+
+        let layout = Layout {
+            old_len,
+            num_gaps,
+            new_len,
+            pars,
+            time_axis: &time_axis,
+        };
+        let merge_set = |_key: &String, old, new| merge_sets(old, new, &layout);
+        self.basic = merge_sets(Some(mem::take(&mut self.basic)), Some(new.basic), &layout);
+        self.service_operation = merge_keyed(
+            mem::take(&mut self.service_operation),
+            new.service_operation,
+            merge_set,
+        );
+        self.cache = merge_keyed(mem::take(&mut self.cache), new.cache, merge_set);
+        self.call_chain = merge_keyed(
+            mem::take(&mut self.call_chain),
+            new.call_chain,
+            |_proc_oper, old, new| {
+                let keyed = |ccds: Option<Vec<CallChainData>>| -> Vec<_> {
+                    ccds.unwrap_or_default()
+                        .into_iter()
+                        .map(|ccd| (ccd.full_key.clone(), ccd))
+                        .collect()
+                };
+                let mut call_chains: Vec<_> =
+                    merge_keyed(keyed(old), keyed(new), |_full_key, old, new| {
+                        merge_call_chain(old, new, &layout)
+                    })
+                    .into_iter()
+                    .map(|(_full_key, ccd)| ccd)
+                    .collect();
+                // the most frequent call-chains on top, as in 'CCReportItems::get_keys'
+                call_chains.sort_by(|a, b| total_count(b).total_cmp(&total_count(a)));
+                call_chains
+            },
+        );
+
+        self.version = Version::STITCHED;
+        self.time_axis = time_axis.to_vec();
+        self.seasonal_period = pars.seasonal_period;
+        self.change_point_bound = pars.change_point_bound;
+    }
+}
+
+/// Merge the 'old' and 'new' values per key. The keyed lists of a stitched dataset are sorted on key, so a key that only exists in 'new'
+/// is inserted at its sorted position.
+fn merge_keyed<K: Ord, V>(
+    old: Vec<(K, V)>,
+    new: Vec<(K, V)>,
+    merge: impl Fn(&K, Option<V>, Option<V>) -> V,
+) -> Vec<(K, V)> {
+    let mut merged: BTreeMap<K, (Option<V>, Option<V>)> = BTreeMap::new();
+    old.into_iter()
+        .for_each(|(key, val)| merged.entry(key).or_default().0 = Some(val));
+    new.into_iter()
+        .for_each(|(key, val)| merged.entry(key).or_default().1 = Some(val));
+    merged
+        .into_iter()
+        .map(|(key, (old_val, new_val))| {
+            let val = merge(&key, old_val, new_val);
+            (key, val)
+        })
+        .collect()
+}
+
+/// Merge the lines of two stitched sets per metric and recompute the regressions on the concatenated data.
+/// A metric can occur multiple times in a set, so lines are matched on the metric and its occurrence.
+/// The lines keep the order of the report-items of 'old', followed by the lines that only exist in 'new'.
+fn merge_sets(old: Option<StitchedSet>, new: Option<StitchedSet>, layout: &Layout) -> StitchedSet {
+    let keyed = |set: Option<StitchedSet>| -> Vec<_> {
+        let lines = set.map(|set| set.0).unwrap_or_default();
+        let mut occurrences = BTreeMap::new();
+        lines
+            .into_iter()
+            .map(|line| {
+                let occurrence = occurrences.entry(line.metric).or_insert(0);
+                *occurrence += 1;
+                ((line.metric, *occurrence), line)
+            })
+            .collect()
+    };
+    let merge_line = |metric: Metric, old: Option<StitchedLine>, new: Option<StitchedLine>| {
+        let mut data = old.map(|line| line.data).unwrap_or_default();
+        data.resize(layout.old_len + layout.num_gaps, None);
+        data.extend(new.map(|line| line.data).unwrap_or_default());
+        data.resize(layout.old_len + layout.num_gaps + layout.new_len, None);
+        StitchedLine::compute_new(metric, data, layout.pars, layout.time_axis)
+    };
+    let mut new = keyed(new);
+    let mut lines: Vec<_> = keyed(old)
+        .into_iter()
+        .map(|(key, old_line)| {
+            let new_line = new
+                .iter()
+                .position(|(new_key, _)| *new_key == key)
+                .map(|pos| new.remove(pos).1);
+            merge_line(key.0, Some(old_line), new_line)
+        })
+        .collect();
+    lines.extend(
+        new.into_iter()
+            .map(|((metric, _occurrence), new_line)| merge_line(metric, None, Some(new_line))),
+    );
+    StitchedSet(lines)
+}
+
+/// The total count of a call-chain over all columns (the first line is the count)
+fn total_count(ccd: &CallChainData) -> f64 {
+    ccd.data
+        .0
+        .first()
+        .map(|line| line.data.iter().flatten().sum())
+        .unwrap_or(0.0)
+}
+
+fn merge_call_chain(
+    old: Option<CallChainData>,
+    new: Option<CallChainData>,
+    layout: &Layout,
+) -> CallChainData {
+    let (mut base, old_data, new_data) = match (old, new) {
+        (Some(mut old), new) => {
+            let old_data = mem::take(&mut old.data);
+            (old, Some(old_data), new.map(|ccd| ccd.data))
+        }
+        (None, Some(mut new)) => {
+            let new_data = mem::take(&mut new.data);
+            (new, None, Some(new_data))
+        }
+        (None, None) => unreachable!("a key exists in the old or the new data"),
+    };
+    base.data = merge_sets(old_data, new_data, layout);
+    base
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{
+        anomalies::DEFAULT_ANOMALY_PARS,
+        stitch_list::{StitchColumn, StitchSourceItem},
+        stitched_line::StitchedLine,
+        stitched_set::StitchedSet,
+        time_axis::TimeAxis,
+        Stitched,
+    };
+    use crate::{AnalysisContext, Metric, StitchList, StitchParameters};
+
+    const DAY: i64 = 86_400_000_000;
+
+    /// a column of an analysis at 'day' days after 2023-10-01
+    fn column(day: i64) -> StitchColumn {
+        StitchColumn {
+            label: format!("day {day}"),
+            timestamp_micros: 1_696_118_400_000_000 + day * DAY,
+            filled: true,
+            ..Default::default()
+        }
+    }
+
+    fn stitched(keys: &[(&str, Vec<Option<f64>>)], num_columns: usize) -> Stitched {
+        let mut stitched = Stitched::default();
+        stitched.sources.0 = (0..num_columns)
+            .map(|col| StitchSourceItem::new(Some(col as u32), &format!("d{col}.json")))
+            .collect();
+        stitched.service_operation = keys
+            .iter()
+            .map(|(key, data)| {
+                let line = StitchedLine::compute_new(
+                    Metric::Count,
+                    data.clone(),
+                    &DEFAULT_ANOMALY_PARS,
+                    &TimeAxis::default(),
+                );
+                (key.to_string(), StitchedSet(vec![line]))
+            })
+            .collect();
+        stitched
+    }
+
+    #[test]
+    fn append_extends_and_pads_lines() {
+        let mut old = stitched(
+            &[
+                ("a", vec![Some(1.0), Some(2.0)]),
+                ("b", vec![Some(5.0), None]),
+            ],
+            2,
+        );
+        let new = stitched(
+            &[
+                ("c", vec![Some(7.0)]),
+                ("a", vec![Some(3.0)]),
+                ("ab", vec![Some(4.0)]),
+            ],
+            1,
+        );
+
+        old.merge(new, Vec::new(), &DEFAULT_ANOMALY_PARS);
+
+        let data: Vec<_> = old
+            .service_operation
+            .iter()
+            .map(|(key, set)| (&key[..], set.0[0].data.clone()))
+            .collect();
+        assert_eq!(
+            data,
+            [
+                ("a", vec![Some(1.0), Some(2.0), Some(3.0)]),
+                // a new key is inserted at its sorted position
+                ("ab", vec![None, None, Some(4.0)]),
+                ("b", vec![Some(5.0), None, None]),
+                ("c", vec![None, None, Some(7.0)]),
+            ]
+        );
+        let lin_regr = old.service_operation[0].1 .0[0].lin_regr.as_ref().unwrap();
+        assert!((lin_regr.slope - 1.0).abs() < 1e-9);
+        let columns: Vec<_> = old.sources.0.iter().map(|src| src.column).collect();
+        assert_eq!(columns, [Some(0), Some(1), Some(2)]);
+    }
+
+    #[test]
+    fn gap_columns_follow_the_smallest_interval() {
+        let mut old = stitched(&[], 2);
+        old.columns = vec![column(0), column(1)];
+
+        let gaps = old.gap_columns(&[column(4), column(6)]).unwrap();
+
+        let days: Vec<_> = gaps
+            .iter()
+            .map(|gap| (gap.timestamp_micros - column(0).timestamp_micros) / DAY)
+            .collect();
+        assert_eq!(days, [2, 3]);
+        assert_eq!(gaps[0].label, "2023-10-03");
+        assert!(gaps.iter().all(|gap| !gap.filled));
+        // consecutive columns do not get a gap, and new columns should follow the existing columns
+        assert!(old.gap_columns(&[column(2)]).unwrap().is_empty());
+        assert!(old.gap_columns(&[column(1)]).is_err());
+    }
+
+    #[test]
+    fn time_axis_after_appending_with_gaps() {
+        let mut old = stitched(&[("a", vec![Some(1.0), Some(2.0)])], 2);
+        old.columns = vec![column(0), column(1)];
+        old.time_axis = vec![0.0, 1.0];
+        let mut new = stitched(&[("a", vec![Some(5.0)])], 1);
+        new.columns = vec![column(4)];
+
+        let gaps = old.gap_columns(&new.columns).unwrap();
+        old.merge(new, gaps, &DEFAULT_ANOMALY_PARS);
+
+        assert_eq!(old.time_axis, [0.0, 1.0, 2.0, 3.0, 4.0]);
+        assert_eq!(old.num_columns(), 5);
+        let line = &old.service_operation[0].1 .0[0];
+        assert_eq!(line.data, [Some(1.0), Some(2.0), None, None, Some(5.0)]);
+        let lin_regr = line.lin_regr.as_ref().unwrap();
+        assert!((lin_regr.slope - 1.0).abs() < 1e-9);
+
+        // a line-based dataset keeps the column-index as x-axis
+        let mut old = stitched(&[("a", vec![Some(1.0)])], 1);
+        old.merge(stitched(&[], 1), Vec::new(), &DEFAULT_ANOMALY_PARS);
+        assert!(old.time_axis.is_empty());
+    }
+
+    #[test]
+    fn append_rejects_a_drop_count() {
+        let mut old = stitched(&[("a", vec![Some(1.0)])], 1);
+        let pars = StitchParameters {
+            drop_count: 1,
+            read_threads: 1,
+            anomaly_pars: DEFAULT_ANOMALY_PARS,
+        };
+        let result = old.append(&AnalysisContext::default(), StitchList::default(), &pars);
+        assert!(result.is_err());
+        assert_eq!(old.num_columns(), 1);
+    }
+}
@@ -1,5 +1,11 @@
-use super::anomalies::{Anomalies, AnomalyParameters};
+use super::{
+    anomalies::{Anomalies, AnomalyParameters},
+    change_point::{self, ChangePoint},
+    seasonal::SeasonalModel,
+    time_axis::TimeAxis,
+};
 use crate::{
+    context::AnalysisContext,
     utils::{self, ExponentialRegression, LinearRegression},
     Metric,
 };
@@ -43,6 +49,12 @@ pub struct StitchedLine {
     pub exp_regr: Option<ExponentialRegression>,
     pub best_fit: BestFit,
     pub st_line: Option<ShortTermStitchedLine>,
+    /// The x-values of the data-points used by the regression (not stored, as the time-axis is part of the stitched dataset)
+    #[serde(skip)]
+    pub time_axis: TimeAxis,
+    /// The seasonal model, which is recomputed from the data when the stitched dataset is loaded
+    #[serde(skip)]
+    pub seasonal: Option<SeasonalModel>,
 }
 
 impl StitchedLine {
@@ -66,14 +78,24 @@ impl StitchedLine {
             exp_regr,
             best_fit,
             st_line,
+            time_axis: TimeAxis::default(),
+            seasonal: None,
         }
     }
 
     /// compute a data-series (StitchedLine) including linear regression, the average of the data and possibly a short-term line for the last few datapoint.
     /// The Short-Term line is used to detect anomalies. However, this is only computed if the full dataset significantly exceed the size of the ST
-    pub fn compute_new(metric: Metric, data: Vec<Option<f64>>, pars: &AnomalyParameters) -> Self {
-        let lin_regr = LinearRegression::new(&data);
-        let exp_regr = ExponentialRegression::new(&data);
+    /// The regression uses the 'time_axis' as x-values, so the slopes and growth are per day when the time-axis is known.
+    pub fn compute_new(
+        metric: Metric,
+        data: Vec<Option<f64>>,
+        pars: &AnomalyParameters,
+        time_axis: &TimeAxis,
+    ) -> Self {
+        let x_axis = time_axis.values(data.len());
+        let lin_regr = LinearRegression::new_on_axis(&data, &x_axis);
+        let exp_regr = ExponentialRegression::new_on_axis(&data, &x_axis);
+        let seasonal = SeasonalModel::new(&data, &x_axis, pars.seasonal_period);
         let best_fit = match (&lin_regr, &exp_regr) {
             (None, None) => BestFit::None,
             (Some(_), None) => BestFit::LinRegr,
@@ -88,15 +110,14 @@ impl StitchedLine {
         };
 
         let st_line = if data.len() >= MIN_POINTS_FOR_ST_MULTIPLIER * pars.st_num_points {
-            let st_data: Vec<_> = data
-                .iter()
-                .skip(data.len() - pars.st_num_points)
-                .copied()
-                .collect();
+            let skip = data.len() - pars.st_num_points;
+            let st_data: Vec<_> = data.iter().skip(skip).copied().collect();
             // Only if the Lineair regression is possible a ShortTermStitchedLine is returne
-            LinearRegression::new(&st_data).map(|lr| ShortTermStitchedLine {
-                data: st_data,
-                lin_regr: lr,
+            LinearRegression::new_on_axis(&st_data, &x_axis[skip..]).map(|lr| {
+                ShortTermStitchedLine {
+                    data: st_data,
+                    lin_regr: lr,
+                }
             })
         } else {
             None
@@ -107,16 +128,35 @@ impl StitchedLine {
             .iter()
             .fold(0, |cnt, val| if val.is_some() { cnt + 1 } else { cnt });
 
-        Self::new(
-            metric,
-            data,
-            num_filled_columns,
-            data_avg,
-            lin_regr,
-            exp_regr,
-            best_fit,
-            st_line,
-        )
+        Self {
+            time_axis: time_axis.clone(),
+            seasonal,
+            ..Self::new(
+                metric,
+                data,
+                num_filled_columns,
+                data_avg,
+                lin_regr,
+                exp_regr,
+                best_fit,
+                st_line,
+            )
+        }
+    }
+
+    /// The x-value of the data-point 'idx' as used in the regression
+    pub fn x(&self, idx: usize) -> f64 {
+        self.time_axis.x(idx)
+    }
+
+    /// Attach the time-axis and recompute the seasonal model, as these are not stored per line.
+    pub fn restore(&mut self, time_axis: &TimeAxis, seasonal_period: usize) {
+        self.time_axis = time_axis.clone();
+        self.seasonal = SeasonalModel::new(
+            &self.data,
+            &time_axis.values(self.data.len()),
+            seasonal_period,
+        );
     }
 
     pub fn anomalies(&self, pars: &AnomalyParameters) -> Option<Anomalies> {
@@ -146,12 +186,17 @@ impl StitchedLine {
 
     /// Compute a scaled slope by moving the average value to 0.5.
     /// This will scale down the slope as if data stems from the interval [0,1], provided data has a symetric distribution.
+    /// With a seasonal model the slope of the de-seasonalised trend is used.
     pub fn scaled_slope(&self) -> Option<f64> {
         self.data_avg.and_then(|avg| {
             if avg.abs() > 1e-100 {
-                self.lin_regr
-                    .as_ref()
-                    .map(|lin_reg| lin_reg.slope / (2.0 * avg))
+                match &self.seasonal {
+                    Some(seasonal) => Some(seasonal.trend.slope / (2.0 * avg)),
+                    None => self
+                        .lin_regr
+                        .as_ref()
+                        .map(|lin_reg| lin_reg.slope / (2.0 * avg)),
+                }
             } else {
                 None
             }
@@ -161,21 +206,45 @@ impl StitchedLine {
     /// Compute a scaled slope on the short term data by moving the average value to 0.5.
     /// This will scale down the slope as if data stems from the interval [0,1], provided data has a symetric distribution.
     /// The average of the full dataset is used for the scaling, and not the average of the short-term daata.
+    /// With a seasonal model the slope is computed over the de-seasonalised short-term data.
     pub fn scaled_st_slope(&self) -> Option<f64> {
         self.data_avg.and_then(|avg| {
             if avg.abs() > 1e-100 {
-                self.st_line
-                    .as_ref()
-                    .map(|stl| stl.lin_regr.slope / (2.0 * avg))
+                self.st_line.as_ref().and_then(|stl| {
+                    let slope = match &self.seasonal {
+                        Some(seasonal) => {
+                            let offset = self.data.len() - stl.data.len();
+                            let x_axis = &self.time_axis.values(self.data.len())[offset..];
+                            LinearRegression::new_on_axis(
+                                &seasonal.deseasonalise(&stl.data, offset),
+                                x_axis,
+                            )?
+                            .slope
+                        }
+                        None => stl.lin_regr.slope,
+                    };
+                    Some(slope / (2.0 * avg))
+                })
             } else {
                 None
             }
         })
     }
 
+    /// The deviation of the last value scaled by the L1-deviation. With a seasonal model this is the last residual scaled by the average absolute residual.
     pub fn last_deviation_scaled(&self) -> Option<f64> {
+        if let Some(seasonal) = &self.seasonal {
+            return self.last_seasonal_deviation().and_then(|deviation| {
+                if seasonal.trend.L1_deviation.abs() > 1e-100 {
+                    Some(deviation / seasonal.trend.L1_deviation)
+                } else {
+                    None
+                }
+            });
+        }
         self.lin_regr.as_ref().and_then(|lr| {
-            lr.get_deviation(&self.data, self.data.len() - 1)
+            let last = self.data.len() - 1;
+            lr.get_deviation(self.data[last], self.x(last))
                 .and_then(|deviation| {
                     if lr.L1_deviation.abs() > 1e-100 {
                         Some(deviation / lr.L1_deviation)
@@ -186,9 +255,52 @@ impl StitchedLine {
         })
     }
 
+    /// The change-points with a score of at least 'bound', ordered on column-index.
+    /// The detection runs on the residuals of the linear regression (or of the seasonal model), such that a steady trend or a seasonal pattern
+    /// is not detected as a shift. The model-value is added back to the levels before and after a change-point, so these are levels of the metric.
+    pub fn change_points(&self, bound: f64) -> Vec<ChangePoint> {
+        if bound <= 0.0 {
+            return Vec::new();
+        }
+        let model = |idx: usize| match (&self.seasonal, &self.lin_regr) {
+            (Some(seasonal), _) => Some(seasonal.predict(idx, self.x(idx))),
+            (None, Some(lr)) => Some(lr.predict(self.x(idx))),
+            (None, None) => None,
+        };
+        if model(0).is_none() {
+            return Vec::new();
+        }
+        let residuals: Vec<_> = self
+            .data
+            .iter()
+            .enumerate()
+            .map(|(idx, y)| y.zip(model(idx)).map(|(y, m)| y - m))
+            .collect();
+        let mut change_points = change_point::detect_change_points(
+            &residuals,
+            bound,
+            self.data_avg.unwrap_or_default(),
+        );
+        change_points.iter_mut().for_each(|cp| {
+            let level = model(cp.idx).unwrap_or_default();
+            cp.before += level;
+            cp.after += level;
+        });
+        change_points
+    }
+
+    /// The residual of the last value with respect to the seasonal model
+    fn last_seasonal_deviation(&self) -> Option<f64> {
+        self.seasonal.as_ref().and_then(|seasonal| {
+            let last = self.data.len() - 1;
+            self.data[last].map(|val| val - seasonal.predict(last, self.x(last)))
+        })
+    }
+
     fn last_exp_model_deviation(&self) -> Option<f64> {
         self.exp_regr.as_ref().and_then(|er| {
-            self.data[self.data.len() - 1].map(|val| val - er.predict((self.data.len() - 1) as f64))
+            let last = self.data.len() - 1;
+            self.data[last].map(|val| val - er.predict(self.x(last)))
         })
     }
 
@@ -207,13 +319,13 @@ impl StitchedLine {
             })
             .collect::<Vec<_>>()
             .join("; ");
-        format!("label; NUM_FILLED; {columns}; ; ; best_fit; slope; y_intercept; r2; L1_norm; scaled_slope; last_deviation; periodic_growth; exp_a, exp_b; exp_r2; exp_last_dev;")
+        format!("label; NUM_FILLED; {columns}; ; ; best_fit; slope; y_intercept; r2; L1_norm; scaled_slope; last_deviation; periodic_growth; exp_a, exp_b; exp_r2; exp_last_dev; seasonal_period; seasonal_slope; seasonal_L1_norm; seasonal_last_dev;")
     }
 
     /// Show the current line as a string in the csv-format with a ';' separator
-    pub fn to_csv_string(&self, prefixes: &[&str]) -> String {
+    pub fn to_csv_string(&self, ctx: &AnalysisContext, prefixes: &[&str]) -> String {
         // Produce the CSV_output
-        let values = utils::floats_ref_to_string(&self.data, "; ");
+        let values = utils::floats_ref_to_string(ctx, &self.data, "; ");
         let header = prefixes.join("; ");
 
         let (exp_a, exp_b, exp_r2) = match &self.exp_regr {
@@ -221,23 +333,35 @@ impl StitchedLine {
             None => (None, None, None),
         };
 
+        let (seasonal_period, seasonal_slope, seasonal_l1) = match &self.seasonal {
+            Some(sm) => (
+                sm.period.to_string(),
+                Some(sm.trend.slope),
+                Some(sm.trend.L1_deviation),
+            ),
+            None => (String::new(), None, None),
+        };
+
         if let Some(lr) = &self.lin_regr {
             format!(
-                "{header}; {}; {}; {values}; ; ; {}; {}; {}; {}; {}; {}; {}; {}; {}; {}; {}; {};",
+                "{header}; {}; {}; {values}; ; ; {}; {}; {}; {}; {}; {}; {}; {}; {}; {}; {}; {}; {seasonal_period}; {}; {}; {};",
                 self.metric.to_str(),
                 self.num_filled_columns,
                 self.best_fit.to_string(),
-                utils::format_float(lr.slope),
-                utils::format_float(lr.y_intercept),
-                utils::format_float(lr.R_squared),
-                utils::format_float(lr.L1_deviation),
-                utils::format_float_opt(self.scaled_slope()),
-                utils::format_float_opt(self.last_deviation_scaled()),
-                utils::format_float_opt(self.periodic_growth()),
-                utils::format_float_opt(exp_a),
-                utils::format_float_opt(exp_b),
-                utils::format_float_opt(exp_r2),
-                utils::format_float_opt(self.last_exp_model_deviation())
+                utils::format_float(ctx, lr.slope),
+                utils::format_float(ctx, lr.y_intercept),
+                utils::format_float(ctx, lr.R_squared),
+                utils::format_float(ctx, lr.L1_deviation),
+                utils::format_float_opt(ctx, self.scaled_slope()),
+                utils::format_float_opt(ctx, self.last_deviation_scaled()),
+                utils::format_float_opt(ctx, self.periodic_growth()),
+                utils::format_float_opt(ctx, exp_a),
+                utils::format_float_opt(ctx, exp_b),
+                utils::format_float_opt(ctx, exp_r2),
+                utils::format_float_opt(ctx, self.last_exp_model_deviation()),
+                utils::format_float_opt(ctx, seasonal_slope),
+                utils::format_float_opt(ctx, seasonal_l1),
+                utils::format_float_opt(ctx, self.last_seasonal_deviation())
             )
         } else {
             format!(
@@ -248,8 +372,67 @@ impl StitchedLine {
         }
     }
 
+    /// The seasonal component of each column as a csv-line (only when a seasonal model is available)
+    pub fn seasonal_csv_string(&self, ctx: &AnalysisContext, prefixes: &[&str]) -> Option<String> {
+        self.seasonal.as_ref().map(|seasonal| {
+            let components: Vec<_> = (0..self.data.len())
+                .map(|idx| Some(seasonal.component(idx)))
+                .collect();
+            format!(
+                "{}; {}:seasonal; ; {}; ; ;",
+                prefixes.join("; "),
+                self.metric.to_str(),
+                utils::floats_ref_to_string(ctx, &components, "; ")
+            )
+        })
+    }
+
     // /// Get a subset of selected data-points for each of the stiched lines in the stitched set.
     // /// assume that the size of the selection was checked by the upstream process (the caller).
     // pub fn get_selection(&self, selection: &Vec<bool>) -> Self {
     // }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::StitchedLine;
+    use crate::{
+        stitch::{anomalies::DEFAULT_ANOMALY_PARS, time_axis::TimeAxis},
+        Metric,
+    };
+
+    fn line(data: Vec<Option<f64>>) -> StitchedLine {
+        StitchedLine::compute_new(
+            Metric::Count,
+            data,
+            &DEFAULT_ANOMALY_PARS,
+            &TimeAxis::default(),
+        )
+    }
+
+    #[test]
+    fn linear_trend_has_no_change_points() {
+        let trend = line((0..30).map(|i| Some(100.0 + 2.0 * i as f64)).collect());
+        assert!(trend.change_points(4.0).is_empty());
+    }
+
+    #[test]
+    fn shift_on_top_of_a_trend_is_detected() {
+        let noise = [0.4, -0.3, 0.1, -0.2, 0.3, -0.4, 0.2, 0.0, -0.1, 0.3];
+        let data = (0..30)
+            .map(|i| {
+                let shift = if i < 24 { 0.0 } else { 20.0 };
+                Some(100.0 + 2.0 * i as f64 + shift + noise[i % noise.len()])
+            })
+            .collect();
+
+        let cps = line(data).change_points(4.0);
+
+        assert_eq!(cps.iter().map(|cp| cp.idx).collect::<Vec<_>>(), [24]);
+        // the trend is at 148 in column 24, which shifts to 168
+        assert!((cps[0].before - 148.0).abs() < 1.0, "{cps:?}");
+        assert!((cps[0].after - 168.0).abs() < 1.0, "{cps:?}");
+        // a bound of 0.0 disables the detection
+        assert!(line(vec![Some(1.0); 10]).change_points(0.0).is_empty());
+    }
+}
@@ -0,0 +1,97 @@
+//! Export of a stitched dataset as a long-format parquet table (one row per source column, key and metric)
+use super::{Stitched, StitchedSet};
+use crate::utils::{self, float_column, int_column, string_column};
+use arrow::record_batch::RecordBatch;
+use std::{collections::HashMap, error::Error};
+
+/// The rows of the long-format table
+#[derive(Default)]
+struct LongTable {
+    column: Vec<i64>,
+    source: Vec<String>,
+    kind: Vec<&'static str>,
+    service_oper: Vec<String>,
+    key: Vec<String>,
+    metric: Vec<&'static str>,
+    value: Vec<Option<f64>>,
+}
+
+impl LongTable {
+    /// add all filled values of the stitched set (empty columns are skipped)
+    fn add(
+        &mut self,
+        labels: &HashMap<usize, &str>,
+        kind: &'static str,
+        service_oper: &str,
+        key: &str,
+        stitched_set: &StitchedSet,
+    ) {
+        stitched_set.0.iter().for_each(|line| {
+            line.data
+                .iter()
+                .enumerate()
+                .filter(|(_, value)| value.is_some())
+                .for_each(|(col, value)| {
+                    self.column.push(col as i64);
+                    self.source
+                        .push(labels.get(&col).copied().unwrap_or_default().to_owned());
+                    self.kind.push(kind);
+                    self.service_oper.push(service_oper.to_owned());
+                    self.key.push(key.to_owned());
+                    self.metric.push(line.metric.to_str());
+                    self.value.push(*value);
+                })
+        })
+    }
+
+    fn into_record_batch(self) -> Result<RecordBatch, Box<dyn Error>> {
+        Ok(RecordBatch::try_from_iter(vec![
+            ("column", int_column(self.column)),
+            ("source", string_column(self.source)),
+            ("kind", string_column(self.kind)),
+            ("service_oper", string_column(self.service_oper)),
+            ("key", string_column(self.key)),
+            ("metric", string_column(self.metric)),
+            ("value", float_column(self.value)),
+        ])?)
+    }
+}
+
+impl Stitched {
+    /// Write the stitched dataset as a long-format parquet-table with columns: column, source, kind, service_oper, key, metric and value.
+    /// The kind is one of 'basic', 'service_oper', 'call_chain' or 'cache'.
+    pub fn to_parquet(&self, file_name: &str) {
+        let labels: HashMap<_, _> = self
+            .sources
+            .0
+            .iter()
+            .filter_map(|src| {
+                src.column
+                    .map(|col| (col as usize, src.description.as_str()))
+            })
+            .collect();
+
+        let mut table = LongTable::default();
+        table.add(&labels, "basic", "", "", &self.basic);
+        self.service_operation
+            .iter()
+            .for_each(|(po, stitched_set)| {
+                table.add(&labels, "service_oper", po, po, stitched_set)
+            });
+        self.call_chain.iter().for_each(|(po, call_chains)| {
+            call_chains
+                .iter()
+                .for_each(|ccd| table.add(&labels, "call_chain", po, &ccd.full_key, &ccd.data))
+        });
+        self.cache.iter().for_each(|(prefix, stitched_set)| {
+            table.add(&labels, "cache", "", prefix, stitched_set)
+        });
+
+        if let Err(err) = table
+            .into_record_batch()
+            .and_then(|batch| utils::write_parquet(file_name, &batch))
+        {
+            panic!("failed to write '{file_name}' as parquet!! {err:?}");
+        }
+    }
+}
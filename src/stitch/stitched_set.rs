@@ -1,6 +1,6 @@
-use crate::Metric;
+use crate::{context::AnalysisContext, Metric};
 
-use super::{anomalies::DEFAULT_ANOMALY_PARS, stitched_line::StitchedLine};
+use super::{anomalies::AnomalyParameters, stitched_line::StitchedLine, time_axis::TimeAxis};
 use std::iter;
 
 use serde::{Deserialize, Serialize};
@@ -11,10 +11,13 @@ use serde::{Deserialize, Serialize};
 pub struct StitchedSet(pub Vec<StitchedLine>);
 
 impl StitchedSet {
-    pub fn csv_output(&self, prefixes: &[&str]) -> Vec<String> {
+    pub fn csv_output(&self, ctx: &AnalysisContext, prefixes: &[&str]) -> Vec<String> {
         self.0
             .iter()
-            .map(|line| line.to_csv_string(prefixes))
+            .flat_map(|line| {
+                iter::once(line.to_csv_string(ctx, prefixes))
+                    .chain(line.seasonal_csv_string(ctx, prefixes))
+            })
             .collect()
     }
 
@@ -75,7 +78,12 @@ impl StitchedSet {
 
     /// Get a subset of selected data-points for each of the stiched lines in the stitched set, or None if the selection does not contain any f64 values (only None)
     /// assume that the size of the selection was checked by the upstream process (the caller).
-    pub fn get_selection(&self, selection: &[bool]) -> Option<Self> {
+    pub fn get_selection(
+        &self,
+        selection: &[bool],
+        time_axis: &TimeAxis,
+        pars: &AnomalyParameters,
+    ) -> Option<Self> {
         let data: Vec<_> = self
             .0
             .iter()
@@ -92,7 +100,7 @@ impl StitchedSet {
             Some(StitchedSet(
                 data.into_iter()
                     .map(|(lbl, data, _)| {
-                        StitchedLine::compute_new(lbl.to_owned(), data, &DEFAULT_ANOMALY_PARS)
+                        StitchedLine::compute_new(lbl.to_owned(), data, pars, time_axis)
                     })
                     .collect(),
             ))
@@ -0,0 +1,111 @@
+//! Export of a stitched dataset to a SQLite database with the time-series in normalised tables
+use super::{Stitched, StitchedSet};
+use rusqlite::{params, Connection, Transaction};
+use std::{error::Error, fs, path::Path};
+
+const SCHEMA: &str = "
+CREATE TABLE sources (
+    column_idx INTEGER PRIMARY KEY,
+    description TEXT NOT NULL
+);
+CREATE TABLE series (
+    id INTEGER PRIMARY KEY,
+    kind TEXT NOT NULL CHECK (kind IN ('basic', 'service_oper', 'call_chain', 'cache')),
+    service_oper TEXT NOT NULL,
+    key TEXT NOT NULL,
+    rooted INTEGER,
+    is_leaf INTEGER,
+    metric TEXT NOT NULL,
+    data_avg REAL,
+    slope REAL
+);
+CREATE INDEX series_key ON series (kind, key);
+CREATE INDEX series_service_oper ON series (service_oper);
+CREATE TABLE series_values (
+    series_id INTEGER NOT NULL REFERENCES series (id),
+    column_idx INTEGER NOT NULL,
+    value REAL NOT NULL,
+    PRIMARY KEY (series_id, column_idx)
+);
+CREATE INDEX series_values_column ON series_values (column_idx);
+";
+
+/// Add all stitched lines of a set as series (one per metric) including their filled values
+fn insert_stitched_set(
+    tx: &Transaction,
+    kind: &str,
+    service_oper: &str,
+    key: &str,
+    chain_type: Option<(bool, bool)>,
+    stitched_set: &StitchedSet,
+) -> Result<(), Box<dyn Error>> {
+    for line in stitched_set.0.iter() {
+        tx.prepare_cached(
+            "INSERT INTO series (kind, service_oper, key, rooted, is_leaf, metric, data_avg, slope) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        )?
+        .execute(params![
+            kind,
+            service_oper,
+            key,
+            chain_type.map(|ct| ct.0),
+            chain_type.map(|ct| ct.1),
+            line.metric.to_str(),
+            line.data_avg,
+            line.lin_regr.as_ref().map(|lr| lr.slope)
+        ])?;
+        let series_id = tx.last_insert_rowid();
+        let mut stmt = tx.prepare_cached(
+            "INSERT INTO series_values (series_id, column_idx, value) VALUES (?1, ?2, ?3)",
+        )?;
+        for (col, value) in line.data.iter().enumerate() {
+            if let Some(value) = value {
+                stmt.execute(params![series_id, col as i64, value])?;
+            }
+        }
+    }
+    Ok(())
+}
+
+impl Stitched {
+    /// Write the stitched dataset to a new SQLite database (an existing file is replaced).
+    /// Each metric of a process/operation, call-chain or cache-prefix is a series and the values per input-file (column) are stored in 'series_values'.
+    pub fn write_sqlite(&self, file_name: &str) -> Result<(), Box<dyn Error>> {
+        if Path::new(file_name).exists() {
+            fs::remove_file(file_name)?;
+        }
+        let mut conn = Connection::open(file_name)?;
+        conn.pragma_update(None, "foreign_keys", "ON")?;
+        let tx = conn.transaction()?;
+        tx.execute_batch(SCHEMA)?;
+
+        for src in self.sources.0.iter() {
+            if let Some(col) = src.column {
+                tx.execute(
+                    "INSERT INTO sources (column_idx, description) VALUES (?1, ?2)",
+                    params![col, src.description],
+                )?;
+            }
+        }
+        insert_stitched_set(&tx, "basic", "", "", None, &self.basic)?;
+        for (po, stitched_set) in self.service_operation.iter() {
+            insert_stitched_set(&tx, "service_oper", po, po, None, stitched_set)?;
+        }
+        for (po, call_chains) in self.call_chain.iter() {
+            for ccd in call_chains.iter() {
+                insert_stitched_set(
+                    &tx,
+                    "call_chain",
+                    po,
+                    &ccd.full_key,
+                    Some((ccd.rooted, ccd.is_leaf)),
+                    &ccd.data,
+                )?;
+            }
+        }
+        for (prefix, stitched_set) in self.cache.iter() {
+            insert_stitched_set(&tx, "cache", "", prefix, None, stitched_set)?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,100 @@
+//! The time-axis of a stitched dataset. The columns of a structured stitch-list are analyses taken at (possibly) irregular moments, so the
+//! regression uses the time of each column (in days since the first column) as x-value instead of the column-index.
+//! For a line-based stitch-list the moments are unknown and the column-index is used.
+use std::sync::Arc;
+
+use super::stitch_list::StitchColumn;
+
+pub(super) const MICROS_PER_DAY: f64 = 86_400_000_000.0;
+
+/// The x-values (days since the first column) of the columns. Without a time-axis the column-index is used as x-value.
+#[derive(Clone, Debug, Default)]
+pub struct TimeAxis(Option<Arc<Vec<f64>>>);
+
+impl TimeAxis {
+    pub fn new(x_values: Vec<f64>) -> Self {
+        if x_values.is_empty() {
+            Self(None)
+        } else {
+            Self(Some(Arc::new(x_values)))
+        }
+    }
+
+    /// Derive the time-axis from the timestamps of a structured stitch-list. Without columns (a line-based stitch-list) the column-index is used.
+    pub fn from_columns(columns: &[StitchColumn]) -> Self {
+        let timestamps: Vec<_> = columns.iter().map(|col| col.timestamp_micros).collect();
+        Self::new(days_since_first(&timestamps))
+    }
+
+    /// The x-values of a series of 'len' data-points
+    pub fn values(&self, len: usize) -> Vec<f64> {
+        match &self.0 {
+            Some(x_values) if x_values.len() == len => x_values.to_vec(),
+            _ => (0..len).map(|idx| idx as f64).collect(),
+        }
+    }
+
+    /// The x-value of the column 'idx'
+    pub fn x(&self, idx: usize) -> f64 {
+        self.0
+            .as_ref()
+            .and_then(|x_values| x_values.get(idx).copied())
+            .unwrap_or(idx as f64)
+    }
+
+    /// The time-axis of the selected columns
+    pub fn select(&self, selection: &[bool]) -> Self {
+        match &self.0 {
+            Some(x_values) => Self::new(
+                x_values
+                    .iter()
+                    .zip(selection)
+                    .filter(|(_x, sel)| **sel)
+                    .map(|(x, _sel)| *x)
+                    .collect(),
+            ),
+            None => Self(None),
+        }
+    }
+
+    pub fn to_vec(&self) -> Vec<f64> {
+        self.0.as_ref().map(|x| x.to_vec()).unwrap_or_default()
+    }
+}
+
+/// Translate the timestamps (micro-seconds) to days since the first column.
+/// An empty vector is returned if there are less than two timestamps or the timestamps are not increasing, such that the column-index is used.
+fn days_since_first(timestamps: &[i64]) -> Vec<f64> {
+    if timestamps.len() < 2 || timestamps.windows(2).any(|w| w[1] <= w[0]) {
+        return Vec::new();
+    }
+    timestamps
+        .iter()
+        .map(|ts| (ts - timestamps[0]) as f64 / MICROS_PER_DAY)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{days_since_first, TimeAxis};
+
+    const DAY: i64 = 86_400_000_000;
+
+    #[test]
+    fn irregular_days() {
+        assert_eq!(
+            days_since_first(&[10 * DAY, 12 * DAY, 13 * DAY, 16 * DAY + DAY / 2]),
+            [0.0, 2.0, 3.0, 6.5]
+        );
+        // insufficient or decreasing timestamps fall back to the column-index
+        assert!(days_since_first(&[DAY]).is_empty());
+        assert!(days_since_first(&[2 * DAY, DAY]).is_empty());
+    }
+
+    #[test]
+    fn line_based_list_uses_the_column_index() {
+        let time_axis = TimeAxis::from_columns(&[]);
+        assert_eq!(time_axis.values(3), [0.0, 1.0, 2.0]);
+        assert_eq!(time_axis.x(4), 4.0);
+    }
+}
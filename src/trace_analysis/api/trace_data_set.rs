@@ -1,9 +1,13 @@
 use super::utils;
 use crate::{
+    context::AnalysisContext,
     mermaid,
     stats::StatsRec,
     utils::{write_string_to_file, TimeStats},
-    view_api::types::ProcessList,
+    view_api::{
+        types::{ErrorTemplateList, ProcessList},
+        HtmlParameters, HtmlReport,
+    },
     MermaidScope, Metric, TraceScope, ViewError, Viewer,
 };
 use log::{error, info};
@@ -34,15 +38,45 @@ impl TraceDataSet {
             self.get_mermaid_diagram(service_oper, call_chain_key, edge_value, scope, compact);
         write_diagram(folder, service_oper, diagram);
     }
+
+    /// Write a self-contained html-report with the summary, the tables of the csv-output and the diagrams of the busiest service/operations.
+    pub fn write_html(&self, ctx: &AnalysisContext, path: &Path, pars: &HtmlParameters) {
+        let table_ctx = &ctx.without_comma_float();
+        let report = HtmlReport {
+            title: format!("Trace analysis: {}", path.display()),
+            summary: self
+                .0
+                .summary_lines()
+                .into_iter()
+                .map(|line| match line.split_once(';') {
+                    Some((k, v)) => (k.trim_end_matches(':').to_owned(), v.trim().to_owned()),
+                    None => (line, String::new()),
+                })
+                .collect(),
+            tables: vec![
+                ("Processes".to_owned(), self.0.process_lines(table_ctx)),
+                (
+                    "Service/Operations".to_owned(),
+                    self.0.service_oper_lines(table_ctx),
+                ),
+                ("Call-chains".to_owned(), self.0.call_chain_lines(table_ctx)),
+                (
+                    "Error templates".to_owned(),
+                    self.0.error_template_lines(table_ctx),
+                ),
+            ],
+        };
+        report.write_file(ctx, self, path, pars);
+    }
 }
 
 impl Viewer for TraceDataSet {
-    fn from_file(file_name: &str) -> Result<Box<Self>, ViewError> {
+    fn from_file(ctx: &AnalysisContext, file_name: &str) -> Result<Box<Self>, ViewError> {
         if Path::new(file_name).exists() {
             info!("Trying to load the file {file_name}");
 
             let file_path = Path::new(file_name).to_path_buf().into_os_string(); //get_full_path(base_path, input);
-            match StatsRec::read_file(&file_path) {
+            match StatsRec::read_file(ctx, &file_path) {
                 Ok(stats_rec) => Ok(Box::new(TraceDataSet::new(stats_rec))),
                 Err(err) => Err(ViewError::load_failure(
                     file_name.to_owned(),
@@ -72,6 +106,16 @@ impl Viewer for TraceDataSet {
         utils::get_call_chain_list(&self.0, proc_oper, metric, scope, inbound_idx)
     }
 
+    /// Get the most frequent error-message templates for a specific service-operation
+    fn get_error_templates(
+        &self,
+        ctx: &AnalysisContext,
+        service_oper: &str,
+        top_n: usize,
+    ) -> ErrorTemplateList {
+        utils::get_error_templates(ctx, &self.0, service_oper, top_n)
+    }
+
     fn get_mermaid_diagram(
         &self,
         service_oper: &str,
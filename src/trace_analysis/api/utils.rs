@@ -1,5 +1,6 @@
 use crate::{
-    types::{ProcessList, ProcessListItem},
+    context::AnalysisContext,
+    types::{ErrorTemplateItem, ErrorTemplateList, ProcessList, ProcessListItem},
     view_api::reorder_and_renumber,
     Metric, StatsRec, TraceScope,
 };
@@ -190,3 +191,28 @@ pub fn get_call_chain_list(
     // }
     unimplemented!()
 }
+
+/// get the most frequent error-templates of a service/operation
+pub fn get_error_templates(
+    ctx: &AnalysisContext,
+    data: &StatsRec,
+    service_oper: &str,
+    top_n: usize,
+) -> ErrorTemplateList {
+    data.stats
+        .iter()
+        .flat_map(|(service, v)| {
+            v.operation
+                .0
+                .iter()
+                .filter(move |(oper, _)| format!("{service}/{oper}") == service_oper)
+        })
+        .flat_map(|(_, oper_stats)| oper_stats.get_error_templates(ctx).0)
+        .take(top_n)
+        .map(|et| ErrorTemplateItem {
+            template: et.template,
+            count: et.count as i64,
+            examples: et.examples,
+        })
+        .collect()
+}
@@ -1,12 +1,12 @@
 //! Deduplication of traces based on the GUID (Identifier of the traces)
 use crate::{
     processed::Trace,
-    utils::{self, Chapter},
+    utils::{self, Chapter, Report, ReportEntry, Severity},
 };
 use std::collections::HashSet;
 
 /// deduplicate all the traces based on traceId and report effect,
-pub fn deduplicate(traces: Vec<Trace>) -> Vec<Trace> {
+pub fn deduplicate(report: &mut Report, traces: Vec<Trace>) -> Vec<Trace> {
     let initial_num = traces.len();
 
     let mut observed_id = HashSet::new();
@@ -28,14 +28,21 @@ pub fn deduplicate(traces: Vec<Trace>) -> Vec<Trace> {
     let num_duplicates = duplicated_ids.len();
     let remaining = traces.len();
     utils::report(
+        report,
         Chapter::Summary,
+        "deduplication",
         format!(
             "Removed {num_duplicates}:  So list of {initial_num} traces reduced to {remaining}"
         ),
     );
-    utils::report(
-        Chapter::Details,
-        format!("Removed duplicates: {duplicated_ids:?}"),
+    utils::report_entry(
+        report,
+        ReportEntry::new(
+            Chapter::Details,
+            Severity::Info,
+            "duplicate_traces",
+            format!("Removed duplicates: {duplicated_ids:?}"),
+        ),
     );
 
     traces
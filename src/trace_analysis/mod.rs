@@ -1,6 +1,7 @@
 use crate::{
+    context::AnalysisContext,
     processed, raw,
-    stats::{self as crate_stats, BasicStatsRec, TraceExtVec},
+    stats::{self as crate_stats, call_chain::CChainRepair, BasicStatsRec, TraceExtVec},
     utils, MermaidScope, Metric,
 };
 pub use api::TraceDataSet;
@@ -20,8 +21,11 @@ mod write;
 ///    2. Would make the system less memory intensive as it will become a streaming pipeline which consumes intermediate data as it is produced.
 ///    3. Would make injection of the processed::extract_traces unnecessary (now needed to process at the bottom of the tree)
 /// The challenging part is the stats module where we partition data over two streams.
-///  
+///
+/// The settings of the analysis are taken from 'ctx', which also collects the report (see 'AnalysisContext::take_report').
+#[allow(clippy::too_many_arguments)]
 pub fn analyze_file_or_folder(
+    ctx: &mut AnalysisContext,
     path: &Path,
     caching_processes: Vec<String>,
     cc_path: &str,
@@ -29,10 +33,11 @@ pub fn analyze_file_or_folder(
     output_ext: &str,
     display_service_oper: Option<&str>,
     display_call_chain: Option<&str>,
+    repair: &CChainRepair,
 ) -> PathBuf {
     // Read raw jaeger-traces and process them to clean traces.
     let (traces, num_files, folder) =
-        raw::read_process_file_or_folder(path, processed::extract_traces);
+        raw::read_process_file_or_folder(ctx, path, processed::extract_traces);
 
     let mut bsr = BasicStatsRec {
         num_files,
@@ -47,7 +52,7 @@ pub fn analyze_file_or_folder(
     );
 
     // When joining traces from multiple files we can have duplicates. These should be removed to prevent incorrect statistics
-    let traces = dedup::deduplicate(traces);
+    let traces = dedup::deduplicate(&mut ctx.report, traces);
 
     // Translate to Extended traces and write the traces to a JSON file
     let traces = crate_stats::build_trace_ext(traces, &folder);
@@ -59,7 +64,15 @@ pub fn analyze_file_or_folder(
         traces.iter().for_each(|trace| trace.write_trace());
     }
 
-    let stats_rec = stats::process_and_fix_traces(folder.clone(), traces, bsr, cc_path, output_ext);
+    let stats_rec = stats::process_and_fix_traces(
+        ctx,
+        folder.clone(),
+        traces,
+        bsr,
+        cc_path,
+        output_ext,
+        repair,
+    );
 
     // Assume some default parameters
     let compact = false;
@@ -1,9 +1,12 @@
 //! Creating the statistics
 use crate::{
+    context::AnalysisContext,
     stats::{
-        self, call_chain::CChainEndPointCache, file, BasicStatsRec, StatsRec, TraceExt, TraceExtVec,
+        self,
+        call_chain::{CChainEndPointCache, CChainRepair, RepairStats},
+        file, BasicStatsRec, CacheStats, LoopStats, StatsRec, TraceExt, TraceExtVec,
     },
-    utils::{self, Chapter},
+    utils::{self, Chapter, Report, ReportEntry, Severity},
 };
 use std::{
     collections::HashMap,
@@ -11,9 +14,14 @@ use std::{
 };
 
 /// deterimine the cchain folder based upon cc_path if this is an absolute path. If cc_path is a relative path it will be located as a sub-folder of 'folder'.
-fn get_cchain_folder(folder: &Path, cc_path: &str) -> PathBuf {
+fn get_cchain_folder(report: &mut Report, folder: &Path, cc_path: &str) -> PathBuf {
     // Cchain-folder for input and output are set to the same folder.
-    utils::report(Chapter::Details, format!("Input for cc_path = {cc_path}"));
+    utils::report(
+        report,
+        Chapter::Details,
+        "cchain_folder",
+        format!("Input for cc_path = {cc_path}"),
+    );
     let cc_path = {
         let cc_path_full = Path::new(cc_path).to_path_buf();
         if cc_path_full.is_absolute() {
@@ -28,7 +36,9 @@ fn get_cchain_folder(folder: &Path, cc_path: &str) -> PathBuf {
     //     format!("Translates to full cc_path = {}", cc_path.display()),
     // );
     utils::report(
+        report,
         Chapter::Details,
+        "cchain_folder",
         format!(
             "Translates to (full) cchain_folder = {}",
             cchain_folder.display()
@@ -39,27 +49,100 @@ fn get_cchain_folder(folder: &Path, cc_path: &str) -> PathBuf {
 
 /// create the statistics over all traces using the caching_processes
 fn create_trace_statistics(
+    ctx: &mut AnalysisContext,
     traces: &[TraceExt],
     bsr: &BasicStatsRec,
     rooted_spans_only: bool,
 ) -> StatsRec {
-    let mut cumm_stats = StatsRec::new(bsr.clone());
+    let mut cumm_stats = StatsRec::new(ctx, bsr.clone());
 
     let traces: Vec<_> = traces.iter().collect(); // switch to references
 
     traces
         .iter()
-        .for_each(|tr| cumm_stats.extend_statistics(&tr.trace, rooted_spans_only));
+        .for_each(|tr| cumm_stats.extend_statistics(&mut ctx.report, &tr.trace, rooted_spans_only));
     cumm_stats
 }
 
 /// write a file showing the statistics in csv and json/bincode format
-fn write_cumulative_trace_stats(csv_file: PathBuf, cumm_stats: StatsRec, output_ext: &str) {
+fn write_cumulative_trace_stats(
+    ctx: &AnalysisContext,
+    csv_file: PathBuf,
+    cumm_stats: StatsRec,
+    output_ext: &str,
+) {
     // write some cumulative statistics to a CSV-file for Excel analysis.
-    stats::write_stats_to_csv_file(csv_file.to_str().unwrap(), &cumm_stats);
+    stats::write_stats_to_csv_file(ctx, csv_file.to_str().unwrap(), &cumm_stats);
 
     // write the full cumulative data to file based on output_ext (either json or bin_code)
-    file::write_stats(csv_file.to_str().unwrap(), cumm_stats, output_ext);
+    file::write_stats(ctx, csv_file.to_str().unwrap(), cumm_stats, output_ext);
+}
+
+/// write the cache hit-ratio report in csv-format, provided caching processes are defined for this analysis.
+fn write_cache_stats(ctx: &mut AnalysisContext, stats_folder: &Path, cumm_stats: &StatsRec) {
+    let cache_stats = CacheStats::new(cumm_stats);
+    if cache_stats.is_empty() {
+        return;
+    }
+    let mut csv_file = stats_folder.to_path_buf();
+    csv_file.push("cache_hit_ratio.csv");
+    let csv_file = csv_file.to_str().unwrap();
+    if let Err(err) = utils::write_string_to_file(csv_file, cache_stats.to_csv_string(ctx)) {
+        panic!("Writing to file '{csv_file}' failed with error: {err:?}");
+    };
+    utils::report(
+        &mut ctx.report,
+        Chapter::Summary,
+        "cache_stats",
+        format!(
+            "Computed cache hit-ratios for {} call-chains to caching processes {:?}",
+            cache_stats.0.len(),
+            cumm_stats.caching_processes
+        ),
+    );
+}
+
+/// write the report of all call-chains that contain loops (recursive calls) in csv-format.
+fn write_loop_stats(ctx: &mut AnalysisContext, stats_folder: &Path, traces: &[TraceExt]) {
+    let loop_stats = LoopStats::new(traces);
+    let mut csv_file = stats_folder.to_path_buf();
+    csv_file.push("loops.csv");
+    let csv_file = csv_file.to_str().unwrap();
+    if let Err(err) = utils::write_string_to_file(csv_file, loop_stats.to_csv_string(ctx)) {
+        panic!("Writing to file '{csv_file}' failed with error: {err:?}");
+    };
+    if !loop_stats.is_empty() {
+        utils::report(
+            &mut ctx.report,
+            Chapter::Summary,
+            "loops",
+            format!(
+                "Detected {} call-chains containing loops (see {csv_file})",
+                loop_stats.0.len()
+            ),
+        );
+    }
+}
+
+/// report how much of the traffic of the non-rooted call-chains is assigned exactly and how much is assigned probabilistically
+fn report_repair_stats(report: &mut Report, repair_stats: &RepairStats) {
+    utils::report(
+        report,
+        Chapter::Summary,
+        "repair_summary",
+        repair_stats.summary(),
+    );
+    report.counters.fixes = repair_stats.num_fixes();
+    report.counters.failed_fixes = repair_stats.failed_chains;
+    let probabilistic = repair_stats.dominant_calls + repair_stats.proportional_calls;
+    if probabilistic > 0 {
+        utils::report(
+            report,
+            Chapter::Issues,
+            "probabilistic_fixes",
+            format!("{probabilistic} calls of non-rooted call-chains are assigned probabilistically based on the observed counts of the candidate call-chains."),
+        );
+    }
 }
 
 /// Statistics are written per endpoint to the 'Stats' folder, and incomplete traces are corrected (when possible)
@@ -78,13 +161,16 @@ fn write_cumulative_trace_stats(csv_file: PathBuf, cumm_stats: StatsRec, output_
 ///     * the number of end-points that appaer in these traces
 ///     * the number of incomplete traces
 ///     * the number of fixes applied to these incomplete traces (beware that some traces have multiple issues, and not all incomplete traces have been resolved)
+#[allow(clippy::too_many_arguments)]
 fn write_end_point_stats_and_correct_incomplete(
+    ctx: &mut AnalysisContext,
     stats_folder: &Path,
     traces: Vec<TraceExt>, // moving data in and extracting later to prevent the need to copy data. Really needed??
     cchain_cache: &mut CChainEndPointCache,
     bsr: BasicStatsRec,
     output_ext: &str,
     rooted_spans_only: bool,
+    repair: &CChainRepair,
     //    cchain_folder: &PathBuf, // temporary var (TODO: move to caches)
 ) -> (usize, usize) {
     let mut traces_by_endpoint = HashMap::new();
@@ -111,15 +197,17 @@ fn write_end_point_stats_and_correct_incomplete(
         let (traces, part_traces): (Vec<_>, Vec<_>) = traces.into_iter().partition(|tr| tr.trace.missing_span_ids.is_empty());
         //TODO: we can produce the call-chains over incomplete traces too if we only include the rooted paths
         let mut cumm_stats = if !traces.is_empty() {
-            let cumm_stats = create_trace_statistics(&traces[..], &bsr, rooted_spans_only);
+            let cumm_stats = create_trace_statistics(ctx, &traces[..], &bsr, rooted_spans_only);
 
 
-            cchain_cache.create_update_entry(&k, cumm_stats.call_chain_keys());
+            // the call-chains are marked as seen at the date of the most recent trace
+            let seen = cumm_stats.end_dt_micros.iter().max().map(|micros| cumm_stats.timezone.to_local(*micros).date());
+            cchain_cache.create_update_entry(&mut ctx.report, &k, cumm_stats.call_chain_keys(), seen);
 
             cumm_stats
         } else {
             println!("No complete traces, so we can not produce the call-chain file");
-            StatsRec::new(bsr.clone())
+            StatsRec::new(ctx, bsr.clone())
         };
 
         let part_trace_len = part_traces.len();
@@ -128,17 +216,21 @@ fn write_end_point_stats_and_correct_incomplete(
             let trace_len = traces.len();
             let tot_trace = trace_len + part_trace_len;
             let part_frac = 100.0 * part_trace_len as f64 / tot_trace as f64;
-            utils::report(Chapter::Analysis, format!("For end-point (root) '{k}' found {part_trace_len} incomplete out of {tot_trace} traces ({part_frac:.1}%)"));
+            utils::report_entry(
+                &mut ctx.report,
+                ReportEntry::new(Chapter::Analysis, Severity::Info, "incomplete_traces", format!("For end-point (root) '{k}' found {part_trace_len} incomplete out of {tot_trace} traces ({part_frac:.1}%)"))
+                    .endpoint(&k),
+            );
         }
 
         all_traces.extend(traces);
 
 
         // and add these to the statistics
-        part_traces.iter().for_each(|tr| cumm_stats.extend_statistics(&tr.trace, rooted_spans_only) );
+        part_traces.iter().for_each(|tr| cumm_stats.extend_statistics(&mut ctx.report, &tr.trace, rooted_spans_only) );
 
         // amend/fix traces
-        let ep_num_fixes = cumm_stats.fix_call_chain(cchain_cache);
+        let ep_num_fixes = cumm_stats.fix_call_chain(&mut ctx.report, cchain_cache, repair).num_fixes();
         num_fixes += ep_num_fixes;
 
         cumm_stats.num_files = num_files.try_into().unwrap();
@@ -151,7 +243,7 @@ fn write_end_point_stats_and_correct_incomplete(
         cumm_stats.num_call_chains = total;
         cumm_stats.num_unrooted_cc_after_fixes = num_unrooted;
 
-        write_cumulative_trace_stats(csv_file, cumm_stats, output_ext);
+        write_cumulative_trace_stats(ctx, csv_file, cumm_stats, output_ext);
 
         all_traces.extend(part_traces);
     });
@@ -163,11 +255,13 @@ fn write_end_point_stats_and_correct_incomplete(
 
 /// process a vector of traces
 pub fn process_and_fix_traces(
+    ctx: &mut AnalysisContext,
     folder: PathBuf,
     traces: Vec<TraceExt>,
     mut bsr: BasicStatsRec,
     cc_path: &str,
     output_ext: &str,
+    repair: &CChainRepair,
 ) -> StatsRec {
     let total_traces = traces.len();
 
@@ -176,46 +270,57 @@ pub fn process_and_fix_traces(
     // TODO: consider whether this uncorrected version is needed.
     let mut csv_file = stats_folder.clone();
     csv_file.push("cummulative_trace_stats_uncorrected.csv");
-    let mut cumm_stats = create_trace_statistics(&traces, &bsr, false);
+    let mut cumm_stats = create_trace_statistics(ctx, &traces, &bsr, false);
     let (total_cc, num_unrooted) = cumm_stats.count_call_chains();
     bsr.init_num_unrooted_cc = num_unrooted;
     cumm_stats.init_num_unrooted_cc = num_unrooted;
     cumm_stats.num_call_chains = total_cc;
-    write_cumulative_trace_stats(csv_file, cumm_stats.clone(), output_ext);
+    write_cumulative_trace_stats(ctx, csv_file, cumm_stats.clone(), output_ext);
+
+    write_loop_stats(ctx, &stats_folder, &traces);
 
     let num_files: i32 = TraceExtVec(&traces[..]).num_files().try_into().unwrap();
 
-    let mut cchain_cache = CChainEndPointCache::new(get_cchain_folder(&folder, cc_path));
+    let mut cchain_cache =
+        CChainEndPointCache::new(get_cchain_folder(&mut ctx.report, &folder, cc_path));
 
     let (num_end_points, incomplete_traces_read) = write_end_point_stats_and_correct_incomplete(
+        ctx,
         &stats_folder,
         traces,
         &mut cchain_cache,
         bsr.clone(),
         output_ext,
         false,
+        repair,
     );
     if cumm_stats.num_incomplete_traces != incomplete_traces_read {
-        utils::report(
+        utils::report_entry(&mut ctx.report, ReportEntry::new(
             Chapter::Issues,
+            Severity::Error,
+            "incomplete_trace_mismatch",
             format!(
                 "The number of incomplete traces was {} but analysis per endpoint showed {} incomplete traces",
                 cumm_stats.num_incomplete_traces, incomplete_traces_read,
             ),
-        )
+        ));
     }
 
-    let num_fixes = cumm_stats.fix_call_chain(&mut cchain_cache); // mutable needed for internal reasons of CChain_cache
+    let repair_stats = cumm_stats.fix_call_chain(&mut ctx.report, &mut cchain_cache, repair); // mutable needed for internal reasons of CChain_cache
+    let num_fixes = repair_stats.num_fixes();
+    report_repair_stats(&mut ctx.report, &repair_stats);
 
     let (total_cc, num_unrooted) = cumm_stats.count_call_chains();
     if cumm_stats.num_call_chains != total_cc {
-        utils::report(
+        utils::report_entry(&mut ctx.report, ReportEntry::new(
             Chapter::Issues,
+            Severity::Error,
+            "call_chain_mismatch",
             format!(
                 "The number of call_chains was {} after analysis per endpoint (root-path) we have {}",
                 cumm_stats.num_call_chains, total_cc,
             ),
-        )
+        ));
     }
 
     cumm_stats.num_files = num_files;
@@ -226,14 +331,20 @@ pub fn process_and_fix_traces(
     // writing out the version with corrected call-chains.
     let mut csv_file = stats_folder.clone();
     csv_file.push("cummulative_trace_stats.csv");
-    write_cumulative_trace_stats(csv_file, cumm_stats.clone(), output_ext);
+    write_cumulative_trace_stats(ctx, csv_file, cumm_stats.clone(), output_ext);
+
+    write_cache_stats(ctx, &stats_folder, &cumm_stats);
 
     println!();
-    utils::report(Chapter::Summary, format!("Processed {total_traces} traces covering {} end-points  (on average {:.1} traces per end-point).",
+    utils::report(&mut ctx.report, Chapter::Summary, "num_traces", format!("Processed {total_traces} traces covering {} end-points  (on average {:.1} traces per end-point).",
         bsr.num_endpoints,
         total_traces as f64/bsr.num_endpoints as f64));
+    ctx.report.counters.num_traces = total_traces;
+    ctx.report.counters.incomplete_traces = bsr.num_incomplete_traces;
     utils::report(
+        &mut ctx.report,
         Chapter::Summary,
+        "incomplete_traces_total",
         format!(
             "Observed {} incomplete traces, which is {:.1}% of the total",
             bsr.num_incomplete_traces,
@@ -1,16 +1,9 @@
-use std::sync::Mutex;
-
-static COMMA_FLOAT: Mutex<bool> = Mutex::new(false);
-
-pub fn set_comma_float(val: bool) {
-    let mut guard = COMMA_FLOAT.lock().unwrap();
-    *guard = val
-}
+use crate::context::AnalysisContext;
 
 /// format_float will format will replace the floating point '.' with a comma ',' such that the excel is readable in the Dutch Excel :-(
-pub fn format_float(val: f64) -> String {
+pub fn format_float(ctx: &AnalysisContext, val: f64) -> String {
     let s = format!("{}", val);
-    if *COMMA_FLOAT.lock().unwrap() {
+    if ctx.comma_float {
         s.replace('.', ",")
     } else {
         s
@@ -18,19 +11,19 @@ pub fn format_float(val: f64) -> String {
 }
 
 /// format_float will format will replace the floating point '.' with a comma ',' such that the excel is readable in the Dutch Excel :-(
-pub fn format_float_opt(val: Option<f64>) -> String {
+pub fn format_float_opt(ctx: &AnalysisContext, val: Option<f64>) -> String {
     match val {
-        Some(v) => format_float(v),
+        Some(v) => format_float(ctx, v),
         None => "--".to_owned(),
     }
 }
 
 /// write a series of floats to a string without consuming them.
-pub fn floats_ref_to_string(values: &[Option<f64>], sep: &str) -> String {
+pub fn floats_ref_to_string(ctx: &AnalysisContext, values: &[Option<f64>], sep: &str) -> String {
     values
         .iter()
         .map(|v| match v {
-            Some(v) => format_float(*v),
+            Some(v) => format_float(ctx, *v),
             None => "".to_string(),
         })
         .collect::<Vec<_>>()
@@ -38,12 +31,12 @@ pub fn floats_ref_to_string(values: &[Option<f64>], sep: &str) -> String {
 }
 
 /// write a series of floats to a string and consume the original input (could consume it with into_iter() which might be slightly more efficient)
-pub fn floats_to_string(values: Vec<Option<f64>>, sep: &str) -> String {
-    // floats_ref_to_string(&values, sep)
+pub fn floats_to_string(ctx: &AnalysisContext, values: Vec<Option<f64>>, sep: &str) -> String {
+    // floats_ref_to_string(ctx, &values, sep)
     values
         .into_iter()
         .map(|v| match v {
-            Some(v) => format_float(v),
+            Some(v) => format_float(ctx, v),
             None => "".to_string(),
         })
         .collect::<Vec<_>>()
@@ -0,0 +1,232 @@
+//! Configuration files (TOML) for the command-line tools.
+//! The keys of the config-file are the long names of the command-line flags (with '_' or '-'), for example 'comma_float = false' or 'l1_dev_bound = 2.5'.
+//! Tables (sections) are only used for grouping, so '[anomaly_pars]' followed by 'l1_dev_bound = 2.5' is equivalent to the top-level key.
+//! The values of the config-file are injected in front of the command-line arguments, such that flags on the command-line override the file-values.
+//! A key of the config-file is skipped when its flag is on the command-line, so a list-valued flag (such as 'severity_rule') is replaced by the
+//! command-line values instead of being extended. A positional argument (such as the input of 'trace_analysis') is also taken from the
+//! config-file only when it is missing on the command-line.
+use clap::{parser::ValueSource, CommandFactory};
+use serde::Serialize;
+use std::{collections::HashSet, env, error::Error, ffi::OsString, fs, path::Path};
+use toml::{Table, Value};
+
+/// The flag that refers to the config-file
+const CONFIG_FLAG: &str = "--config";
+
+/// Translate a config-file to a list of command-line arguments ('--key=value')
+pub fn config_to_args(path: &Path) -> Result<Vec<String>, Box<dyn Error>> {
+    let mut args = Vec::new();
+    table_to_args(&read_table(path)?, &mut args)?;
+    Ok(args)
+}
+
+fn read_table(path: &Path) -> Result<Table, Box<dyn Error>> {
+    let contents = fs::read_to_string(path)?;
+    Ok(contents
+        .parse()
+        .map_err(|err| format!("Failed to parse config-file '{}': {err}", path.display()))?)
+}
+
+fn table_to_args(table: &Table, args: &mut Vec<String>) -> Result<(), Box<dyn Error>> {
+    table.iter().try_for_each(|(key, value)| {
+        let flag = format!("--{}", key.replace('_', "-"));
+        match value {
+            Value::Table(table) => table_to_args(table, args)?,
+            Value::Array(values) => values.iter().try_for_each(|value| {
+                scalar_to_string(key, value).map(|v| args.push(format!("{flag}={v}")))
+            })?,
+            value => args.push(format!("{flag}={}", scalar_to_string(key, value)?)),
+        }
+        Ok(())
+    })
+}
+
+/// Remove the keys (also within tables) of the arguments in 'on_command_line', where '-' and '_' are equivalent in a key.
+fn remove_keys(table: &mut Table, on_command_line: &HashSet<String>) {
+    table.retain(|key, value| {
+        if let Value::Table(table) = value {
+            remove_keys(table, on_command_line);
+        }
+        !on_command_line.contains(&key.replace('-', "_"))
+    });
+}
+
+fn scalar_to_string(key: &str, value: &Value) -> Result<String, Box<dyn Error>> {
+    match value {
+        Value::String(s) => Ok(s.to_owned()),
+        Value::Integer(i) => Ok(i.to_string()),
+        Value::Float(f) => Ok(f.to_string()),
+        Value::Boolean(b) => Ok(b.to_string()),
+        _ => Err(format!(
+            "Unsupported value for '{key}' in config-file: {value}"
+        ))?,
+    }
+}
+
+/// Find the value of the '--config' flag in the command-line arguments
+fn find_config(cli_args: &[OsString]) -> Option<OsString> {
+    let flag_value = format!("{CONFIG_FLAG}=");
+    cli_args.iter().enumerate().find_map(|(idx, arg)| {
+        let arg_str = arg.to_string_lossy();
+        if arg_str == CONFIG_FLAG {
+            cli_args.get(idx + 1).cloned()
+        } else {
+            arg_str.strip_prefix(&flag_value).map(OsString::from)
+        }
+    })
+}
+
+/// Parse the command-line arguments, where the values of the config-file passed via '--config <file>' are used as defaults.
+/// The command should be marked with 'args_override_self = true', such that a command-line flag overrides the value from the config-file.
+pub fn parse_args_with_config<T: clap::Parser>() -> T {
+    let cli_args: Vec<OsString> = env::args_os().collect();
+    let args = args_with_config::<T>(cli_args).unwrap_or_else(|err| {
+        eprintln!("Failed to read config-file {err}");
+        std::process::exit(1);
+    });
+    T::parse_from(args)
+}
+
+/// Combine the config-file passed via '--config <file>' with the command-line arguments 'cli_args' (starting with the program-name).
+/// The flags of the config-file are placed in front of the command-line arguments, where the flags that are on the command-line are skipped.
+/// The positional arguments of the config-file are only added when these are missing on the command-line.
+pub fn args_with_config<T: CommandFactory>(
+    mut cli_args: Vec<OsString>,
+) -> Result<Vec<OsString>, Box<dyn Error>> {
+    let Some(config) = find_config(&cli_args[1..]) else {
+        return Ok(cli_args);
+    };
+    let config = Path::new(&config);
+    let mut table = read_table(config).map_err(|err| format!("'{}': {err}", config.display()))?;
+
+    // the positional arguments are removed from the table as these can not be passed as '--key=value'
+    let cli_matches = T::command()
+        .ignore_errors(true)
+        .try_get_matches_from(&cli_args)?;
+    let positionals = T::command()
+        .get_positionals()
+        .filter_map(|arg| {
+            let id = arg.get_id().as_str();
+            let value = table
+                .remove(id)
+                .or_else(|| table.remove(&id.replace('_', "-")))?;
+            let on_command_line = cli_matches.value_source(id) == Some(ValueSource::CommandLine);
+            (!on_command_line).then(|| scalar_to_string(id, &value))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // the flags on the command-line replace the config-values, which matters for flags that take multiple values
+    let on_command_line: HashSet<_> = T::command()
+        .get_arguments()
+        .map(|arg| arg.get_id().as_str())
+        .filter(|id| cli_matches.value_source(id) == Some(ValueSource::CommandLine))
+        .map(|id| id.to_owned())
+        .collect();
+    remove_keys(&mut table, &on_command_line);
+
+    let mut config_args = Vec::new();
+    table_to_args(&table, &mut config_args)?;
+
+    let cli_tail = cli_args.split_off(1);
+    cli_args.extend(
+        config_args
+            .into_iter()
+            .chain(positionals)
+            .map(OsString::from),
+    );
+    cli_args.extend(cli_tail);
+    Ok(cli_args)
+}
+
+/// Write the effective configuration (the parsed arguments) as a config-file, such that the run can be reproduced via '--config <file>'.
+pub fn write_config<T: Serialize>(path: &Path, args: &T) {
+    match toml::to_string(args) {
+        Ok(contents) => {
+            if let Err(err) = fs::write(path, contents) {
+                println!("Failed to write config to '{}': {err:?}", path.display());
+            }
+        }
+        Err(err) => println!("Failed to serialize config: {err:?}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{args_with_config, table_to_args};
+    use clap::Parser;
+    use std::{env, ffi::OsString, fs};
+    use toml::Table;
+
+    #[derive(Parser, Debug)]
+    #[command(args_override_self = true)]
+    struct TestArgs {
+        input: String,
+        #[arg(long)]
+        config: Option<String>,
+        #[arg(long, default_value_t = 0)]
+        drop_count: usize,
+        #[arg(long)]
+        severity_rule: Vec<String>,
+    }
+
+    fn parse(config: &str, cli_args: &[&str]) -> TestArgs {
+        let path = env::temp_dir().join("jaeger_stats_args_with_config.toml");
+        fs::write(&path, config).unwrap();
+        let cli_args = ["test", "--config", path.to_str().unwrap()]
+            .iter()
+            .chain(cli_args)
+            .map(OsString::from)
+            .collect();
+        TestArgs::parse_from(args_with_config::<TestArgs>(cli_args).unwrap())
+    }
+
+    #[test]
+    fn command_line_overrides_config() {
+        let config = "input = \"from_config\"\ndrop_count = 3\n";
+
+        let args = parse(config, &[]);
+        assert_eq!((&args.input[..], args.drop_count), ("from_config", 3));
+
+        let args = parse(config, &["--drop-count", "5", "from_cli"]);
+        assert_eq!((&args.input[..], args.drop_count), ("from_cli", 5));
+    }
+
+    #[test]
+    fn command_line_replaces_config_list() {
+        let config = "input = \"in\"\n[rules]\nseverity_rule = [\"low=*\", \"high=rate\"]\n";
+
+        let args = parse(config, &[]);
+        assert_eq!(args.severity_rule, ["low=*", "high=rate"]);
+
+        let args = parse(config, &["--severity-rule", "critical=count"]);
+        assert_eq!(args.severity_rule, ["critical=count"]);
+    }
+
+    #[test]
+    fn config_table_to_args() {
+        let table: Table = r#"
+            comma_float = false
+            drop-count = 3
+            caching_process = "cache"
+            normalisation_rule = ["/{ID}=/\\d+", "/{X}=/x"]
+
+            [anomaly_pars]
+            l1_dev_bound = 2.5
+        "#
+        .parse()
+        .unwrap();
+        let mut args = Vec::new();
+        table_to_args(&table, &mut args).unwrap();
+        assert_eq!(
+            args,
+            [
+                "--l1-dev-bound=2.5",
+                "--caching-process=cache",
+                "--comma-float=false",
+                "--drop-count=3",
+                "--normalisation-rule=/{ID}=/\\d+",
+                "--normalisation-rule=/{X}=/x",
+            ]
+        );
+    }
+}
@@ -37,4 +37,20 @@ impl<T: Hash + Eq + PartialEq> Counted<T> {
             .or_insert(count);
         *cnt
     }
+
+    /// add the counts of 'other' to this list
+    pub fn extend(&mut self, other: Counted<T>) {
+        other.0.into_iter().for_each(|(item, count)| {
+            self.add_item_count(item, count);
+        })
+    }
+
+    /// iterate over the items and their counts
+    pub fn iter(&self) -> impl Iterator<Item = (&T, &usize)> {
+        self.0.iter()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
 }
@@ -1,4 +1,4 @@
-use crate::utils::write_string_to_file;
+use crate::utils::{write_string_to_file, write_xlsx, XlsxSheet};
 use std::path::Path;
 
 #[derive(Default)]
@@ -6,6 +6,8 @@ pub struct CsvFileBuffer {
     buffer: Vec<String>,
     start_toc: usize,
     toc_index: usize,
+    /// The sheet-name and the first line of each section (used for xlsx output)
+    sections: Vec<(String, usize)>,
 }
 
 impl CsvFileBuffer {
@@ -33,11 +35,17 @@ impl CsvFileBuffer {
 
     /// Add a section prefixed by a few empty lines, and also add the section to the table of contents, assuming space was reserved
     pub fn add_section(&mut self, title: &str) {
+        self.add_sheet_section(title, title);
+    }
+
+    /// Add a section (see 'add_section') that is shown on a sheet named 'sheet' in the xlsx-output
+    pub fn add_sheet_section(&mut self, sheet: &str, title: &str) {
         self.add_empty_lines(2);
         self.buffer.push(format!("## {title}"));
         self.buffer[self.start_toc + self.toc_index] =
             format!("{:3} @ row {}: {title}", self.toc_index, self.num_lines());
         self.toc_index += 1;
+        self.sections.push((sheet.to_owned(), self.num_lines()));
     }
 
     /// Append all string_data to the buffer by moving it.
@@ -50,6 +58,45 @@ impl CsvFileBuffer {
         self.buffer.push(line);
     }
 
+    /// The sheet-name and lines of each section, where empty lines are dropped. Lines before the first section (the table of contents) are skipped.
+    pub fn into_sections(self) -> Vec<(String, Vec<String>)> {
+        let ends: Vec<_> = self
+            .sections
+            .iter()
+            .skip(1)
+            .map(|(_, start)| start - 1) // skip the title-line of the next section
+            .chain([self.buffer.len()])
+            .collect();
+        self.sections
+            .iter()
+            .zip(ends)
+            .map(|((sheet, start), end)| {
+                let lines = self.buffer[*start..end]
+                    .iter()
+                    .filter(|line| !line.trim().is_empty())
+                    .cloned()
+                    .collect();
+                (sheet.to_owned(), lines)
+            })
+            .collect()
+    }
+
+    /// write the sections as sheets of an xlsx-workbook and drop the data.
+    /// The buffer should be filled without comma-floats (see 'AnalysisContext::without_comma_float').
+    pub fn write_xlsx(self, path: &Path) {
+        let sheets: Vec<_> = self
+            .into_sections()
+            .into_iter()
+            .map(|(sheet, lines)| XlsxSheet::new(&sheet, lines))
+            .collect();
+        if let Err(err) = write_xlsx(path, &sheets) {
+            println!(
+                "Writing file '{}' failed with Error: {err:?}",
+                path.display()
+            )
+        }
+    }
+
     /// write the data to file and drop it
     pub fn write_file(self, path: &Path) {
         match write_string_to_file(path.to_str().unwrap(), self.buffer.join("\n")) {
@@ -1,26 +1,14 @@
+use crate::context::AnalysisContext;
 use chrono::NaiveDateTime;
-use std::sync::Mutex;
 
-// UTC + 2 hours
-static TX_OFFSET: Mutex<i64> = Mutex::new(0);
-
-pub fn set_tz_offset_minutes(minutes: i64) {
-    let mut guard = TX_OFFSET.lock().unwrap();
-    *guard = minutes * 60 * 1000 * 1000;
-}
-
-/// micros_to_datetime takes the number of micro-seconds since epoch (unsigned) and returns a UTC-DateTime.
-pub fn micros_to_datetime(epoch_micros: i64) -> NaiveDateTime {
-    let epoch_micros = epoch_micros + *TX_OFFSET.lock().unwrap();
-    NaiveDateTime::from_timestamp_micros(epoch_micros)
-        .unwrap_or_else(|| panic!("Invalid time provided in micros: {epoch_micros}"))
+/// micros_to_datetime takes the number of micro-seconds since epoch (UTC) and returns the local date-time in the timezone of the analysis-context.
+pub fn micros_to_datetime(ctx: &AnalysisContext, epoch_micros: i64) -> NaiveDateTime {
+    ctx.timezone.to_local(epoch_micros)
 }
 
-/// Get the microseconds sinds epoch (corrected for UTC)
-pub fn datetime_to_micros(dt: NaiveDateTime) -> i64 {
-    let micros = dt.timestamp_micros();
-    let offset = -1 * *TX_OFFSET.lock().unwrap();
-    micros + offset
+/// Get the microseconds sinds epoch (UTC) of a local date-time in the timezone of the analysis-context
+pub fn datetime_to_micros(ctx: &AnalysisContext, dt: NaiveDateTime) -> i64 {
+    ctx.timezone.to_utc_micros(dt)
 }
 
 /// date-time string as microsecond precision
@@ -36,6 +24,7 @@ pub fn datetime_millis_str(dt: NaiveDateTime) -> String {
 #[cfg(test)]
 mod test {
     use super::{datetime_micros_str, micros_to_datetime};
+    use crate::context::AnalysisContext;
 
     use chrono::{NaiveDate, NaiveDateTime};
 
@@ -53,7 +42,7 @@ mod test {
         const SECONDS: u32 = 22;
         const MILLIS: u32 = 462;
 
-        let dt1 = micros_to_datetime(DT1);
+        let dt1 = micros_to_datetime(&AnalysisContext::default(), DT1);
         // dt is now based on the NaiveDateTime
         //let ndt1 = NaiveDateTime::from_timestamp_micros(DT1).unwrap();
 
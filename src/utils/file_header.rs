@@ -0,0 +1,233 @@
+//! Version-headers of the json and bincode output-files and the checks applied when loading them.
+//! A bincode-file starts with an 8-byte magic (identifying the kind of data) followed by the major and minor version (u16, little-endian).
+//! A json-file contains a top-level 'version' field. Files without header or version-field are older files that need migration.
+use crate::view_api::Version;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{
+    error::Error,
+    fmt,
+    fs::{self, File},
+    io::{self, Write},
+    path::Path,
+};
+
+const HEADER_LEN: usize = 12;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileKind {
+    Stats,
+    Stitched,
+}
+
+impl FileKind {
+    fn magic(&self) -> &'static [u8; 8] {
+        match self {
+            FileKind::Stats => b"JSSTATS\0",
+            FileKind::Stitched => b"JSSTITCH",
+        }
+    }
+
+    pub fn current_version(&self) -> Version {
+        match self {
+            FileKind::Stats => Version::STATS,
+            FileKind::Stitched => Version::STITCHED,
+        }
+    }
+}
+
+impl fmt::Display for FileKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FileKind::Stats => write!(f, "statistics"),
+            FileKind::Stitched => write!(f, "stitched"),
+        }
+    }
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum FileFormatError {
+    #[error("'{path}' contains {found} data, while a {expected} file was expected")]
+    WrongKind {
+        path: String,
+        expected: FileKind,
+        found: FileKind,
+    },
+    #[error("'{path}' is a {kind} file of version {found}, which is newer than the supported version {supported}. Please upgrade the tools")]
+    TooNew {
+        path: String,
+        kind: FileKind,
+        found: Version,
+        supported: Version,
+    },
+    #[error("'{path}' is a {kind} file of version {found}, for which no migration exists")]
+    NoMigration {
+        path: String,
+        kind: FileKind,
+        found: Version,
+    },
+    #[error("Failed to parse '{path}' as {kind} file of version {version}: {msg}")]
+    Corrupt {
+        path: String,
+        kind: FileKind,
+        version: Version,
+        msg: String,
+    },
+}
+
+impl FileFormatError {
+    pub fn corrupt(path: &Path, kind: FileKind, version: Version, err: impl fmt::Display) -> Self {
+        FileFormatError::Corrupt {
+            path: path.display().to_string(),
+            kind,
+            version,
+            msg: err.to_string(),
+        }
+    }
+}
+
+/// The kind and version of a file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FileHeader {
+    pub kind: FileKind,
+    pub version: Version,
+}
+
+impl FileHeader {
+    /// Check that the file contains the 'expected' kind of data and that its version is not newer than the supported version
+    pub fn check(&self, path: &Path, expected: FileKind) -> Result<(), FileFormatError> {
+        if self.kind != expected {
+            Err(FileFormatError::WrongKind {
+                path: path.display().to_string(),
+                expected,
+                found: self.kind,
+            })
+        } else if self.version > expected.current_version() {
+            Err(FileFormatError::TooNew {
+                path: path.display().to_string(),
+                kind: self.kind,
+                found: self.version,
+                supported: expected.current_version(),
+            })
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Write the 'data' as bincode preceded by the header for the current version of this 'kind' of data.
+pub fn write_bincode<T: Serialize>(
+    path: &Path,
+    kind: FileKind,
+    data: &T,
+) -> Result<(), Box<dyn Error>> {
+    let f = File::create(path)?;
+    let mut writer = io::BufWriter::new(f);
+    let version = kind.current_version();
+    writer.write_all(kind.magic())?;
+    writer.write_all(&version.major.to_le_bytes())?;
+    writer.write_all(&version.minor.to_le_bytes())?;
+    bincode::serialize_into(&mut writer, data)?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// Parse the header of the bincode 'bytes' and return it together with the payload. Older files without a header return None and all bytes.
+pub fn split_bincode_header(bytes: &[u8]) -> (Option<FileHeader>, &[u8]) {
+    if bytes.len() >= HEADER_LEN {
+        for kind in [FileKind::Stats, FileKind::Stitched] {
+            if bytes[0..8] == kind.magic()[..] {
+                let major = u16::from_le_bytes([bytes[8], bytes[9]]);
+                let minor = u16::from_le_bytes([bytes[10], bytes[11]]);
+                let header = FileHeader {
+                    kind,
+                    version: Version::new(major, minor),
+                };
+                return (Some(header), &bytes[HEADER_LEN..]);
+            }
+        }
+    }
+    (None, bytes)
+}
+
+/// read a bincode file and split it in the (optional) header and the payload
+pub fn read_bincode(path: &Path) -> io::Result<(Option<FileHeader>, Vec<u8>)> {
+    let mut bytes = fs::read(path)?;
+    let (header, _) = split_bincode_header(&bytes);
+    if header.is_some() {
+        bytes.drain(..HEADER_LEN);
+    }
+    Ok((header, bytes))
+}
+
+/// Determine the kind and version of a parsed json-file. A file without version-field gets version 'Version::UNVERSIONED'.
+fn json_header(path: &Path, value: &Value) -> Result<FileHeader, Box<dyn Error>> {
+    let version = match value.get("version") {
+        Some(version) => Version::deserialize(version)?,
+        None => Version::UNVERSIONED,
+    };
+    let kind = match (value.get("trace_id"), value.get("sources")) {
+        (Some(_), _) => FileKind::Stats,
+        (None, Some(_)) => FileKind::Stitched,
+        (None, None) => Err(format!(
+            "'{}' is neither a statistics nor a stitched file",
+            path.display()
+        ))?,
+    };
+    Ok(FileHeader { kind, version })
+}
+
+/// Parse a json-file once and return its kind and version together with the parsed contents, such that the contents can be deserialized without reading the file again.
+pub fn read_json_with_header(path: &Path) -> Result<(FileHeader, Value), Box<dyn Error>> {
+    let reader = io::BufReader::new(File::open(path)?);
+    let value: Value = serde_json::from_reader(reader)?;
+    let header = json_header(path, &value)?;
+    Ok((header, value))
+}
+
+/// Determine the kind and version of a json-file.
+pub fn peek_json_header(path: &Path) -> Result<FileHeader, Box<dyn Error>> {
+    read_json_with_header(path).map(|(header, _)| header)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{split_bincode_header, FileHeader, FileKind};
+    use crate::view_api::Version;
+    use std::path::Path;
+
+    #[test]
+    fn bincode_header_round_trip() {
+        let mut bytes = b"JSSTITCH".to_vec();
+        bytes.extend(5u16.to_le_bytes());
+        bytes.extend(7u16.to_le_bytes());
+        bytes.extend([1, 2, 3]);
+        let (header, payload) = split_bincode_header(&bytes);
+        let header = header.unwrap();
+        assert_eq!(header.kind, FileKind::Stitched);
+        assert_eq!(header.version, Version::new(5, 7));
+        assert_eq!(payload, &[1, 2, 3]);
+
+        // no magic, so an (older) file without header
+        let (header, payload) = split_bincode_header(&[0; 20]);
+        assert!(header.is_none());
+        assert_eq!(payload.len(), 20);
+    }
+
+    #[test]
+    fn check_rejects_newer_version_and_wrong_kind() {
+        let path = Path::new("x.bincode");
+        let header = FileHeader {
+            kind: FileKind::Stats,
+            version: Version::STATS,
+        };
+        assert!(header.check(path, FileKind::Stats).is_ok());
+        assert!(header.check(path, FileKind::Stitched).is_err());
+        let newer = FileHeader {
+            kind: FileKind::Stats,
+            version: Version::new(Version::STATS.major + 1, 0),
+        };
+        let err = newer.check(path, FileKind::Stats).unwrap_err();
+        assert!(err.to_string().contains("newer than the supported version"));
+    }
+}
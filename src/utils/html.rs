@@ -0,0 +1,364 @@
+//! A minimal builder of self-contained HTML-pages (inline style, script and svg-charts) for shareable reports.
+use crate::context::AnalysisContext;
+use crate::view_api::types::ChartDataParameters;
+use std::{fmt::Write, fs, path::Path};
+
+const STYLE: &str = "
+body { font-family: sans-serif; margin: 2em; color: #222; }
+h1 { font-size: 1.6em; } h2 { font-size: 1.3em; margin-top: 2em; border-bottom: 1px solid #ccc; }
+table { border-collapse: collapse; font-size: 0.85em; margin: 0.5em 0; }
+th, td { border: 1px solid #ddd; padding: 2px 6px; text-align: left; }
+th { background: #f0f0f0; cursor: pointer; position: sticky; top: 0; }
+td.num { text-align: right; }
+.scroll { max-height: 40em; overflow: auto; }
+pre.mermaid { background: #fafafa; border: 1px solid #eee; padding: 0.5em; overflow: auto; }
+figure { margin: 1em 0; } figcaption { font-weight: bold; }
+.chart { display: inline-block; margin: 0.5em 1em 0.5em 0; vertical-align: top; }
+.note { color: #666; font-size: 0.85em; }
+";
+
+/// Sorting of a table when clicking on a header (numerical when both cells are numbers)
+const SORT_SCRIPT: &str = "
+document.querySelectorAll('table.sortable th').forEach(function (th) {
+  th.addEventListener('click', function () {
+    var table = th.closest('table'), body = table.tBodies[0], idx = th.cellIndex;
+    var asc = th.dataset.order !== 'asc';
+    th.parentNode.querySelectorAll('th').forEach(function (h) { delete h.dataset.order; });
+    th.dataset.order = asc ? 'asc' : 'desc';
+    var value = function (row) { return row.cells[idx] ? row.cells[idx].textContent.trim() : ''; };
+    Array.from(body.rows).sort(function (a, b) {
+      var x = value(a), y = value(b), nx = parseFloat(x), ny = parseFloat(y);
+      var res = (!isNaN(nx) && !isNaN(ny)) ? nx - ny : x.localeCompare(y);
+      return asc ? res : -res;
+    }).forEach(function (row) { body.appendChild(row); });
+  });
+});
+";
+
+const COLORS: [&str; 6] = [
+    "#1f77b4", "#ff7f0e", "#2ca02c", "#d62728", "#9467bd", "#8c564b",
+];
+const CHART_WIDTH: f64 = 480.0;
+const CHART_HEIGHT: f64 = 220.0;
+const MARGIN_LEFT: f64 = 60.0;
+const MARGIN_BOTTOM: f64 = 40.0;
+const MARGIN_TOP: f64 = 10.0;
+
+/// Escape the text for use in html-content or attribute-values
+pub fn escape_html(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn is_number(s: &str) -> bool {
+    s.parse::<f64>().is_ok_and(|v| v.is_finite())
+}
+
+/// A page with a table of contents, where each section gets an anchor
+pub struct HtmlPage {
+    title: String,
+    toc: Vec<(String, String)>,
+    body: String,
+}
+
+impl HtmlPage {
+    pub fn new(title: &str) -> Self {
+        Self {
+            title: title.to_owned(),
+            toc: Vec::new(),
+            body: String::new(),
+        }
+    }
+
+    pub fn add_section(&mut self, title: &str) {
+        let anchor = format!("s{}", self.toc.len());
+        writeln!(self.body, "<h2 id=\"{anchor}\">{}</h2>", escape_html(title)).unwrap();
+        self.toc.push((anchor, title.to_owned()));
+    }
+
+    pub fn add_note(&mut self, note: &str) {
+        writeln!(self.body, "<p class=\"note\">{}</p>", escape_html(note)).unwrap();
+    }
+
+    pub fn add_key_values(&mut self, items: &[(String, String)]) {
+        self.body.push_str("<table>\n");
+        items.iter().for_each(|(k, v)| {
+            writeln!(
+                self.body,
+                "<tr><th>{}</th><td>{}</td></tr>",
+                escape_html(k),
+                escape_html(v)
+            )
+            .unwrap()
+        });
+        self.body.push_str("</table>\n");
+    }
+
+    /// Add a sortable table based on ';'-separated lines where the first line contains the headers. Lines starting with '#' are skipped.
+    pub fn add_table(&mut self, lines: &[String]) {
+        let mut lines = lines
+            .iter()
+            .filter(|line| !line.trim().is_empty() && !line.starts_with('#'));
+        let Some(header) = lines.next() else {
+            return;
+        };
+        self.body
+            .push_str("<div class=\"scroll\"><table class=\"sortable\">\n<thead><tr>");
+        header
+            .split(';')
+            .for_each(|h| write!(self.body, "<th>{}</th>", escape_html(h.trim())).unwrap());
+        self.body.push_str("</tr></thead>\n<tbody>\n");
+        lines.for_each(|line| {
+            self.body.push_str("<tr>");
+            line.split(';').map(|v| v.trim()).for_each(|v| {
+                if is_number(v) {
+                    write!(self.body, "<td class=\"num\">{v}</td>").unwrap()
+                } else {
+                    write!(self.body, "<td>{}</td>", escape_html(v)).unwrap()
+                }
+            });
+            self.body.push_str("</tr>\n");
+        });
+        self.body.push_str("</tbody></table></div>\n");
+    }
+
+    pub fn add_mermaid(&mut self, caption: &str, diagram: &str) {
+        writeln!(
+            self.body,
+            "<figure><figcaption>{}</figcaption><pre class=\"mermaid\">{}</pre></figure>",
+            escape_html(caption),
+            escape_html(diagram)
+        )
+        .unwrap();
+    }
+
+    /// Add a line-chart as inline svg. Missing values (None) interrupt the line.
+    pub fn add_chart(&mut self, chart: &ChartDataParameters) {
+        self.body.push_str("<div class=\"chart\">");
+        writeln!(self.body, "<div><b>{}</b></div>", escape_html(&chart.title)).unwrap();
+        chart.description.iter().for_each(|(k, v)| {
+            writeln!(
+                self.body,
+                "<div class=\"note\">{}: {}</div>",
+                escape_html(k),
+                escape_html(v)
+            )
+            .unwrap()
+        });
+        self.body.push_str(&svg_chart(chart));
+        self.body.push_str("</div>\n");
+    }
+
+    /// The page as html, where the diagrams are rendered with the mermaid-library of the analysis-context 'ctx'
+    pub fn to_html(&self, ctx: &AnalysisContext) -> String {
+        let mut html = String::new();
+        writeln!(
+            html,
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n<style>{STYLE}</style>\n</head>\n<body>",
+            escape_html(&self.title)
+        )
+        .unwrap();
+        writeln!(html, "<h1>{}</h1>\n<ul>", escape_html(&self.title)).unwrap();
+        self.toc.iter().for_each(|(anchor, title)| {
+            writeln!(
+                html,
+                "<li><a href=\"#{anchor}\">{}</a></li>",
+                escape_html(title)
+            )
+            .unwrap()
+        });
+        html.push_str("</ul>\n");
+        html.push_str(&self.body);
+        writeln!(html, "<script>{SORT_SCRIPT}</script>").unwrap();
+        if let Some(mermaid_js) = &ctx.mermaid_js {
+            // prevent an early close of the script-element
+            let mermaid_js = mermaid_js.replace("</script", "<\\/script");
+            writeln!(
+                html,
+                "<script>{mermaid_js}</script>\n<script>mermaid.initialize({{ startOnLoad: true }});</script>"
+            )
+            .unwrap();
+        }
+        html.push_str("</body>\n</html>\n");
+        html
+    }
+
+    /// Is the mermaid-library available to render the diagrams
+    pub fn renders_mermaid(ctx: &AnalysisContext) -> bool {
+        ctx.mermaid_js.is_some()
+    }
+
+    pub fn write_file(&self, ctx: &AnalysisContext, path: &Path) {
+        if let Err(err) = fs::write(path, self.to_html(ctx)) {
+            println!(
+                "Writing file '{}' failed with Error: {err:?}",
+                path.display()
+            )
+        }
+    }
+}
+
+/// Format the value of a tick on the y-axis
+fn tick_label(v: f64) -> String {
+    if v.abs() >= 100.0 || v == 0.0 {
+        format!("{v:.0}")
+    } else {
+        format!("{v:.2}")
+    }
+}
+
+fn svg_chart(chart: &ChartDataParameters) -> String {
+    let values = chart
+        .lines
+        .iter()
+        .flat_map(|line| line.data.iter().flatten())
+        .filter(|v| v.is_finite());
+    let (min, max) = values.fold((0.0_f64, f64::MIN), |(min, max), v| {
+        (min.min(*v), max.max(*v))
+    });
+    let max = if max <= min { min + 1.0 } else { max };
+    let num_points = chart
+        .lines
+        .iter()
+        .map(|line| line.data.len())
+        .max()
+        .unwrap_or(0)
+        .max(chart.labels.len());
+
+    let plot_width = CHART_WIDTH - MARGIN_LEFT - 10.0;
+    let plot_height = CHART_HEIGHT - MARGIN_TOP - MARGIN_BOTTOM;
+    let x = |idx: usize| {
+        MARGIN_LEFT
+            + if num_points > 1 {
+                idx as f64 * plot_width / (num_points - 1) as f64
+            } else {
+                plot_width / 2.0
+            }
+    };
+    let y = |v: f64| MARGIN_TOP + plot_height * (1.0 - (v - min) / (max - min));
+
+    let legend_height = 14.0 * chart.lines.len() as f64;
+    let mut svg = String::new();
+    writeln!(
+        svg,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{CHART_WIDTH}\" height=\"{}\" font-size=\"10\">",
+        CHART_HEIGHT + legend_height
+    )
+    .unwrap();
+    // axes and ticks
+    writeln!(
+        svg,
+        "<rect x=\"{MARGIN_LEFT}\" y=\"{MARGIN_TOP}\" width=\"{plot_width}\" height=\"{plot_height}\" fill=\"none\" stroke=\"#999\"/>"
+    )
+    .unwrap();
+    (0..=4).for_each(|i| {
+        let v = min + (max - min) * i as f64 / 4.0;
+        writeln!(
+            svg,
+            "<text x=\"{}\" y=\"{:.1}\" text-anchor=\"end\">{}</text>",
+            MARGIN_LEFT - 4.0,
+            y(v) + 3.0,
+            tick_label(v)
+        )
+        .unwrap();
+    });
+    if num_points > 0 {
+        let mut label_idx = vec![0, num_points / 2, num_points - 1];
+        label_idx.dedup();
+        label_idx.into_iter().for_each(|idx| {
+            let label = chart.labels.get(idx).map(|s| s.as_str()).unwrap_or("");
+            let label: String = label.chars().take(24).collect();
+            writeln!(
+                svg,
+                "<text x=\"{:.1}\" y=\"{}\" text-anchor=\"middle\">{}</text>",
+                x(idx),
+                CHART_HEIGHT - MARGIN_BOTTOM + 14.0,
+                escape_html(&label)
+            )
+            .unwrap();
+        });
+    }
+    // the markers as dashed vertical lines
+    chart.markers.iter().for_each(|marker| {
+        writeln!(
+            svg,
+            "<line x1=\"{0:.1}\" y1=\"{MARGIN_TOP}\" x2=\"{0:.1}\" y2=\"{1}\" stroke=\"#c00\" stroke-dasharray=\"4,3\"><title>{2}</title></line>",
+            x(marker.index),
+            MARGIN_TOP + plot_height,
+            escape_html(&marker.description)
+        )
+        .unwrap();
+    });
+    // the lines split in segments of consecutive values
+    chart.lines.iter().enumerate().for_each(|(line_idx, line)| {
+        let color = COLORS[line_idx % COLORS.len()];
+        let mut segments: Vec<Vec<(usize, f64)>> = vec![Vec::new()];
+        line.data.iter().enumerate().for_each(|(idx, v)| match v {
+            Some(v) if v.is_finite() => segments.last_mut().unwrap().push((idx, *v)),
+            _ => segments.push(Vec::new()),
+        });
+        segments.iter().filter(|seg| !seg.is_empty()).for_each(|seg| {
+            let points: Vec<_> = seg
+                .iter()
+                .map(|(idx, v)| format!("{:.1},{:.1}", x(*idx), y(*v)))
+                .collect();
+            writeln!(
+                svg,
+                "<polyline points=\"{}\" fill=\"none\" stroke=\"{color}\" stroke-width=\"1.5\"/>",
+                points.join(" ")
+            )
+            .unwrap();
+            if line_idx == 0 {
+                seg.iter().for_each(|(idx, v)| {
+                    writeln!(
+                        svg,
+                        "<circle cx=\"{:.1}\" cy=\"{:.1}\" r=\"2\" fill=\"{color}\"><title>{}: {v}</title></circle>",
+                        x(*idx),
+                        y(*v),
+                        escape_html(chart.labels.get(*idx).map(|s| s.as_str()).unwrap_or(""))
+                    )
+                    .unwrap()
+                });
+            }
+        });
+        writeln!(
+            svg,
+            "<text x=\"{MARGIN_LEFT}\" y=\"{:.1}\" fill=\"{color}\">&#9632; {}</text>",
+            CHART_HEIGHT - MARGIN_BOTTOM + 30.0 + 14.0 * line_idx as f64,
+            escape_html(&line.label)
+        )
+        .unwrap();
+    });
+    svg.push_str("</svg>\n");
+    svg
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{escape_html, HtmlPage};
+    use crate::context::AnalysisContext;
+
+    #[test]
+    fn escapes_markup() {
+        assert_eq!(
+            escape_html(r#"a<b & "c">"#),
+            "a&lt;b &amp; &quot;c&quot;&gt;"
+        );
+    }
+
+    #[test]
+    fn table_has_header_and_numeric_cells() {
+        let mut page = HtmlPage::new("t");
+        page.add_table(&[
+            "#comment".to_owned(),
+            "Process; Count".to_owned(),
+            "a/b; 12".to_owned(),
+        ]);
+        let html = page.to_html(&AnalysisContext::default());
+        assert!(html.contains("<th>Process</th><th>Count</th>"));
+        assert!(html.contains("<td>a/b</td><td class=\"num\">12</td>"));
+        assert!(!html.contains("#comment"));
+    }
+}
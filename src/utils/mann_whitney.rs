@@ -0,0 +1,128 @@
+//! The Mann-Whitney U test (Wilcoxon rank-sum test) to check whether two samples originate from the same distribution.
+//! The p-value is computed via the normal approximation (including a correction for ties), which is accurate for samples of 8 or more values.
+
+/// The outcome of a Mann-Whitney U test of sample 'before' versus sample 'after'
+#[derive(Debug, Clone, Copy)]
+pub struct MannWhitney {
+    /// The U-statistic of the 'after' sample
+    pub u: f64,
+    /// The z-score of U under the normal approximation (positive when 'after' tends to have larger values)
+    pub z: f64,
+    /// two-sided p-value
+    pub p_value: f64,
+    /// Probability that a random value of 'after' exceeds a random value of 'before' (ties count half).
+    /// A value of 0.5 means no shift, while values close to 1.0 indicate that 'after' is larger.
+    pub effect_size: f64,
+}
+
+/// complementary error-function with a fractional error below 1.2e-7 (Numerical Recipes, erfcc)
+fn erfc(x: f64) -> f64 {
+    let z = x.abs();
+    let t = 1.0 / (1.0 + 0.5 * z);
+    let ans = t
+        * (-z * z - 1.26551223
+            + t * (1.00002368
+                + t * (0.37409196
+                    + t * (0.09678418
+                        + t * (-0.18628806
+                            + t * (0.27886807
+                                + t * (-1.13520398
+                                    + t * (1.48851587 + t * (-0.82215223 + t * 0.17087277)))))))))
+            .exp();
+    if x >= 0.0 {
+        ans
+    } else {
+        2.0 - ans
+    }
+}
+
+impl MannWhitney {
+    /// Perform the test. Returns None when one of the samples is empty.
+    pub fn test(before: &[i64], after: &[i64]) -> Option<Self> {
+        let n1 = before.len();
+        let n2 = after.len();
+        if n1 == 0 || n2 == 0 {
+            return None;
+        }
+
+        // rank the combined sample, where ties get the average rank
+        let mut values: Vec<(i64, bool)> = before
+            .iter()
+            .map(|v| (*v, false))
+            .chain(after.iter().map(|v| (*v, true)))
+            .collect();
+        values.sort_unstable_by_key(|(v, _)| *v);
+
+        let n = values.len();
+        let mut rank_sum_after = 0.0;
+        let mut tie_correction = 0.0;
+        let mut idx = 0;
+        while idx < n {
+            let mut end = idx + 1;
+            while end < n && values[end].0 == values[idx].0 {
+                end += 1;
+            }
+            let avg_rank = (idx + 1 + end) as f64 / 2.0;
+            let num_after = values[idx..end].iter().filter(|(_, a)| *a).count();
+            rank_sum_after += avg_rank * num_after as f64;
+            let t = (end - idx) as f64;
+            tie_correction += t * t * t - t;
+            idx = end;
+        }
+
+        let (n1, n2, n) = (n1 as f64, n2 as f64, n as f64);
+        let u = rank_sum_after - n2 * (n2 + 1.0) / 2.0;
+        let mean_u = n1 * n2 / 2.0;
+        let var_u = n1 * n2 / 12.0 * ((n + 1.0) - tie_correction / (n * (n - 1.0)).max(1.0));
+        let z = if var_u > 0.0 {
+            (u - mean_u) / var_u.sqrt()
+        } else {
+            0.0
+        };
+        let p_value = erfc(z.abs() / 2_f64.sqrt()).min(1.0);
+        Some(Self {
+            u,
+            z,
+            p_value,
+            effect_size: u / (n1 * n2),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MannWhitney;
+
+    #[test]
+    fn identical_samples() {
+        let sample = vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 10];
+        let mw = MannWhitney::test(&sample, &sample).unwrap();
+        assert!((mw.effect_size - 0.5).abs() < 1e-10);
+        assert!(mw.z.abs() < 1e-10);
+        assert!((mw.p_value - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn shifted_samples() {
+        let before: Vec<i64> = (1..=10).collect();
+        let after: Vec<i64> = (11..=20).collect();
+        let mw = MannWhitney::test(&before, &after).unwrap();
+        assert_eq!(mw.u, 100.0);
+        assert_eq!(mw.effect_size, 1.0);
+        assert!(mw.z > 0.0);
+        // exact z = 50 / sqrt(175) = 3.78, so p is about 1.6e-4
+        assert!(mw.p_value < 2e-4 && mw.p_value > 1e-4);
+
+        let mw = MannWhitney::test(&after, &before).unwrap();
+        assert_eq!(mw.u, 0.0);
+        assert!(mw.z < 0.0);
+    }
+
+    #[test]
+    fn all_values_tied() {
+        let mw = MannWhitney::test(&[5, 5, 5], &[5, 5]).unwrap();
+        assert_eq!(mw.z, 0.0);
+        assert!((mw.p_value - 1.0).abs() < 1e-6);
+        assert!(MannWhitney::test(&[], &[1]).is_none());
+    }
+}
@@ -2,38 +2,52 @@
 //!
 mod aggregate_data;
 mod comma_float;
+mod config;
 mod counted;
 mod csv_file;
 mod datetime;
 mod file;
+mod file_header;
 mod fs;
 mod hash;
+mod html;
+mod mann_whitney;
+mod parquet;
 mod rate;
 mod regression;
 mod report;
 mod time_stats;
+mod timezone;
+mod xlsx;
 
 pub use self::{
     aggregate_data::{AdditiveData, AggregateData, AverageData},
-    comma_float::{
-        floats_ref_to_string, floats_to_string, format_float, format_float_opt, set_comma_float,
-    },
+    comma_float::{floats_ref_to_string, floats_to_string, format_float, format_float_opt},
+    config::{args_with_config, config_to_args, parse_args_with_config, write_config},
     counted::Counted,
     csv_file::CsvFileBuffer,
-    datetime::{
-        datetime_micros_str, datetime_millis_str, datetime_to_micros, micros_to_datetime,
-        set_tz_offset_minutes,
-    },
+    datetime::{datetime_micros_str, datetime_millis_str, datetime_to_micros, micros_to_datetime},
     file::{
         clean_os_string, current_folder, extend_create_folder, extend_with_base_path,
         extend_with_base_path_opt, extract_base_path, is_rooted_path, read_lines,
         write_string_to_file,
     },
+    file_header::{
+        peek_json_header, read_bincode, read_json_with_header, split_bincode_header, write_bincode,
+        FileFormatError, FileHeader, FileKind,
+    },
     fs::canonicalize_path,
     hash::{hash, string_hash},
-    rate::{calc_rate, set_show_rate_output},
+    html::{escape_html, HtmlPage},
+    mann_whitney::MannWhitney,
+    parquet::{
+        bool_column, float_column, int_column, string_column, timestamp_column, write_parquet,
+    },
+    rate::calc_rate,
     regression::ExponentialRegression,
     regression::LinearRegression,
-    report::{report, write_report, Chapter},
+    report::{report, report_entry, Chapter, Report, ReportCounters, ReportEntry, Severity},
     time_stats::TimeStats,
+    timezone::Timezone,
+    xlsx::{write_xlsx, XlsxSheet},
 };
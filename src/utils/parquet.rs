@@ -0,0 +1,82 @@
+//! Writing arrow RecordBatches to parquet-files, such that the results can be loaded in pandas, polars or DuckDB.
+use arrow::{
+    array::{
+        ArrayRef, BooleanArray, Float64Array, Int64Array, StringArray, TimestampMicrosecondArray,
+    },
+    record_batch::RecordBatch,
+};
+use parquet::{arrow::ArrowWriter, basic::Compression, file::properties::WriterProperties};
+use std::{error::Error, fs::File, sync::Arc};
+
+/// write a single RecordBatch to a (snappy compressed) parquet-file
+pub fn write_parquet(file_name: &str, batch: &RecordBatch) -> Result<(), Box<dyn Error>> {
+    let file = File::create(file_name)?;
+    let props = WriterProperties::builder()
+        .set_compression(Compression::SNAPPY)
+        .build();
+    let mut writer = ArrowWriter::try_new(file, batch.schema(), Some(props))?;
+    writer.write(batch)?;
+    writer.close()?;
+    Ok(())
+}
+
+pub fn string_column<S: AsRef<str>>(values: impl IntoIterator<Item = S>) -> ArrayRef {
+    Arc::new(StringArray::from_iter_values(values))
+}
+
+pub fn int_column(values: impl IntoIterator<Item = i64>) -> ArrayRef {
+    Arc::new(Int64Array::from_iter_values(values))
+}
+
+pub fn bool_column(values: impl IntoIterator<Item = bool>) -> ArrayRef {
+    Arc::new(values.into_iter().map(Some).collect::<BooleanArray>())
+}
+
+/// column of nullable floats
+pub fn float_column(values: impl IntoIterator<Item = Option<f64>>) -> ArrayRef {
+    Arc::new(values.into_iter().collect::<Float64Array>())
+}
+
+/// column of nullable timestamps (represented as micro-seconds since epoch)
+pub fn timestamp_column(values: impl IntoIterator<Item = Option<i64>>) -> ArrayRef {
+    Arc::new(values.into_iter().collect::<TimestampMicrosecondArray>())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{float_column, string_column, write_parquet};
+    use arrow::{
+        array::{Array, Float64Array},
+        record_batch::RecordBatch,
+    };
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+    use std::fs::{self, File};
+
+    #[test]
+    fn write_and_read_back() {
+        let batch = RecordBatch::try_from_iter(vec![
+            ("key", string_column(["a", "b"])),
+            ("value", float_column([Some(1.5), None])),
+        ])
+        .unwrap();
+        let path = std::env::temp_dir().join("jaeger_stats_write_and_read_back.parquet");
+        let file_name = path.to_str().unwrap();
+        write_parquet(file_name, &batch).unwrap();
+
+        let reader = ParquetRecordBatchReaderBuilder::try_new(File::open(&path).unwrap())
+            .unwrap()
+            .build()
+            .unwrap();
+        let batches: Vec<_> = reader.map(|b| b.unwrap()).collect();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(batches.len(), 1);
+        assert_eq!(batches[0].schema(), batch.schema());
+        let values = batches[0]
+            .column(1)
+            .as_any()
+            .downcast_ref::<Float64Array>()
+            .unwrap();
+        assert_eq!(values.value(0), 1.5);
+        assert!(values.is_null(1));
+    }
+}
@@ -1,16 +1,9 @@
-use std::sync::Mutex;
-
-static SHOW_OUTPUT: Mutex<bool> = Mutex::new(false);
-
-#[allow(dead_code)]
-pub fn set_show_rate_output(val: bool) {
-    let mut guard = SHOW_OUTPUT.lock().unwrap();
-    *guard = val
-}
-
 /// When having too few points the rates will become unreliable
 const POINTS_NEEDED_FOR_RATE: i32 = 10;
 
+/// Show the details of the rate-computations on the console
+const SHOW_OUTPUT: bool = false;
+
 /// returns an average and a median rate (after dropping the outliers)
 pub fn calc_rate(data: &[i64], num_outliers: i32) -> Option<(f64, f64)> {
     assert!(num_outliers >= 0);
@@ -28,7 +21,7 @@ pub fn calc_rate(data: &[i64], num_outliers: i32) -> Option<(f64, f64)> {
     // drop the expected a number of outliers
     data.sort_unstable();
 
-    if *SHOW_OUTPUT.lock().unwrap() {
+    if SHOW_OUTPUT {
         println!("Show the sorted data before skipping the outliers!!");
         data.iter()
             .enumerate()
@@ -50,7 +43,7 @@ pub fn calc_rate(data: &[i64], num_outliers: i32) -> Option<(f64, f64)> {
     let t_med = data[med_idx] as f64 / 1e6;
     let med_rate = 1.0 / t_med;
 
-    if *SHOW_OUTPUT.lock().unwrap() {
+    if SHOW_OUTPUT {
         println!("  t_avg = {t_avg} ->  rate_avg={avg_rate} en t_med={t_med}  ->  med_rate {med_rate} voor  {} punten (after skipping {num_outliers} outliers).", data.len());
     }
 
@@ -1,24 +1,34 @@
-use super::{DataPoint, DataSet, LinearRegression};
+use super::{linear_regr::x_range, DataPoint, DataSet, LinearRegression};
 use serde::{Deserialize, Serialize};
 
 #[allow(non_snake_case)]
 #[derive(Debug, Deserialize, Serialize)]
 pub struct ExponentialRegression {
     /// the multiplier of the curve y = a * b^x
+    #[serde(deserialize_with = "super::nan_if_null")]
     pub a: f64,
     /// the base of the exponent in the curve y = a * b^x
+    #[serde(deserialize_with = "super::nan_if_null")]
     pub b: f64,
     /// Average growth per period (which is derived from b)
+    #[serde(deserialize_with = "super::nan_if_null")]
     pub avg_growth_per_period: f64,
     /// the R_squared is computed in log-space for the line.
+    #[serde(deserialize_with = "super::nan_if_null")]
     pub R_squared: f64,
 }
 
 impl ExponentialRegression {
+    /// Exponential regression where the index in 'orig_data' is used as x-value (equidistant data).
     pub fn new(orig_data: &[Option<f64>]) -> Option<Self> {
-        let data = get_log_dataset(orig_data);
-        let orig_len = orig_data.len();
-        match LinearRegression::new_from_dataset(&data, orig_len) {
+        let x_axis: Vec<_> = (0..orig_data.len()).map(|idx| idx as f64).collect();
+        Self::new_on_axis(orig_data, &x_axis)
+    }
+
+    /// Exponential regression where 'x_axis' contains the x-value of each data-point, so the growth is per unit of the x-axis.
+    pub fn new_on_axis(orig_data: &[Option<f64>], x_axis: &[f64]) -> Option<Self> {
+        let data = get_log_dataset(orig_data, x_axis);
+        match LinearRegression::new_from_dataset(&data, x_range(x_axis)) {
             Some(lr) => {
                 let b = lr.slope.exp();
                 Some(Self {
@@ -38,17 +48,12 @@ impl ExponentialRegression {
     }
 }
 
-/// Get the dataset over x and ln(y) for all filled values, where x = counting from 0 to N-1
-fn get_log_dataset(data: &[Option<f64>]) -> DataSet {
+/// Get the dataset over x and ln(y) for all filled values, where x is taken from the 'x_axis'
+fn get_log_dataset(data: &[Option<f64>], x_axis: &[f64]) -> DataSet {
+    assert_eq!(data.len(), x_axis.len(), "x-axis does not match the data");
     data.iter()
-        .enumerate()
-        // TODO: is  the .as_ref() on next line needed as f64 is copy?
-        .filter_map(|(idx, val)| {
-            val.as_ref().map(|val| DataPoint {
-                x: idx as f64,
-                y: (*val).ln(),
-            })
-        })
+        .zip(x_axis)
+        .filter_map(|(val, x)| val.map(|y| DataPoint { x: *x, y: y.ln() }))
         .collect()
 }
 
@@ -5,21 +5,33 @@ use serde::{Deserialize, Serialize};
 #[allow(non_snake_case)]
 #[derive(Debug, Deserialize, Serialize)]
 pub struct LinearRegression {
+    #[serde(deserialize_with = "super::nan_if_null")]
     pub slope: f64,
+    #[serde(deserialize_with = "super::nan_if_null")]
     pub y_intercept: f64,
+    #[serde(deserialize_with = "super::nan_if_null")]
     pub R_squared: f64,
+    #[serde(deserialize_with = "super::nan_if_null")]
     pub L1_deviation: f64,
     pub avg_growth_per_period: Option<f64>,
 }
 
 impl LinearRegression {
+    /// Linear regression where the index in 'orig_data' is used as x-value (equidistant data).
     pub fn new(orig_data: &[Option<f64>]) -> Option<Self> {
-        let data = get_dataset(orig_data);
-        let orig_len = orig_data.len();
-        Self::new_from_dataset(&data, orig_len)
+        let x_axis: Vec<_> = (0..orig_data.len()).map(|idx| idx as f64).collect();
+        Self::new_on_axis(orig_data, &x_axis)
     }
 
-    pub fn new_from_dataset(data: &DataSet, orig_len: usize) -> Option<Self> {
+    /// Linear regression where 'x_axis' contains the x-value of each data-point (for example the time in days).
+    /// The slope and the 'avg_growth_per_period' are expressed per unit of the x-axis.
+    pub fn new_on_axis(orig_data: &[Option<f64>], x_axis: &[f64]) -> Option<Self> {
+        let data = get_dataset(orig_data, x_axis);
+        Self::new_from_dataset(&data, x_range(x_axis))
+    }
+
+    /// Linear regression on 'data' where 'x_range' is the range of the x-axis of the full series (including missing values).
+    pub fn new_from_dataset(data: &DataSet, x_range: (f64, f64)) -> Option<Self> {
         if data.len() < 2 {
             // insufficient data to compute a value
             None
@@ -33,9 +45,9 @@ impl LinearRegression {
             let L1_deviation = get_L1_deviation(data, slope, y_intercept);
 
             let avg_growth_per_period = {
-                let start = y_intercept;
-                let num_step = (orig_len - 1) as f64;
-                let end = y_intercept + slope * num_step;
+                let start = y_intercept + slope * x_range.0;
+                let num_step = x_range.1 - x_range.0;
+                let end = y_intercept + slope * x_range.1;
                 let mid_point = (start + end) / 2.0; // using mid-point as it represents dataset better (or does this all single out?)
                 if mid_point.abs() > 1e-100 {
                     Some((end - start) / mid_point / (num_step / 2.0))
@@ -55,13 +67,9 @@ impl LinearRegression {
             })
         }
     }
-    pub fn get_deviation(&self, data: &[Option<f64>], idx: usize) -> Option<f64> {
-        assert!(idx < data.len());
-        data[idx].map(|y| {
-            let x = (idx + 1) as f64;
-            let expect = self.y_intercept + x * self.slope;
-            y - expect
-        })
+    /// the deviation of the value 'y' at 'x' from the regression line
+    pub fn get_deviation(&self, y: Option<f64>, x: f64) -> Option<f64> {
+        y.map(|y| y - self.predict(x))
     }
 
     /// predict the y value for a specific x-value
@@ -70,17 +78,20 @@ impl LinearRegression {
     }
 }
 
-/// Get the dataset over x and y for all filled values, where x = counting from 0 to N-1
-fn get_dataset(data: &[Option<f64>]) -> DataSet {
+/// The first and last value of the x-axis
+pub(super) fn x_range(x_axis: &[f64]) -> (f64, f64) {
+    (
+        x_axis.first().copied().unwrap_or_default(),
+        x_axis.last().copied().unwrap_or_default(),
+    )
+}
+
+/// Get the dataset over x and y for all filled values, where x is taken from the 'x_axis'
+fn get_dataset(data: &[Option<f64>], x_axis: &[f64]) -> DataSet {
+    assert_eq!(data.len(), x_axis.len(), "x-axis does not match the data");
     data.iter()
-        .enumerate()
-        // TODO: is  the .as_ref() on next line needed as f64 is copy?
-        .filter_map(|(idx, val)| {
-            val.as_ref().map(|val| DataPoint {
-                x: idx as f64,
-                y: *val,
-            })
-        })
+        .zip(x_axis)
+        .filter_map(|(val, x)| val.map(|y| DataPoint { x: *x, y }))
         .collect()
 }
 
@@ -204,6 +215,55 @@ mod tests {
         );
     }
 
+    #[test]
+    fn irregular_axis() {
+        // y = 2x + 1 sampled at irregular moments, so the slope is per unit of the x-axis
+        let input = vec![Some(1.0), Some(3.0), Some(9.0), Some(15.0)];
+        let x_axis = [0.0, 1.0, 4.0, 7.0];
+
+        let lr = LinearRegression::new_on_axis(&input, &x_axis).unwrap();
+
+        assert!(match_floats(lr.slope, 2.0), "Slope incorrect: {}", lr.slope);
+        assert!(match_floats(lr.y_intercept, 1.0), "y_intersect incorrect");
+        assert!(match_floats(lr.R_squared, 1.0), "R_squared incorrect");
+        // from 1 to 15 over 7 days with a mid-point of 8
+        assert!(match_float_opts(
+            lr.avg_growth_per_period,
+            Some(14.0 / 8.0 / 3.5)
+        ));
+    }
+
+    #[test]
+    fn json_round_trip_of_a_nan() {
+        let lr = LinearRegression {
+            slope: 0.0,
+            y_intercept: 5.0,
+            R_squared: f64::NAN,
+            L1_deviation: 0.0,
+            avg_growth_per_period: None,
+        };
+        let json = serde_json::to_string(&lr).unwrap();
+        assert!(json.contains("\"R_squared\":null"), "{json}");
+        let lr2: LinearRegression = serde_json::from_str(&json).unwrap();
+        assert!(lr2.R_squared.is_nan());
+        assert_eq!(lr2.y_intercept, 5.0);
+        let lr3: LinearRegression =
+            bincode::deserialize(&bincode::serialize(&lr).unwrap()).unwrap();
+        assert!(lr3.R_squared.is_nan());
+    }
+
+    #[test]
+    fn deviation_at_the_x_of_the_data_point() {
+        // y = 2x + 1 with a jump of 3 at the last data-point (x = 4)
+        let input = [Some(1.0), Some(3.0), Some(5.0), Some(7.0), Some(12.0)];
+        let lr = LinearRegression::new(&[Some(1.0), Some(3.0), Some(5.0), Some(7.0)]).unwrap();
+
+        // a value on the line has no deviation, so the x-value is not shifted by one
+        assert!(match_float_opts(lr.get_deviation(input[3], 3.0), Some(0.0)));
+        assert!(match_float_opts(lr.get_deviation(input[4], 4.0), Some(3.0)));
+        assert_eq!(lr.get_deviation(None, 4.0), None);
+    }
+
     #[test]
     fn lectures_test() {
         // exmple taken from source: https://www.ncl.ac.uk/webtemplate/ask-assets/external/maths-resources/statistics/regression-and-correlation/coefficient-of-determination-r-squared.html
@@ -11,5 +11,16 @@ pub type DataSet = Vec<DataPoint>;
 
 type Averages = (f64, f64);
 
+/// Json has no NaN, so a NaN (for example the R_squared of a constant series) is written as null by serde_json and read back as NaN.
+/// Binary formats such as bincode store the NaN itself.
+fn nan_if_null<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<f64, D::Error> {
+    use serde::Deserialize;
+    if deserializer.is_human_readable() {
+        Option::<f64>::deserialize(deserializer).map(|value| value.unwrap_or(f64::NAN))
+    } else {
+        f64::deserialize(deserializer)
+    }
+}
+
 pub use exponential_regr::ExponentialRegression;
 pub use linear_regr::LinearRegression;
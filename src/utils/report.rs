@@ -1,7 +1,10 @@
-// use crate::report::Chapter;
-use std::{fs::File, io::Write, sync::Mutex};
+//! The report of an analysis. Each entry has a chapter, a severity, a stable code and optionally the trace, end-point or file it refers to,
+//! such that the report can be written both as text ('report.txt') and as machine-readable json ('report.json').
+use serde::Serialize;
+use std::{fs::File, io::Write};
 
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
 #[repr(u8)]
 pub enum Chapter {
     Summary = 0,
@@ -20,33 +23,293 @@ impl Chapter {
     }
 }
 
-static STORE: Mutex<Vec<Vec<String>>> = Mutex::new(Vec::new());
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+}
 
-pub fn report(chapter: Chapter, msg: String) {
-    let idx = chapter.discriminant();
-    if idx == Chapter::Summary as usize {
-        println!("{msg}");
-    }
+/// A single message of the report
+#[derive(Debug, Clone, Serialize)]
+pub struct ReportEntry {
+    pub chapter: Chapter,
+    pub severity: Severity,
+    /// A stable identifier of the kind of message (for example 'incomplete_traces'), that can be used for filtering
+    pub code: &'static str,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trace_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub endpoint: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+}
 
-    {
-        let mut guard = STORE.lock().unwrap();
-        while guard.len() <= idx {
-            guard.push(Vec::new());
+impl ReportEntry {
+    pub fn new(chapter: Chapter, severity: Severity, code: &'static str, message: String) -> Self {
+        Self {
+            chapter,
+            severity,
+            code,
+            message,
+            trace_id: None,
+            endpoint: None,
+            file: None,
         }
-        guard[idx].push(msg);
+    }
+
+    pub fn trace_id(mut self, trace_id: &str) -> Self {
+        self.trace_id = Some(trace_id.to_owned());
+        self
+    }
+
+    pub fn endpoint(mut self, endpoint: &str) -> Self {
+        self.endpoint = Some(endpoint.to_owned());
+        self
+    }
+
+    pub fn file(mut self, file: &str) -> Self {
+        self.file = Some(file.to_owned());
+        self
+    }
+}
+
+/// The summary counters of an analysis
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize)]
+pub struct ReportCounters {
+    pub num_traces: usize,
+    pub incomplete_traces: usize,
+    /// non-rooted call-chains that have been repaired
+    pub fixes: usize,
+    /// non-rooted call-chains that could not be repaired
+    pub failed_fixes: usize,
+    pub unknown_call_directions: usize,
+    pub ignored_tags: usize,
+}
+
+impl ReportCounters {
+    /// add the counters of 'other' to these counters
+    pub fn add(&mut self, other: &ReportCounters) {
+        self.num_traces += other.num_traces;
+        self.incomplete_traces += other.incomplete_traces;
+        self.fixes += other.fixes;
+        self.failed_fixes += other.failed_fixes;
+        self.unknown_call_directions += other.unknown_call_directions;
+        self.ignored_tags += other.ignored_tags;
+    }
+}
+
+/// The report of an analysis: the entries in order of reporting and the summary counters.
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct Report {
+    pub counters: ReportCounters,
+    entries: Vec<ReportEntry>,
+}
+
+impl Report {
+    /// Add a message with the default severity of the chapter
+    pub fn add(&mut self, chapter: Chapter, code: &'static str, msg: String) {
+        self.add_entry(ReportEntry::new(
+            chapter,
+            default_severity(chapter),
+            code,
+            msg,
+        ));
+    }
+
+    pub fn add_entry(&mut self, entry: ReportEntry) {
+        self.entries.push(entry);
+    }
+
+    /// Append the entries and counters of 'other' to this report
+    pub fn append(&mut self, other: Report) {
+        self.counters.add(&other.counters);
+        self.entries.extend(other.entries);
+    }
+
+    pub fn entries(&self) -> &[ReportEntry] {
+        &self.entries
+    }
+
+    /// The messages reported in 'chapter'
+    pub fn lines(&self, chapter: Chapter) -> Vec<&str> {
+        self.entries
+            .iter()
+            .filter(|entry| entry.chapter == chapter)
+            .map(|entry| &entry.message[..])
+            .collect()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// The report as text, with the chapter-name above each chapter. All chapters up to the last chapter that has entries are shown.
+    pub fn to_text(&self) -> String {
+        let num_chapters = self
+            .entries
+            .iter()
+            .map(|entry| entry.chapter.discriminant() + 1)
+            .max()
+            .unwrap_or(0);
+        CHAPTER_NAMES[..num_chapters]
+            .iter()
+            .enumerate()
+            .map(|(idx, name)| {
+                let lines: Vec<_> = self
+                    .entries
+                    .iter()
+                    .filter(|entry| entry.chapter.discriminant() == idx)
+                    .map(|entry| &entry.message[..])
+                    .collect();
+                format!("{name}\n{}\n\n", lines.join("\n"))
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    pub fn write_file(&self, path: &str) {
+        let mut f = File::create(path).expect("Failed to create report-file");
+        f.write_all(self.to_text().as_bytes())
+            .expect("Failed to write to report.");
+    }
+
+    /// Write the report (counters and entries) as json
+    pub fn write_json(&self, path: &str) {
+        let f = File::create(path).expect("Failed to create json report-file");
+        serde_json::to_writer_pretty(f, self).expect("Failed to write json report.");
     }
 }
 
-pub fn write_report(path: &str) {
-    let mut guard = STORE.lock().unwrap();
-    let contents = (0..guard.len())
-        .map(|idx| format!("{}\n{}\n\n", CHAPTER_NAMES[idx], guard[idx].join("\n")))
-        .collect::<Vec<_>>()
-        .join("\n");
-    let mut f = File::create(path).expect("Failed to create report-file");
-    f.write_all(contents.as_bytes())
-        .expect("Failed to write to report.");
+/// The severity of messages that are reported without an explicit severity
+fn default_severity(chapter: Chapter) -> Severity {
+    match chapter {
+        Chapter::Issues => Severity::Warning,
+        _ => Severity::Info,
+    }
+}
 
-    // wipe the contents
-    *guard = Vec::new();
+/// Add a message with a stable 'code' to 'report'. Messages of the Summary are also shown on the console.
+pub fn report(report: &mut Report, chapter: Chapter, code: &'static str, msg: String) {
+    report_entry(
+        report,
+        ReportEntry::new(chapter, default_severity(chapter), code, msg),
+    );
+}
+
+/// Add an entry to 'report'. Entries of the Summary are also shown on the console.
+pub fn report_entry(report: &mut Report, entry: ReportEntry) {
+    if entry.chapter == Chapter::Summary {
+        println!("{}", entry.message);
+    }
+    report.add_entry(entry);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Chapter, Report, ReportCounters, ReportEntry, Severity};
+    use std::{env, fs};
+
+    fn sample_report() -> Report {
+        let mut report = Report::default();
+        report.add(
+            Chapter::Summary,
+            "num_traces",
+            "Processed 2 traces".to_owned(),
+        );
+        report.add(
+            Chapter::Issues,
+            "probabilistic_fixes",
+            "1 call assigned".to_owned(),
+        );
+        report.add_entry(
+            ReportEntry::new(
+                Chapter::Analysis,
+                Severity::Error,
+                "call_chain_mismatch",
+                "Mismatch".to_owned(),
+            )
+            .trace_id("abc")
+            .endpoint("gateway/GET"),
+        );
+        report.counters.num_traces = 2;
+        report
+    }
+
+    #[test]
+    fn entries_have_severity_and_code() {
+        let report = sample_report();
+        let entries = report.entries();
+        assert_eq!(
+            entries
+                .iter()
+                .map(|entry| (entry.chapter, entry.severity, entry.code))
+                .collect::<Vec<_>>(),
+            [
+                (Chapter::Summary, Severity::Info, "num_traces"),
+                (Chapter::Issues, Severity::Warning, "probabilistic_fixes"),
+                (Chapter::Analysis, Severity::Error, "call_chain_mismatch"),
+            ]
+        );
+        assert_eq!(entries[2].trace_id.as_deref(), Some("abc"));
+        assert_eq!(entries[2].endpoint.as_deref(), Some("gateway/GET"));
+        assert_eq!(entries[2].file, None);
+        assert_eq!(report.lines(Chapter::Issues), ["1 call assigned"]);
+    }
+
+    #[test]
+    fn counters_are_added_on_append() {
+        let mut report = sample_report();
+        let mut other = Report {
+            counters: ReportCounters {
+                num_traces: 3,
+                fixes: 1,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        other.add(Chapter::Details, "test", "Other".to_owned());
+        report.append(other);
+
+        assert_eq!(
+            report.counters,
+            ReportCounters {
+                num_traces: 5,
+                fixes: 1,
+                ..Default::default()
+            }
+        );
+        assert_eq!(report.entries().len(), 4);
+    }
+
+    #[test]
+    fn text_shows_all_chapters_up_to_the_last_used() {
+        // the empty 'Ingest' chapter is shown, the chapters after 'Analysis' are not
+        assert_eq!(
+            sample_report().to_text(),
+            "Summary\nProcessed 2 traces\n\n\nIssues\n1 call assigned\n\n\nIngest\n\n\n\nAnalysis\nMismatch\n\n"
+        );
+        assert_eq!(Report::default().to_text(), "");
+    }
+
+    #[test]
+    fn write_json_with_counters_and_entries() {
+        let path = env::temp_dir().join("jaeger_stats_report.json");
+        sample_report().write_json(path.to_str().unwrap());
+        let json: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+
+        assert_eq!(json["counters"]["num_traces"], 2);
+        assert_eq!(json["counters"]["failed_fixes"], 0);
+        let entries = json["entries"].as_array().unwrap();
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0]["chapter"], "summary");
+        assert_eq!(entries[0]["severity"], "info");
+        assert_eq!(entries[0]["code"], "num_traces");
+        assert!(entries[0].get("trace_id").is_none());
+        assert_eq!(entries[2]["severity"], "error");
+        assert_eq!(entries[2]["trace_id"], "abc");
+    }
 }
@@ -1,6 +1,8 @@
 //! This module contains some tools om timing statistics, such as averages, min, max, median values.
 //! The input is an array of i64 values that represent microseconds. The outputs are metrics in milliseconds.
 
+use crate::context::AnalysisContext;
+
 #[allow(dead_code)]
 
 /// Wrapper to implement time-functions on an array of integers that represent times in nicro-seconds.
@@ -12,8 +14,8 @@ impl<'a> TimeStats<'a> {
         *self.0.iter().min().expect("Not an integer") as f64 / 1000_f64
     }
 
-    pub fn get_min_millis_str(&self) -> String {
-        super::format_float(self.get_min_millis())
+    pub fn get_min_millis_str(&self, ctx: &AnalysisContext) -> String {
+        super::format_float(ctx, self.get_min_millis())
     }
 
     /// Computation of a P-percentile value, which is an exiting value that exceed P% of the measured values.
@@ -28,8 +30,8 @@ impl<'a> TimeStats<'a> {
         }
     }
 
-    pub fn get_p_millis_str(&self, p: f64) -> String {
-        super::format_float_opt(self.get_p_millis(p))
+    pub fn get_p_millis_str(&self, ctx: &AnalysisContext, p: f64) -> String {
+        super::format_float_opt(ctx, self.get_p_millis(p))
     }
 
     /// Determine medium in milliseconds. For an odd sized array the Median equals the p50 value (percentile 50).
@@ -51,24 +53,24 @@ impl<'a> TimeStats<'a> {
         }
     }
 
-    pub fn get_median_millis_str(&self) -> String {
-        super::format_float_opt(self.get_median_millis())
+    pub fn get_median_millis_str(&self, ctx: &AnalysisContext) -> String {
+        super::format_float_opt(ctx, self.get_median_millis())
     }
 
     pub fn get_avg_millis(&self) -> f64 {
         self.0.iter().sum::<i64>() as f64 / (1000_f64 * self.0.len() as f64)
     }
 
-    pub fn get_avg_millis_str(&self) -> String {
-        super::format_float(self.get_avg_millis())
+    pub fn get_avg_millis_str(&self, ctx: &AnalysisContext) -> String {
+        super::format_float(ctx, self.get_avg_millis())
     }
 
     pub fn get_max_millis(&self) -> f64 {
         *self.0.iter().max().expect("Not an integer") as f64 / 1000_f64
     }
 
-    pub fn get_max_millis_str(&self) -> String {
-        super::format_float(self.get_max_millis())
+    pub fn get_max_millis_str(&self, ctx: &AnalysisContext) -> String {
+        super::format_float(ctx, self.get_max_millis())
     }
 
     pub fn get_avg_rate(&self, num_files: i32) -> Option<f64> {
@@ -79,8 +81,8 @@ impl<'a> TimeStats<'a> {
         }
     }
 
-    pub fn get_avg_rate_str(&self, num_files: i32) -> String {
-        super::format_float_opt(self.get_avg_rate(num_files))
+    pub fn get_avg_rate_str(&self, ctx: &AnalysisContext, num_files: i32) -> String {
+        super::format_float_opt(ctx, self.get_avg_rate(num_files))
     }
 
     /// as the distribution is not symmetric (t >/ 0) the median is not a good estimator for the rate as it exludes one tail.
@@ -95,8 +97,8 @@ impl<'a> TimeStats<'a> {
 
     /// as the distribution is not symmetric (t >/ 0) the median is not a good estimator for the rate as it exludes one tail.
     /// You can expect that the median T (duration between samples) is above the average, and thus the median rate (f = 1/T) is lower.
-    pub fn get_median_rate_str(&self, num_files: i32) -> String {
-        super::format_float_opt(self.get_median_rate(num_files))
+    pub fn get_median_rate_str(&self, ctx: &AnalysisContext, num_files: i32) -> String {
+        super::format_float_opt(ctx, self.get_median_rate(num_files))
     }
 }
 
@@ -0,0 +1,149 @@
+//! The timezone used to show the (UTC) timestamps of the traces as local date-times.
+//! A named IANA timezone (for example 'Europe/Amsterdam') takes daylight-saving time into account, while a fixed offset is applied to all timestamps.
+use chrono::{DateTime, LocalResult, NaiveDateTime, Offset, TimeZone};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use std::{fmt, str::FromStr};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(into = "String", try_from = "String")]
+pub enum Timezone {
+    /// A fixed offset relative to UTC in minutes
+    Offset(i64),
+    /// An IANA timezone with daylight-saving time
+    Named(Tz),
+}
+
+impl Default for Timezone {
+    fn default() -> Self {
+        Timezone::Offset(0)
+    }
+}
+
+impl Timezone {
+    /// The offset in micro-seconds that applies at the UTC-moment 'utc'
+    fn offset_micros_at_utc(&self, utc: &NaiveDateTime) -> i64 {
+        match self {
+            Timezone::Offset(minutes) => minutes * 60 * 1_000_000,
+            Timezone::Named(tz) => {
+                tz.offset_from_utc_datetime(utc).fix().local_minus_utc() as i64 * 1_000_000
+            }
+        }
+    }
+
+    /// Translate the micro-seconds since epoch (UTC) to a local date-time
+    pub fn to_local(&self, utc_micros: i64) -> NaiveDateTime {
+        let utc = DateTime::from_timestamp_micros(utc_micros)
+            .unwrap_or_else(|| panic!("Invalid time provided in micros: {utc_micros}"))
+            .naive_utc();
+        utc + chrono::Duration::microseconds(self.offset_micros_at_utc(&utc))
+    }
+
+    /// Translate a local date-time to micro-seconds since epoch (UTC).
+    /// During the switch back from daylight-saving time a local time occurs twice, in which case the first occurence is used.
+    /// A local time that is skipped at the switch to daylight-saving time uses the offset before the switch.
+    pub fn to_utc_micros(&self, local: NaiveDateTime) -> i64 {
+        let offset_micros = match self {
+            Timezone::Offset(minutes) => minutes * 60 * 1_000_000,
+            Timezone::Named(tz) => match tz.offset_from_local_datetime(&local) {
+                LocalResult::Single(offset) | LocalResult::Ambiguous(offset, _) => {
+                    offset.fix().local_minus_utc() as i64 * 1_000_000
+                }
+                // a day earlier is safely before the switch
+                LocalResult::None => {
+                    self.offset_micros_at_utc(&(local - chrono::Duration::days(1)))
+                }
+            },
+        };
+        local.and_utc().timestamp_micros() - offset_micros
+    }
+}
+
+impl FromStr for Timezone {
+    type Err = String;
+
+    /// Parse an offset in minutes (for example '120') or an IANA timezone-name (for example 'Europe/Amsterdam')
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Ok(minutes) = s.trim().parse::<i64>() {
+            return Ok(Timezone::Offset(minutes));
+        }
+        s.trim()
+            .parse::<Tz>()
+            .map(Timezone::Named)
+            .map_err(|_| format!("'{s}' is not an offset in minutes or an IANA timezone"))
+    }
+}
+
+impl fmt::Display for Timezone {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Timezone::Offset(minutes) => write!(f, "{minutes}"),
+            Timezone::Named(tz) => write!(f, "{}", tz.name()),
+        }
+    }
+}
+
+impl From<Timezone> for String {
+    fn from(tz: Timezone) -> Self {
+        tz.to_string()
+    }
+}
+
+impl TryFrom<String> for Timezone {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Timezone;
+    use chrono::NaiveDate;
+
+    fn dt(month: u32, day: u32, hour: u32, min: u32) -> chrono::NaiveDateTime {
+        NaiveDate::from_ymd_opt(2023, month, day)
+            .unwrap()
+            .and_hms_opt(hour, min, 0)
+            .unwrap()
+    }
+
+    fn utc_micros(month: u32, day: u32, hour: u32, min: u32) -> i64 {
+        dt(month, day, hour, min).and_utc().timestamp_micros()
+    }
+
+    #[test]
+    fn named_timezone_follows_daylight_saving() {
+        let tz: Timezone = "Europe/Amsterdam".parse().unwrap();
+        // winter-time is UTC+1 and summer-time is UTC+2
+        assert_eq!(tz.to_local(utc_micros(1, 15, 12, 0)), dt(1, 15, 13, 0));
+        assert_eq!(tz.to_local(utc_micros(7, 15, 12, 0)), dt(7, 15, 14, 0));
+        // the switch to summer-time on 26 March 2023 at 01:00 UTC
+        assert_eq!(tz.to_local(utc_micros(3, 26, 0, 59)), dt(3, 26, 1, 59));
+        assert_eq!(tz.to_local(utc_micros(3, 26, 1, 0)), dt(3, 26, 3, 0));
+
+        let micros = utc_micros(7, 15, 12, 0);
+        assert_eq!(tz.to_utc_micros(tz.to_local(micros)), micros);
+        // 02:30 is skipped in spring, so the winter-offset is used
+        assert_eq!(tz.to_utc_micros(dt(3, 26, 2, 30)), utc_micros(3, 26, 1, 30));
+        // 02:30 occurs twice in autumn, the first occurence (summer-time) is used
+        assert_eq!(
+            tz.to_utc_micros(dt(10, 29, 2, 30)),
+            utc_micros(10, 29, 0, 30)
+        );
+    }
+
+    #[test]
+    fn parse_and_display() {
+        assert_eq!("120".parse::<Timezone>(), Ok(Timezone::Offset(120)));
+        let epoch_plus_2h = NaiveDate::from_ymd_opt(1970, 1, 1)
+            .unwrap()
+            .and_hms_opt(2, 0, 0)
+            .unwrap();
+        assert_eq!(Timezone::Offset(120).to_local(0), epoch_plus_2h);
+        let tz: Timezone = "Europe/Amsterdam".parse().unwrap();
+        assert_eq!(tz.to_string(), "Europe/Amsterdam");
+        assert!("Mars/Olympus".parse::<Timezone>().is_err());
+    }
+}
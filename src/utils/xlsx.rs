@@ -0,0 +1,144 @@
+//! Writing ';'-separated csv-lines as an Excel workbook with one sheet per section, typed (numeric) cells, a frozen header-row and auto-filters.
+use rust_xlsxwriter::{Format, Workbook};
+use std::{collections::HashSet, error::Error, path::Path};
+
+/// Excel limits the length of a sheet-name to 31 characters
+const MAX_SHEET_NAME_LEN: usize = 31;
+/// Excel limits the length of the text in a cell
+const MAX_CELL_LEN: usize = 32767;
+
+/// A sheet of the workbook where the first line contains the headers.
+pub struct XlsxSheet {
+    pub name: String,
+    pub lines: Vec<String>,
+}
+
+impl XlsxSheet {
+    pub fn new(name: &str, lines: Vec<String>) -> Self {
+        Self {
+            name: name.to_owned(),
+            lines,
+        }
+    }
+}
+
+/// Turn the name into a valid and unique sheet-name
+fn sheet_name(name: &str, used: &mut HashSet<String>) -> String {
+    let base: String = name
+        .chars()
+        .map(|c| match c {
+            '[' | ']' | ':' | '*' | '?' | '/' | '\\' => '_',
+            c => c,
+        })
+        .take(MAX_SHEET_NAME_LEN)
+        .collect();
+    let base = base.trim().trim_matches('\'').to_owned();
+    let mut name = if base.is_empty() {
+        "Sheet".to_owned()
+    } else {
+        base
+    };
+    let mut idx = 2;
+    while used.contains(&name.to_lowercase()) {
+        let suffix = format!(" ({idx})");
+        let prefix: String = name
+            .chars()
+            .take(MAX_SHEET_NAME_LEN - suffix.len())
+            .collect();
+        name = format!("{prefix}{suffix}");
+        idx += 1;
+    }
+    used.insert(name.to_lowercase());
+    name
+}
+
+/// A cell-value is written as a number when it can be parsed as a number. Empty cells and '--' (missing values) are left blank.
+enum Cell<'a> {
+    Blank,
+    Number(f64),
+    Text(&'a str),
+}
+
+impl<'a> From<&'a str> for Cell<'a> {
+    fn from(s: &'a str) -> Self {
+        let s = s.trim();
+        match s {
+            "" | "--" => Cell::Blank,
+            s => match s.parse::<f64>() {
+                Ok(v) if v.is_finite() => Cell::Number(v),
+                _ => Cell::Text(s),
+            },
+        }
+    }
+}
+
+/// write the sheets to a workbook. The lines of a sheet are expected to be ';'-separated and formatted without comma-floats (see 'AnalysisContext::without_comma_float').
+pub fn write_xlsx(path: &Path, sheets: &[XlsxSheet]) -> Result<(), Box<dyn Error>> {
+    let mut workbook = Workbook::new();
+    let header_format = Format::new().set_bold();
+    let mut used = HashSet::new();
+
+    for sheet in sheets.iter() {
+        let worksheet = workbook.add_worksheet();
+        worksheet.set_name(sheet_name(&sheet.name, &mut used))?;
+
+        let mut num_cols = 0;
+        for (row, line) in sheet.lines.iter().enumerate() {
+            let row = row as u32;
+            for (col, value) in line.split(';').enumerate() {
+                let col = col as u16;
+                num_cols = num_cols.max(col + 1);
+                match Cell::from(value) {
+                    Cell::Blank => (),
+                    Cell::Text(s) if row == 0 => {
+                        worksheet.write_string_with_format(row, col, s, &header_format)?;
+                    }
+                    Cell::Text(s) => {
+                        let s: String = s.chars().take(MAX_CELL_LEN).collect();
+                        worksheet.write_string(row, col, s)?;
+                    }
+                    Cell::Number(v) => {
+                        worksheet.write_number(row, col, v)?;
+                    }
+                }
+            }
+        }
+        if sheet.lines.len() > 1 && num_cols > 0 {
+            worksheet.set_freeze_panes(1, 0)?;
+            worksheet.autofilter(0, 0, sheet.lines.len() as u32 - 1, num_cols - 1)?;
+        }
+    }
+    workbook.save(path)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{sheet_name, Cell};
+    use std::collections::HashSet;
+
+    #[test]
+    fn cells_are_typed() {
+        assert!(matches!(Cell::from(" 12"), Cell::Number(v) if v == 12.0));
+        assert!(matches!(Cell::from("0.25 "), Cell::Number(v) if v == 0.25));
+        assert!(matches!(Cell::from("--"), Cell::Blank));
+        assert!(matches!(Cell::from(""), Cell::Blank));
+        assert!(matches!(Cell::from("NaN"), Cell::Text("NaN")));
+        assert!(matches!(Cell::from("a/b"), Cell::Text("a/b")));
+    }
+
+    #[test]
+    fn sheet_names_are_valid_and_unique() {
+        let mut used = HashSet::new();
+        assert_eq!(
+            sheet_name("Service/Operation", &mut used),
+            "Service_Operation"
+        );
+        let long = "Statistics per Process/Operation combination";
+        let first = sheet_name(long, &mut used);
+        let second = sheet_name(long, &mut used);
+        assert_eq!(first.chars().count(), 31);
+        assert!(second.ends_with(" (2)"));
+        assert_eq!(second.chars().count(), 31);
+    }
+}
@@ -0,0 +1,81 @@
+//! A self-contained html-report of a dataset (analysis or stitched set) containing the summary, sortable tables, the mermaid diagrams and the time-series charts.
+use super::{MermaidScope, Metric, Viewer};
+use crate::{context::AnalysisContext, utils::HtmlPage};
+use std::{cmp::Reverse, path::Path};
+
+/// The metrics that are shown as time-series charts per service/operation
+const CHART_METRICS: [Metric; 4] = [
+    Metric::Count,
+    Metric::AvgDurationMillis,
+    Metric::P95Millis,
+    Metric::FracNotHttpOk,
+];
+
+#[derive(Debug, Clone)]
+pub struct HtmlParameters {
+    /// The number of service/operations (ranked on count) that get a diagram and charts
+    pub max_service_opers: usize,
+}
+
+impl Default for HtmlParameters {
+    fn default() -> Self {
+        Self {
+            max_service_opers: 10,
+        }
+    }
+}
+
+/// The contents of the report. The tables are ';'-separated lines with a header on the first line.
+pub struct HtmlReport {
+    pub title: String,
+    pub summary: Vec<(String, String)>,
+    pub tables: Vec<(String, Vec<String>)>,
+}
+
+impl HtmlReport {
+    /// Write the report, where the diagrams and charts are derived via the 'viewer'.
+    pub fn write_file(
+        &self,
+        ctx: &AnalysisContext,
+        viewer: &dyn Viewer,
+        path: &Path,
+        pars: &HtmlParameters,
+    ) {
+        let mut page = HtmlPage::new(&self.title);
+
+        page.add_section("Summary");
+        page.add_key_values(&self.summary);
+
+        self.tables.iter().for_each(|(title, lines)| {
+            page.add_section(title);
+            page.add_table(lines);
+        });
+
+        let mut service_opers = viewer.get_process_list(Metric::Count);
+        service_opers.sort_by_key(|so| Reverse(so.avg_count));
+        service_opers.truncate(pars.max_service_opers);
+
+        page.add_section("Diagrams");
+        if !HtmlPage::renders_mermaid(ctx) {
+            page.add_note("The diagrams are shown as mermaid-source. Provide a local copy of 'mermaid.min.js' via '--mermaid-js' to render them in the report.");
+        }
+        service_opers.iter().for_each(|so| {
+            let diagram =
+                viewer.get_mermaid_diagram(&so.key, None, Metric::Count, MermaidScope::Full, true);
+            page.add_mermaid(&so.display, &diagram);
+        });
+
+        if viewer.is_time_series() {
+            page.add_section("Charts");
+            service_opers.iter().for_each(|so| {
+                CHART_METRICS.iter().for_each(|metric| {
+                    if let Some(chart) = viewer.get_service_oper_chart_data(&so.key, *metric) {
+                        page.add_chart(&chart)
+                    }
+                })
+            });
+        }
+
+        page.write_file(ctx, path);
+    }
+}
@@ -27,6 +27,11 @@ pub enum Metric {
     P90Millis,
     P95Millis,
     P99Millis,
+    CacheHits,
+    CacheMisses,
+    CacheHitRatio,
+    CacheHitMissDiffMillis,
+    CacheFlushLoadIncrease,
 }
 
 impl Metric {
@@ -40,7 +45,7 @@ impl Metric {
 }
 
 /// The Metric_labels should all be set in Lower-case
-const METRIC_LABELS: [&str; 22] = [
+const METRIC_LABELS: [&str; 27] = [
     "NONE",
     "num_files",
     "occurance percentage",
@@ -63,6 +68,11 @@ const METRIC_LABELS: [&str; 22] = [
     "p90 millis",
     "p95 millis",
     "p99 millis",
+    "cache hits",
+    "cache misses",
+    "cache hit ratio",
+    "cache hit-miss diff millis",
+    "cache flush load increase",
 ];
 
 impl ToString for Metric {
@@ -1,4 +1,5 @@
 mod file;
+mod html_report;
 mod mermaid_scope;
 mod metric;
 mod proc_list_utils;
@@ -9,6 +10,7 @@ mod view_error;
 mod viewer;
 
 pub use file::load_viewer;
+pub use html_report::{HtmlParameters, HtmlReport};
 pub use mermaid_scope::MermaidScope;
 pub use metric::Metric;
 pub use proc_list_utils::reorder_and_renumber;
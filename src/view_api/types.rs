@@ -1,4 +1,5 @@
 use serde::Serialize;
+use std::collections::BTreeMap;
 
 pub type ServiceOperString = String;
 
@@ -16,12 +17,30 @@ pub struct ProcessListItem {
 
 pub type ProcessList = Vec<ProcessListItem>;
 
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ErrorTemplateItem {
+    pub template: String,
+    pub count: i64,
+    pub examples: Vec<String>,
+}
+
+pub type ErrorTemplateList = Vec<ErrorTemplateItem>;
+
 #[derive(Serialize, Debug)]
 pub struct ChartLine {
     pub label: String,
     pub data: Vec<Option<f64>>,
 }
 
+/// A marker at a column of a chart, for example a detected change-point
+#[derive(Serialize, Debug)]
+pub struct ChartMarker {
+    pub index: usize,
+    pub label: String,
+    pub description: String,
+}
+
 #[derive(Serialize, Debug)]
 pub struct ChartDataParameters {
     pub title: String,
@@ -30,6 +49,7 @@ pub struct ChartDataParameters {
     pub description: Vec<(String, String)>,
     pub labels: Vec<String>,
     pub lines: Vec<ChartLine>,
+    pub markers: Vec<ChartMarker>,
 }
 
 #[derive(Serialize, Debug)]
@@ -44,6 +64,9 @@ pub struct SelectLabel {
     pub idx: i64, // could be u64, but will be used in json, so will be signed anyway
     pub label: String,
     pub selected: bool,
+    /// the tags of the column (only for a structured stitch-list)
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub tags: BTreeMap<String, String>,
 }
 
 pub type Selection = Vec<SelectLabel>;
@@ -1,6 +1,7 @@
 use serde::{Deserialize, Serialize};
+use std::fmt;
 
-#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Version {
     pub major: u16,
     pub minor: u16,
@@ -13,7 +14,25 @@ impl Default for Version {
 }
 
 impl Version {
-    pub fn new(major: u16, minor: u16) -> Self {
+    /// Files written before versioning was introduced (no version-field)
+    pub const UNVERSIONED: Version = Version::new(0, 0);
+    /// Current version of the statistics files (StatsRec). Version 0.3 introduced the header of the bincode-files and 0.4 the timezone.
+    pub const STATS: Version = Version::new(0, 4);
+    /// Current version of the stitched files. Version 0.4 introduced the cache-statistics, 0.5 the header of the bincode-files, 0.6 the column-descriptions, 0.7 the time-axis, 0.8 the seasonal period and 0.9 the change-point bound.
+    pub const STITCHED: Version = Version::new(0, 9);
+
+    pub const fn new(major: u16, minor: u16) -> Self {
         Self { major, minor }
     }
+
+    /// used as serde-default for files without a version-field
+    pub fn unversioned() -> Self {
+        Self::UNVERSIONED
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}", self.major, self.minor)
+    }
 }
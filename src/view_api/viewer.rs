@@ -1,11 +1,12 @@
 use super::{
-    types::{ChartDataParameters, ProcessList, Selection, Table},
+    types::{ChartDataParameters, ErrorTemplateList, ProcessList, Selection, Table},
     MermaidScope, Metric, TraceScope, ViewError,
 };
+use crate::context::AnalysisContext;
 
 pub trait Viewer {
     /// Read the file (either Traces or Stitched) and create a viewer for it.
-    fn from_file(file_name: &str) -> Result<Box<Self>, ViewError>
+    fn from_file(ctx: &AnalysisContext, file_name: &str) -> Result<Box<Self>, ViewError>
     where
         Self: Sized;
 
@@ -48,6 +49,18 @@ pub trait Viewer {
         None
     }
 
+    #[allow(unused_variables)]
+    /// Get the most frequent error-message templates for a specific service-operation (at most 'top_n' templates)
+    fn get_error_templates(
+        &self,
+        ctx: &AnalysisContext,
+        service_oper: &str,
+        top_n: usize,
+    ) -> ErrorTemplateList {
+        // default implementation as the raw error-messages are not retained in all datasets.
+        Vec::new()
+    }
+
     /// filestats are always derived from the original dataset
     fn get_file_stats(&self) -> Table {
         panic!("Get_file_stats only exists for time-series data")